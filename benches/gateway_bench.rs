@@ -1,6 +1,11 @@
 //! Performance benchmarks for the API Gateway.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+#[path = "../tests/common/harness.rs"]
+mod harness;
 
 use criterion::{Criterion, Throughput, black_box, criterion_group, criterion_main};
+use harness::TestGateway;
 use std::collections::HashMap;
 use std::time::Duration;
 
@@ -14,7 +19,7 @@ fn bench_hashmap_lookup(c: &mut Criterion) {
     group.throughput(Throughput::Elements(1));
 
     group.bench_function("hashmap_exact_match", |b| {
-        b.iter(|| black_box(map.get("/api/v1/route500")))
+        b.iter(|| black_box(map.get("/api/v1/route500")));
     });
 
     group.bench_function("hashmap_miss", |b| b.iter(|| black_box(map.get("/api/v1/nonexistent"))));
@@ -28,11 +33,11 @@ fn bench_string_operations(c: &mut Criterion) {
     let path = "/api/v1/users/123/profile";
 
     group.bench_function("path_split", |b| {
-        b.iter(|| black_box(path.split('/').collect::<Vec<_>>()))
+        b.iter(|| black_box(path.split('/').collect::<Vec<_>>()));
     });
 
     group.bench_function("path_starts_with", |b| {
-        b.iter(|| black_box(path.starts_with("/api/v1")))
+        b.iter(|| black_box(path.starts_with("/api/v1")));
     });
 
     group.finish();
@@ -40,22 +45,15 @@ fn bench_string_operations(c: &mut Criterion) {
 
 fn bench_token_bucket(c: &mut Criterion) {
     use std::sync::atomic::{AtomicU64, Ordering};
-    use std::time::Instant;
 
     struct SimpleBucket {
         tokens: AtomicU64,
-        last_refill: std::sync::Mutex<Instant>,
-        capacity: u64,
-        refill_rate: u64,
     }
 
     impl SimpleBucket {
-        fn new(capacity: u64, refill_rate: u64) -> Self {
+        fn new(capacity: u64) -> Self {
             Self {
                 tokens: AtomicU64::new(capacity),
-                last_refill: std::sync::Mutex::new(Instant::now()),
-                capacity,
-                refill_rate,
             }
         }
 
@@ -76,7 +74,7 @@ fn bench_token_bucket(c: &mut Criterion) {
         }
     }
 
-    let bucket = SimpleBucket::new(1000, 100);
+    let bucket = SimpleBucket::new(1000);
 
     let mut group = c.benchmark_group("rate_limiting");
     group.throughput(Throughput::Elements(1));
@@ -94,7 +92,7 @@ fn bench_cache_key_generation(c: &mut Criterion) {
     let query = "include=profile&fields=name,email";
 
     group.bench_function("key_format", |b| {
-        b.iter(|| black_box(format!("{}:{}:{}", method, path, query)))
+        b.iter(|| black_box(format!("{}:{}:{}", method, path, query)));
     });
 
     group.bench_function("key_concat", |b| {
@@ -106,10 +104,126 @@ fn bench_cache_key_generation(c: &mut Criterion) {
             key.push(':');
             key.push_str(query);
             black_box(key)
-        })
+        });
+    });
+
+    group.finish();
+}
+
+/// Threads repeatedly starting and finishing single-flights on distinct keys,
+/// with few shards (heavy lock contention) versus the default count.
+fn bench_coalesce_contention(c: &mut Criterion) {
+    use rustway::features::coalesce::{Flight, RequestCoalescer};
+
+    const THREADS: usize = 8;
+    const FLIGHTS_PER_THREAD: usize = 1_000;
+
+    let keys: Vec<Vec<String>> = (0..THREADS)
+        .map(|t| (0..FLIGHTS_PER_THREAD).map(|i| format!("GET /t{}/{}", t, i)).collect())
+        .collect();
+
+    let mut group = c.benchmark_group("coalesce_contention");
+    group.throughput(Throughput::Elements((THREADS * FLIGHTS_PER_THREAD) as u64));
+
+    for (name, coalescer) in [
+        ("2_shards", RequestCoalescer::with_shards(2)),
+        ("default_shards", RequestCoalescer::new()),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                std::thread::scope(|scope| {
+                    for thread_keys in &keys {
+                        let coalescer = &coalescer;
+                        scope.spawn(move || {
+                            for key in thread_keys {
+                                if let Flight::Leader(leader) = coalescer.join("bench", key.clone()) {
+                                    black_box(&leader);
+                                }
+                            }
+                        });
+                    }
+                });
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// Verifying one API key against a large store, plaintext and hashed, next to
+/// cloning the whole key map, which is what a per-request copy would cost.
+fn bench_api_key_verification(c: &mut Criterion) {
+    use http::HeaderMap;
+    use rustway::{
+        config::{ApiKeyDetails, ApiKeyStore, AuthConfig, SecretsConfig, hash_api_key},
+        features::auth::auth::verify_token,
+    };
+
+    const KEYS: usize = 10_000;
+
+    let details = |i: usize| ApiKeyDetails {
+        user_id: format!("user{}", i),
+        roles: vec!["user".to_string()],
+        status: "active".to_string(),
+    };
+    let plain = ApiKeyStore {
+        keys: (0..KEYS).map(|i| (format!("key-{}", i), details(i))).collect(),
+        ..Default::default()
+    };
+    let hashed = ApiKeyStore {
+        keys: (0..KEYS)
+            .map(|i| (hash_api_key(&format!("key-{}", i)), details(i)))
+            .collect(),
+        hashed: true,
+        ..Default::default()
+    };
+    let auth: AuthConfig = serde_yaml::from_str("type: ApiKey").unwrap();
+    let secrets = SecretsConfig::default();
+    let mut headers = HeaderMap::new();
+    headers.insert("x-api-key", "key-5000".parse().unwrap());
+
+    let mut group = c.benchmark_group("api_key_verification");
+    group.throughput(Throughput::Elements(1));
+
+    group.bench_function("plaintext_lookup", |b| {
+        b.iter(|| black_box(verify_token(&headers, &auth, &secrets, &plain).is_ok()));
+    });
+    group.bench_function("hashed_lookup", |b| {
+        b.iter(|| black_box(verify_token(&headers, &auth, &secrets, &hashed).is_ok()));
+    });
+    group.bench_function("full_map_clone", |b| b.iter(|| black_box(plain.keys.clone())));
+
+    group.finish();
+}
+
+/// End-to-end request through an in-process gateway to the example backend.
+fn bench_proxy_roundtrip(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let gateway = runtime.block_on(TestGateway::start(
+        r#"
+routes:
+  - name: echo
+    path: /api/echo
+    destination: "{backend}/echo"
+"#,
+    ));
+    let client = reqwest::Client::new();
+    let url = format!("{}/api/echo/bench", gateway.base_url);
+
+    let mut group = c.benchmark_group("proxy");
+    group.throughput(Throughput::Elements(1));
+
+    group.bench_function("roundtrip", |b| {
+        b.iter(|| {
+            runtime.block_on(async {
+                let resp = client.get(&url).send().await.unwrap();
+                black_box(resp.bytes().await.unwrap())
+            })
+        });
     });
 
     group.finish();
+    runtime.block_on(gateway.shutdown());
 }
 
 criterion_group! {
@@ -117,7 +231,7 @@ criterion_group! {
     config = Criterion::default()
         .measurement_time(Duration::from_secs(5))
         .sample_size(100);
-    targets = bench_hashmap_lookup, bench_string_operations, bench_token_bucket, bench_cache_key_generation
+    targets = bench_hashmap_lookup, bench_string_operations, bench_token_bucket, bench_cache_key_generation, bench_coalesce_contention, bench_api_key_verification, bench_proxy_roundtrip
 }
 
 criterion_main!(benches);
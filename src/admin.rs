@@ -0,0 +1,215 @@
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::HeaderMap,
+};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::sync::Arc;
+
+use crate::{
+    config::GatewayConfig,
+    errors::AppError,
+    features::auth::auth::{AuthOutcome, authenticate, check_claims, check_roles},
+    middleware::route_match::RequestContext,
+    state::AppState,
+};
+
+/// `GET /admin/routes`: every configured route with how many requests it
+/// has proxied since startup and when it was last hit.
+pub async fn list_routes_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, AppError> {
+    authorize(&state, &context, &headers).await?;
+    let config = state.config.read().await;
+
+    let routes: Vec<Value> = config
+        .routes
+        .iter()
+        .map(|route| {
+            let traffic = state.route_traffic.snapshot(&route.name);
+            json!({
+                "name": route.name,
+                "path": route.path,
+                "requests": traffic.requests,
+                "last_request_ms": traffic.last_request_ms,
+            })
+        })
+        .collect();
+    Ok(Json(json!({ "routes": routes })))
+}
+
+/// `POST /admin/routes/{name}/switch`: moves a blue-green route's traffic to
+/// its other group.
+pub async fn switch_route_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, AppError> {
+    authorize(&state, &context, &headers).await?;
+    let config = state.config.read().await;
+
+    let route = config
+        .routes
+        .iter()
+        .find(|r| r.name == name)
+        .ok_or(AppError::RouteNotFound)?;
+    let blue_green = route
+        .blue_green
+        .as_ref()
+        .ok_or_else(|| AppError::NotBlueGreen(name.clone()))?;
+
+    let active = state.blue_green.switch(&route.name, blue_green);
+    Ok(Json(json!({
+        "route": route.name,
+        "active": active,
+        "destinations": blue_green.group(active),
+    })))
+}
+
+#[derive(Deserialize)]
+pub struct MatchQuery {
+    pub path: String,
+    pub host: Option<String>,
+    pub method: Option<String>,
+}
+
+/// `GET /admin/match?path=/foo&host=bar&method=GET`: the route a request
+/// would be sent to, and the rule that picked it. Routes match on path only,
+/// so `host` and `method` are echoed back but never change the answer.
+pub async fn match_route_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    Query(query): Query<MatchQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, AppError> {
+    authorize(&state, &context, &headers).await?;
+    let config = state.config.read().await;
+
+    let request = json!({
+        "path": query.path,
+        "host": query.host,
+        "method": query.method,
+    });
+    Ok(Json(match config.find_route_with_rule(&query.path) {
+        Some((route, rule)) => json!({
+            "request": request,
+            "matched": true,
+            "route": route.name,
+            "route_path": route.path,
+            "rule": rule.as_str(),
+        }),
+        None => json!({
+            "request": request,
+            "matched": false,
+            "reason": "no route path matches or prefixes the request path",
+        }),
+    }))
+}
+
+/// `GET /admin/circuit-breakers/events`: recent circuit breaker state
+/// transitions, oldest first, up to `tuning.circuit_breaker_events`.
+pub async fn circuit_breaker_events_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, AppError> {
+    authorize(&state, &context, &headers).await?;
+
+    Ok(Json(json!({ "events": state.circuit_breaker_store.events() })))
+}
+
+#[derive(Deserialize)]
+pub struct DestinationRequest {
+    pub destination: String,
+}
+
+/// `POST /admin/destinations/drain`: stops sending new requests to a
+/// destination, e.g. before patching it. In-flight requests finish.
+pub async fn drain_destination_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    headers: HeaderMap,
+    Json(request): Json<DestinationRequest>,
+) -> Result<Json<Value>, AppError> {
+    authorize(&state, &context, &headers).await?;
+    let config = state.config.read().await;
+    known_destination(&config, &request.destination)?;
+
+    let changed = state.drained.drain(&request.destination);
+    Ok(Json(json!({
+        "destination": request.destination,
+        "drained": true,
+        "changed": changed,
+    })))
+}
+
+/// `POST /admin/destinations/undrain`: puts a drained destination back in rotation.
+pub async fn undrain_destination_handler(
+    State(state): State<Arc<AppState>>,
+    Extension(context): Extension<RequestContext>,
+    headers: HeaderMap,
+    Json(request): Json<DestinationRequest>,
+) -> Result<Json<Value>, AppError> {
+    authorize(&state, &context, &headers).await?;
+
+    let changed = state.drained.undrain(&request.destination);
+    Ok(Json(json!({
+        "destination": request.destination,
+        "drained": false,
+        "changed": changed,
+    })))
+}
+
+/// Draining a URL no route sends to is almost certainly a typo.
+fn known_destination(config: &GatewayConfig, destination: &str) -> Result<(), AppError> {
+    let known = config
+        .routes
+        .iter()
+        .any(|route| route.all_destinations().contains(&destination));
+    if known {
+        Ok(())
+    } else {
+        Err(AppError::UnknownDestination(destination.to_string()))
+    }
+}
+
+/// Admin endpoints answer 404 unless `server.admin` is configured, and
+/// never fail open.
+async fn authorize(state: &AppState, context: &RequestContext, headers: &HeaderMap) -> Result<(), AppError> {
+    // Copied out of the config so a slow identity provider doesn't hold the
+    // read lock, and with it config reloads
+    let methods = state
+        .config
+        .read()
+        .await
+        .server
+        .admin
+        .as_ref()
+        .map(|admin| admin.auth.clone())
+        .ok_or(AppError::RouteNotFound)?;
+    match authenticate(
+        headers,
+        None,
+        context.client_ip,
+        &methods,
+        &state.secrets,
+        &state.key_store,
+        &state.jwks,
+        &state.http_client,
+    )
+    .await?
+    {
+        AuthOutcome::Authenticated(claims, auth_config) => {
+            if let Some(required_roles) = &auth_config.roles {
+                check_roles(&claims.roles, required_roles)?;
+            }
+            check_claims(&claims, &auth_config.required_claims)?;
+            Ok(())
+        }
+        AuthOutcome::FailedOpen => Err(AppError::AuthUnavailable),
+    }
+}
@@ -6,6 +6,7 @@ use axum::{
 use http::StatusCode;
 use serde_json::Value;
 use std::sync::Arc;
+use tokio::sync::Semaphore;
 use tracing::{error, info};
 
 use crate::{features::health_check::parse_duration, state::AppState};
@@ -21,12 +22,16 @@ fn json_response(status: StatusCode, body: impl Into<Body>) -> Response {
 pub async fn aggregate_handler(State(state): State<Arc<AppState>>, Path(path): Path<String>) -> Response {
     let request_path = format!("/{}", path);
 
-    let (sources, route_name) = {
+    let (sources, route_name, max_concurrent) = {
         let config = state.config.read().await;
         match config.find_route_for_path(&request_path) {
             Some(route) if route.aggregate.is_some() => {
                 // Safe: we just checked is_some()
-                (route.aggregate.clone().unwrap_or_default(), route.name.clone())
+                (
+                    route.aggregate.clone().unwrap_or_default(),
+                    route.name.clone(),
+                    route.max_concurrent_subrequests,
+                )
             }
             _ => return json_response(StatusCode::NOT_FOUND, "No aggregate route found"),
         }
@@ -34,18 +39,24 @@ pub async fn aggregate_handler(State(state): State<Arc<AppState>>, Path(path): P
 
     info!(route = %route_name, sources = sources.len(), "Aggregating responses");
 
+    // Sub-requests beyond the cap wait for a running one to finish
+    let permits = max_concurrent.map(|max| Arc::new(Semaphore::new(max)));
     let mut handles = Vec::new();
     for source in &sources {
         let client = state.http_client.clone();
+        let permits = permits.clone();
         let url = source.path.clone();
         let field = source.field.clone();
         let timeout = source
             .timeout
-            .as_ref()
-            .map(|t| parse_duration(t))
-            .unwrap_or(std::time::Duration::from_secs(5));
+            .as_deref()
+            .map_or(std::time::Duration::from_secs(5), parse_duration);
 
         handles.push(tokio::spawn(async move {
+            let _permit = match &permits {
+                Some(permits) => permits.clone().acquire_owned().await.ok(),
+                None => None,
+            };
             let result = client.get(&url).timeout(timeout).send().await;
             match result {
                 std::result::Result::Ok(resp) if resp.status().is_success() => {
@@ -5,21 +5,26 @@ use axum::{
     Router,
     extract::Request,
     middleware::{from_fn, from_fn_with_state},
-    routing::{any, get},
+    routing::{any, get, post},
 };
-use axum_client_ip::ClientIpSource;
 use http::{HeaderName, Method as HttpMethod, StatusCode};
 use tower_http::{compression::CompressionLayer, cors::CorsLayer, trace::TraceLayer};
-use uuid::Uuid;
 
 use crate::{
+    admin::{
+        circuit_breaker_events_handler, drain_destination_handler, list_routes_handler, match_route_handler,
+        switch_route_handler, undrain_destination_handler,
+    },
     aggregate::aggregate_handler,
     grpc_proxy::grpc_proxy_handler,
     middleware::{
         access_log::layer as access_log_layer, auth::auth::layer as auth_layer, cache::cache::layer as cache_layer,
-        circuit_breaker::circuit_breaker::layer as circuit_breaker_layer,
+        capture::layer as capture_layer, circuit_breaker::circuit_breaker::layer as circuit_breaker_layer,
+        coalesce::layer as coalesce_layer, global_circuit_breaker::layer as global_circuit_breaker_layer,
+        idempotency::layer as idempotency_layer, qos::layer as qos_layer,
         rate_limiter::rate_limit::layer as ratelimiter_layer, request_id::request_id::layer as request_id_layer,
-        tracing_ctx::layer as tracing_ctx_layer,
+        route_match::layer as route_match_layer, route_metrics::layer as route_metrics_layer,
+        stats::layer as stats_layer, tracing_ctx::layer as tracing_ctx_layer, uri_limit::layer as uri_limit_layer,
     },
     proxy::proxy_handler,
     state::AppState,
@@ -32,7 +37,10 @@ pub const REQUEST_ID_HEADER: &str = "x-request-id";
 pub fn create_app(state: Arc<AppState>, cors: &crate::config::CorsConfig, body_limit: usize) -> Result<Router, Error> {
     let proxy_router = Router::new()
         .route("/{*path}", any(proxy_handler))
+        .route_layer(from_fn_with_state(state.clone(), qos_layer))
         .route_layer(from_fn_with_state(state.clone(), circuit_breaker_layer))
+        .route_layer(from_fn_with_state(state.clone(), coalesce_layer))
+        .route_layer(from_fn_with_state(state.clone(), idempotency_layer))
         .route_layer(from_fn_with_state(state.clone(), cache_layer))
         .route_layer(from_fn_with_state(state.clone(), ratelimiter_layer))
         .route_layer(from_fn_with_state(state.clone(), auth_layer));
@@ -41,23 +49,32 @@ pub fn create_app(state: Arc<AppState>, cors: &crate::config::CorsConfig, body_l
     let agg_router = Router::new().route("/agg/{*path}", get(aggregate_handler));
     let grpc_router = Router::new().route("/grpc/{*path}", any(grpc_proxy_handler));
     let prometheus_router = Router::new().route("/metrics", get(metrics_handler));
+    let admin_router = Router::new()
+        .route("/admin/routes", get(list_routes_handler))
+        .route("/admin/match", get(match_route_handler))
+        .route("/admin/circuit-breakers/events", get(circuit_breaker_events_handler))
+        .route("/admin/routes/{name}/switch", post(switch_route_handler))
+        .route("/admin/destinations/drain", post(drain_destination_handler))
+        .route("/admin/destinations/undrain", post(undrain_destination_handler));
 
     // Build CORS layer
     let cors_layer = if cors.enabled {
         let origins: Vec<_> = cors.origins.iter().filter_map(|o| o.parse().ok()).collect();
         let methods: Vec<HttpMethod> = cors.methods.iter().filter_map(|m| m.parse().ok()).collect();
         let mut layer = CorsLayer::new().allow_methods(methods).allow_origin(origins);
-        if !cors.allow_headers.is_empty() {
+        if cors.allow_headers.is_empty() {
+            layer = layer.allow_headers(tower_http::cors::Any);
+        } else {
             let headers: Vec<HeaderName> = cors.allow_headers.iter().filter_map(|h| h.parse().ok()).collect();
             layer = layer.allow_headers(headers);
-        } else {
-            layer = layer.allow_headers(tower_http::cors::Any);
         }
         Some(layer)
     } else {
         None
     };
 
+    let request_id_state = state.clone();
+
     let router = Router::new()
         .route("/health", get(|| async { (StatusCode::OK, "OK") }))
         .merge(ws_router)
@@ -65,10 +82,18 @@ pub fn create_app(state: Arc<AppState>, cors: &crate::config::CorsConfig, body_l
         .merge(grpc_router)
         .merge(proxy_router)
         .merge(prometheus_router)
+        .merge(admin_router)
+        .layer(from_fn_with_state(state.clone(), capture_layer))
+        .layer(from_fn_with_state(state.clone(), global_circuit_breaker_layer))
         .layer(from_fn(tracing_ctx_layer))
-        .layer(from_fn(access_log_layer))
-        .with_state(state)
-        .layer(ClientIpSource::ConnectInfo.into_extension());
+        .layer(from_fn_with_state(state.clone(), access_log_layer))
+        .layer(from_fn_with_state(state.clone(), route_metrics_layer))
+        .layer(from_fn_with_state(state.clone(), stats_layer))
+        // Outermost of the gateway's own layers, so all of them share one route lookup
+        .layer(from_fn_with_state(state.clone(), route_match_layer))
+        // Oversized URIs never reach the route lookup
+        .layer(from_fn_with_state(state.clone(), uri_limit_layer))
+        .with_state(state);
 
     let router = if let Some(cors_layer) = cors_layer {
         router.layer(cors_layer)
@@ -82,12 +107,12 @@ pub fn create_app(state: Arc<AppState>, cors: &crate::config::CorsConfig, body_l
 
     Ok(router
         .layer(TraceLayer::new_for_http().make_span_with(|request: &Request<_>| {
-            let uuid = Uuid::new_v4().to_string();
+            // request_id_layer runs first and always sets the header
             let request_id = request
                 .headers()
                 .get(REQUEST_ID_HEADER)
                 .and_then(|value| value.to_str().ok())
-                .unwrap_or(uuid.as_str());
+                .unwrap_or("unknown");
 
             tracing::error_span!(
                     "request",
@@ -96,5 +121,5 @@ pub fn create_app(state: Arc<AppState>, cors: &crate::config::CorsConfig, body_l
                     uri = %request.uri(),
             )
         }))
-        .layer(from_fn(request_id_layer)))
+        .layer(from_fn_with_state(request_id_state, request_id_layer)))
 }
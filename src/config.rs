@@ -1,12 +1,25 @@
-use std::{collections::HashMap, fs, path::Path, sync::Arc};
-
-use anyhow::{Error, Ok};
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+    str::FromStr,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+use anyhow::Error;
+use jsonwebtoken::{Algorithm, DecodingKey};
 use regex::Regex;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tracing::info;
 
+use crate::constants;
+use crate::errors::ConfigError;
+use crate::features::auth::basic::PasswordHash;
 use crate::features::health_check::HealthCheckConfig;
 use crate::features::load_balancer::LoadBalanceStrategy;
+use crate::features::tls::TlsConfig;
 
 // ==================== Top-level Config ====================
 
@@ -25,10 +38,142 @@ pub struct GatewayConfig {
     pub cors: CorsConfig,
     #[serde(default)]
     pub include: Vec<String>,
+    #[serde(default)]
+    pub tuning: TuningConfig,
+    #[serde(default)]
+    pub degradation: DegradationConfig,
+    #[serde(default)]
+    pub security: SecurityConfig,
     #[serde(skip)]
     route_tree: Option<matchit::Router<usize>>,
 }
 
+// ==================== Tuning ====================
+
+/// Internal knobs, read once at startup. Unset values use `crate::constants`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TuningConfig {
+    #[serde(default = "default_static_cache_capacity")]
+    pub static_cache_capacity: u64,
+    #[serde(default = "default_static_cache_ttl")]
+    pub static_cache_ttl: String,
+    #[serde(default = "default_health_check_timeout")]
+    pub health_check_timeout: String,
+    #[serde(default = "default_reload_channel_buffer")]
+    pub reload_channel_buffer: usize,
+    #[serde(default = "default_tls_reload_debounce")]
+    pub tls_reload_debounce: String,
+    #[serde(default = "default_max_response_header_bytes")]
+    pub max_response_header_bytes: usize,
+    /// Lock shards in the `coalesce` single-flight map; a power of two above 1.
+    /// More shards mean less contention between unrelated keys. Unset: four
+    /// per CPU, rounded up to a power of two.
+    pub coalesce_shards: Option<usize>,
+    /// How often idle rate limit buckets and circuits are dropped.
+    #[serde(default = "default_store_cleanup_interval")]
+    pub store_cleanup_interval: String,
+    /// Entries checked per cleanup tick, so large stores are reaped a slice
+    /// at a time. Unset: every entry each tick.
+    pub store_cleanup_batch: Option<usize>,
+    /// Least time between JWKS fetches caused by tokens with an unknown `kid`.
+    #[serde(default = "default_jwks_miss_cooldown")]
+    pub jwks_miss_cooldown: String,
+    /// Recent circuit breaker state transitions kept in memory for
+    /// `GET /admin/circuit-breakers/events`; 0 keeps none.
+    #[serde(default = "default_circuit_breaker_events")]
+    pub circuit_breaker_events: usize,
+}
+
+fn default_static_cache_capacity() -> u64 {
+    constants::DEFAULT_STATIC_CACHE_CAPACITY
+}
+fn default_static_cache_ttl() -> String {
+    constants::DEFAULT_STATIC_CACHE_TTL.to_string()
+}
+fn default_health_check_timeout() -> String {
+    constants::DEFAULT_HEALTH_CHECK_TIMEOUT.to_string()
+}
+fn default_reload_channel_buffer() -> usize {
+    constants::DEFAULT_RELOAD_CHANNEL_BUFFER
+}
+fn default_tls_reload_debounce() -> String {
+    constants::DEFAULT_TLS_RELOAD_DEBOUNCE.to_string()
+}
+fn default_max_response_header_bytes() -> usize {
+    constants::DEFAULT_MAX_RESPONSE_HEADER_BYTES
+}
+fn default_store_cleanup_interval() -> String {
+    constants::DEFAULT_STORE_CLEANUP_INTERVAL.to_string()
+}
+fn default_jwks_miss_cooldown() -> String {
+    constants::DEFAULT_JWKS_MISS_COOLDOWN.to_string()
+}
+fn default_circuit_breaker_events() -> usize {
+    constants::DEFAULT_CIRCUIT_BREAKER_EVENTS
+}
+
+impl Default for TuningConfig {
+    fn default() -> Self {
+        Self {
+            static_cache_capacity: default_static_cache_capacity(),
+            static_cache_ttl: default_static_cache_ttl(),
+            health_check_timeout: default_health_check_timeout(),
+            reload_channel_buffer: default_reload_channel_buffer(),
+            tls_reload_debounce: default_tls_reload_debounce(),
+            max_response_header_bytes: default_max_response_header_bytes(),
+            coalesce_shards: None,
+            store_cleanup_interval: default_store_cleanup_interval(),
+            store_cleanup_batch: None,
+            jwks_miss_cooldown: default_jwks_miss_cooldown(),
+            circuit_breaker_events: default_circuit_breaker_events(),
+        }
+    }
+}
+
+// ==================== Degradation ====================
+
+/// What each subsystem does when its backing store or dependency fails.
+/// The defaults keep the gateway serving except where that would skip auth.
+#[derive(Debug, Deserialize, Clone)]
+pub struct DegradationConfig {
+    #[serde(default = "default_fail_open")]
+    pub rate_limit: FailurePolicy,
+    /// Default `on_error` for route auth methods that don't set one.
+    #[serde(default)]
+    pub auth: FailurePolicy,
+    #[serde(default = "default_fail_open")]
+    pub cache: FailurePolicy,
+}
+
+fn default_fail_open() -> FailurePolicy {
+    FailurePolicy::FailOpen
+}
+
+impl Default for DegradationConfig {
+    fn default() -> Self {
+        Self {
+            rate_limit: default_fail_open(),
+            auth: FailurePolicy::default(),
+            cache: default_fail_open(),
+        }
+    }
+}
+
+// ==================== Security ====================
+
+/// Request limits enforced before route matching, and what backends learn
+/// about the client.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct SecurityConfig {
+    /// Longest path plus query string accepted; longer requests get 414.
+    /// Unset: only the HTTP server's own limits apply.
+    pub max_uri_length: Option<usize>,
+    /// Tell backends the client's IP via `X-Forwarded-For` (appended to),
+    /// `X-Real-IP` and `X-Forwarded-Proto`. Routes can override it.
+    #[serde(default)]
+    pub forward_client_ip: bool,
+}
+
 // ==================== Service Abstraction (#61) ====================
 
 #[derive(Debug, Deserialize, Clone)]
@@ -50,9 +195,11 @@ pub struct ServiceConfig {
 #[derive(Debug, Deserialize, Clone, Default)]
 pub struct RouteDefaults {
     pub timeout: Option<String>,
+    pub request_deadline: Option<String>,
     pub retry: Option<RetryConfig>,
     #[serde(default)]
     pub load_balance: LoadBalanceStrategy,
+    pub response_header_allowlist: Option<Vec<String>>,
 }
 
 // ==================== Server Config ====================
@@ -62,6 +209,106 @@ pub struct ServerConfig {
     pub addr: String,
     #[serde(default)]
     pub pool: PoolConfig,
+    #[serde(default)]
+    pub runtime: RuntimeConfig,
+    /// Terminate TLS on `addr` with this cert/key; certs are hot-reloaded on change.
+    pub tls: Option<TlsConfig>,
+    #[serde(default)]
+    pub cache: CacheStoreConfig,
+    pub circuit_breaker: Option<GlobalCircuitBreakerConfig>,
+    /// Enables the `/admin/...` endpoints, guarded by these auth methods.
+    pub admin: Option<AdminConfig>,
+    /// Caps requests proxied at once and admits waiting ones by QoS class.
+    pub qos: Option<QosConfig>,
+    /// Caps retries across all routes so a failing backend can't multiply load.
+    pub retry_budget: Option<RetryBudgetConfig>,
+    /// Match request paths to routes ignoring ASCII case. Backends still get
+    /// the path as the client sent it.
+    #[serde(default)]
+    pub case_insensitive_paths: bool,
+    /// Refuse to load (or reload) a config with more routes than this, so a
+    /// generated config can't grow the gateway's memory unnoticed.
+    pub max_routes: Option<usize>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct AdminConfig {
+    #[serde(deserialize_with = "one_or_many_auth")]
+    pub auth: Vec<AuthConfig>,
+}
+
+/// Where cached responses live. `memory` is per process; `redis` is shared
+/// between instances and needs the `redis` cargo feature.
+#[derive(Debug, Deserialize, Clone)]
+pub struct CacheStoreConfig {
+    #[serde(default)]
+    pub backend: CacheBackend,
+    pub redis_url: Option<String>,
+    #[serde(default = "default_cache_key_prefix")]
+    pub key_prefix: String,
+    #[serde(default = "default_cache_max_capacity")]
+    pub max_capacity: u64,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CacheBackend {
+    #[default]
+    Memory,
+    Redis,
+}
+
+fn default_cache_key_prefix() -> String {
+    "rustygw:cache:".to_string()
+}
+fn default_cache_max_capacity() -> u64 {
+    constants::DEFAULT_RESPONSE_CACHE_CAPACITY
+}
+
+impl Default for CacheStoreConfig {
+    fn default() -> Self {
+        Self {
+            backend: CacheBackend::default(),
+            redis_url: None,
+            key_prefix: default_cache_key_prefix(),
+            max_capacity: default_cache_max_capacity(),
+        }
+    }
+}
+
+/// Tokio runtime tuning. Unset values keep Tokio's defaults
+/// (one worker per core, 512 blocking threads).
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct RuntimeConfig {
+    pub worker_threads: Option<usize>,
+    pub max_blocking_threads: Option<usize>,
+}
+
+impl RuntimeConfig {
+    /// `GATEWAY_WORKER_THREADS` / `GATEWAY_MAX_BLOCKING_THREADS` override the config file.
+    #[must_use]
+    pub fn with_env_overrides(mut self) -> Self {
+        let from_env = |name: &str| std::env::var(name).ok().and_then(|v| v.trim().parse::<usize>().ok());
+        if let Some(n) = from_env("GATEWAY_WORKER_THREADS") {
+            self.worker_threads = Some(n);
+        }
+        if let Some(n) = from_env("GATEWAY_MAX_BLOCKING_THREADS") {
+            self.max_blocking_threads = Some(n);
+        }
+        self
+    }
+
+    pub fn build(&self) -> std::io::Result<tokio::runtime::Runtime> {
+        let mut builder = tokio::runtime::Builder::new_multi_thread();
+        builder.enable_all();
+        if let Some(n) = self.worker_threads.filter(|n| *n > 0) {
+            builder.worker_threads(n);
+        }
+        if let Some(n) = self.max_blocking_threads.filter(|n| *n > 0) {
+            builder.max_blocking_threads(n);
+        }
+        builder.build()
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -76,6 +323,10 @@ pub struct PoolConfig {
     pub request_timeout: String,
     #[serde(default = "default_body_limit")]
     pub body_limit: String,
+    /// Connections opened to each destination at startup, before serving,
+    /// so early requests skip connect/TLS setup. Capped at `max_idle_per_host`.
+    #[serde(default)]
+    pub warmup_connections: usize,
 }
 
 fn default_pool_idle_timeout() -> String {
@@ -102,6 +353,7 @@ impl Default for PoolConfig {
             connect_timeout: default_connect_timeout(),
             request_timeout: default_request_timeout(),
             body_limit: default_body_limit(),
+            warmup_connections: 0,
         }
     }
 }
@@ -145,6 +397,29 @@ pub struct IdentityConfig {
 pub enum AuthType {
     Jwt,
     ApiKey,
+    /// Validates the token against an external introspection endpoint.
+    Introspection,
+    /// Takes the identity from headers set by a trusted peer that already
+    /// authenticated the caller (e.g. a service mesh sidecar), unverified.
+    TrustedHeader,
+    /// Verifies an HMAC-SHA256 signature of the raw body, as webhook
+    /// providers send. Buffers the request body.
+    HmacSignature,
+    /// HTTP Basic credentials, checked against the key store's `users`.
+    Basic,
+}
+
+/// What to do when a dependency (the introspection endpoint, the rate limit
+/// or cache store) is unreachable, as opposed to rejecting the request.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailurePolicy {
+    /// Reject with 503.
+    #[default]
+    FailClosed,
+    /// Carry on without the dependency: no claims, no rate limit, or a cache
+    /// miss. Only for low-sensitivity routes.
+    FailOpen,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -152,6 +427,139 @@ pub struct AuthConfig {
     #[serde(rename = "type")]
     pub auth_type: AuthType,
     pub roles: Option<Vec<String>>,
+    /// Required for `Introspection`: receives `token=<bearer token>` as a form
+    /// POST and answers `{"active": bool, "sub": ..., "roles": [...]}`.
+    pub introspection_url: Option<String>,
+    /// Longest the method may take to verify a request, including calls to
+    /// an introspection or JWKS endpoint. Running out counts as the
+    /// dependency being down, so `on_error` decides.
+    #[serde(default = "default_auth_timeout")]
+    pub timeout: String,
+    /// Unset: `degradation.auth`.
+    pub on_error: Option<FailurePolicy>,
+    /// Required for `TrustedHeader`: peers (CIDR or single IP) whose identity
+    /// headers are believed. The same headers from anyone else are rejected.
+    #[serde(default)]
+    pub trusted_ips: Vec<String>,
+    /// `TrustedHeader`: carries the caller's user id.
+    #[serde(default = "default_user_header")]
+    pub user_header: String,
+    /// `TrustedHeader`: carries the caller's roles, comma-separated.
+    #[serde(default = "default_roles_header")]
+    pub roles_header: String,
+    /// `Jwt`: verify tokens against this JWKS (e.g. an identity provider's
+    /// `/.well-known/jwks.json`), picking the key by the token's `kid`,
+    /// instead of the gateway's own secret or public key.
+    pub jwks_url: Option<String>,
+    /// `Jwt` with `jwks_url`: how long a fetched key set is used before it's
+    /// fetched again. A token with an unknown `kid` triggers a fetch sooner.
+    #[serde(default = "default_jwks_refresh")]
+    pub jwks_refresh: String,
+    /// Claims the caller's token must carry once its roles pass, e.g.
+    /// `{tenant: acme, plan: [pro, enterprise]}`: a value must be equal, a
+    /// list must contain it. A list-valued claim matches if any item does.
+    #[serde(default)]
+    pub required_claims: HashMap<String, ClaimRequirement>,
+    /// `ApiKey`: the header carrying the key, e.g. when `Authorization` holds
+    /// a JWT. Unset: `X-API-Key`, falling back to `Authorization: Bearer`.
+    pub header: Option<String>,
+    /// `Jwt`: tokens must carry this `iss`. Unset: any issuer.
+    pub issuer: Option<String>,
+    /// `Jwt`: tokens must list this in `aud`. Unset: tokens carrying an `aud`
+    /// are rejected, as they always were.
+    pub audience: Option<String>,
+    /// Required for `HmacSignature`: the shared secret, usually `${ENV_VAR}`.
+    pub signing_secret: Option<String>,
+    /// `HmacSignature`: carries `sha256=<hex>` (or bare hex).
+    #[serde(default = "default_signature_header")]
+    pub signature_header: String,
+}
+
+/// What one of `required_claims` accepts.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum ClaimRequirement {
+    OneOf(Vec<serde_json::Value>),
+    Equals(serde_json::Value),
+}
+
+impl ClaimRequirement {
+    pub fn accepts(&self, value: &serde_json::Value) -> bool {
+        match self {
+            ClaimRequirement::OneOf(allowed) => allowed.contains(value),
+            ClaimRequirement::Equals(expected) => expected == value,
+        }
+    }
+}
+
+fn default_auth_timeout() -> String {
+    "2s".to_string()
+}
+fn default_jwks_refresh() -> String {
+    "10m".to_string()
+}
+fn default_user_header() -> String {
+    "x-authenticated-user".to_string()
+}
+fn default_roles_header() -> String {
+    "x-user-roles".to_string()
+}
+fn default_signature_header() -> String {
+    "x-signature".to_string()
+}
+
+/// Accepts `destinations` as plain URLs or `{url, weight}` entries, turning
+/// the latter into the inline `url|weight=N` form.
+fn plain_or_weighted_destinations<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Destination {
+        Url(String),
+        Weighted { url: String, weight: u32 },
+    }
+
+    Ok(Vec::<Destination>::deserialize(deserializer)?
+        .into_iter()
+        .map(|d| match d {
+            Destination::Url(url) => url,
+            Destination::Weighted { url, weight } => format!("{}|weight={}", url, weight),
+        })
+        .collect())
+}
+
+fn one_or_many_auth<'de, D>(deserializer: D) -> Result<Vec<AuthConfig>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let methods = match Option::<serde_yaml::Value>::deserialize(deserializer)? {
+        Some(serde_yaml::Value::Sequence(chain)) => chain,
+        Some(method) => vec![method],
+        None => return Ok(Vec::new()),
+    };
+    // `type: [Jwt, ApiKey]` is shorthand for one method per type, in that
+    // order, sharing the other settings (e.g. `roles`)
+    let mut expanded = Vec::with_capacity(methods.len());
+    for method in methods {
+        match method.get("type") {
+            Some(serde_yaml::Value::Sequence(types)) => {
+                for auth_type in types {
+                    let mut single = method.clone();
+                    if let Some(fields) = single.as_mapping_mut() {
+                        fields.insert("type".into(), auth_type.clone());
+                    }
+                    expanded.push(single);
+                }
+            }
+            _ => expanded.push(method),
+        }
+    }
+    expanded
+        .into_iter()
+        .map(|method| serde_yaml::from_value(method).map_err(serde::de::Error::custom))
+        .collect()
 }
 
 // ==================== Route Config ====================
@@ -162,28 +570,194 @@ pub struct RouteConfig {
     pub path: String,
     #[serde(default)]
     pub destination: String,
-    #[serde(default)]
+    /// Plain URLs, or `{url, weight}` entries for weighted balancing.
+    #[serde(default, deserialize_with = "plain_or_weighted_destinations")]
     pub destinations: Vec<String>,
     pub service: Option<String>,
     #[serde(default)]
     pub load_balance: LoadBalanceStrategy,
-    pub auth: Option<AuthConfig>,
+    /// One auth method, or a list tried in order; the first that
+    /// authenticates supplies the `Claims`.
+    #[serde(default, deserialize_with = "one_or_many_auth")]
+    pub auth: Vec<AuthConfig>,
     pub rate_limit: Option<RateLimitConfig>,
     pub cache: Option<CacheConfig>,
+    /// Answers requests repeating an idempotency key with the first one's
+    /// response instead of calling the backend again.
+    pub idempotency: Option<IdempotencyConfig>,
     pub circuit_breaker: Option<CircuitBreakerConfig>,
     pub health_check: Option<HealthCheckConfig>,
     pub retry: Option<RetryConfig>,
     pub timeout: Option<String>,
+    /// Total time budget from when the gateway receives the request, retries
+    /// included. The remaining budget is sent to backends as `x-request-deadline`.
+    pub request_deadline: Option<String>,
+    /// Per-destination timeouts keyed by destination URL; others use `timeout`.
+    /// Also filled from the inline form `"http://slow:8000|timeout=10s"`.
+    #[serde(default)]
+    pub destination_timeouts: HashMap<String, String>,
+    /// Relative share of traffic per destination URL; unlisted destinations
+    /// weigh 1. Filled from `{url, weight}` entries and the inline form
+    /// `"http://a:8000|weight=9"`.
+    #[serde(default)]
+    pub destination_weights: HashMap<String, u32>,
+    /// Derives the timeout from the route's recent latency instead of using
+    /// `timeout` / `destination_timeouts`.
+    pub adaptive_timeout: Option<AdaptiveTimeoutConfig>,
+    /// Rewrites the path left after the route's prefix before it's
+    /// appended to the destination.
+    pub rewrite: Option<RewriteConfig>,
     pub transform: Option<TransformConfig>,
     #[serde(default)]
     pub tls_skip_verify: bool,
+    /// Backend redirects to follow before relaying the 3xx (default 0: relay).
+    #[serde(default)]
+    pub max_redirects: u32,
+    /// Hosts a followed redirect may go to. `.example.com` also matches subdomains.
+    /// Empty means only the hosts of this route's destinations.
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    /// Forward the request body to the backend as it arrives instead of
+    /// buffering it. Such requests get a single attempt, as the body can't be replayed.
+    #[serde(default)]
+    pub stream_request_body: bool,
+    /// Share one upstream call among identical GET/HEAD requests in flight.
+    #[serde(default)]
+    pub coalesce: bool,
+    /// Upstream status -> status sent to the client, e.g. `{418: 503}`.
+    /// Circuit breakers still judge the upstream status.
+    #[serde(default)]
+    pub status_map: HashMap<u16, u16>,
+    /// `Content-Type` set on responses whose backend sent none.
+    pub default_content_type: Option<String>,
+    /// When set, only these upstream response headers reach the client.
+    /// Headers the gateway adds itself (`x-request-id`, transforms) are kept.
+    pub response_header_allowlist: Option<Vec<String>>,
+    /// Client request headers to let through to the backend: in `allow` mode
+    /// these on top of a small safe default set, in `block` mode every header
+    /// but these. Unset forwards every client header.
+    pub forward_headers: Option<Vec<String>>,
+    #[serde(default)]
+    pub forward_headers_mode: ForwardHeadersMode,
+    /// Sends a share of traffic to a canary destination, rolled back
+    /// automatically when its error rate exceeds the budget.
+    pub canary: Option<CanaryConfig>,
+    /// Lets trusted callers pin one of the route's destinations with a header.
+    pub destination_override: Option<DestinationOverrideConfig>,
+    /// Two destination groups with one `active`; replaces `destination(s)`.
+    /// Switch with `POST /admin/routes/{name}/switch` or by reloading the config.
+    pub blue_green: Option<BlueGreenConfig>,
+    /// Log verbosity for this route's requests (`trace`..`error`), for
+    /// debugging one route without raising the global level.
+    pub log_level: Option<String>,
+    /// Request and response headers whose values go in this route's access
+    /// log lines; credential headers are logged as `[REDACTED]`.
+    #[serde(default)]
+    pub log_headers: Vec<String>,
     pub aggregate: Option<Vec<AggregateSource>>,
+    /// Caps how many `aggregate` sub-requests run at once; unset runs them all together.
+    pub max_concurrent_subrequests: Option<usize>,
+    /// Serve this file (or files under this directory) instead of proxying.
+    pub static_file: Option<String>,
+    /// Overrides `security.forward_client_ip` for this route.
+    pub forward_client_ip: Option<bool>,
+    #[serde(default)]
+    pub middleware: MiddlewareToggles,
+}
+
+/// Per-route switches for the middleware stack. A disabled layer passes the
+/// request straight through, even if the route configures that feature.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Deserialize, Clone)]
+pub struct MiddlewareToggles {
+    #[serde(default = "default_true")]
+    pub auth: bool,
+    #[serde(default = "default_true")]
+    pub rate_limit: bool,
+    #[serde(default = "default_true")]
+    pub cache: bool,
+    #[serde(default = "default_true")]
+    pub circuit_breaker: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for MiddlewareToggles {
+    fn default() -> Self {
+        Self {
+            auth: true,
+            rate_limit: true,
+            cache: true,
+            circuit_breaker: true,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct RateLimitConfig {
+    /// Limit for unauthenticated callers and callers with none of `roles`.
     pub requests: u64,
     pub period: String,
+    /// Per-role limits in priority order; the first role the caller holds wins.
+    #[serde(default)]
+    pub roles: Vec<RoleRateLimit>,
+    /// What a bucket belongs to: `"header:<name>"` (e.g. a tenant id header) or
+    /// `"claim:<name>"` from the authenticated token. Requests without it fall
+    /// back to the client IP; `x-service-name` is ignored, since any caller
+    /// can set it.
+    pub key: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct RoleRateLimit {
+    pub role: String,
+    pub requests: u64,
+    /// Defaults to the route's `period`.
+    pub period: Option<String>,
+}
+
+impl RateLimitConfig {
+    /// Picks the limit for a caller with `user_roles`.
+    /// Returns the matched role (if any), the request count and the period.
+    pub fn limit_for_roles(&self, user_roles: &[String]) -> (Option<&str>, u64, &str) {
+        self.roles.iter().find(|limit| user_roles.contains(&limit.role)).map_or(
+            (None, self.requests, self.period.as_str()),
+            |limit| {
+                (
+                    Some(limit.role.as_str()),
+                    limit.requests,
+                    limit.period.as_deref().unwrap_or(&self.period),
+                )
+            },
+        )
+    }
+}
+
+/// Timeout of `multiplier` times the route's p99 latency over its last
+/// `window` responses, kept within `min`..`max`. `max` applies until
+/// `min_samples` responses have been seen.
+#[derive(Debug, Deserialize, Clone)]
+pub struct AdaptiveTimeoutConfig {
+    #[serde(default = "default_adaptive_multiplier")]
+    pub multiplier: f64,
+    pub min: String,
+    pub max: String,
+    #[serde(default = "default_adaptive_window")]
+    pub window: usize,
+    #[serde(default = "default_adaptive_min_samples")]
+    pub min_samples: usize,
+}
+
+fn default_adaptive_multiplier() -> f64 {
+    3.0
+}
+fn default_adaptive_window() -> usize {
+    100
+}
+fn default_adaptive_min_samples() -> usize {
+    20
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -192,8 +766,21 @@ pub struct RetryConfig {
     pub count: u32,
     #[serde(default)]
     pub retry_on: Vec<u16>,
+    /// Delay before the first retry; each later retry waits twice as long.
     #[serde(default = "default_backoff")]
     pub backoff: String,
+    /// Total attempts, the first included; overrides `count` when set.
+    pub max_attempts: Option<u32>,
+    /// Also retry POST and PATCH requests that carry no `Idempotency-Key`.
+    #[serde(default)]
+    pub retry_non_idempotent: bool,
+}
+
+impl RetryConfig {
+    /// How many times a request may be sent, at least once.
+    pub fn attempts(&self) -> u32 {
+        self.max_attempts.unwrap_or(self.count + 1).max(1)
+    }
 }
 
 fn default_retries() -> u32 {
@@ -214,6 +801,56 @@ pub struct TransformConfig {
     #[serde(default)]
     pub remove_response_headers: Vec<String>,
     pub rewrite_path: Option<String>,
+    #[serde(default)]
+    pub query_params: QueryParamsTransform,
+}
+
+/// Path rewrite for the backend: `strip_prefix` comes off first, then the
+/// first match of `pattern` is replaced by `replacement`, which may refer to
+/// capture groups as `$1` or `${name}`.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct RewriteConfig {
+    pub strip_prefix: Option<String>,
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub replacement: String,
+    /// `pattern`, compiled on first use; validation has checked it compiles.
+    #[serde(skip)]
+    regex: OnceLock<Option<Regex>>,
+}
+
+impl RewriteConfig {
+    pub fn apply<'a>(&self, path: &'a str) -> Cow<'a, str> {
+        let path = match &self.strip_prefix {
+            Some(prefix) => path.strip_prefix(prefix.as_str()).unwrap_or(path),
+            None => path,
+        };
+        let regex = self
+            .regex
+            .get_or_init(|| self.pattern.as_deref().and_then(|p| Regex::new(p).ok()));
+        match regex {
+            Some(regex) => regex.replace(path, self.replacement.as_str()),
+            None => Cow::Borrowed(path),
+        }
+    }
+}
+
+/// Query parameter rewrites applied before forwarding.
+/// Order: remove, then rename, then add (add overrides existing values).
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct QueryParamsTransform {
+    #[serde(default)]
+    pub add: HashMap<String, String>,
+    #[serde(default)]
+    pub remove: Vec<String>,
+    #[serde(default)]
+    pub rename: HashMap<String, String>,
+}
+
+impl QueryParamsTransform {
+    pub fn is_empty(&self) -> bool {
+        self.add.is_empty() && self.remove.is_empty() && self.rename.is_empty()
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -227,6 +864,44 @@ pub struct AggregateSource {
 #[derive(Debug, Deserialize, Clone)]
 pub struct CacheConfig {
     pub ttl: String,
+    /// Randomizes each entry's TTL by up to this percentage either way (0-99)
+    /// so entries cached together don't expire together.
+    #[serde(default)]
+    pub ttl_jitter: u8,
+    /// Client ranges (CIDR or single IP) allowed to force a cache bypass
+    /// with `Cache-Control: no-cache`. Empty means nobody can bypass.
+    #[serde(default)]
+    pub bypass_trusted_ips: Vec<String>,
+    /// Responses larger than this (e.g. `512KB`, `1MB`) are served but not
+    /// cached, so one route can't crowd the shared cache.
+    pub max_entry_size: Option<String>,
+    /// Responses smaller than this are served but not cached; they're cheap
+    /// to fetch again and not worth an entry.
+    pub min_entry_size: Option<String>,
+    /// How long past its `ttl` an entry is still served when the backend
+    /// fails (5xx or unreachable), marked `X-Cache: STALE-ERROR`.
+    pub stale_if_error: Option<String>,
+    /// Only these query parameters are part of the cache key; others (e.g. a
+    /// session id) still reach the backend but don't split the cache. Unset:
+    /// the whole query string counts.
+    pub key_query_params: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct IdempotencyConfig {
+    /// How long after the first request its response is replayed, e.g. `24h`.
+    #[serde(default = "default_idempotency_window")]
+    pub window: String,
+    /// The header carrying the client's key.
+    #[serde(default = "default_idempotency_header")]
+    pub header: String,
+}
+
+fn default_idempotency_window() -> String {
+    "24h".to_string()
+}
+fn default_idempotency_header() -> String {
+    "idempotency-key".to_string()
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -234,6 +909,176 @@ pub struct CircuitBreakerConfig {
     pub failure_threshold: u32,
     pub success_threshold: u32,
     pub open_duration: String,
+    /// Enables backoff: each trip in a row multiplies the open duration by
+    /// `backoff_multiplier`, up to this cap.
+    pub max_open_duration: Option<String>,
+    #[serde(default = "default_cb_backoff_multiplier")]
+    pub backoff_multiplier: f64,
+    /// Staying closed this long resets the backoff. Defaults to `max_open_duration`.
+    pub backoff_reset_after: Option<String>,
+}
+
+fn default_cb_backoff_multiplier() -> f64 {
+    2.0
+}
+
+impl CircuitBreakerConfig {
+    /// Open duration for the `trips`-th trip in a row (1-based).
+    pub fn open_duration_for(&self, trips: u32) -> Duration {
+        use crate::middleware::rate_limiter::rate_limit::parse_duration;
+        let base = parse_duration(&self.open_duration).unwrap_or_default();
+        let Some(max) = self.max_open_duration.as_deref().and_then(|m| parse_duration(m).ok()) else {
+            return base;
+        };
+        let trips = i32::try_from(trips.saturating_sub(1)).unwrap_or(i32::MAX);
+        let factor = self.backoff_multiplier.max(1.0).powi(trips);
+        base.mul_f64(factor.min(f64::from(u32::MAX))).min(max.max(base))
+    }
+
+    pub fn backoff_reset_after(&self) -> Duration {
+        self.backoff_reset_after
+            .as_deref()
+            .or(self.max_open_duration.as_deref())
+            .and_then(|d| crate::middleware::rate_limiter::rate_limit::parse_duration(d).ok())
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BlueGreenConfig {
+    pub blue: Vec<String>,
+    pub green: Vec<String>,
+    #[serde(default)]
+    pub active: DeploymentColor,
+}
+
+impl BlueGreenConfig {
+    pub fn group(&self, color: DeploymentColor) -> &[String] {
+        match color {
+            DeploymentColor::Blue => &self.blue,
+            DeploymentColor::Green => &self.green,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DeploymentColor {
+    #[default]
+    Blue,
+    Green,
+}
+
+impl DeploymentColor {
+    #[must_use]
+    pub fn other(self) -> Self {
+        match self {
+            DeploymentColor::Blue => DeploymentColor::Green,
+            DeploymentColor::Green => DeploymentColor::Blue,
+        }
+    }
+}
+
+/// Traffic split between a route's stable destinations and one canary.
+#[derive(Deserialize, Debug, Clone)]
+pub struct CanaryConfig {
+    pub destination: String,
+    /// Percentage of requests (0-100) sent to the canary.
+    pub weight: u8,
+    /// Fraction of failed canary requests (0.0-1.0) that triggers rollback.
+    #[serde(default = "default_canary_error_rate_threshold")]
+    pub error_rate_threshold: f64,
+    /// Don't evaluate the error rate until the window has this many canary requests.
+    #[serde(default = "default_canary_min_requests")]
+    pub min_requests: u64,
+    #[serde(default = "default_canary_window")]
+    pub window: String,
+}
+
+fn default_canary_error_rate_threshold() -> f64 {
+    0.1
+}
+fn default_canary_min_requests() -> u64 {
+    10
+}
+fn default_canary_window() -> String {
+    "60s".to_string()
+}
+
+/// Gateway-wide breaker: opens when the share of 5xx responses across all
+/// routes within `window` reaches `error_rate_threshold`.
+#[derive(Deserialize, Debug, Clone)]
+pub struct GlobalCircuitBreakerConfig {
+    /// Fraction of failed requests (0.0-1.0) that opens the breaker.
+    pub error_rate_threshold: f64,
+    /// Don't evaluate the error rate until the window has this many requests.
+    #[serde(default = "default_global_cb_min_requests")]
+    pub min_requests: u64,
+    #[serde(default = "default_global_cb_window")]
+    pub window: String,
+    pub open_duration: String,
+    #[serde(default = "default_global_cb_success_threshold")]
+    pub success_threshold: u32,
+}
+
+fn default_global_cb_min_requests() -> u64 {
+    20
+}
+fn default_global_cb_window() -> String {
+    "10s".to_string()
+}
+fn default_global_cb_success_threshold() -> u32 {
+    1
+}
+
+/// Gateway-wide concurrency cap. Once `max_concurrent` requests are being
+/// proxied, new ones wait and are admitted highest-priority class first.
+#[derive(Deserialize, Debug, Clone)]
+pub struct QosConfig {
+    pub max_concurrent: usize,
+    /// Requests allowed to wait for a slot; beyond this they get 503.
+    #[serde(default = "default_qos_max_queue")]
+    pub max_queue: usize,
+    /// How long a request waits for a slot before it gets 503.
+    #[serde(default = "default_qos_queue_timeout")]
+    pub queue_timeout: String,
+    /// Header naming the caller's class. Clients can send it too, so only set
+    /// this behind a proxy that overwrites it.
+    pub class_header: Option<String>,
+    #[serde(default)]
+    pub classes: Vec<QosClass>,
+}
+
+/// Requests matching no class get priority 0.
+#[derive(Deserialize, Debug, Clone)]
+pub struct QosClass {
+    pub name: String,
+    /// Higher is admitted first.
+    pub priority: u8,
+    /// Callers whose auth claims carry any of these roles belong to the class.
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+fn default_qos_max_queue() -> usize {
+    1000
+}
+fn default_qos_queue_timeout() -> String {
+    "5s".to_string()
+}
+
+/// Token bucket shared by every route's retries: it holds up to `retries`
+/// tokens and refills at `retries` per `period`. A retry takes a token; once
+/// none are left the failure is returned without retrying.
+#[derive(Deserialize, Debug, Clone)]
+pub struct RetryBudgetConfig {
+    pub retries: u32,
+    #[serde(default = "default_retry_budget_period")]
+    pub period: String,
+}
+
+fn default_retry_budget_period() -> String {
+    "1s".to_string()
 }
 
 // ==================== Observability ====================
@@ -242,18 +1087,230 @@ pub struct CircuitBreakerConfig {
 pub struct ObservabilityConfig {
     #[serde(default)]
     pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub request_id: RequestIdConfig,
+    /// Writes sampled requests to a JSONL file for `rustygw replay`.
+    pub capture: Option<CaptureConfig>,
+    /// Requests slower than this (e.g. "2s", "500ms") are logged at WARN.
+    #[serde(default, deserialize_with = "optional_duration")]
+    pub slow_request_threshold: Option<Duration>,
+}
+
+/// An optional duration setting, parsed when the config is loaded rather
+/// than on every use.
+fn optional_duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|s| {
+            crate::middleware::rate_limiter::rate_limit::parse_duration(&s)
+                .map_err(|e| serde::de::Error::custom(format!("invalid duration '{}': {}", s, e)))
+        })
+        .transpose()
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct CaptureConfig {
+    pub path: String,
+    /// Fraction of requests to capture, 0.0-1.0.
+    #[serde(default = "default_capture_sample_rate")]
+    pub sample_rate: f64,
+    /// Stop capturing after this many requests.
+    #[serde(default = "default_capture_max_requests")]
+    pub max_requests: u64,
+    /// Larger (or unsized) bodies are recorded without the body.
+    #[serde(default = "default_capture_max_body_bytes")]
+    pub max_body_bytes: usize,
+    /// Header values replaced with `[REDACTED]`; matched case-insensitively.
+    #[serde(default = "default_capture_redact_headers")]
+    pub redact_headers: Vec<String>,
+}
+
+fn default_capture_sample_rate() -> f64 {
+    0.01
+}
+fn default_capture_max_requests() -> u64 {
+    10_000
+}
+fn default_capture_max_body_bytes() -> usize {
+    64 * 1024
+}
+fn default_capture_redact_headers() -> Vec<String> {
+    crate::features::capture::SENSITIVE_HEADERS
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// How ids are generated for requests that arrive without `x-request-id`,
+/// and when the client's own id is kept. Read at startup; changing it
+/// requires a restart.
 #[derive(Debug, Deserialize, Clone, Default)]
+pub struct RequestIdConfig {
+    #[serde(default)]
+    pub format: RequestIdFormat,
+    #[serde(default)]
+    pub policy: RequestIdPolicy,
+}
+
+/// Whether an inbound `x-request-id` is used. The id ends up in logs and
+/// backend requests, so arbitrary client input shouldn't be taken blindly.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestIdPolicy {
+    /// Every request gets a generated id; the client's is ignored.
+    AlwaysGenerate,
+    /// The client's id is kept if it is 1-128 chars of `[A-Za-z0-9._:-]`.
+    #[default]
+    TrustIfValid,
+    /// The client's id is kept as sent; for gateways behind trusted callers.
+    Trust,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestIdFormat {
+    #[default]
+    UuidV4,
+    Ulid,
+    Nanoid,
+}
+
+/// Lets trusted callers pick the backend with a header, for debugging or
+/// trying a canary. Only the route's own destinations, canary included, can
+/// be picked; the header never reaches the backend.
+#[derive(Debug, Deserialize, Clone)]
+pub struct DestinationOverrideConfig {
+    #[serde(default = "default_destination_override_header")]
+    pub header: String,
+    /// Peers (CIDR or single IP) whose header is honored; anyone else's is ignored.
+    pub trusted_ips: Vec<String>,
+}
+
+fn default_destination_override_header() -> String {
+    "x-route-to".to_string()
+}
+
+/// How a route's `forward_headers` list is applied.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ForwardHeadersMode {
+    #[default]
+    Allow,
+    Block,
+}
+
+#[derive(Debug, Deserialize, Clone)]
 pub struct MetricsConfig {
     #[serde(default)]
     pub enabled: bool,
+    /// Upper bound on distinct `route` label values; extra routes report as "other".
+    #[serde(default = "default_max_route_labels")]
+    pub max_route_labels: usize,
+    #[serde(default)]
+    pub exporter: MetricsExporter,
+    /// Required when `exporter: statsd`.
+    pub statsd: Option<StatsdConfig>,
+    /// Required when `exporter: otlp`.
+    pub otlp: Option<OtlpConfig>,
+}
+
+/// Where metrics go. Prometheus is scraped at `/metrics`; the others push.
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricsExporter {
+    #[default]
+    Prometheus,
+    Statsd,
+    Otlp,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct StatsdConfig {
+    /// UDP address of the StatsD / DogStatsD agent, e.g. `127.0.0.1:8125`.
+    pub addr: String,
+    /// Prepended to every metric name, e.g. `rustygw.`.
+    #[serde(default)]
+    pub prefix: String,
+    /// Send labels as DogStatsD tags (`|#route:users`). Plain StatsD has no tags.
+    #[serde(default = "default_true")]
+    pub tags: bool,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct OtlpConfig {
+    /// Collector base URL; metrics are POSTed as JSON to `{endpoint}/v1/metrics`.
+    pub endpoint: String,
+    #[serde(default = "default_otlp_interval")]
+    pub interval: String,
+}
+
+fn default_otlp_interval() -> String {
+    "10s".to_string()
+}
+
+fn default_max_route_labels() -> usize {
+    100
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_route_labels: default_max_route_labels(),
+            exporter: MetricsExporter::default(),
+            statsd: None,
+            otlp: None,
+        }
+    }
 }
 
 // ==================== Route helpers ====================
 
 impl RouteConfig {
+    /// Whether the upstream response header `name` may be passed to the client.
+    /// `Content-Encoding` always is: the body is forwarded still encoded.
+    pub fn response_header_allowed(&self, name: &http::HeaderName) -> bool {
+        *name == http::header::CONTENT_ENCODING
+            || self
+                .response_header_allowlist
+                .as_ref()
+                .is_none_or(|allowed| allowed.iter().any(|h| h.eq_ignore_ascii_case(name.as_str())))
+    }
+
+    /// Whether the client request header `name` may be passed to the backend.
+    pub fn request_header_forwarded(&self, name: &http::HeaderName) -> bool {
+        let Some(listed) = &self.forward_headers else {
+            return true;
+        };
+        let in_list = listed.iter().any(|h| h.eq_ignore_ascii_case(name.as_str()));
+        match self.forward_headers_mode {
+            ForwardHeadersMode::Allow => in_list || crate::proxy::DEFAULT_FORWARD_HEADERS.contains(&name.as_str()),
+            ForwardHeadersMode::Block => !in_list,
+        }
+    }
+
+    /// What follows the route's path in `request_path`, in the client's
+    /// casing. The prefix compares ignoring ASCII case, as the route may have
+    /// been matched with `case_insensitive_paths`.
+    pub fn strip_path_prefix<'a>(&self, request_path: &'a str) -> Option<&'a str> {
+        let prefix = request_path.get(..self.path.len())?;
+        prefix
+            .eq_ignore_ascii_case(&self.path)
+            .then(|| &request_path[self.path.len()..])
+    }
+
+    /// Every destination of the route, both blue-green groups included.
     pub fn all_destinations(&self) -> Vec<&str> {
+        if let Some(blue_green) = &self.blue_green {
+            return blue_green
+                .blue
+                .iter()
+                .chain(&blue_green.green)
+                .map(String::as_str)
+                .collect();
+        }
         if self.destinations.is_empty() {
             if self.destination.is_empty() {
                 vec![]
@@ -261,7 +1318,50 @@ impl RouteConfig {
                 vec![&self.destination]
             }
         } else {
-            self.destinations.iter().map(|s| s.as_str()).collect()
+            self.destinations.iter().map(String::as_str).collect()
+        }
+    }
+
+    /// The route's destination (canary included) that `requested` names,
+    /// ignoring a trailing slash; `None` for anything else.
+    pub fn own_destination(&self, requested: &str) -> Option<&str> {
+        let requested = requested.trim().trim_end_matches('/');
+        self.all_destinations()
+            .into_iter()
+            .chain(self.canary.as_ref().map(|c| c.destination.as_str()))
+            .find(|d| d.trim_end_matches('/') == requested)
+    }
+
+    /// Timeout for requests to `destination`, falling back to the route timeout.
+    pub fn timeout_for(&self, destination: &str) -> Option<&str> {
+        self.destination_timeouts
+            .get(destination)
+            .or(self.timeout.as_ref())
+            .map(String::as_str)
+    }
+
+    /// Moves inline `|timeout=...` and `|weight=...` options out of the destination URLs.
+    fn split_destination_options(&mut self) {
+        let mut timeouts = Vec::new();
+        let mut weights = Vec::new();
+        for dest in std::iter::once(&mut self.destination).chain(self.destinations.iter_mut()) {
+            if let Some((url, options)) = dest.split_once('|') {
+                for option in options.split('|') {
+                    if let Some(timeout) = option.trim().strip_prefix("timeout=") {
+                        timeouts.push((url.trim().to_string(), timeout.trim().to_string()));
+                    } else if let Some(weight) = option.trim().strip_prefix("weight=") {
+                        // Unparseable weights become 0 so validation reports them
+                        weights.push((url.trim().to_string(), weight.trim().parse().unwrap_or(0)));
+                    }
+                }
+                *dest = url.trim().to_string();
+            }
+        }
+        for (url, timeout) in timeouts {
+            self.destination_timeouts.entry(url).or_insert(timeout);
+        }
+        for (url, weight) in weights {
+            self.destination_weights.entry(url).or_insert(weight);
         }
     }
 }
@@ -269,14 +1369,14 @@ impl RouteConfig {
 // ==================== Config Loading ====================
 
 impl GatewayConfig {
-    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, anyhow::Error> {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
         let path = path.as_ref();
         let content = fs::read_to_string(path)?;
 
         // #63: Environment variable interpolation
         let content = interpolate_env_vars(&content);
 
-        let mut config: GatewayConfig = serde_yaml::from_str(&content)?;
+        let mut config: GatewayConfig = serde_yaml::from_str(&content).map_err(|e| ConfigError::parse_file(path, e))?;
 
         // #65: Process includes
         let base_dir = path.parent().unwrap_or(Path::new("."));
@@ -284,7 +1384,8 @@ impl GatewayConfig {
             let include_path = base_dir.join(&include_pattern);
             if include_path.is_file() {
                 let inc_content = interpolate_env_vars(&fs::read_to_string(&include_path)?);
-                let inc: serde_yaml::Value = serde_yaml::from_str(&inc_content)?;
+                let inc: serde_yaml::Value =
+                    serde_yaml::from_str(&inc_content).map_err(|e| ConfigError::parse_file(&include_path, e))?;
                 merge_include(&mut config, &inc, &include_pattern)?;
                 info!(file = %include_pattern, "Loaded include file");
             } else {
@@ -292,26 +1393,40 @@ impl GatewayConfig {
                 for entry in glob::glob(include_path.to_str().unwrap_or(""))? {
                     let entry = entry?;
                     let inc_content = interpolate_env_vars(&fs::read_to_string(&entry)?);
-                    let inc: serde_yaml::Value = serde_yaml::from_str(&inc_content)?;
+                    let inc: serde_yaml::Value =
+                        serde_yaml::from_str(&inc_content).map_err(|e| ConfigError::parse_file(&entry, e))?;
                     merge_include(&mut config, &inc, entry.to_str().unwrap_or(""))?;
                     info!(file = ?entry, "Loaded include file");
                 }
             }
         }
 
+        config.finalize()?;
+        Ok(config)
+    }
+
+    /// Parses a config from a YAML string. Includes are not processed since
+    /// there is no base directory to resolve them against.
+    pub fn from_yaml(content: &str) -> Result<Self, ConfigError> {
+        let mut config: GatewayConfig = serde_yaml::from_str(&interpolate_env_vars(content))?;
+        config.finalize()?;
+        Ok(config)
+    }
+
+    fn finalize(&mut self) -> Result<(), ConfigError> {
         // #61: Resolve service references
-        config.resolve_services();
+        self.resolve_services();
 
         // #62: Apply defaults
-        config.apply_defaults();
+        self.apply_defaults();
 
         // #64: Validate
-        config.validate()?;
+        self.validate()?;
 
         // Build route matching tree
-        config.build_route_tree();
+        self.build_route_tree();
 
-        Ok(config)
+        Ok(())
     }
 
     /// #61: Resolve service references in routes
@@ -325,20 +1440,20 @@ impl GatewayConfig {
                 // Set destinations from service
                 if route_mut.destinations.is_empty() && route_mut.destination.is_empty() {
                     if !svc.urls.is_empty() {
-                        route_mut.destinations = svc.urls.clone();
+                        route_mut.destinations.clone_from(&svc.urls);
                     } else if let Some(url) = &svc.url {
-                        route_mut.destination = url.clone();
+                        route_mut.destination.clone_from(url);
                     }
                 }
                 // Inherit service config if not set on route
                 if route_mut.health_check.is_none() {
-                    route_mut.health_check = svc.health_check.clone();
+                    route_mut.health_check.clone_from(&svc.health_check);
                 }
                 if route_mut.retry.is_none() {
-                    route_mut.retry = svc.retry.clone();
+                    route_mut.retry.clone_from(&svc.retry);
                 }
                 if route_mut.timeout.is_none() {
-                    route_mut.timeout = svc.timeout.clone();
+                    route_mut.timeout.clone_from(&svc.timeout);
                 }
                 if !route_mut.tls_skip_verify {
                     route_mut.tls_skip_verify = svc.tls_skip_verify;
@@ -353,115 +1468,728 @@ impl GatewayConfig {
     /// #62: Apply global defaults to routes missing config
     fn apply_defaults(&mut self) {
         let defaults = self.defaults.clone();
+        let auth_on_error = self.degradation.auth;
         for route in &mut self.routes {
             let route_mut = Arc::make_mut(route);
+            route_mut.split_destination_options();
+            for auth in &mut route_mut.auth {
+                auth.on_error.get_or_insert(auth_on_error);
+            }
             if route_mut.timeout.is_none() {
-                route_mut.timeout = defaults.timeout.clone();
+                route_mut.timeout.clone_from(&defaults.timeout);
             }
             if route_mut.retry.is_none() {
-                route_mut.retry = defaults.retry.clone();
+                route_mut.retry.clone_from(&defaults.retry);
+            }
+            if route_mut.request_deadline.is_none() {
+                route_mut.request_deadline.clone_from(&defaults.request_deadline);
+            }
+            if route_mut.response_header_allowlist.is_none() {
+                route_mut
+                    .response_header_allowlist
+                    .clone_from(&defaults.response_header_allowlist);
             }
         }
     }
 
     /// #64: Validate config and return clear errors
-    fn validate(&self) -> Result<(), anyhow::Error> {
+    #[allow(clippy::too_many_lines)]
+    fn validate(&self) -> Result<(), ConfigError> {
         let mut errors = Vec::new();
+        let mut seen_names = HashSet::new();
+        let mut seen_paths: HashMap<String, &str> = HashMap::new();
+
+        if let Some(max) = self.server.max_routes
+            && self.routes.len() > max
+        {
+            errors.push(ConfigError::TooManyRoutes {
+                count: self.routes.len(),
+                max,
+            });
+        }
+
+        if let Some(cb) = &self.server.circuit_breaker {
+            if !(cb.error_rate_threshold > 0.0 && cb.error_rate_threshold <= 1.0) {
+                errors.push(ConfigError::InvalidGlobalCircuitBreaker(format!(
+                    "error_rate_threshold must be in (0, 1], got {}",
+                    cb.error_rate_threshold
+                )));
+            }
+            for (field, value) in [("window", &cb.window), ("open_duration", &cb.open_duration)] {
+                if let Err(e) = crate::middleware::rate_limiter::rate_limit::parse_duration(value) {
+                    errors.push(ConfigError::InvalidGlobalCircuitBreaker(format!(
+                        "{} '{}': {}",
+                        field, value, e
+                    )));
+                }
+            }
+        }
+
+        if let Some(tls) = &self.server.tls
+            && let Err(e) = tls.check()
+        {
+            errors.push(ConfigError::InvalidTls(e.to_string()));
+        }
+
+        if let Some(qos) = &self.server.qos {
+            if qos.max_concurrent == 0 {
+                errors.push(ConfigError::InvalidQos("max_concurrent must be at least 1".to_string()));
+            }
+            if let Err(e) = crate::middleware::rate_limiter::rate_limit::parse_duration(&qos.queue_timeout) {
+                errors.push(ConfigError::InvalidQos(format!(
+                    "queue_timeout '{}': {}",
+                    qos.queue_timeout, e
+                )));
+            }
+            if let Some(header) = &qos.class_header
+                && http::HeaderName::from_bytes(header.as_bytes()).is_err()
+            {
+                errors.push(ConfigError::InvalidQos(format!(
+                    "class_header '{}' is not a valid header name",
+                    header
+                )));
+            }
+            let mut names = HashSet::new();
+            for class in &qos.classes {
+                if !names.insert(class.name.as_str()) {
+                    errors.push(ConfigError::InvalidQos(format!("duplicate class '{}'", class.name)));
+                }
+            }
+        }
+
+        if let Some(shards) = self.tuning.coalesce_shards
+            && (shards < 2 || !shards.is_power_of_two())
+        {
+            errors.push(ConfigError::InvalidTuning(format!(
+                "coalesce_shards must be a power of two greater than 1, got {}",
+                shards
+            )));
+        }
+        match crate::middleware::rate_limiter::rate_limit::parse_duration(&self.tuning.store_cleanup_interval) {
+            Ok(interval) if interval.is_zero() => errors.push(ConfigError::InvalidTuning(
+                "store_cleanup_interval must be greater than zero".to_string(),
+            )),
+            Ok(_) => {}
+            Err(_) => errors.push(ConfigError::InvalidTuning(format!(
+                "store_cleanup_interval '{}' is not a valid duration",
+                self.tuning.store_cleanup_interval
+            ))),
+        }
+        if self.security.max_uri_length == Some(0) {
+            errors.push(ConfigError::InvalidSecurity(
+                "max_uri_length must be at least 1".to_string(),
+            ));
+        }
+        if self.tuning.store_cleanup_batch == Some(0) {
+            errors.push(ConfigError::InvalidTuning(
+                "store_cleanup_batch must be at least 1".to_string(),
+            ));
+        }
+
+        if let Some(budget) = &self.server.retry_budget {
+            match crate::middleware::rate_limiter::rate_limit::parse_duration(&budget.period) {
+                Ok(period) if period.is_zero() => {
+                    errors.push(ConfigError::InvalidRetryBudget(
+                        "period must be greater than zero".to_string(),
+                    ));
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    errors.push(ConfigError::InvalidRetryBudget(format!(
+                        "period '{}': {}",
+                        budget.period, e
+                    )));
+                }
+            }
+        }
+
+        let metrics = &self.observability.metrics;
+        match metrics.exporter {
+            MetricsExporter::Statsd if metrics.statsd.is_none() => errors.push(ConfigError::InvalidObservability(
+                "metrics exporter 'statsd' requires metrics.statsd".to_string(),
+            )),
+            MetricsExporter::Prometheus | MetricsExporter::Statsd => {}
+            MetricsExporter::Otlp => match &metrics.otlp {
+                None => errors.push(ConfigError::InvalidObservability(
+                    "metrics exporter 'otlp' requires metrics.otlp".to_string(),
+                )),
+                Some(otlp) => {
+                    if let Err(e) = crate::middleware::rate_limiter::rate_limit::parse_duration(&otlp.interval) {
+                        errors.push(ConfigError::InvalidObservability(format!(
+                            "metrics.otlp.interval '{}': {}",
+                            otlp.interval, e
+                        )));
+                    }
+                }
+            },
+        }
 
         for route in &self.routes {
+            if !seen_names.insert(route.name.as_str()) {
+                errors.push(ConfigError::DuplicateRouteName(route.name.clone()));
+            }
+            let match_path = self.route_match_path(&route.path);
+            match seen_paths.get(&match_path) {
+                Some(existing) => errors.push(ConfigError::DuplicateRoutePath {
+                    path: route.path.clone(),
+                    route: route.name.clone(),
+                    existing: (*existing).to_string(),
+                }),
+                None => {
+                    seen_paths.insert(match_path, route.name.as_str());
+                }
+            }
+
             // Check service reference exists
             if let Some(svc_name) = &route.service
                 && !self.services.contains_key(svc_name)
             {
-                errors.push(format!(
-                    "Route '{}' references service '{}' which is not defined in services",
-                    route.path, svc_name
-                ));
+                errors.push(ConfigError::UnknownService {
+                    route: route.path.clone(),
+                    service: svc_name.clone(),
+                });
             }
 
-            // Check route has at least one destination (unless aggregate)
+            // Check route has at least one destination (unless aggregate or static)
             if route.aggregate.is_none()
+                && route.static_file.is_none()
+                && route.blue_green.is_none()
                 && route.destination.is_empty()
                 && route.destinations.is_empty()
                 && route.service.is_none()
             {
-                errors.push(format!(
-                    "Route '{}' has no destination, destinations, or service defined",
-                    route.path
-                ));
+                errors.push(ConfigError::MissingDestination(route.path.clone()));
+            }
+
+            for (from, to) in &route.status_map {
+                if let Some(code) = [from, to]
+                    .into_iter()
+                    .find(|c| http::StatusCode::from_u16(**c).is_err())
+                {
+                    errors.push(ConfigError::InvalidStatusMap {
+                        route: route.path.clone(),
+                        reason: format!("{} is not a valid status code", code),
+                    });
+                }
+            }
+
+            if let Some(over) = &route.destination_override {
+                if over.trusted_ips.is_empty() {
+                    errors.push(ConfigError::InvalidDestinationOverride {
+                        route: route.path.clone(),
+                        reason: "trusted_ips is required".to_string(),
+                    });
+                }
+                for range in over
+                    .trusted_ips
+                    .iter()
+                    .filter(|r| !crate::utils::ip_range::is_valid_range(r))
+                {
+                    errors.push(ConfigError::InvalidDestinationOverride {
+                        route: route.path.clone(),
+                        reason: format!("trusted_ips entry '{}' is not an IP or CIDR range", range),
+                    });
+                }
+                if http::HeaderName::from_bytes(over.header.as_bytes()).is_err() {
+                    errors.push(ConfigError::InvalidDestinationOverride {
+                        route: route.path.clone(),
+                        reason: format!("'{}' is not a valid header name", over.header),
+                    });
+                }
+            }
+
+            for auth in &route.auth {
+                if auth.auth_type == AuthType::Introspection && auth.introspection_url.is_none() {
+                    errors.push(ConfigError::InvalidAuth {
+                        route: route.path.clone(),
+                        reason: "Introspection requires introspection_url".to_string(),
+                    });
+                }
+                if let Err(e) = crate::middleware::rate_limiter::rate_limit::parse_duration(&auth.timeout) {
+                    errors.push(ConfigError::InvalidAuth {
+                        route: route.path.clone(),
+                        reason: format!("timeout '{}': {}", auth.timeout, e),
+                    });
+                }
+                if auth.jwks_url.is_some() && auth.auth_type != AuthType::Jwt {
+                    errors.push(ConfigError::InvalidAuth {
+                        route: route.path.clone(),
+                        reason: "jwks_url only applies to Jwt".to_string(),
+                    });
+                }
+                if let Some(header) = &auth.header {
+                    if auth.auth_type != AuthType::ApiKey {
+                        errors.push(ConfigError::InvalidAuth {
+                            route: route.path.clone(),
+                            reason: "header only applies to ApiKey".to_string(),
+                        });
+                    } else if http::HeaderName::from_bytes(header.as_bytes()).is_err() {
+                        errors.push(ConfigError::InvalidAuth {
+                            route: route.path.clone(),
+                            reason: format!("'{}' is not a valid header name", header),
+                        });
+                    }
+                }
+                if (auth.issuer.is_some() || auth.audience.is_some()) && auth.auth_type != AuthType::Jwt {
+                    errors.push(ConfigError::InvalidAuth {
+                        route: route.path.clone(),
+                        reason: "issuer and audience only apply to Jwt".to_string(),
+                    });
+                }
+                if auth.auth_type == AuthType::TrustedHeader {
+                    if auth.trusted_ips.is_empty() {
+                        errors.push(ConfigError::InvalidAuth {
+                            route: route.path.clone(),
+                            reason: "TrustedHeader requires trusted_ips".to_string(),
+                        });
+                    }
+                    for range in auth
+                        .trusted_ips
+                        .iter()
+                        .filter(|r| !crate::utils::ip_range::is_valid_range(r))
+                    {
+                        errors.push(ConfigError::InvalidAuth {
+                            route: route.path.clone(),
+                            reason: format!("trusted_ips entry '{}' is not an IP or CIDR range", range),
+                        });
+                    }
+                    for header in [&auth.user_header, &auth.roles_header] {
+                        if http::HeaderName::from_bytes(header.as_bytes()).is_err() {
+                            errors.push(ConfigError::InvalidAuth {
+                                route: route.path.clone(),
+                                reason: format!("'{}' is not a valid header name", header),
+                            });
+                        }
+                    }
+                }
+                if auth.auth_type == AuthType::HmacSignature {
+                    if auth.signing_secret.as_deref().is_none_or(str::is_empty) {
+                        errors.push(ConfigError::InvalidAuth {
+                            route: route.path.clone(),
+                            reason: "HmacSignature requires signing_secret".to_string(),
+                        });
+                    }
+                    if http::HeaderName::from_bytes(auth.signature_header.as_bytes()).is_err() {
+                        errors.push(ConfigError::InvalidAuth {
+                            route: route.path.clone(),
+                            reason: format!("'{}' is not a valid header name", auth.signature_header),
+                        });
+                    }
+                }
+            }
+
+            if let Some(cb) = &route.circuit_breaker {
+                let durations = [
+                    ("open_duration", Some(&cb.open_duration)),
+                    ("max_open_duration", cb.max_open_duration.as_ref()),
+                    ("backoff_reset_after", cb.backoff_reset_after.as_ref()),
+                ];
+                for (field, value) in durations.into_iter().filter_map(|(f, v)| v.map(|v| (f, v))) {
+                    if let Err(e) = crate::middleware::rate_limiter::rate_limit::parse_duration(value) {
+                        errors.push(ConfigError::InvalidCircuitBreaker {
+                            route: route.path.clone(),
+                            reason: format!("{} '{}': {}", field, value, e),
+                        });
+                    }
+                }
+                if cb.backoff_multiplier < 1.0 {
+                    errors.push(ConfigError::InvalidCircuitBreaker {
+                        route: route.path.clone(),
+                        reason: format!("backoff_multiplier must be at least 1, got {}", cb.backoff_multiplier),
+                    });
+                }
+            }
+
+            if let Some(content_type) = &route.default_content_type
+                && http::HeaderValue::from_str(content_type).is_err()
+            {
+                errors.push(ConfigError::InvalidDefaultContentType {
+                    route: route.path.clone(),
+                    value: content_type.clone(),
+                });
+            }
+
+            for header in route.response_header_allowlist.iter().flatten() {
+                if http::HeaderName::from_bytes(header.as_bytes()).is_err() {
+                    errors.push(ConfigError::InvalidResponseHeaderAllowlist {
+                        route: route.path.clone(),
+                        header: header.clone(),
+                    });
+                }
+            }
+
+            if let Some(pattern) = route.rewrite.as_ref().and_then(|r| r.pattern.as_ref())
+                && let Err(e) = Regex::new(pattern)
+            {
+                errors.push(ConfigError::InvalidRewrite {
+                    route: route.path.clone(),
+                    reason: format!("pattern '{}': {}", pattern, e),
+                });
+            }
+
+            for header in route.forward_headers.iter().flatten() {
+                if http::HeaderName::from_bytes(header.as_bytes()).is_err() {
+                    errors.push(ConfigError::InvalidForwardHeaders {
+                        route: route.path.clone(),
+                        header: header.clone(),
+                    });
+                }
+            }
+
+            if let Some(level) = &route.log_level
+                && level.parse::<tracing::Level>().is_err()
+            {
+                errors.push(ConfigError::InvalidLogLevel {
+                    route: route.path.clone(),
+                    level: level.clone(),
+                });
+            }
+
+            if let Some(deadline) = &route.request_deadline {
+                match crate::middleware::rate_limiter::rate_limit::parse_duration(deadline) {
+                    Ok(d) if d.is_zero() => errors.push(ConfigError::InvalidRequestDeadline {
+                        route: route.path.clone(),
+                        reason: "must be greater than zero".to_string(),
+                    }),
+                    Ok(_) => {}
+                    Err(e) => errors.push(ConfigError::InvalidRequestDeadline {
+                        route: route.path.clone(),
+                        reason: format!("'{}': {}", deadline, e),
+                    }),
+                }
+            }
+
+            for (destination, _) in route.destination_weights.iter().filter(|(_, w)| **w == 0) {
+                errors.push(ConfigError::InvalidDestinationWeight {
+                    route: route.path.clone(),
+                    destination: destination.clone(),
+                });
+            }
+
+            if let Some(adaptive) = &route.adaptive_timeout {
+                let invalid = |reason: String| ConfigError::InvalidAdaptiveTimeout {
+                    route: route.path.clone(),
+                    reason,
+                };
+                let parse = crate::middleware::rate_limiter::rate_limit::parse_duration;
+                match (parse(&adaptive.min), parse(&adaptive.max)) {
+                    (Ok(min), Ok(max)) if min > max => errors.push(invalid(format!(
+                        "min '{}' is above max '{}'",
+                        adaptive.min, adaptive.max
+                    ))),
+                    (min, max) => {
+                        for (field, value, result) in [("min", &adaptive.min, min), ("max", &adaptive.max, max)] {
+                            if let Err(e) = result {
+                                errors.push(invalid(format!("{} '{}': {}", field, value, e)));
+                            }
+                        }
+                    }
+                }
+                if adaptive.multiplier <= 0.0 {
+                    errors.push(invalid(format!(
+                        "multiplier must be greater than zero, got {}",
+                        adaptive.multiplier
+                    )));
+                }
+                if adaptive.window == 0 || adaptive.min_samples > adaptive.window {
+                    errors.push(invalid(format!(
+                        "window must be at least 1 and at least min_samples ({}), got {}",
+                        adaptive.min_samples, adaptive.window
+                    )));
+                }
+            }
+
+            if let Some(cache) = &route.cache
+                && cache.ttl_jitter >= 100
+            {
+                errors.push(ConfigError::InvalidCache {
+                    route: route.path.clone(),
+                    reason: format!("ttl_jitter must be below 100, got {}", cache.ttl_jitter),
+                });
+            }
+
+            if let Some(cache) = &route.cache {
+                for range in cache
+                    .bypass_trusted_ips
+                    .iter()
+                    .filter(|r| !crate::utils::ip_range::is_valid_range(r))
+                {
+                    errors.push(ConfigError::InvalidCache {
+                        route: route.path.clone(),
+                        reason: format!("bypass_trusted_ips entry '{}' is not an IP or CIDR range", range),
+                    });
+                }
+            }
+
+            if let Some(cache) = &route.cache
+                && let Some(window) = &cache.stale_if_error
+                && let Err(e) = crate::middleware::rate_limiter::rate_limit::parse_duration(window)
+            {
+                errors.push(ConfigError::InvalidCache {
+                    route: route.path.clone(),
+                    reason: format!("stale_if_error '{}': {}", window, e),
+                });
+            }
+
+            if let Some(cache) = &route.cache
+                && let (Some(min), Some(max)) = (&cache.min_entry_size, &cache.max_entry_size)
+                && crate::features::health_check::parse_body_limit(min)
+                    > crate::features::health_check::parse_body_limit(max)
+            {
+                errors.push(ConfigError::InvalidCache {
+                    route: route.path.clone(),
+                    reason: format!("min_entry_size ({}) is larger than max_entry_size ({})", min, max),
+                });
+            }
+
+            if let Some(idempotency) = &route.idempotency {
+                if let Err(e) = crate::middleware::rate_limiter::rate_limit::parse_duration(&idempotency.window) {
+                    errors.push(ConfigError::InvalidIdempotency {
+                        route: route.path.clone(),
+                        reason: format!("window '{}': {}", idempotency.window, e),
+                    });
+                }
+                if http::HeaderName::from_bytes(idempotency.header.as_bytes()).is_err() {
+                    errors.push(ConfigError::InvalidIdempotency {
+                        route: route.path.clone(),
+                        reason: format!("'{}' is not a valid header name", idempotency.header),
+                    });
+                }
+            }
+
+            if let Some(health_check) = &route.health_check {
+                for status in &health_check.expected_status {
+                    if !(100..=599).contains(status) {
+                        errors.push(ConfigError::InvalidHealthCheck {
+                            route: route.path.clone(),
+                            reason: format!("expected_status {} is not an HTTP status", status),
+                        });
+                    }
+                }
+            }
+
+            if let Some(blue_green) = &route.blue_green {
+                let mut reasons = Vec::new();
+                if blue_green.blue.is_empty() || blue_green.green.is_empty() {
+                    reasons.push("blue and green must each have at least one destination".to_string());
+                }
+                if !route.destination.is_empty() || !route.destinations.is_empty() || route.service.is_some() {
+                    reasons.push("cannot be combined with destination, destinations or service".to_string());
+                }
+                errors.extend(reasons.into_iter().map(|reason| ConfigError::InvalidBlueGreen {
+                    route: route.path.clone(),
+                    reason,
+                }));
+            }
+
+            if let Some(canary) = &route.canary {
+                let mut reasons = Vec::new();
+                if canary.destination.is_empty() {
+                    reasons.push("destination must not be empty".to_string());
+                }
+                if canary.weight > 100 {
+                    reasons.push(format!("weight must be at most 100, got {}", canary.weight));
+                }
+                if !(canary.error_rate_threshold > 0.0 && canary.error_rate_threshold <= 1.0) {
+                    reasons.push(format!(
+                        "error_rate_threshold must be in (0, 1], got {}",
+                        canary.error_rate_threshold
+                    ));
+                }
+                if let Err(e) = crate::middleware::rate_limiter::rate_limit::parse_duration(&canary.window) {
+                    reasons.push(format!("window '{}': {}", canary.window, e));
+                }
+                errors.extend(reasons.into_iter().map(|reason| ConfigError::InvalidCanary {
+                    route: route.path.clone(),
+                    reason,
+                }));
+            }
+
+            // Check rate limit can actually be enforced
+            if let Some(rl) = &route.rate_limit {
+                if let Some(key) = &rl.key
+                    && crate::middleware::rate_limiter::rate_limit::KeySource::parse(key).is_none()
+                {
+                    errors.push(ConfigError::InvalidRateLimit {
+                        route: route.path.clone(),
+                        reason: format!("key '{}' must be 'header:<name>' or 'claim:<name>'", key),
+                    });
+                }
+                let limits = std::iter::once((None, rl.requests, rl.period.as_str())).chain(rl.roles.iter().map(|r| {
+                    (
+                        Some(r.role.as_str()),
+                        r.requests,
+                        r.period.as_deref().unwrap_or(&rl.period),
+                    )
+                }));
+                for (role, requests, period) in limits {
+                    let reason = if requests == 0 {
+                        Some("requests must be greater than zero".to_string())
+                    } else {
+                        match crate::middleware::rate_limiter::rate_limit::parse_duration(period) {
+                            Ok(p) if p.is_zero() => Some("period must be greater than zero".to_string()),
+                            Ok(_) => None,
+                            Err(e) => Some(format!("period '{}': {}", period, e)),
+                        }
+                    };
+                    if let Some(reason) = reason {
+                        errors.push(ConfigError::InvalidRateLimit {
+                            route: route.path.clone(),
+                            reason: match role {
+                                Some(role) => format!("role '{}': {}", role, reason),
+                                None => reason,
+                            },
+                        });
+                    }
+                }
+            }
+
+            if route.max_concurrent_subrequests == Some(0) {
+                errors.push(ConfigError::ZeroAggregateConcurrency {
+                    route: route.path.clone(),
+                });
             }
 
             // Check aggregate sources have required fields
             if let Some(agg) = &route.aggregate {
                 for source in agg {
                     if source.field.is_empty() {
-                        errors.push(format!(
-                            "Route '{}' aggregate source '{}' has empty field",
-                            route.path, source.service
-                        ));
+                        errors.push(ConfigError::EmptyAggregateField {
+                            route: route.path.clone(),
+                            service: source.service.clone(),
+                        });
                     }
                     if source.path.is_empty() {
-                        errors.push(format!(
-                            "Route '{}' aggregate source '{}' has empty path",
-                            route.path, source.service
-                        ));
+                        errors.push(ConfigError::EmptyAggregatePath {
+                            route: route.path.clone(),
+                            service: source.service.clone(),
+                        });
                     }
                 }
             }
         }
 
-        if errors.is_empty() {
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!(
-                "Config validation errors:\n  - {}",
-                errors.join("\n  - ")
-            ))
+        match errors.len() {
+            0 => Ok(()),
+            1 => Err(errors.remove(0)),
+            _ => Err(ConfigError::Multiple(errors)),
         }
     }
 
     pub fn find_route_for_path(&self, request_path: &str) -> Option<Arc<RouteConfig>> {
+        self.find_route_with_rule(request_path).map(|(route, _)| route)
+    }
+
+    /// Like `find_route_for_path`, also saying which rule picked the route.
+    pub fn find_route_with_rule(&self, request_path: &str) -> Option<(Arc<RouteConfig>, RouteMatchRule)> {
+        let match_path = self.request_match_path(request_path);
         if let Some(ref tree) = self.route_tree
-            && let std::result::Result::Ok(matched) = tree.at(request_path)
+            && let std::result::Result::Ok(matched) = tree.at(&match_path)
         {
-            return Some(self.routes[*matched.value].clone());
+            return Some((self.routes[*matched.value].clone(), RouteMatchRule::Pattern));
         }
-        // Fallback to prefix matching for catch-all routes like "/"
+        self.find_route_by_prefix(request_path)
+            .map(|route| (route, RouteMatchRule::Prefix))
+    }
+
+    /// Fallback to prefix matching for catch-all routes like "/"
+    fn find_route_by_prefix(&self, request_path: &str) -> Option<Arc<RouteConfig>> {
         self.routes
             .iter()
-            .filter(|r| request_path.starts_with(&r.path) && !r.path.contains('{'))
+            .filter(|r| !r.path.contains('{'))
+            .filter(|r| {
+                request_path.as_bytes().get(..r.path.len()).is_some_and(|prefix| {
+                    if self.server.case_insensitive_paths {
+                        prefix.eq_ignore_ascii_case(r.path.as_bytes())
+                    } else {
+                        prefix == r.path.as_bytes()
+                    }
+                })
+            })
             .max_by_key(|r| r.path.len())
             .cloned()
     }
 
+    /// The request path as the route tree sees it.
+    fn request_match_path<'a>(&self, request_path: &'a str) -> Cow<'a, str> {
+        if self.server.case_insensitive_paths {
+            Cow::Owned(request_path.to_ascii_lowercase())
+        } else {
+            Cow::Borrowed(request_path)
+        }
+    }
+
+    /// A route path as inserted in the route tree. Only literal segments are
+    /// lowercased; `{param}` names must still match the destination's.
+    fn route_match_path(&self, route_path: &str) -> String {
+        if !self.server.case_insensitive_paths {
+            return route_path.to_string();
+        }
+        let mut in_param = false;
+        route_path
+            .chars()
+            .map(|c| {
+                match c {
+                    '{' => in_param = true,
+                    '}' => in_param = false,
+                    _ => {}
+                }
+                if in_param { c } else { c.to_ascii_lowercase() }
+            })
+            .collect()
+    }
+
     /// Match a path and return captured parameters for proxy substitution
     #[allow(clippy::type_complexity)]
     pub fn match_route_with_params(&self, request_path: &str) -> Option<(Arc<RouteConfig>, Vec<(String, String)>)> {
+        let match_path = self.request_match_path(request_path);
         if let Some(ref tree) = self.route_tree
-            && let std::result::Result::Ok(matched) = tree.at(request_path)
+            && let std::result::Result::Ok(matched) = tree.at(&match_path)
         {
             let params: Vec<(String, String)> = matched
                 .params
                 .iter()
-                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .map(|(k, v)| {
+                    let value = original_case(request_path, &match_path, v).unwrap_or(v);
+                    (k.to_string(), value.to_string())
+                })
                 .collect();
             return Some((self.routes[*matched.value].clone(), params));
         }
         // Fallback: no params
-        self.find_route_for_path(request_path).map(|r| (r, vec![]))
+        self.find_route_by_prefix(request_path).map(|r| (r, vec![]))
     }
 
     fn build_route_tree(&mut self) {
         let mut router = matchit::Router::new();
         for (i, route) in self.routes.iter().enumerate() {
-            if let std::result::Result::Err(e) = router.insert(&route.path, i) {
+            if let std::result::Result::Err(e) = router.insert(self.route_match_path(&route.path), i) {
                 tracing::warn!(route = %route.name, path = %route.path, "Failed to add route to tree: {}", e);
-                continue;
             }
         }
         self.route_tree = Some(router);
     }
 
+    /// Rough cost of the route table, logged at startup to size instances.
+    pub fn resource_estimate(&self) -> ResourceEstimate {
+        let mut estimate = ResourceEstimate {
+            routes: self.routes.len(),
+            ..Default::default()
+        };
+        for route in &self.routes {
+            let text = route.name.len()
+                + route.path.len()
+                + route.destination.len()
+                + route.destinations.iter().map(String::len).sum::<usize>();
+            estimate.approx_bytes += std::mem::size_of::<RouteConfig>() + text + ROUTE_TREE_BYTES_PER_ROUTE;
+            if let Some(pattern) = route.rewrite.as_ref().and_then(|r| r.pattern.as_ref()) {
+                estimate.regexes += 1;
+                estimate.approx_bytes += REGEX_BASE_BYTES + REGEX_BYTES_PER_PATTERN_BYTE * pattern.len();
+            }
+        }
+        estimate
+    }
+
     /// Public wrappers for testing
     pub fn resolve_services_pub(&mut self) {
         self.resolve_services();
@@ -469,7 +2197,7 @@ impl GatewayConfig {
     pub fn apply_defaults_pub(&mut self) {
         self.apply_defaults();
     }
-    pub fn validate_pub(&self) -> Result<(), anyhow::Error> {
+    pub fn validate_pub(&self) -> Result<(), ConfigError> {
         self.validate()
     }
     pub fn build_route_tree_pub(&mut self) {
@@ -477,6 +2205,48 @@ impl GatewayConfig {
     }
 }
 
+/// Approximate memory per route in the path-matching tree.
+const ROUTE_TREE_BYTES_PER_ROUTE: usize = 256;
+/// Approximate memory of a compiled regex: a fixed part plus a share that
+/// grows with the pattern.
+const REGEX_BASE_BYTES: usize = 4 * 1024;
+const REGEX_BYTES_PER_PATTERN_BYTE: usize = 256;
+
+/// Route table size as estimated by `GatewayConfig::resource_estimate`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceEstimate {
+    pub routes: usize,
+    /// Regexes compiled for routes, e.g. `rewrite.pattern`.
+    pub regexes: usize,
+    /// Routes, their matching tree and compiled regexes, in bytes.
+    pub approx_bytes: usize,
+}
+
+/// `part` is a slice of `lowered`, an ASCII-lowercased copy of `original`;
+/// returns the same span of `original`, so captured params keep their case.
+fn original_case<'a>(original: &'a str, lowered: &str, part: &str) -> Option<&'a str> {
+    let start = (part.as_ptr() as usize).checked_sub(lowered.as_ptr() as usize)?;
+    original.get(start..start.checked_add(part.len())?)
+}
+
+/// How a request path was matched to a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteMatchRule {
+    /// The whole path matched the route's path, `{param}` segments included.
+    Pattern,
+    /// The route's path is the longest literal prefix of the request path.
+    Prefix,
+}
+
+impl RouteMatchRule {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteMatchRule::Pattern => "pattern",
+            RouteMatchRule::Prefix => "prefix",
+        }
+    }
+}
+
 /// Public wrapper for env var interpolation (for testing)
 pub fn interpolate_env_vars_pub(content: &str) -> String {
     interpolate_env_vars(content)
@@ -486,6 +2256,7 @@ pub fn interpolate_env_vars_pub(content: &str) -> String {
 
 fn interpolate_env_vars(content: &str) -> String {
     use std::sync::LazyLock;
+    #[allow(clippy::expect_used)]
     static ENV_RE: LazyLock<Regex> =
         LazyLock::new(|| Regex::new(r"\$\{([^}]+)\}").expect("Invalid env var regex — this is a compile-time bug"));
     ENV_RE
@@ -498,15 +2269,17 @@ fn interpolate_env_vars(content: &str) -> String {
 
 // ==================== Include Merging (#65) ====================
 
-fn merge_include(config: &mut GatewayConfig, inc: &serde_yaml::Value, _file: &str) -> Result<(), anyhow::Error> {
+fn merge_include(config: &mut GatewayConfig, inc: &serde_yaml::Value, file: &str) -> Result<(), ConfigError> {
     // Merge services
     if let Some(services) = inc.get("services") {
-        let svcs: HashMap<String, ServiceConfig> = serde_yaml::from_value(services.clone())?;
+        let svcs: HashMap<String, ServiceConfig> =
+            serde_yaml::from_value(services.clone()).map_err(|e| ConfigError::parse_file(file, e))?;
         config.services.extend(svcs);
     }
     // Merge routes
     if let Some(routes) = inc.get("routes") {
-        let new_routes: Vec<Arc<RouteConfig>> = serde_yaml::from_value(routes.clone())?;
+        let new_routes: Vec<Arc<RouteConfig>> =
+            serde_yaml::from_value(routes.clone()).map_err(|e| ConfigError::parse_file(file, e))?;
         config.routes.extend(new_routes);
     }
     Ok(())
@@ -514,9 +2287,18 @@ fn merge_include(config: &mut GatewayConfig, inc: &serde_yaml::Value, _file: &st
 
 // ==================== API Key Store ====================
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Clone, Default)]
 pub struct ApiKeyStore {
+    /// Keyed by the API key itself, or with `hashed` by its SHA-256 digest.
+    #[serde(default)]
     pub keys: HashMap<String, ApiKeyDetails>,
+    /// The map keys are `hash_api_key` digests, so reading the file doesn't
+    /// yield working credentials. Plaintext stores keep working.
+    #[serde(default)]
+    pub hashed: bool,
+    /// Users of `Basic` auth methods, keyed by username.
+    #[serde(default)]
+    pub users: HashMap<String, BasicUser>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -527,6 +2309,17 @@ pub struct ApiKeyDetails {
     pub status: String,
 }
 
+#[derive(Debug, Deserialize, Clone)]
+pub struct BasicUser {
+    /// As printed by `rustygw hash-password <password>`; the password itself
+    /// is never stored.
+    pub password_hash: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default = "default_status")]
+    pub status: String,
+}
+
 fn default_status() -> String {
     "active".to_string()
 }
@@ -534,21 +2327,96 @@ fn default_status() -> String {
 impl ApiKeyStore {
     pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, anyhow::Error> {
         let content = fs::read_to_string(path)?;
-        serde_yaml::from_str(&content).map_err(Into::into)
+        let mut store: ApiKeyStore = serde_yaml::from_str(&content)?;
+        if store.hashed {
+            let mut digests = HashMap::with_capacity(store.keys.len());
+            for (digest, details) in store.keys {
+                // Naming the user, not the entry, in case a raw key was pasted in
+                if digest.len() != 64 || !digest.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    anyhow::bail!(
+                        "API key store is hashed, but the key of '{}' is not a SHA-256 hex digest",
+                        details.user_id
+                    );
+                }
+                digests.insert(digest.to_ascii_lowercase(), details);
+            }
+            store.keys = digests;
+        }
+        for (username, user) in &store.users {
+            if PasswordHash::parse(&user.password_hash).is_none() {
+                anyhow::bail!(
+                    "password_hash of user '{}' is not a `rustygw hash-password` hash",
+                    username
+                );
+            }
+        }
+        Ok(store)
+    }
+
+    /// The details for a presented API key, hashing it first when the store
+    /// holds digests.
+    pub fn lookup(&self, key: &str) -> Option<&ApiKeyDetails> {
+        if self.hashed {
+            self.keys.get(&hash_api_key(key))
+        } else {
+            self.keys.get(key)
+        }
     }
 }
 
+/// Lowercase hex SHA-256 of an API key, as a `hashed: true` store lists it.
+/// `rustygw hash-api-key <key>` prints it for operators filling in the file.
+pub fn hash_api_key(key: &str) -> String {
+    crate::features::auth::basic::encode_hex(ring::digest::digest(&ring::digest::SHA256, key.as_bytes()).as_ref())
+}
+
 // ==================== Secrets ====================
 
+#[derive(Default)]
 pub struct SecretsConfig {
     pub jwt_secret: String,
+    /// Algorithm JWTs must be signed with (`JWT_ALGORITHM`, default HS256).
+    /// Tokens signed any other way are rejected.
+    pub jwt_algorithm: Algorithm,
+    /// Verifies RS*, PS*, ES* and EdDSA tokens, loaded from the PEM file at
+    /// `JWT_PUBLIC_KEY_PATH`; HMAC algorithms use `jwt_secret` instead.
+    pub jwt_public_key: Option<DecodingKey>,
 }
 
 impl SecretsConfig {
     pub fn from_env() -> Result<Self, Error> {
+        let jwt_algorithm = match std::env::var("JWT_ALGORITHM") {
+            Ok(name) => Algorithm::from_str(&name).map_err(|_| anyhow::anyhow!("Unknown JWT_ALGORITHM '{}'", name))?,
+            Err(_) => Algorithm::HS256,
+        };
+        if matches!(jwt_algorithm, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) {
+            return Ok(Self {
+                jwt_secret: std::env::var("JWT_SECRET")
+                    .map_err(|_| anyhow::anyhow!("JWT_SECRET must be set in .env file"))?,
+                jwt_algorithm,
+                jwt_public_key: None,
+            });
+        }
+
+        let path = std::env::var("JWT_PUBLIC_KEY_PATH")
+            .map_err(|_| anyhow::anyhow!("JWT_PUBLIC_KEY_PATH must be set for JWT_ALGORITHM {:?}", jwt_algorithm))?;
+        let pem = fs::read(&path).map_err(|e| anyhow::anyhow!("Failed to read JWT public key '{}': {}", path, e))?;
         Ok(Self {
-            jwt_secret: std::env::var("JWT_SECRET")
-                .map_err(|_| anyhow::anyhow!("JWT_SECRET must be set in .env file"))?,
+            jwt_secret: std::env::var("JWT_SECRET").unwrap_or_default(),
+            jwt_algorithm,
+            jwt_public_key: Some(
+                public_key_from_pem(jwt_algorithm, &pem)
+                    .map_err(|e| anyhow::anyhow!("Invalid JWT public key '{}': {}", path, e))?,
+            ),
         })
     }
 }
+
+/// Decoding key for an asymmetric JWT algorithm from a PEM public key.
+pub fn public_key_from_pem(algorithm: Algorithm, pem: &[u8]) -> jsonwebtoken::errors::Result<DecodingKey> {
+    match algorithm {
+        Algorithm::ES256 | Algorithm::ES384 => DecodingKey::from_ec_pem(pem),
+        Algorithm::EdDSA => DecodingKey::from_ed_pem(pem),
+        _ => DecodingKey::from_rsa_pem(pem),
+    }
+}
@@ -0,0 +1,24 @@
+//! Built-in defaults for the `tuning` config section. Operators override
+//! them in `gateway.yaml`; code reads the resolved `TuningConfig` instead.
+
+/// Entries kept in the static file cache.
+pub const DEFAULT_STATIC_CACHE_CAPACITY: u64 = 1_000;
+/// How long a static file stays cached before it is re-read from disk.
+pub const DEFAULT_STATIC_CACHE_TTL: &str = "60s";
+/// Timeout for a single backend health probe.
+pub const DEFAULT_HEALTH_CHECK_TIMEOUT: &str = "3s";
+/// Pending change events the config file watcher buffers.
+pub const DEFAULT_RELOAD_CHANNEL_BUFFER: usize = 1;
+/// Wait after a TLS file change so cert and key both land before reloading.
+pub const DEFAULT_TLS_RELOAD_DEBOUNCE: &str = "200ms";
+/// Largest total size (names plus values) of backend response headers the
+/// gateway relays; larger header sections get a 502.
+pub const DEFAULT_MAX_RESPONSE_HEADER_BYTES: usize = 64 * 1024;
+/// Entries kept by the in-memory response cache.
+pub const DEFAULT_RESPONSE_CACHE_CAPACITY: u64 = 10_000;
+/// How often idle rate limit buckets and circuit breaker entries are reaped.
+pub const DEFAULT_STORE_CLEANUP_INTERVAL: &str = "60s";
+/// Least time between JWKS fetches triggered by tokens with an unknown `kid`.
+pub const DEFAULT_JWKS_MISS_COOLDOWN: &str = "30s";
+/// Circuit breaker state transitions kept for `/admin/circuit-breakers/events`.
+pub const DEFAULT_CIRCUIT_BREAKER_EVENTS: usize = 256;
@@ -2,6 +2,7 @@ use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
 };
+use axum_prometheus::metrics::counter;
 use reqwest::Error;
 
 #[derive(Debug)]
@@ -12,37 +13,111 @@ pub enum AppError {
     // Auth errors
     AuthFailed(String),
     MissingAuthToken,
-    InvalidAuthHeader,
+    /// The `Authorization` header doesn't hold credentials of the scheme the
+    /// method expects, e.g. `Bearer`.
+    InvalidAuthHeader(&'static str),
+    /// An `ApiKey` method's header is absent (or not text).
+    MissingApiKey(String),
     InsufficientPermissions,
     TokenExpired,
+    /// An auth dependency (e.g. the introspection endpoint) couldn't be reached.
+    AuthUnavailable,
 
     // Proxy errors
     RouteNotFound,
     ProxyError(Error),
+    /// The backend didn't answer within the route's (or destination's) timeout.
+    GatewayTimeout,
     InvalidDestination(String),
+    StaticFileNotFound,
+    /// The route's `request_deadline` passed before a backend answered.
+    DeadlineExceeded,
+    /// The request body is larger than `server.pool.body_limit`.
+    PayloadTooLarge,
+    /// The request path and query exceed `security.max_uri_length`.
+    UriTooLong,
+    /// The client's request body couldn't be read: an aborted upload or a
+    /// truncated or malformed body.
+    RequestBodyRead,
+    /// The backend's response headers exceed `tuning.max_response_header_bytes`.
+    UpstreamHeadersTooLarge,
+    /// The backend refused or failed the WebSocket handshake.
+    WebSocketBackendFailed,
+    /// A trusted `destination_override` header named a backend the route
+    /// doesn't use.
+    DestinationNotAllowed(String),
     InternalServerError,
+
+    // Admin errors
+    /// The route exists but has no `blue_green` config to switch.
+    NotBlueGreen(String),
+    /// No route sends requests to this destination.
+    UnknownDestination(String),
+}
+
+impl AppError {
+    /// The variant's name, used as the `kind` label of `gateway_errors_total`
+    /// so the label set stays fixed.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::RateLimited => "RateLimited",
+            AppError::ServiceUnavailable => "ServiceUnavailable",
+            AppError::AuthFailed(_) => "AuthFailed",
+            AppError::MissingAuthToken => "MissingAuthToken",
+            AppError::InvalidAuthHeader(_) => "InvalidAuthHeader",
+            AppError::MissingApiKey(_) => "MissingApiKey",
+            AppError::InsufficientPermissions => "InsufficientPermissions",
+            AppError::TokenExpired => "TokenExpired",
+            AppError::AuthUnavailable => "AuthUnavailable",
+            AppError::RouteNotFound => "RouteNotFound",
+            AppError::ProxyError(_) => "ProxyError",
+            AppError::GatewayTimeout => "GatewayTimeout",
+            AppError::InvalidDestination(_) => "InvalidDestination",
+            AppError::StaticFileNotFound => "StaticFileNotFound",
+            AppError::DeadlineExceeded => "DeadlineExceeded",
+            AppError::PayloadTooLarge => "PayloadTooLarge",
+            AppError::UriTooLong => "UriTooLong",
+            AppError::RequestBodyRead => "RequestBodyRead",
+            AppError::UpstreamHeadersTooLarge => "UpstreamHeadersTooLarge",
+            AppError::WebSocketBackendFailed => "WebSocketBackendFailed",
+            AppError::DestinationNotAllowed(_) => "DestinationNotAllowed",
+            AppError::InternalServerError => "InternalServerError",
+            AppError::NotBlueGreen(_) => "NotBlueGreen",
+            AppError::UnknownDestination(_) => "UnknownDestination",
+        }
+    }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        counter!("gateway_errors_total", "kind" => self.kind()).increment(1);
         let (status, error_message) = match self {
             AppError::RateLimited => (StatusCode::TOO_MANY_REQUESTS, "Too many requests".to_string()),
             AppError::AuthFailed(reason) => (StatusCode::UNAUTHORIZED, format!("Authentication failed: {}", reason)),
             AppError::MissingAuthToken => (StatusCode::UNAUTHORIZED, "Missing 'Authorization' header".to_string()),
-            AppError::InvalidAuthHeader => (
+            AppError::InvalidAuthHeader(scheme) => (
                 StatusCode::UNAUTHORIZED,
-                "Invalid 'Authorization' header format. Expected 'Bearer <token>'.".to_string(),
+                format!(
+                    "Invalid 'Authorization' header format. Expected '{} <credentials>'.",
+                    scheme
+                ),
             ),
+            AppError::MissingApiKey(header) => (StatusCode::UNAUTHORIZED, format!("Missing '{}' header", header)),
             AppError::InsufficientPermissions => (
                 StatusCode::FORBIDDEN,
                 "You do not have permission to access this resource.".to_string(),
             ),
             AppError::TokenExpired => (StatusCode::UNAUTHORIZED, "Token has expired".to_string()),
+            AppError::AuthUnavailable => (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Authentication service unavailable".to_string(),
+            ),
             AppError::RouteNotFound => (StatusCode::NOT_FOUND, "Route not found".to_string()),
             AppError::ProxyError(e) => {
                 tracing::error!("Proxy error: {}", e);
                 (StatusCode::BAD_GATEWAY, "Error proxying request".to_string())
             }
+            AppError::GatewayTimeout => (StatusCode::GATEWAY_TIMEOUT, "Backend timed out".to_string()),
             AppError::InvalidDestination(url) => {
                 tracing::error!("Invalid destination URL configured: {}", url);
                 (
@@ -50,11 +125,36 @@ impl IntoResponse for AppError {
                     "Invalid gateway configuration".to_string(),
                 )
             }
+            AppError::PayloadTooLarge => (StatusCode::PAYLOAD_TOO_LARGE, "Request body too large".to_string()),
+            AppError::UriTooLong => (StatusCode::URI_TOO_LONG, "Request URI too long".to_string()),
+            AppError::RequestBodyRead => (StatusCode::BAD_REQUEST, "Failed to read request body".to_string()),
+            AppError::UpstreamHeadersTooLarge => (
+                StatusCode::BAD_GATEWAY,
+                "Upstream response headers too large".to_string(),
+            ),
+            AppError::StaticFileNotFound => (StatusCode::NOT_FOUND, "File not found".to_string()),
+            AppError::DeadlineExceeded => (StatusCode::GATEWAY_TIMEOUT, "Request deadline exceeded".to_string()),
+            AppError::WebSocketBackendFailed => (
+                StatusCode::BAD_GATEWAY,
+                "Failed to connect to backend WebSocket".to_string(),
+            ),
+            AppError::DestinationNotAllowed(destination) => (
+                StatusCode::BAD_REQUEST,
+                format!("Destination '{}' is not configured for this route", destination),
+            ),
             AppError::InternalServerError => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "An internal server error occurred".to_string(),
             ),
             AppError::ServiceUnavailable => (StatusCode::SERVICE_UNAVAILABLE, "Service Unavailable".to_string()),
+            AppError::NotBlueGreen(route) => (
+                StatusCode::CONFLICT,
+                format!("Route '{}' has no blue_green deployment", route),
+            ),
+            AppError::UnknownDestination(destination) => (
+                StatusCode::NOT_FOUND,
+                format!("No route uses destination '{}'", destination),
+            ),
         };
 
         (status, error_message).into_response()
@@ -63,6 +163,184 @@ impl IntoResponse for AppError {
 
 impl From<reqwest::Error> for AppError {
     fn from(error: reqwest::Error) -> Self {
+        if error.is_timeout() {
+            return AppError::GatewayTimeout;
+        }
         AppError::ProxyError(error)
     }
 }
+
+/// A backing store (rate limit, cache) couldn't be reached. What happens
+/// next is up to the subsystem's `degradation` policy.
+#[derive(Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct StoreUnavailable(pub String);
+
+/// Errors raised while loading or validating `gateway.yaml`.
+/// Converts into `anyhow::Error` via `?` for callers that only need the message.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Failed to read config file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to fetch config from {url}: {reason}")]
+    Fetch { url: String, reason: String },
+    #[error("Failed to parse config: {0}")]
+    Parse(#[from] serde_yaml::Error),
+    #[error("Failed to parse {path}{}: {source}", location_suffix(*.line, *.column))]
+    ParseFile {
+        path: String,
+        line: Option<usize>,
+        column: Option<usize>,
+        source: serde_yaml::Error,
+    },
+    #[error("Invalid include pattern: {0}")]
+    IncludePattern(#[from] glob::PatternError),
+    #[error("Failed to resolve include: {0}")]
+    Include(#[from] glob::GlobError),
+
+    #[error("Duplicate route name '{0}'")]
+    DuplicateRouteName(String),
+    #[error("Route '{route}' duplicates path '{path}' of route '{existing}'")]
+    DuplicateRoutePath {
+        path: String,
+        route: String,
+        existing: String,
+    },
+    #[error("Route '{0}' has no destination, destinations, or service defined")]
+    MissingDestination(String),
+    #[error("Route '{route}' references service '{service}' which is not defined in services")]
+    UnknownService { route: String, service: String },
+    #[error("Route '{route}' has an invalid rate limit: {reason}")]
+    InvalidRateLimit { route: String, reason: String },
+    #[error("Route '{route}' aggregate source '{service}' has empty field")]
+    EmptyAggregateField { route: String, service: String },
+    #[error("Route '{route}' aggregate source '{service}' has empty path")]
+    EmptyAggregatePath { route: String, service: String },
+    #[error("Route '{route}' has max_concurrent_subrequests of 0; no aggregate source could run")]
+    ZeroAggregateConcurrency { route: String },
+    #[error("Route '{route}' has an invalid circuit breaker: {reason}")]
+    InvalidCircuitBreaker { route: String, reason: String },
+    #[error("Route '{route}' has an invalid status_map: {reason}")]
+    InvalidStatusMap { route: String, reason: String },
+    #[error("Route '{route}' has an invalid auth method: {reason}")]
+    InvalidAuth { route: String, reason: String },
+    #[error("Route '{route}' has an invalid default_content_type '{value}'")]
+    InvalidDefaultContentType { route: String, value: String },
+    #[error("Route '{route}' has an invalid response_header_allowlist entry '{header}'")]
+    InvalidResponseHeaderAllowlist { route: String, header: String },
+    #[error("Route '{route}' has an invalid destination_override: {reason}")]
+    InvalidDestinationOverride { route: String, reason: String },
+    #[error("Route '{route}' has an invalid rewrite: {reason}")]
+    InvalidRewrite { route: String, reason: String },
+    #[error("Route '{route}' has an invalid forward_headers entry '{header}'")]
+    InvalidForwardHeaders { route: String, header: String },
+    #[error("Route '{route}' has an invalid log_level '{level}': expected trace, debug, info, warn or error")]
+    InvalidLogLevel { route: String, level: String },
+    #[error("Route '{route}' has an invalid request_deadline: {reason}")]
+    InvalidRequestDeadline { route: String, reason: String },
+    #[error("Route '{route}' gives destination '{destination}' a weight that isn't a positive integer")]
+    InvalidDestinationWeight { route: String, destination: String },
+    #[error("Route '{route}' has an invalid adaptive_timeout: {reason}")]
+    InvalidAdaptiveTimeout { route: String, reason: String },
+    #[error("Route '{route}' has an invalid cache: {reason}")]
+    InvalidCache { route: String, reason: String },
+    #[error("Route '{route}' has an invalid idempotency: {reason}")]
+    InvalidIdempotency { route: String, reason: String },
+    #[error("Route '{route}' has an invalid blue_green: {reason}")]
+    InvalidBlueGreen { route: String, reason: String },
+    #[error("Route '{route}' has an invalid canary: {reason}")]
+    InvalidCanary { route: String, reason: String },
+    #[error("Route '{route}' has an invalid health_check: {reason}")]
+    InvalidHealthCheck { route: String, reason: String },
+    #[error("Global circuit breaker is invalid: {0}")]
+    InvalidGlobalCircuitBreaker(String),
+    #[error("Config has {count} routes, more than server.max_routes ({max})")]
+    TooManyRoutes { count: usize, max: usize },
+    #[error("TLS config is invalid: {0}")]
+    InvalidTls(String),
+    #[error("QoS config is invalid: {0}")]
+    InvalidQos(String),
+    #[error("Retry budget is invalid: {0}")]
+    InvalidRetryBudget(String),
+    #[error("Observability config is invalid: {0}")]
+    InvalidObservability(String),
+    #[error("Tuning config is invalid: {0}")]
+    InvalidTuning(String),
+    #[error("Security config is invalid: {0}")]
+    InvalidSecurity(String),
+
+    #[error("Config validation errors:\n  - {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n  - "))]
+    Multiple(Vec<ConfigError>),
+}
+
+impl ConfigError {
+    /// Wraps a YAML error with the file it came from and, when serde_yaml
+    /// knows it, the 1-based line and column.
+    pub fn parse_file(path: impl AsRef<std::path::Path>, source: serde_yaml::Error) -> Self {
+        let location = source.location();
+        ConfigError::ParseFile {
+            path: path.as_ref().display().to_string(),
+            line: location.as_ref().map(serde_yaml::Location::line),
+            column: location.as_ref().map(serde_yaml::Location::column),
+            source,
+        }
+    }
+
+    /// Two routes claiming the same name or path. On hot reload these are
+    /// usually an edit colliding with an existing route rather than a typo.
+    pub fn is_route_conflict(&self) -> bool {
+        matches!(
+            self,
+            ConfigError::DuplicateRouteName(_) | ConfigError::DuplicateRoutePath { .. }
+        )
+    }
+
+    /// Flattens `Multiple` so callers can inspect each failure individually.
+    pub fn into_errors(self) -> Vec<ConfigError> {
+        match self {
+            ConfigError::Multiple(errors) => errors,
+            other => vec![other],
+        }
+    }
+}
+
+fn location_suffix(line: Option<usize>, column: Option<usize>) -> String {
+    match (line, column) {
+        (Some(line), Some(column)) => format!(" (line {}, column {})", line, column),
+        (Some(line), None) => format!(" (line {})", line),
+        _ => String::new(),
+    }
+}
+
+/// Why a hot reload was rejected; the previous config stays active either way.
+#[derive(Debug, thiserror::Error)]
+pub enum ReloadError {
+    #[error("Reload rejected, conflicting routes:\n  - {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n  - "))]
+    RouteConflict(Vec<ConfigError>),
+    #[error("Reload rejected: {0}")]
+    Invalid(ConfigError),
+}
+
+impl ReloadError {
+    /// Label for logs and the `gateway_config_reload_failures_total` metric.
+    pub fn category(&self) -> &'static str {
+        match self {
+            ReloadError::RouteConflict(_) => "route_conflict",
+            ReloadError::Invalid(_) => "invalid_config",
+        }
+    }
+}
+
+impl From<ConfigError> for ReloadError {
+    fn from(error: ConfigError) -> Self {
+        let errors = error.into_errors();
+        if errors.iter().any(ConfigError::is_route_conflict) {
+            ReloadError::RouteConflict(errors)
+        } else {
+            ReloadError::Invalid(match <[ConfigError; 1]>::try_from(errors) {
+                Ok([error]) => error,
+                Err(errors) => ConfigError::Multiple(errors),
+            })
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use dashmap::DashMap;
+
+use crate::{config::AdaptiveTimeoutConfig, middleware::rate_limiter::rate_limit::parse_duration};
+
+/// Recent backend latencies per route, for `adaptive_timeout`. Only the last
+/// `window` responses are kept, so the timeout follows the route as it
+/// speeds up or slows down. Resets on restart.
+pub struct LatencyTracker {
+    routes: DashMap<String, Arc<Mutex<VecDeque<Duration>>>>,
+}
+
+impl LatencyTracker {
+    pub fn new() -> Self {
+        Self { routes: DashMap::new() }
+    }
+
+    pub fn record(&self, route: &str, latency: Duration, window: usize) {
+        let samples = match self.routes.get(route) {
+            Some(s) => s.clone(),
+            None => self.routes.entry(route.to_string()).or_default().clone(),
+        };
+        let mut samples = samples.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        while samples.len() >= window.max(1) {
+            samples.pop_front();
+        }
+        samples.push_back(latency);
+    }
+
+    /// p99 of the kept samples, once there are at least `min_samples`.
+    pub fn p99(&self, route: &str, min_samples: usize) -> Option<Duration> {
+        let samples = self.routes.get(route)?.clone();
+        let mut sorted: Vec<Duration> = samples
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .iter()
+            .copied()
+            .collect();
+        if sorted.is_empty() || sorted.len() < min_samples {
+            return None;
+        }
+        sorted.sort_unstable();
+        // Nearest-rank p99, ceil(0.99 * n), in integers
+        let rank = sorted.len() - sorted.len() / 100;
+        Some(sorted[rank - 1])
+    }
+
+    /// `multiplier` times the route's p99, within `min`..`max`. Until enough
+    /// responses have been seen, `max`.
+    pub fn timeout_for(&self, route: &str, config: &AdaptiveTimeoutConfig) -> Duration {
+        let min = parse_duration(&config.min).unwrap_or_default();
+        let max = parse_duration(&config.max).unwrap_or(min).max(min);
+        match self.p99(route, config.min_samples) {
+            Some(p99) => p99.mul_f64(config.multiplier).clamp(min, max),
+            None => max,
+        }
+    }
+}
+
+impl Default for LatencyTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
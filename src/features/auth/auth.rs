@@ -1,43 +1,222 @@
-use std::collections::HashSet;
+use std::{
+    collections::{HashMap, HashSet},
+    net::IpAddr,
+};
 
 use http::HeaderMap;
-use jsonwebtoken::{DecodingKey, Validation, decode, errors::ErrorKind};
+use jsonwebtoken::{Algorithm, DecodingKey, Validation, decode, errors::ErrorKind};
 use serde::{Deserialize, Serialize};
-use tracing::debug;
+use serde_json::Value;
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
 
+use super::{
+    basic::{authenticate_basic, verify_basic},
+    introspection::introspect,
+    jwks::JwksCache,
+    signature::verify_signature,
+    trusted_header::trusted_identity,
+};
 use crate::{
-    config::{ApiKeyStore, AuthType, SecretsConfig},
+    config::{ApiKeyStore, AuthConfig, AuthType, ClaimRequirement, FailurePolicy, SecretsConfig},
     errors::AppError,
+    features::health_check::parse_duration,
 };
 
+/// Where an `ApiKey` method looks for the key when it sets no `header`.
+pub const DEFAULT_API_KEY_HEADER: &str = "x-api-key";
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Claims {
     pub sub: String, // Subject (Uer Id)
     pub roles: Vec<String>,
     pub exp: usize, // Required for JWT validation
+    /// Issuer, checked against the method's `issuer` when it sets one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iss: Option<String>,
+    /// Audience, checked against the method's `audience` when it sets one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<Audience>,
+    /// Any other claims the token carries, e.g. `tenant`.
+    #[serde(flatten, default)]
+    pub extra: HashMap<String, Value>,
+}
+
+/// The `aud` claim: one audience or several.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum Audience {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Claims {
+    /// A claim's value as the token carried it; `sub`, `iss` and `aud` included.
+    pub fn value(&self, name: &str) -> Option<Value> {
+        match name {
+            "sub" => Some(Value::String(self.sub.clone())),
+            "roles" => serde_json::to_value(&self.roles).ok(),
+            "iss" => self.iss.clone().map(Value::String),
+            "aud" => self.aud.as_ref().and_then(|aud| serde_json::to_value(aud).ok()),
+            _ => self.extra.get(name).cloned(),
+        }
+    }
+
+    /// A claim's value as text, for keying on it; `sub` included.
+    pub fn claim(&self, name: &str) -> Option<String> {
+        match name {
+            "sub" => return Some(self.sub.clone()),
+            "iss" => return self.iss.clone(),
+            "aud" => {
+                return self.aud.as_ref().map(|aud| match aud {
+                    Audience::One(aud) => aud.clone(),
+                    Audience::Many(auds) => auds.join(","),
+                });
+            }
+            _ => {}
+        }
+        match self.extra.get(name)? {
+            Value::Null => None,
+            Value::String(s) => Some(s.clone()),
+            other => Some(other.to_string()),
+        }
+    }
+}
+
+/// Result of running a route's auth methods.
+pub enum AuthOutcome<'a> {
+    /// A method accepted the token.
+    Authenticated(Claims, &'a AuthConfig),
+    /// No method accepted the token, but one with `on_error: fail_open` could
+    /// not reach its dependency, so the request goes through without claims.
+    FailedOpen,
+}
+
+/// Tries each of the route's auth methods in order and returns the claims of
+/// the first that accepts the token, along with that method. When none does,
+/// the most specific error is returned: a rejected credential over one that
+/// was never sent, and the earlier method on a tie. `client_ip` is the
+/// connecting peer, checked by `TrustedHeader`; `body` is the buffered request
+/// body, which `HmacSignature` needs and fails without.
+#[allow(clippy::too_many_arguments)]
+pub async fn authenticate<'a>(
+    headers: &HeaderMap,
+    body: Option<&[u8]>,
+    client_ip: Option<IpAddr>,
+    methods: &'a [AuthConfig],
+    secrets: &SecretsConfig,
+    key_store: &RwLock<ApiKeyStore>,
+    jwks: &JwksCache,
+    client: &reqwest::Client,
+) -> Result<AuthOutcome<'a>, AppError> {
+    let mut best_error: Option<AppError> = None;
+    let mut failed_open = false;
+    for method in methods {
+        let verification = async {
+            match method.auth_type {
+                AuthType::Introspection => match extract_bearer_token(headers) {
+                    Ok(token) => introspect(client, method, token).await,
+                    Err(e) => Err(e),
+                },
+                AuthType::Jwt if method.jwks_url.is_some() => match extract_bearer_token(headers) {
+                    Ok(token) => jwks.verify(client, method, token).await,
+                    Err(e) => Err(e),
+                },
+                AuthType::TrustedHeader => trusted_identity(headers, method, client_ip),
+                AuthType::HmacSignature => verify_signature(headers, body, method),
+                AuthType::Basic => authenticate_basic(headers, key_store).await,
+                _ => verify_token(headers, method, secrets, &*key_store.read().await),
+            }
+        };
+        // Bounds the whole step, including waits on a JWKS fetch already in
+        // progress, so a slow dependency can't stall requests
+        let result = tokio::time::timeout(parse_duration(&method.timeout), verification)
+            .await
+            .unwrap_or_else(|_| {
+                warn!(auth_type = ?method.auth_type, timeout = %method.timeout, "Auth method timed out");
+                Err(AppError::AuthUnavailable)
+            });
+        match result {
+            Ok(claims) => return Ok(AuthOutcome::Authenticated(claims, method)),
+            Err(AppError::AuthUnavailable) if method.on_error == Some(FailurePolicy::FailOpen) => {
+                warn!(auth_type = ?method.auth_type, "Auth dependency unavailable, failing open");
+                failed_open = true;
+            }
+            Err(e) => {
+                debug!(auth_type = ?method.auth_type, "Auth method did not match: {:?}", e);
+                if best_error
+                    .as_ref()
+                    .is_none_or(|best| specificity(&e) > specificity(best))
+                {
+                    best_error = Some(e);
+                }
+            }
+        }
+    }
+    if failed_open {
+        return Ok(AuthOutcome::FailedOpen);
+    }
+    Err(best_error.unwrap_or(AppError::MissingAuthToken))
+}
+
+/// How much an auth error says about the caller's credential: none sent for
+/// the method, one sent in the wrong form, or one that was checked.
+fn specificity(error: &AppError) -> u8 {
+    match error {
+        AppError::MissingAuthToken | AppError::MissingApiKey(_) => 0,
+        AppError::InvalidAuthHeader(_) => 1,
+        _ => 2,
+    }
 }
 
 pub fn verify_token(
     headers: &HeaderMap,
-    auth_config: &crate::config::AuthConfig,
+    auth_config: &AuthConfig,
     secrets: &SecretsConfig,
     key_store: &ApiKeyStore,
 ) -> Result<Claims, AppError> {
-    let token = extract_bearer_token(headers)?;
-
     match auth_config.auth_type {
-        AuthType::Jwt => verify_jwt(token, secrets),
-        AuthType::ApiKey => verify_api_key(token, key_store),
+        AuthType::Jwt => verify_jwt(extract_bearer_token(headers)?, secrets, auth_config),
+        AuthType::ApiKey => verify_api_key(extract_api_key(headers, auth_config)?, key_store),
+        AuthType::Basic => verify_basic(headers, key_store),
+        // Needs an HTTP call; handled by `authenticate`
+        AuthType::Introspection => Err(AppError::AuthUnavailable),
+        // Needs the peer address; handled by `authenticate`
+        AuthType::TrustedHeader => Err(AppError::AuthFailed(
+            "Request is not from a trusted source.".to_string(),
+        )),
+        // Needs the request body; handled by `authenticate`
+        AuthType::HmacSignature => Err(AppError::AuthFailed("Request body is not available.".to_string())),
     }
 }
 
 fn extract_bearer_token(headers: &HeaderMap) -> Result<&str, AppError> {
+    extract_credentials(headers, "Bearer")
+}
+
+/// The credentials of an `Authorization: <scheme> <credentials>` header. The
+/// scheme is matched case-insensitively, as HTTP specifies.
+pub(super) fn extract_credentials<'h>(headers: &'h HeaderMap, scheme: &'static str) -> Result<&'h str, AppError> {
     let auth_header = headers
         .get("Authorization")
         .and_then(|value| value.to_str().ok())
         .ok_or(AppError::MissingAuthToken)?;
 
-    auth_header.strip_prefix("Bearer ").ok_or(AppError::InvalidAuthHeader)
+    match auth_header.split_once(' ') {
+        Some((given, credentials)) if given.eq_ignore_ascii_case(scheme) => Ok(credentials.trim_start()),
+        _ => Err(AppError::InvalidAuthHeader(scheme)),
+    }
+}
+
+/// Reads an API key from the method's `header`. Without one it's taken from
+/// `X-API-Key`, or else `Authorization: Bearer` as clients used to send it.
+fn extract_api_key<'h>(headers: &'h HeaderMap, auth_config: &AuthConfig) -> Result<&'h str, AppError> {
+    let name = auth_config.header.as_deref().unwrap_or(DEFAULT_API_KEY_HEADER);
+    match headers.get(name) {
+        Some(value) => value.to_str().map_err(|_| AppError::MissingApiKey(name.to_string())),
+        None if auth_config.header.is_none() && headers.contains_key("Authorization") => extract_bearer_token(headers),
+        None => Err(AppError::MissingApiKey(name.to_string())),
+    }
 }
 
 pub fn check_roles(user_roles: &[String], required_roles: &[String]) -> Result<(), AppError> {
@@ -50,15 +229,67 @@ pub fn check_roles(user_roles: &[String], required_roles: &[String]) -> Result<(
     Ok(())
 }
 
+/// Checks a method's `required_claims` against the caller's claims. A missing
+/// or mismatched claim is `InsufficientPermissions`, like a missing role.
+#[allow(clippy::implicit_hasher)]
+pub fn check_claims(claims: &Claims, required: &HashMap<String, ClaimRequirement>) -> Result<(), AppError> {
+    for (name, requirement) in required {
+        let matched = match claims.value(name) {
+            Some(Value::Array(items)) => items.iter().any(|item| requirement.accepts(item)),
+            Some(value) => requirement.accepts(&value),
+            None => false,
+        };
+        if !matched {
+            debug!(claim = %name, "Required claim not matched");
+            return Err(AppError::InsufficientPermissions);
+        }
+    }
+    Ok(())
+}
+
 // ------- Private Helper Functions  -----
 
-fn verify_jwt(token: &str, secrets: &SecretsConfig) -> Result<Claims, AppError> {
+fn verify_jwt(token: &str, secrets: &SecretsConfig, auth_config: &AuthConfig) -> Result<Claims, AppError> {
     debug!("JWT verification attempt, token_len={}", token.len());
-    let key = DecodingKey::from_secret(secrets.jwt_secret.as_ref());
-    let validation = Validation::new(jsonwebtoken::Algorithm::HS256);
-    decode::<Claims>(token, &key, &validation)
+    let secret_key;
+    let key = if let Some(public_key) = &secrets.jwt_public_key {
+        public_key
+    } else {
+        secret_key = DecodingKey::from_secret(secrets.jwt_secret.as_ref());
+        &secret_key
+    };
+    // Only the configured algorithm is accepted, so an HMAC token can't be
+    // passed off as signed with the public key
+    decode_jwt(token, key, secrets.jwt_algorithm, auth_config)
+}
+
+/// Decodes and validates a JWT signed with `algorithm`, checking `iss` and
+/// `aud` against the method's `issuer` and `audience` when it sets them.
+pub(super) fn decode_jwt(
+    token: &str,
+    key: &DecodingKey,
+    algorithm: Algorithm,
+    auth_config: &AuthConfig,
+) -> Result<Claims, AppError> {
+    let mut validation = Validation::new(algorithm);
+    // jsonwebtoken only compares these claims when present, so a configured
+    // one is also made required
+    if let Some(issuer) = &auth_config.issuer {
+        validation.set_issuer(&[issuer]);
+        validation.required_spec_claims.insert("iss".to_string());
+    }
+    // Without one, jsonwebtoken's default stands: a token naming any
+    // audience is rejected
+    if let Some(audience) = &auth_config.audience {
+        validation.set_audience(&[audience]);
+        validation.required_spec_claims.insert("aud".to_string());
+    }
+    decode::<Claims>(token, key, &validation)
         .map_err(|error| match error.kind() {
             ErrorKind::ExpiredSignature => AppError::TokenExpired,
+            ErrorKind::InvalidIssuer => AppError::AuthFailed("JWT issuer is not accepted.".to_string()),
+            ErrorKind::InvalidAudience => AppError::AuthFailed("JWT audience is not accepted.".to_string()),
+            ErrorKind::MissingRequiredClaim(claim) => AppError::AuthFailed(format!("JWT has no {} claim.", claim)),
             _ => AppError::AuthFailed("Invalid JWT.".to_string()),
         })
         .map(|token_data| token_data.claims)
@@ -66,8 +297,7 @@ fn verify_jwt(token: &str, secrets: &SecretsConfig) -> Result<Claims, AppError>
 
 fn verify_api_key(token: &str, key_store: &ApiKeyStore) -> Result<Claims, AppError> {
     let details = key_store
-        .keys
-        .get(token)
+        .lookup(token)
         .ok_or_else(|| AppError::AuthFailed("Invalid API Key.".to_string()))?;
 
     if details.status != "active" {
@@ -78,5 +308,8 @@ fn verify_api_key(token: &str, key_store: &ApiKeyStore) -> Result<Claims, AppErr
         sub: details.user_id.clone(),
         roles: details.roles.clone(),
         exp: 0, // Not applicable for API keys
+        iss: None,
+        aud: None,
+        extra: HashMap::new(),
     })
 }
@@ -0,0 +1,224 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    num::{NonZeroU32, NonZeroUsize},
+    sync::LazyLock,
+};
+
+use axum::response::{IntoResponse, Response};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use http::{HeaderMap, HeaderValue, StatusCode, header::WWW_AUTHENTICATE};
+use ring::{
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use tokio::sync::{RwLock, Semaphore};
+
+use super::{
+    auth::{Claims, extract_credentials},
+    signature::decode_hex,
+};
+use crate::{config::ApiKeyStore, errors::AppError};
+
+/// PBKDF2 rounds for new password hashes. Existing hashes keep the count they
+/// were made with, so raising it doesn't invalidate them.
+pub const DEFAULT_PASSWORD_HASH_ITERATIONS: u32 = 600_000;
+
+/// Password checks hashing at once, one per core. Each holds a blocking-pool
+/// thread for a whole PBKDF2 run, so unbounded Basic attempts, which anyone
+/// can send, could starve everything else on the pool. Waiters are bounded
+/// by the method's auth timeout.
+static PASSWORD_CHECKS: LazyLock<Semaphore> =
+    LazyLock::new(|| Semaphore::new(std::thread::available_parallelism().map_or(4, NonZeroUsize::get)));
+
+const PASSWORD_HASH_SCHEME: &str = "pbkdf2-sha256";
+const SALT_LEN: usize = 16;
+const HASH_LEN: usize = 32;
+
+/// A `pbkdf2-sha256$<iterations>$<salt hex>$<hash hex>` password hash, as a
+/// key store user's `password_hash` holds it.
+pub struct PasswordHash {
+    iterations: NonZeroU32,
+    salt: Vec<u8>,
+    hash: Vec<u8>,
+}
+
+impl PasswordHash {
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut parts = s.split('$');
+        if parts.next()? != PASSWORD_HASH_SCHEME {
+            return None;
+        }
+        let iterations = parts.next()?.parse().ok()?;
+        let salt = decode_hex(parts.next()?)?;
+        let hash = decode_hex(parts.next()?)?;
+        if parts.next().is_some() || salt.is_empty() || hash.len() != HASH_LEN {
+            return None;
+        }
+        Some(Self { iterations, salt, hash })
+    }
+
+    /// Matches no password, at the cost of a default-strength hash. Verified
+    /// in place of a missing user's hash so the response time doesn't reveal
+    /// which usernames exist.
+    fn dummy() -> Self {
+        Self {
+            iterations: NonZeroU32::new(DEFAULT_PASSWORD_HASH_ITERATIONS).unwrap_or(NonZeroU32::MIN),
+            salt: vec![0; SALT_LEN],
+            hash: vec![0; HASH_LEN],
+        }
+    }
+
+    /// Whether `password` hashes to this. The comparison is constant-time;
+    /// the hashing is deliberately slow.
+    pub fn verify(&self, password: &str) -> bool {
+        pbkdf2::verify(
+            pbkdf2::PBKDF2_HMAC_SHA256,
+            self.iterations,
+            &self.salt,
+            password.as_bytes(),
+            &self.hash,
+        )
+        .is_ok()
+    }
+}
+
+/// Hashes a password with a random salt for a key store user's
+/// `password_hash`. `rustygw hash-password <password>` prints it.
+pub fn hash_password(password: &str, iterations: u32) -> Result<String, anyhow::Error> {
+    let iterations = NonZeroU32::new(iterations).ok_or_else(|| anyhow::anyhow!("iterations must be positive"))?;
+    let mut salt = [0u8; SALT_LEN];
+    SystemRandom::new()
+        .fill(&mut salt)
+        .map_err(|_| anyhow::anyhow!("failed to generate a salt"))?;
+    let mut hash = [0u8; HASH_LEN];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        &salt,
+        password.as_bytes(),
+        &mut hash,
+    );
+    Ok(format!(
+        "{}${}${}${}",
+        PASSWORD_HASH_SCHEME,
+        iterations,
+        encode_hex(&salt),
+        encode_hex(&hash)
+    ))
+}
+
+/// Checks `Authorization: Basic` credentials against the key store's users.
+pub fn verify_basic(headers: &HeaderMap, key_store: &ApiKeyStore) -> Result<Claims, AppError> {
+    let (username, password) = basic_credentials(headers)?;
+    let user = find_user(key_store, &username)?;
+    let verified = user.hash.verify(&password);
+    user.into_claims(username, verified)
+}
+
+/// `verify_basic` for the request path: the key store lock is released before
+/// hashing, which runs on the blocking pool so it doesn't stall other requests,
+/// and waits its turn behind `PASSWORD_CHECKS`.
+pub async fn authenticate_basic(headers: &HeaderMap, key_store: &RwLock<ApiKeyStore>) -> Result<Claims, AppError> {
+    let (username, password) = basic_credentials(headers)?;
+    let user = find_user(&*key_store.read().await, &username)?;
+    let permit = PASSWORD_CHECKS
+        .acquire()
+        .await
+        .map_err(|_| AppError::InternalServerError)?;
+    let (user, verified) = tokio::task::spawn_blocking(move || {
+        // Held by the hashing itself, which outlives a request that times out
+        let _permit = permit;
+        let verified = user.hash.verify(&password);
+        (user, verified)
+    })
+    .await
+    .map_err(|_| AppError::InternalServerError)?;
+    user.into_claims(username, verified)
+}
+
+/// The response for an auth error on a route with a `Basic` method: a 401
+/// also asks the client for Basic credentials, as browsers and HTTP tools
+/// expect before they send them.
+pub fn basic_challenge(error: AppError, realm: &str) -> Response {
+    let mut response = error.into_response();
+    if response.status() == StatusCode::UNAUTHORIZED {
+        let challenge = HeaderValue::from_str(&format!("Basic realm=\"{}\"", realm.replace('"', "")))
+            .unwrap_or_else(|_| HeaderValue::from_static("Basic"));
+        response.headers_mut().insert(WWW_AUTHENTICATE, challenge);
+    }
+    response
+}
+
+fn basic_credentials(headers: &HeaderMap) -> Result<(String, String), AppError> {
+    let encoded = extract_credentials(headers, "Basic")?;
+    let decoded = BASE64
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or(AppError::InvalidAuthHeader("Basic"))?;
+    let (username, password) = decoded.split_once(':').ok_or(AppError::InvalidAuthHeader("Basic"))?;
+    Ok((username.to_string(), password.to_string()))
+}
+
+/// A key store user, copied out so the store's lock isn't held while hashing.
+/// A missing user is still hashed against, so it costs the same to reject.
+struct FoundUser {
+    hash: PasswordHash,
+    roles: Vec<String>,
+    active: bool,
+    exists: bool,
+}
+
+impl FoundUser {
+    fn into_claims(self, username: String, verified: bool) -> Result<Claims, AppError> {
+        if !(verified && self.exists) {
+            return Err(invalid_credentials());
+        }
+        // Only told to whoever knows the password
+        if !self.active {
+            return Err(AppError::AuthFailed("User is disabled.".to_string()));
+        }
+        Ok(Claims {
+            sub: username,
+            roles: self.roles,
+            // No token, so nothing expires
+            exp: 0,
+            iss: None,
+            aud: None,
+            extra: HashMap::new(),
+        })
+    }
+}
+
+fn find_user(key_store: &ApiKeyStore, username: &str) -> Result<FoundUser, AppError> {
+    let Some(user) = key_store.users.get(username) else {
+        return Ok(FoundUser {
+            hash: PasswordHash::dummy(),
+            roles: Vec::new(),
+            active: false,
+            exists: false,
+        });
+    };
+    // Checked when the store is loaded
+    let hash = PasswordHash::parse(&user.password_hash).ok_or_else(invalid_credentials)?;
+    Ok(FoundUser {
+        hash,
+        roles: user.roles.clone(),
+        active: user.status == "active",
+        exists: true,
+    })
+}
+
+fn invalid_credentials() -> AppError {
+    // Doesn't say which of the two was wrong
+    AppError::AuthFailed("Invalid username or password.".to_string())
+}
+
+/// Lowercase hex, the inverse of `decode_hex`.
+pub(crate) fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::with_capacity(bytes.len() * 2), |mut hex, b| {
+        let _ = write!(hex, "{:02x}", b);
+        hex
+    })
+}
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::warn;
+
+use super::auth::Claims;
+use crate::{config::AuthConfig, errors::AppError, features::health_check::parse_duration};
+
+#[derive(Debug, Deserialize)]
+struct IntrospectionResponse {
+    active: bool,
+    #[serde(default)]
+    sub: String,
+    #[serde(default)]
+    roles: Vec<String>,
+    #[serde(default)]
+    exp: usize,
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
+}
+
+/// Asks the route's introspection endpoint whether `token` is active.
+/// Transport failures and 5xx answers mean the dependency is down and map to
+/// `AuthUnavailable`, so the route's `on_error` policy can decide.
+pub async fn introspect(client: &reqwest::Client, auth_config: &AuthConfig, token: &str) -> Result<Claims, AppError> {
+    let url = auth_config
+        .introspection_url
+        .as_deref()
+        .ok_or_else(|| AppError::InvalidDestination("introspection_url".to_string()))?;
+
+    let resp = client
+        .post(url)
+        .timeout(parse_duration(&auth_config.timeout))
+        .form(&[("token", token)])
+        .send()
+        .await
+        .map_err(|e| {
+            warn!(url = %url, "Introspection endpoint unreachable: {}", e);
+            AppError::AuthUnavailable
+        })?;
+
+    if resp.status().is_server_error() {
+        warn!(url = %url, status = %resp.status(), "Introspection endpoint failed");
+        return Err(AppError::AuthUnavailable);
+    }
+    if !resp.status().is_success() {
+        return Err(AppError::AuthFailed("Token introspection was rejected.".to_string()));
+    }
+
+    let body: IntrospectionResponse = resp.json().await.map_err(|e| {
+        warn!(url = %url, "Invalid introspection response: {}", e);
+        AppError::AuthUnavailable
+    })?;
+    if !body.active {
+        return Err(AppError::AuthFailed("Token is not active.".to_string()));
+    }
+
+    Ok(Claims {
+        sub: body.sub,
+        roles: body.roles,
+        exp: body.exp,
+        iss: None,
+        aud: None,
+        extra: body.extra,
+    })
+}
@@ -0,0 +1,178 @@
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use jsonwebtoken::{
+    Algorithm, DecodingKey, decode_header,
+    jwk::{Jwk, JwkSet},
+};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use super::auth::{Claims, decode_jwt};
+use crate::{config::AuthConfig, errors::AppError, features::health_check::parse_duration};
+
+/// A key from a JWKS, with the algorithm it is published for, if any.
+struct SigningKey {
+    key: DecodingKey,
+    algorithm: Option<Algorithm>,
+}
+
+/// The last good key set fetched from a JWKS endpoint, by `kid`.
+struct KeySet {
+    keys: Arc<HashMap<String, Arc<SigningKey>>>,
+    /// Last fetch attempt, successful or not.
+    checked_at: Instant,
+}
+
+/// Key sets of the `jwks_url`s in use. A set is refreshed when older than the
+/// auth method's `jwks_refresh`, or when a token names a `kid` it lacks; a
+/// failed fetch keeps the previous set.
+pub struct JwksCache {
+    sets: DashMap<String, KeySet>,
+    /// Least time between fetches triggered by unknown `kid`s, so tokens with
+    /// made-up ids can't make the gateway hammer the identity provider.
+    miss_cooldown: Duration,
+    /// One fetch at a time; callers queued behind it reuse its result.
+    fetching: Mutex<()>,
+}
+
+impl JwksCache {
+    pub fn new(miss_cooldown: Duration) -> Self {
+        Self {
+            sets: DashMap::new(),
+            miss_cooldown,
+            fetching: Mutex::new(()),
+        }
+    }
+
+    /// Fetches the key set at `url` now, replacing the cached one on success.
+    pub async fn refresh(&self, client: &reqwest::Client, url: &str, timeout: Duration) -> Result<usize, AppError> {
+        let started = Instant::now();
+        let _fetching = self.fetching.lock().await;
+        // Someone else fetched while this call waited for the lock
+        if let Some(set) = self.sets.get(url)
+            && set.checked_at >= started
+        {
+            return Ok(set.keys.len());
+        }
+
+        let fetched = fetch(client, url, timeout).await;
+        let mut set = self.sets.entry(url.to_string()).or_insert_with(|| KeySet {
+            keys: Arc::default(),
+            checked_at: Instant::now(),
+        });
+        set.checked_at = Instant::now();
+        let keys = fetched?;
+        info!(url = %url, keys = keys.len(), "JWKS refreshed");
+        let count = keys.len();
+        set.keys = Arc::new(keys);
+        Ok(count)
+    }
+
+    /// Verifies `token` with the key its header's `kid` names in the set at
+    /// the method's `jwks_url`. No usable key set at all is `AuthUnavailable`,
+    /// so the method's `on_error` policy applies.
+    pub async fn verify(
+        &self,
+        client: &reqwest::Client,
+        auth_config: &AuthConfig,
+        token: &str,
+    ) -> Result<Claims, AppError> {
+        let url = auth_config
+            .jwks_url
+            .as_deref()
+            .ok_or_else(|| AppError::InvalidDestination("jwks_url".to_string()))?;
+        let header = decode_header(token).map_err(|_| AppError::AuthFailed("Invalid JWT.".to_string()))?;
+        // An HMAC token would be checked against public key material
+        if matches!(header.alg, Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512) {
+            return Err(AppError::AuthFailed("Invalid JWT.".to_string()));
+        }
+        let kid = header
+            .kid
+            .ok_or_else(|| AppError::AuthFailed("JWT has no key id.".to_string()))?;
+
+        let key = self
+            .signing_key(client, url, &kid, auth_config)
+            .await?
+            .ok_or_else(|| AppError::AuthFailed("JWT signed with an unknown key.".to_string()))?;
+        if key.algorithm.is_some_and(|alg| alg != header.alg) {
+            return Err(AppError::AuthFailed("Invalid JWT.".to_string()));
+        }
+
+        decode_jwt(token, &key.key, header.alg, auth_config)
+    }
+
+    async fn signing_key(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+        kid: &str,
+        auth_config: &AuthConfig,
+    ) -> Result<Option<Arc<SigningKey>>, AppError> {
+        let refresh_every = parse_duration(&auth_config.jwks_refresh);
+        let (key, age) = match self.sets.get(url) {
+            Some(set) => (set.keys.get(kid).cloned(), Some(set.checked_at.elapsed())),
+            None => (None, None),
+        };
+        let due = match (&key, age) {
+            (_, None) => true,
+            (Some(_), Some(age)) => age >= refresh_every,
+            (None, Some(age)) => age >= self.miss_cooldown.min(refresh_every),
+        };
+        if !due {
+            return Ok(key);
+        }
+
+        if let Err(e) = self.refresh(client, url, parse_duration(&auth_config.timeout)).await {
+            warn!(url = %url, "JWKS refresh failed, keeping the last key set: {:?}", e);
+        }
+        match self.sets.get(url) {
+            Some(set) if !set.keys.is_empty() => Ok(set.keys.get(kid).cloned()),
+            _ => Err(AppError::AuthUnavailable),
+        }
+    }
+}
+
+async fn fetch(
+    client: &reqwest::Client,
+    url: &str,
+    timeout: Duration,
+) -> Result<HashMap<String, Arc<SigningKey>>, AppError> {
+    let resp = client.get(url).timeout(timeout).send().await.map_err(|e| {
+        warn!(url = %url, "JWKS endpoint unreachable: {}", e);
+        AppError::AuthUnavailable
+    })?;
+    if !resp.status().is_success() {
+        warn!(url = %url, status = %resp.status(), "JWKS endpoint failed");
+        return Err(AppError::AuthUnavailable);
+    }
+    let set: JwkSet = resp.json().await.map_err(|e| {
+        warn!(url = %url, "Invalid JWKS response: {}", e);
+        AppError::AuthUnavailable
+    })?;
+
+    Ok(set.keys.iter().filter_map(signing_key).collect())
+}
+
+/// Keys without a `kid` can't be selected, and ones jsonwebtoken can't use
+/// are skipped rather than failing the whole set.
+fn signing_key(jwk: &Jwk) -> Option<(String, Arc<SigningKey>)> {
+    let kid = jwk.common.key_id.clone()?;
+    let key = match DecodingKey::from_jwk(jwk) {
+        Ok(key) => key,
+        Err(e) => {
+            warn!(kid = %kid, "Skipping unusable JWKS key: {}", e);
+            return None;
+        }
+    };
+    let algorithm = jwk
+        .common
+        .key_algorithm
+        .and_then(|alg| Algorithm::from_str(&alg.to_string()).ok());
+    Some((kid, Arc::new(SigningKey { key, algorithm })))
+}
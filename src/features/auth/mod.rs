@@ -1,2 +1,7 @@
 #[allow(clippy::module_inception)]
 pub mod auth;
+pub mod basic;
+pub mod introspection;
+pub mod jwks;
+pub mod signature;
+pub mod trusted_header;
@@ -0,0 +1,68 @@
+use std::{collections::HashMap, net::IpAddr};
+
+use http::HeaderMap;
+use tracing::warn;
+
+use super::auth::Claims;
+use crate::{config::AuthConfig, errors::AppError, utils::ip_range::ip_in_ranges};
+
+/// Whether `client_ip` may assert an identity for `method`.
+pub fn is_trusted_source(method: &AuthConfig, client_ip: Option<IpAddr>) -> bool {
+    client_ip.is_some_and(|ip| ip_in_ranges(ip, &method.trusted_ips))
+}
+
+/// Whether the request carries any of `method`'s identity headers.
+pub fn carries_identity(headers: &HeaderMap, method: &AuthConfig) -> bool {
+    headers.contains_key(method.user_header.as_str()) || headers.contains_key(method.roles_header.as_str())
+}
+
+/// Builds claims from identity headers set by a peer that already
+/// authenticated the caller, e.g. a service mesh sidecar. Nothing but the
+/// peer's address is verified, so the same headers from anyone else are
+/// rejected rather than ignored.
+pub fn trusted_identity(
+    headers: &HeaderMap,
+    method: &AuthConfig,
+    client_ip: Option<IpAddr>,
+) -> Result<Claims, AppError> {
+    if !is_trusted_source(method, client_ip) {
+        if carries_identity(headers, method) {
+            warn!(client_ip = ?client_ip, "Rejected identity headers from an untrusted source");
+            return Err(AppError::AuthFailed(
+                "Identity headers are only accepted from trusted sources.".to_string(),
+            ));
+        }
+        return Err(AppError::AuthFailed(
+            "Request is not from a trusted source.".to_string(),
+        ));
+    }
+
+    let sub = headers
+        .get(method.user_header.as_str())
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|user| !user.is_empty())
+        .ok_or_else(|| AppError::AuthFailed(format!("Missing '{}' header.", method.user_header)))?;
+    let roles = headers
+        .get(method.roles_header.as_str())
+        .and_then(|v| v.to_str().ok())
+        .map(|roles| {
+            roles
+                .split(',')
+                .map(str::trim)
+                .filter(|role| !role.is_empty())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(Claims {
+        sub: sub.to_string(),
+        roles,
+        // No token, so nothing expires
+        exp: 0,
+        iss: None,
+        aud: None,
+        extra: HashMap::new(),
+    })
+}
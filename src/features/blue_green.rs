@@ -0,0 +1,78 @@
+use axum_prometheus::metrics::counter;
+use dashmap::DashMap;
+use tracing::info;
+
+use crate::config::{BlueGreenConfig, DeploymentColor, RouteConfig};
+
+#[derive(Clone, Copy)]
+struct ActiveGroup {
+    /// The config's `active` when this entry was set.
+    configured: DeploymentColor,
+    active: DeploymentColor,
+}
+
+/// Which group each blue-green route sends traffic to. Starts at the config's
+/// `active`; `switch` flips it at runtime. A reload that changes `active` in
+/// the config wins over an earlier switch.
+pub struct BlueGreenSwitch {
+    routes: DashMap<String, ActiveGroup>,
+}
+
+impl BlueGreenSwitch {
+    pub fn new() -> Self {
+        Self { routes: DashMap::new() }
+    }
+
+    pub fn active(&self, route: &str, config: &BlueGreenConfig) -> DeploymentColor {
+        if let Some(group) = self.routes.get(route)
+            && group.configured == config.active
+        {
+            return group.active;
+        }
+        self.update(route, config, |_| {}).active
+    }
+
+    /// Sends all new requests for `route` to the other group and returns the
+    /// group that is now active. Requests already sent to the old group are
+    /// not affected.
+    pub fn switch(&self, route: &str, config: &BlueGreenConfig) -> DeploymentColor {
+        let group = self.update(route, config, |group| group.active = group.active.other());
+        info!(route = %route, active = ?group.active, "Blue-green traffic switched");
+        counter!("gateway_blue_green_switches_total", "route" => route.to_string()).increment(1);
+        group.active
+    }
+
+    /// The destinations a request to `route` may go to: the active group for
+    /// blue-green routes, all destinations otherwise.
+    pub fn destinations<'a>(&self, route: &'a RouteConfig) -> Vec<&'a str> {
+        match &route.blue_green {
+            Some(blue_green) => blue_green
+                .group(self.active(&route.name, blue_green))
+                .iter()
+                .map(String::as_str)
+                .collect(),
+            None => route.all_destinations(),
+        }
+    }
+
+    /// Applies `f` to the route's entry under its lock, so concurrent
+    /// switches each flip the group once.
+    fn update(&self, route: &str, config: &BlueGreenConfig, f: impl FnOnce(&mut ActiveGroup)) -> ActiveGroup {
+        let fresh = ActiveGroup {
+            configured: config.active,
+            active: config.active,
+        };
+        let mut entry = self.routes.entry(route.to_string()).or_insert(fresh);
+        if entry.configured != config.active {
+            *entry = fresh;
+        }
+        f(&mut entry);
+        *entry
+    }
+}
+
+impl Default for BlueGreenSwitch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
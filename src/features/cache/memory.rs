@@ -0,0 +1,62 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use moka::{Expiry, future::Cache};
+
+use super::ResponseCache;
+use crate::{errors::StoreUnavailable, state::CachedResponse};
+
+type Entry = (Arc<CachedResponse>, Option<Duration>);
+
+/// Per-process cache; the default backend.
+pub struct MokaResponseCache {
+    inner: Cache<String, Entry>,
+}
+
+/// Expires each entry after the TTL it was inserted with.
+struct PerEntryTtl;
+
+impl Expiry<String, Entry> for PerEntryTtl {
+    fn expire_after_create(&self, _key: &String, value: &Entry, _created_at: Instant) -> Option<Duration> {
+        value.1
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &String,
+        value: &Entry,
+        _updated_at: Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        value.1
+    }
+}
+
+impl MokaResponseCache {
+    pub fn new(max_capacity: u64) -> Self {
+        Self {
+            inner: Cache::builder()
+                .max_capacity(max_capacity)
+                .expire_after(PerEntryTtl)
+                .build(),
+        }
+    }
+}
+
+#[async_trait]
+impl ResponseCache for MokaResponseCache {
+    async fn get(&self, key: &str) -> Result<Option<Arc<CachedResponse>>, StoreUnavailable> {
+        Ok(self.inner.get(key).await.map(|(value, _)| value))
+    }
+
+    async fn insert(&self, key: String, value: Arc<CachedResponse>, ttl: Option<Duration>) {
+        self.inner.insert(key, (value, ttl)).await;
+    }
+
+    async fn invalidate(&self, key: &str) {
+        self.inner.invalidate(key).await;
+    }
+}
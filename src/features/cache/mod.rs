@@ -0,0 +1,56 @@
+pub mod memory;
+#[cfg(feature = "redis")]
+pub mod redis_store;
+
+use std::{sync::Arc, time::Duration};
+
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use crate::{
+    config::{CacheBackend, CacheStoreConfig},
+    errors::StoreUnavailable,
+    state::CachedResponse,
+};
+
+/// Storage for cached responses. Implementations own expiry: `get` never
+/// returns an entry older than the `ttl` it was inserted with.
+#[async_trait]
+pub trait ResponseCache: Send + Sync {
+    /// `Err` when the store can't be reached, which `degradation.cache` handles.
+    async fn get(&self, key: &str) -> Result<Option<Arc<CachedResponse>>, StoreUnavailable>;
+    /// `ttl` of `None` keeps the entry until it is evicted or invalidated.
+    async fn insert(&self, key: String, value: Arc<CachedResponse>, ttl: Option<Duration>);
+    async fn invalidate(&self, key: &str);
+}
+
+#[cfg_attr(not(feature = "redis"), allow(clippy::unused_async))]
+pub async fn build_response_cache(config: &CacheStoreConfig) -> Result<Arc<dyn ResponseCache>> {
+    match config.backend {
+        CacheBackend::Memory => Ok(Arc::new(memory::MokaResponseCache::new(config.max_capacity))),
+        #[cfg(feature = "redis")]
+        CacheBackend::Redis => {
+            let Some(url) = config.redis_url.as_deref() else {
+                bail!("server.cache.redis_url is required for the redis cache backend");
+            };
+            Ok(Arc::new(
+                redis_store::RedisResponseCache::connect(url, &config.key_prefix).await?,
+            ))
+        }
+        #[cfg(not(feature = "redis"))]
+        CacheBackend::Redis => bail!("the redis cache backend requires building with `--features redis`"),
+    }
+}
+
+/// Randomizes `ttl` within +/-`jitter_percent` so entries created together
+/// don't all expire at once.
+pub fn jittered_ttl(ttl: Duration, jitter_percent: u8) -> Duration {
+    if jitter_percent == 0 {
+        return ttl;
+    }
+    let spread = f64::from(jitter_percent.min(100)) / 100.0;
+    // Uniform in [-1.0, 1.0)
+    let unit = (Uuid::new_v4().as_u128() >> 75) as f64 / (1u64 << 52) as f64 - 1.0;
+    ttl.mul_f64(1.0 + unit * spread)
+}
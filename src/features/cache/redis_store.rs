@@ -0,0 +1,83 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use redis::{AsyncCommands, Client, aio::ConnectionManager};
+use tracing::warn;
+
+use super::ResponseCache;
+use crate::{errors::StoreUnavailable, state::CachedResponse};
+
+/// Cache shared by every gateway instance pointed at the same Redis.
+/// Entries are stored as JSON under `key_prefix` + cache key, with Redis
+/// handling expiry. A failed GET is reported as `StoreUnavailable` for
+/// `degradation.cache` to handle; failed writes are only logged.
+pub struct RedisResponseCache {
+    conn: ConnectionManager,
+    key_prefix: String,
+}
+
+impl RedisResponseCache {
+    pub async fn connect(url: &str, key_prefix: &str) -> Result<Self, redis::RedisError> {
+        let conn = ConnectionManager::new(Client::open(url)?).await?;
+        Ok(Self {
+            conn,
+            key_prefix: key_prefix.to_string(),
+        })
+    }
+
+    fn redis_key(&self, key: &str) -> String {
+        format!("{}{}", self.key_prefix, key)
+    }
+}
+
+#[async_trait]
+impl ResponseCache for RedisResponseCache {
+    async fn get(&self, key: &str) -> Result<Option<Arc<CachedResponse>>, StoreUnavailable> {
+        let mut conn = self.conn.clone();
+        let raw: Option<Vec<u8>> = conn
+            .get(self.redis_key(key))
+            .await
+            .map_err(|e| StoreUnavailable(format!("Redis cache GET failed: {}", e)))?;
+        let Some(raw) = raw else {
+            return Ok(None);
+        };
+        match serde_json::from_slice(&raw) {
+            Ok(value) => Ok(Some(Arc::new(value))),
+            Err(e) => {
+                warn!(key = %key, "Discarding undecodable cache entry: {}", e);
+                Ok(None)
+            }
+        }
+    }
+
+    async fn insert(&self, key: String, value: Arc<CachedResponse>, ttl: Option<Duration>) {
+        let payload = match serde_json::to_vec(value.as_ref()) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!(key = %key, "Failed to encode cache entry: {}", e);
+                return;
+            }
+        };
+        let mut conn = self.conn.clone();
+        let redis_key = self.redis_key(&key);
+        let result: redis::RedisResult<()> = match ttl {
+            // PX takes milliseconds; anything under 1ms would be rejected as 0
+            Some(ttl) => {
+                let millis = u64::try_from(ttl.as_millis()).unwrap_or(u64::MAX).max(1);
+                conn.pset_ex(redis_key, payload, millis).await
+            }
+            None => conn.set(redis_key, payload).await,
+        };
+        if let Err(e) = result {
+            warn!(key = %key, "Redis cache SET failed: {}", e);
+        }
+    }
+
+    async fn invalidate(&self, key: &str) {
+        let mut conn = self.conn.clone();
+        let result: redis::RedisResult<()> = conn.del(self.redis_key(key)).await;
+        if let Err(e) = result {
+            warn!(key = %key, "Redis cache DEL failed: {}", e);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use axum_prometheus::metrics::counter;
+use dashmap::DashMap;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{
+    config::CanaryConfig, features::circuit_breaker::error_rate::ErrorRateWindow,
+    middleware::rate_limiter::rate_limit::parse_duration,
+};
+
+struct CanaryState {
+    window: ErrorRateWindow,
+    rolled_back: AtomicBool,
+}
+
+/// Per-route canary error tracking. Keyed by route and canary destination, so
+/// pointing a route at a new canary starts it with a clean slate.
+pub struct CanaryTracker {
+    canaries: DashMap<String, Arc<CanaryState>>,
+}
+
+impl CanaryTracker {
+    pub fn new() -> Self {
+        Self {
+            canaries: DashMap::new(),
+        }
+    }
+
+    /// Decides whether this request goes to the canary. Always false once the
+    /// canary has been rolled back.
+    pub fn routes_to_canary(&self, route: &str, canary: &CanaryConfig) -> bool {
+        if canary.weight == 0 || self.is_rolled_back(route, canary) {
+            return false;
+        }
+        canary.weight >= 100 || (Uuid::new_v4().as_u128() % 100) < u128::from(canary.weight)
+    }
+
+    /// Records a canary response and rolls the canary back once its error
+    /// rate exceeds `error_rate_threshold`.
+    pub fn record(&self, route: &str, canary: &CanaryConfig, failed: bool) {
+        let state = self.state(route, canary);
+        if state.rolled_back.load(Ordering::Relaxed) {
+            return;
+        }
+        if let Some(error_rate) = state.window.observe(failed, canary.error_rate_threshold)
+            && !state.rolled_back.swap(true, Ordering::Relaxed)
+        {
+            warn!(
+                route = %route,
+                canary = %canary.destination,
+                error_rate = error_rate,
+                "Canary error rate exceeded its budget, draining traffic back to stable destinations"
+            );
+            counter!("gateway_canary_rollbacks_total", "route" => route.to_string()).increment(1);
+        }
+    }
+
+    pub fn is_rolled_back(&self, route: &str, canary: &CanaryConfig) -> bool {
+        self.canaries
+            .get(&Self::key(route, canary))
+            .is_some_and(|s| s.rolled_back.load(Ordering::Relaxed))
+    }
+
+    fn state(&self, route: &str, canary: &CanaryConfig) -> Arc<CanaryState> {
+        self.canaries
+            .entry(Self::key(route, canary))
+            .or_insert_with(|| {
+                Arc::new(CanaryState {
+                    window: ErrorRateWindow::new(
+                        parse_duration(&canary.window).unwrap_or_else(|_| Duration::from_secs(60)),
+                        canary.min_requests,
+                    ),
+                    rolled_back: AtomicBool::new(false),
+                })
+            })
+            .clone()
+    }
+
+    fn key(route: &str, canary: &CanaryConfig) -> String {
+        format!("{}|{}", route, canary.destination)
+    }
+}
+
+impl Default for CanaryTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,227 @@
+use std::{
+    collections::HashSet,
+    path::Path,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use bytes::Bytes;
+use http::{HeaderMap, HeaderName, HeaderValue, Method};
+use serde::{Deserialize, Serialize};
+use tokio::{
+    fs::OpenOptions,
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    sync::{mpsc, oneshot},
+};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::config::CaptureConfig;
+
+pub const REDACTED: &str = "[REDACTED]";
+/// Credential headers redacted by default, in captures and access logs.
+pub const SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+];
+
+/// Headers that describe the original connection and are not replayed.
+const SKIP_ON_REPLAY: &[&str] = &["host", "content-length", "connection", "transfer-encoding"];
+
+/// One line of the capture file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapturedRequest {
+    pub method: String,
+    /// Path and query, e.g. `/api/users?page=2`.
+    pub uri: String,
+    pub headers: Vec<(String, String)>,
+    /// Base64 of the body; `None` if it was too large to capture.
+    pub body_base64: Option<String>,
+}
+
+enum CaptureMessage {
+    Record(CapturedRequest),
+    /// Answered once everything queued before it is written.
+    Flush(oneshot::Sender<()>),
+}
+
+/// Samples requests and appends them to the capture file from a background task.
+pub struct RequestCapture {
+    sender: mpsc::Sender<CaptureMessage>,
+    sample_rate: f64,
+    max_requests: u64,
+    max_body_bytes: usize,
+    redact_headers: HashSet<String>,
+    captured: AtomicU64,
+}
+
+impl RequestCapture {
+    pub async fn start(config: &CaptureConfig) -> std::io::Result<Self> {
+        let mut file = OpenOptions::new().create(true).append(true).open(&config.path).await?;
+        let (sender, mut receiver) = mpsc::channel::<CaptureMessage>(256);
+        let path = config.path.clone();
+
+        tokio::spawn(async move {
+            while let Some(message) = receiver.recv().await {
+                let record = match message {
+                    CaptureMessage::Record(record) => record,
+                    CaptureMessage::Flush(done) => {
+                        let _ = done.send(());
+                        continue;
+                    }
+                };
+                let mut line = match serde_json::to_vec(&record) {
+                    Ok(line) => line,
+                    Err(e) => {
+                        warn!("Failed to encode captured request: {}", e);
+                        continue;
+                    }
+                };
+                line.push(b'\n');
+                let written = match file.write_all(&line).await {
+                    Ok(()) => file.flush().await,
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = written {
+                    error!(path = %path, "Failed to write capture file: {}. Capture stopped.", e);
+                    return;
+                }
+            }
+        });
+        info!(path = %config.path, sample_rate = config.sample_rate, "Request capture enabled");
+
+        Ok(Self {
+            sender,
+            sample_rate: config.sample_rate.clamp(0.0, 1.0),
+            max_requests: config.max_requests,
+            max_body_bytes: config.max_body_bytes,
+            redact_headers: config.redact_headers.iter().map(|h| h.to_ascii_lowercase()).collect(),
+            captured: AtomicU64::new(0),
+        })
+    }
+
+    pub fn max_body_bytes(&self) -> usize {
+        self.max_body_bytes
+    }
+
+    /// Decides whether to capture the next request, honoring the sample rate
+    /// and the `max_requests` budget.
+    pub fn should_sample(&self) -> bool {
+        if self.sample_rate <= 0.0 {
+            return false;
+        }
+        if self.sample_rate < 1.0 {
+            let roll = (Uuid::new_v4().as_u128() >> 75) as f64 / (1u64 << 53) as f64;
+            if roll >= self.sample_rate {
+                return false;
+            }
+        }
+        self.captured
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+                (n < self.max_requests).then_some(n + 1)
+            })
+            .is_ok()
+    }
+
+    /// Queues a request for writing. Drops it if the writer is behind.
+    pub fn record(&self, method: &Method, uri: &str, headers: &HeaderMap, body: Option<&Bytes>) {
+        let record = CapturedRequest {
+            method: method.to_string(),
+            uri: uri.to_string(),
+            headers: headers
+                .iter()
+                .map(|(name, value)| {
+                    let value = if self.redact_headers.contains(name.as_str()) {
+                        REDACTED.to_string()
+                    } else {
+                        String::from_utf8_lossy(value.as_bytes()).into_owned()
+                    };
+                    (name.as_str().to_string(), value)
+                })
+                .collect(),
+            body_base64: body.map(|b| BASE64.encode(b)),
+        };
+        if self.sender.try_send(CaptureMessage::Record(record)).is_err() {
+            warn!("Capture writer is behind, dropping captured request");
+        }
+    }
+
+    /// Waits until every request queued so far is written to the file.
+    pub async fn flush(&self) {
+        let (done, written) = oneshot::channel();
+        if self.sender.send(CaptureMessage::Flush(done)).await.is_ok() {
+            let _ = written.await;
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ReplaySummary {
+    pub sent: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+/// Replays every request in a capture file against `target` (e.g.
+/// `http://localhost:8094`). Redacted headers are not sent.
+pub async fn replay_file(path: &Path, target: &str, client: &reqwest::Client) -> std::io::Result<ReplaySummary> {
+    let file = tokio::fs::File::open(path).await?;
+    let mut lines = BufReader::new(file).lines();
+    let mut summary = ReplaySummary::default();
+    let target = target.trim_end_matches('/');
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: CapturedRequest = match serde_json::from_str(&line) {
+            Ok(r) => r,
+            Err(e) => {
+                warn!("Skipping malformed capture line: {}", e);
+                summary.skipped += 1;
+                continue;
+            }
+        };
+        match replay_one(&record, target, client).await {
+            Ok(status) => {
+                info!(method = %record.method, uri = %record.uri, status = %status, "Replayed request");
+                summary.sent += 1;
+            }
+            Err(e) => {
+                warn!(method = %record.method, uri = %record.uri, "Replay failed: {}", e);
+                summary.failed += 1;
+            }
+        }
+    }
+    Ok(summary)
+}
+
+async fn replay_one(
+    record: &CapturedRequest,
+    target: &str,
+    client: &reqwest::Client,
+) -> anyhow::Result<http::StatusCode> {
+    let method: Method = record.method.parse()?;
+    let mut headers = HeaderMap::new();
+    for (name, value) in &record.headers {
+        if value == REDACTED || SKIP_ON_REPLAY.contains(&name.as_str()) {
+            continue;
+        }
+        headers.append(HeaderName::from_bytes(name.as_bytes())?, HeaderValue::from_str(value)?);
+    }
+    let body = match &record.body_base64 {
+        Some(b64) => BASE64.decode(b64)?,
+        None => Vec::new(),
+    };
+
+    let resp = client
+        .request(method, format!("{}{}", target, record.uri))
+        .headers(headers)
+        .body(body)
+        .send()
+        .await?;
+    Ok(resp.status())
+}
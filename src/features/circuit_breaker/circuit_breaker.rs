@@ -1,21 +1,231 @@
-use std::{sync::Arc, time::Instant};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use dashmap::DashMap;
 use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use super::events::{CircuitEvent, CircuitEventLog};
+use crate::{
+    config::CircuitBreakerConfig,
+    features::store_cleanup::{SweepQueue, SweepStats},
+};
 
 #[derive(Debug, Clone)]
 pub enum State {
     Closed { consecutive_failures: u32 },
-    Open { opened_at: Instant },
+    Open { opened_at: Instant, open_for: Duration },
     HalfOpen { consecutive_successes: u32 },
 }
 
+/// Trips since the circuit last stayed closed for `backoff_reset_after`.
+struct Backoff {
+    trips: u32,
+    closed_at: Option<Instant>,
+}
+
+impl State {
+    fn label(&self) -> &'static str {
+        match self {
+            State::Closed { .. } => "closed",
+            State::Open { .. } => "open",
+            State::HalfOpen { .. } => "half_open",
+        }
+    }
+}
+
 pub struct CircuitState {
     pub state: RwLock<State>,
+    backoff: Mutex<Backoff>,
+    /// Where transitions are recorded, when the store keeps a log.
+    events: Option<Arc<CircuitEventLog>>,
+}
+
+impl Default for CircuitState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CircuitState {
+    pub fn new() -> Self {
+        Self {
+            state: RwLock::new(State::Closed {
+                consecutive_failures: 0,
+            }),
+            backoff: Mutex::new(Backoff {
+                trips: 0,
+                closed_at: None,
+            }),
+            events: None,
+        }
+    }
+
+    /// Records this circuit's transitions in `events`.
+    #[must_use]
+    pub fn with_events(mut self, events: Arc<CircuitEventLog>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    fn transition(&self, name: &str, state: &mut State, next: State, reason: impl FnOnce() -> String) {
+        if let Some(events) = &self.events {
+            events.push(name, state.label(), next.label(), reason());
+        }
+        *state = next;
+    }
+
+    fn is_idle(&self) -> bool {
+        let no_trips = match self.backoff.lock() {
+            Ok(b) => b.trips == 0,
+            Err(poisoned) => poisoned.into_inner().trips == 0,
+        };
+        no_trips
+            && self.state.try_read().is_ok_and(|state| {
+                matches!(
+                    *state,
+                    State::Closed {
+                        consecutive_failures: 0
+                    }
+                )
+            })
+    }
+
+    /// Returns false while the circuit is open. An open circuit whose open
+    /// duration has passed moves to half-open and lets the request through.
+    pub async fn allow_request(&self, name: &str) -> bool {
+        let mut state = self.state.write().await;
+        match *state {
+            State::Open { opened_at, open_for } => {
+                if opened_at.elapsed() > open_for {
+                    self.transition(
+                        name,
+                        &mut state,
+                        State::HalfOpen {
+                            consecutive_successes: 0,
+                        },
+                        || format!("open for {}ms, allowing trial requests", open_for.as_millis()),
+                    );
+                    info!(circuit = %name, "Circuit breaker is now HALF-OPEN");
+                    true
+                } else {
+                    false
+                }
+            }
+            State::HalfOpen { .. } => {
+                info!(circuit = %name, "Circuit breaker is HALF-OPEN, allowing trial requests");
+                true
+            }
+            State::Closed { .. } => true,
+        }
+    }
+
+    /// How long the circuit stays open on this trip. With `max_open_duration`
+    /// set, each trip in a row multiplies the previous duration.
+    fn next_open_duration(&self, config: &CircuitBreakerConfig, from_half_open: bool) -> (u32, Duration) {
+        let mut backoff = match self.backoff.lock() {
+            Ok(b) => b,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let recovered = !from_half_open
+            && backoff
+                .closed_at
+                .is_some_and(|closed_at| closed_at.elapsed() >= config.backoff_reset_after());
+        backoff.trips = if recovered { 1 } else { backoff.trips + 1 };
+        // The reset window restarts the next time the circuit closes
+        backoff.closed_at = None;
+        (backoff.trips, config.open_duration_for(backoff.trips))
+    }
+
+    fn mark_closed(&self) {
+        let mut backoff = match self.backoff.lock() {
+            Ok(b) => b,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        backoff.closed_at = Some(Instant::now());
+    }
+
+    /// Feeds the outcome of a request into the state machine.
+    pub async fn record(&self, name: &str, failed: bool, config: &CircuitBreakerConfig) {
+        let mut state = self.state.write().await;
+
+        if failed {
+            // If a trial fails OR a normal request fails, we check the failure threshold.
+            let (failures, from_half_open) = match *state {
+                State::Closed { consecutive_failures } => (consecutive_failures + 1, false),
+                State::HalfOpen { .. } => (1, true), // First failure in HalfOpen state
+                State::Open { .. } => return,
+            };
+
+            if failures >= config.failure_threshold {
+                let (trips, open_for) = self.next_open_duration(config, from_half_open);
+                self.transition(
+                    name,
+                    &mut state,
+                    State::Open {
+                        opened_at: Instant::now(),
+                        open_for,
+                    },
+                    || {
+                        let cause = if from_half_open {
+                            "trial request failed".to_string()
+                        } else {
+                            format!("{} consecutive failures", failures)
+                        };
+                        format!("{}, open for {}ms", cause, open_for.as_millis())
+                    },
+                );
+                warn!(circuit = %name, trips = trips, open_ms = u64::try_from(open_for.as_millis()).unwrap_or(u64::MAX), "Failure threshold reached, circuit is OPENED");
+            } else {
+                *state = State::Closed {
+                    consecutive_failures: failures,
+                };
+                if from_half_open {
+                    self.mark_closed();
+                }
+            }
+        } else {
+            match *state {
+                State::HalfOpen { consecutive_successes } => {
+                    let new_successes = consecutive_successes + 1;
+                    if new_successes >= config.success_threshold {
+                        // Success threshold reached, close the circuit.
+                        self.transition(
+                            name,
+                            &mut state,
+                            State::Closed {
+                                consecutive_failures: 0,
+                            },
+                            || format!("{} consecutive trial successes", new_successes),
+                        );
+                        self.mark_closed();
+                        info!(circuit = %name, "Success threshold reached, circuit is now CLOSED");
+                    } else {
+                        // Increment success count but remain Half-Open.
+                        *state = State::HalfOpen {
+                            consecutive_successes: new_successes,
+                        };
+                        info!(circuit = %name, successes = new_successes, "Trial request succeeded, remaining HALF-OPEN");
+                    }
+                }
+                State::Closed { consecutive_failures } if consecutive_failures > 0 => {
+                    // Reset failure count on success.
+                    *state = State::Closed {
+                        consecutive_failures: 0,
+                    };
+                }
+                _ => {}
+            }
+        }
+    }
 }
 
 pub struct CircuitBreakerStore {
     curcuits: DashMap<String, Arc<CircuitState>>,
+    sweep_queue: SweepQueue,
+    events: Option<Arc<CircuitEventLog>>,
 }
 
 impl Default for CircuitBreakerStore {
@@ -28,19 +238,54 @@ impl CircuitBreakerStore {
     pub fn new() -> Self {
         Self {
             curcuits: DashMap::new(),
+            sweep_queue: SweepQueue::new(),
+            events: None,
         }
     }
 
+    /// Keeps the last `capacity` state transitions of every circuit; 0 keeps none.
+    #[must_use]
+    pub fn with_events(mut self, capacity: usize) -> Self {
+        self.events = (capacity > 0).then(|| Arc::new(CircuitEventLog::new(capacity)));
+        self
+    }
+
+    /// Recent transitions, oldest first; empty without an event log.
+    pub fn events(&self) -> Vec<CircuitEvent> {
+        self.events.as_ref().map(|events| events.recent()).unwrap_or_default()
+    }
+
     pub fn get_or_insert(&self, route_name: &str) -> Arc<CircuitState> {
         self.curcuits
             .entry(route_name.to_string())
             .or_insert_with(|| {
-                Arc::new(CircuitState {
-                    state: RwLock::new(State::Closed {
-                        consecutive_failures: 0,
-                    }),
+                self.sweep_queue.push(route_name.to_string());
+                let circuit = CircuitState::new();
+                Arc::new(match &self.events {
+                    Some(events) => circuit.with_events(events.clone()),
+                    None => circuit,
                 })
             })
             .clone()
     }
+
+    pub fn circuit_count(&self) -> usize {
+        self.curcuits.len()
+    }
+
+    /// Drops circuits that are closed with no failures or trips on record and
+    /// not held by a request, checking at most `batch` (all when `None`).
+    pub fn sweep_idle(&self, batch: Option<usize>) -> SweepStats {
+        self.sweep_queue.sweep(batch, |key| {
+            self.curcuits
+                .remove_if(key, |_, circuit| Arc::strong_count(circuit) == 1 && circuit.is_idle())
+                .is_some()
+        })
+    }
+
+    /// Circuit for a single destination of a route, used when failing over
+    /// between destinations.
+    pub fn for_destination(&self, route_name: &str, destination: &str) -> Arc<CircuitState> {
+        self.get_or_insert(&format!("{}|{}", route_name, destination))
+    }
 }
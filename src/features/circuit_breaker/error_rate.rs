@@ -0,0 +1,68 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+struct Window {
+    started_at: Instant,
+    total: u64,
+    failures: u64,
+}
+
+impl Window {
+    fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            total: 0,
+            failures: 0,
+        }
+    }
+}
+
+/// Share of failed requests over a fixed window that restarts once `length`
+/// has passed.
+pub struct ErrorRateWindow {
+    window: Mutex<Window>,
+    length: Duration,
+    min_requests: u64,
+}
+
+impl ErrorRateWindow {
+    pub fn new(length: Duration, min_requests: u64) -> Self {
+        Self {
+            window: Mutex::new(Window::new()),
+            length,
+            min_requests: min_requests.max(1),
+        }
+    }
+
+    /// Counts the outcome; returns the error rate if it reaches `threshold`
+    /// with at least `min_requests` in the window.
+    pub fn observe(&self, failed: bool, threshold: f64) -> Option<f64> {
+        let mut window = self.lock();
+        if window.started_at.elapsed() > self.length {
+            *window = Window::new();
+        }
+        window.total += 1;
+        if failed {
+            window.failures += 1;
+        }
+
+        if window.total < self.min_requests {
+            return None;
+        }
+        let error_rate = window.failures as f64 / window.total as f64;
+        (error_rate >= threshold).then_some(error_rate)
+    }
+
+    pub fn reset(&self) {
+        *self.lock() = Window::new();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Window> {
+        match self.window.lock() {
+            Ok(w) => w,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+/// One circuit breaker state transition.
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct CircuitEvent {
+    /// The route, or the destination URL for a route's per-destination circuit.
+    pub circuit: String,
+    pub from: &'static str,
+    pub to: &'static str,
+    /// Milliseconds since the Unix epoch.
+    pub at_ms: u64,
+    pub reason: String,
+}
+
+/// The most recent state transitions of all circuits, oldest first, kept for
+/// post-incident analysis. Holds at most `capacity`; older events are dropped.
+pub struct CircuitEventLog {
+    events: Mutex<VecDeque<CircuitEvent>>,
+    capacity: usize,
+}
+
+impl CircuitEventLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            events: Mutex::new(VecDeque::with_capacity(capacity)),
+            capacity,
+        }
+    }
+
+    pub fn push(&self, circuit: &str, from: &'static str, to: &'static str, reason: String) {
+        let mut events = match self.events.lock() {
+            Ok(e) => e,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if events.len() == self.capacity {
+            events.pop_front();
+        }
+        events.push_back(CircuitEvent {
+            circuit: circuit.to_string(),
+            from,
+            to,
+            at_ms: now_ms(),
+            reason,
+        });
+    }
+
+    pub fn recent(&self) -> Vec<CircuitEvent> {
+        let events = match self.events.lock() {
+            Ok(e) => e,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        events.iter().cloned().collect()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+}
@@ -0,0 +1,93 @@
+use std::time::{Duration, Instant};
+
+use tracing::{info, warn};
+
+use super::{
+    circuit_breaker::{CircuitState, State},
+    error_rate::ErrorRateWindow,
+};
+use crate::{config::GlobalCircuitBreakerConfig, middleware::rate_limiter::rate_limit::parse_duration};
+
+/// One circuit for the whole gateway, driven by the aggregate error rate
+/// rather than consecutive failures on a single route.
+pub struct GlobalCircuitBreaker {
+    circuit: CircuitState,
+    window: ErrorRateWindow,
+    error_rate_threshold: f64,
+    open_duration: Duration,
+    success_threshold: u32,
+}
+
+impl GlobalCircuitBreaker {
+    pub fn new(config: &GlobalCircuitBreakerConfig) -> Self {
+        Self {
+            circuit: CircuitState::new(),
+            window: ErrorRateWindow::new(
+                parse_duration(&config.window).unwrap_or_else(|_| Duration::from_secs(10)),
+                config.min_requests,
+            ),
+            error_rate_threshold: config.error_rate_threshold,
+            open_duration: parse_duration(&config.open_duration).unwrap_or_default(),
+            success_threshold: config.success_threshold,
+        }
+    }
+
+    /// Returns false while the breaker is open. Moves to half-open once
+    /// `open_duration` has passed.
+    pub async fn allow_request(&self) -> bool {
+        let mut state = self.circuit.state.write().await;
+        if let State::Open { opened_at, open_for } = *state {
+            if opened_at.elapsed() <= open_for {
+                return false;
+            }
+            *state = State::HalfOpen {
+                consecutive_successes: 0,
+            };
+            info!("Global circuit breaker is now HALF-OPEN");
+        }
+        true
+    }
+
+    pub async fn record(&self, failed: bool) {
+        let mut state = self.circuit.state.write().await;
+        match *state {
+            State::Open { .. } => {}
+            State::HalfOpen { consecutive_successes } => {
+                if failed {
+                    *state = State::Open {
+                        opened_at: Instant::now(),
+                        open_for: self.open_duration,
+                    };
+                    warn!("Global trial request failed, circuit is OPENED again");
+                } else if consecutive_successes + 1 >= self.success_threshold {
+                    *state = State::Closed {
+                        consecutive_failures: 0,
+                    };
+                    self.window.reset();
+                    info!("Global circuit breaker is now CLOSED");
+                } else {
+                    *state = State::HalfOpen {
+                        consecutive_successes: consecutive_successes + 1,
+                    };
+                }
+            }
+            State::Closed { .. } => {
+                if let Some(error_rate) = self.window.observe(failed, self.error_rate_threshold) {
+                    *state = State::Open {
+                        opened_at: Instant::now(),
+                        open_for: self.open_duration,
+                    };
+                    self.window.reset();
+                    warn!(
+                        error_rate = error_rate,
+                        "Gateway error rate threshold reached, global circuit is OPENED"
+                    );
+                }
+            }
+        }
+    }
+
+    pub async fn is_open(&self) -> bool {
+        matches!(*self.circuit.state.read().await, State::Open { .. })
+    }
+}
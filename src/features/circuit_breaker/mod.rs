@@ -1,2 +1,5 @@
 #[allow(clippy::module_inception)]
 pub mod circuit_breaker;
+pub mod error_rate;
+pub mod events;
+pub mod global;
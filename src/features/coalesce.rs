@@ -0,0 +1,108 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
+use axum_prometheus::metrics::{counter, gauge};
+use dashmap::{DashMap, mapref::entry::Entry};
+use tokio::sync::broadcast;
+
+use crate::state::CachedResponse;
+
+const LEADERS_GAUGE: &str = "gateway_coalesce_leaders_in_flight";
+
+/// Single-flight for identical requests: the first caller (the leader) goes
+/// upstream and every caller arriving while it is in flight (a follower)
+/// gets a copy of its response. Keys are spread over independently locked
+/// shards, and a lock is held only while joining or leaving a flight, never
+/// while the leader is upstream.
+pub struct RequestCoalescer {
+    in_flight: DashMap<String, broadcast::Sender<Arc<CachedResponse>>>,
+    coalesced: AtomicU64,
+}
+
+pub enum Flight<'a> {
+    Leader(Leader<'a>),
+    /// Resolves to the leader's response; errors if the leader gave up.
+    Follower(broadcast::Receiver<Arc<CachedResponse>>),
+}
+
+impl RequestCoalescer {
+    pub fn new() -> Self {
+        Self {
+            in_flight: DashMap::new(),
+            coalesced: AtomicU64::new(0),
+        }
+    }
+
+    /// `shards` must be a power of two above 1 (see `tuning.coalesce_shards`).
+    pub fn with_shards(shards: usize) -> Self {
+        Self {
+            in_flight: DashMap::with_shard_amount(shards),
+            coalesced: AtomicU64::new(0),
+        }
+    }
+
+    pub fn join(&self, route: &str, key: String) -> Flight<'_> {
+        match self.in_flight.entry(key) {
+            Entry::Occupied(entry) => {
+                self.coalesced.fetch_add(1, Ordering::Relaxed);
+                counter!("gateway_coalesced_requests_total", "route" => route.to_string()).increment(1);
+                Flight::Follower(entry.get().subscribe())
+            }
+            Entry::Vacant(entry) => {
+                let key = entry.key().clone();
+                entry.insert(broadcast::channel(1).0);
+                gauge!(LEADERS_GAUGE).increment(1.0);
+                Flight::Leader(Leader {
+                    coalescer: self,
+                    key,
+                    completed: false,
+                })
+            }
+        }
+    }
+
+    /// Requests served from another request's upstream call since startup.
+    pub fn coalesced_count(&self) -> u64 {
+        self.coalesced.load(Ordering::Relaxed)
+    }
+
+    pub fn leaders_in_flight(&self) -> usize {
+        self.in_flight.len()
+    }
+}
+
+impl Default for RequestCoalescer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Held by the request that goes upstream. Dropping it without calling
+/// `complete` (e.g. the client went away) releases the followers so they
+/// make their own requests.
+pub struct Leader<'a> {
+    coalescer: &'a RequestCoalescer,
+    key: String,
+    completed: bool,
+}
+
+impl Leader<'_> {
+    pub fn complete(mut self, response: Arc<CachedResponse>) {
+        // Remove first so later arrivals start a new flight instead of missing this one
+        if let Some((_, sender)) = self.coalescer.in_flight.remove(&self.key) {
+            let _ = sender.send(response);
+        }
+        self.completed = true;
+    }
+}
+
+impl Drop for Leader<'_> {
+    fn drop(&mut self) {
+        if !self.completed {
+            self.coalescer.in_flight.remove(&self.key);
+        }
+        gauge!(LEADERS_GAUGE).decrement(1.0);
+    }
+}
@@ -0,0 +1,26 @@
+use axum_prometheus::metrics::counter;
+use tracing::warn;
+
+use crate::{
+    config::FailurePolicy,
+    errors::{AppError, StoreUnavailable},
+};
+
+/// Applies `policy` to a failed backing store: `Ok` to carry on without it,
+/// or 503 to protect the backend. Either way the failure is logged and counted.
+pub fn on_store_error(
+    subsystem: &'static str,
+    policy: FailurePolicy,
+    error: &StoreUnavailable,
+) -> Result<(), AppError> {
+    let policy_label = match policy {
+        FailurePolicy::FailOpen => "fail_open",
+        FailurePolicy::FailClosed => "fail_closed",
+    };
+    warn!(subsystem, policy = policy_label, "Backing store unavailable: {}", error);
+    counter!("gateway_degraded_requests_total", "subsystem" => subsystem, "policy" => policy_label).increment(1);
+    match policy {
+        FailurePolicy::FailOpen => Ok(()),
+        FailurePolicy::FailClosed => Err(AppError::ServiceUnavailable),
+    }
+}
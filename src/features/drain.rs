@@ -0,0 +1,57 @@
+use axum_prometheus::metrics::gauge;
+use dashmap::DashSet;
+use tracing::info;
+
+/// Destinations taken out of rotation for maintenance through the admin API.
+/// A drained destination gets no new requests; ones already sent to it
+/// finish normally. Kept across config reloads, cleared on restart.
+pub struct DrainedDestinations {
+    drained: DashSet<String>,
+}
+
+impl DrainedDestinations {
+    pub fn new() -> Self {
+        Self {
+            drained: DashSet::new(),
+        }
+    }
+
+    /// Returns false if `destination` was already drained.
+    pub fn drain(&self, destination: &str) -> bool {
+        let changed = self.drained.insert(destination.to_string());
+        if changed {
+            info!(destination = %destination, "Destination drained");
+        }
+        gauge!("gateway_drained_destinations").set(self.drained.len() as f64);
+        changed
+    }
+
+    /// Returns false if `destination` wasn't drained.
+    pub fn undrain(&self, destination: &str) -> bool {
+        let changed = self.drained.remove(destination).is_some();
+        if changed {
+            info!(destination = %destination, "Destination undrained");
+        }
+        gauge!("gateway_drained_destinations").set(self.drained.len() as f64);
+        changed
+    }
+
+    pub fn is_drained(&self, destination: &str) -> bool {
+        self.drained.contains(destination)
+    }
+
+    /// `destinations` without the drained ones. Unlike unhealthy destinations,
+    /// drained ones are not used even when nothing else is left.
+    pub fn filter<'a>(&self, destinations: Vec<&'a str>) -> Vec<&'a str> {
+        if self.drained.is_empty() {
+            return destinations;
+        }
+        destinations.into_iter().filter(|d| !self.is_drained(d)).collect()
+    }
+}
+
+impl Default for DrainedDestinations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
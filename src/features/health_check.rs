@@ -2,8 +2,9 @@ use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use dashmap::DashMap;
 use serde::Deserialize;
+use serde_json::Value;
 use tokio::time::Instant;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct HealthCheckConfig {
@@ -11,6 +12,73 @@ pub struct HealthCheckConfig {
     pub interval: String,
     #[serde(default = "default_path")]
     pub path: String,
+    /// Statuses that count as healthy, for backends that answer e.g. 204 or
+    /// 429 when fine. Any 2xx when empty.
+    #[serde(default)]
+    pub expected_status: Vec<u16>,
+    /// Text the response body must contain.
+    #[serde(default)]
+    pub expected_body: Option<String>,
+    /// Fields the response body, as JSON, must have, by dotted path:
+    /// `{status: ok, checks.db: up}`.
+    #[serde(default)]
+    pub expected_json: HashMap<String, Value>,
+}
+
+impl HealthCheckConfig {
+    pub fn accepts_status(&self, status: u16) -> bool {
+        if self.expected_status.is_empty() {
+            (200..300).contains(&status)
+        } else {
+            self.expected_status.contains(&status)
+        }
+    }
+
+    /// Whether the body has to be read to judge a probe.
+    pub fn checks_body(&self) -> bool {
+        self.expected_body.is_some() || !self.expected_json.is_empty()
+    }
+
+    pub fn accepts_body(&self, body: &[u8]) -> bool {
+        if let Some(expected) = &self.expected_body
+            && !String::from_utf8_lossy(body).contains(expected.as_str())
+        {
+            return false;
+        }
+        if self.expected_json.is_empty() {
+            return true;
+        }
+        let Ok(json) = serde_json::from_slice::<Value>(body) else {
+            return false;
+        };
+        self.expected_json
+            .iter()
+            .all(|(path, expected)| path.split('.').try_fold(&json, |v, field| v.get(field)) == Some(expected))
+    }
+}
+
+/// Probes `url` once, judged by `check`'s success criteria. `timeout` bounds
+/// the whole probe, body included.
+pub async fn probe(client: &reqwest::Client, url: &str, timeout: Duration, check: &HealthCheckConfig) -> bool {
+    let Ok(response) = client.get(url).timeout(timeout).send().await else {
+        return false;
+    };
+    let status = response.status().as_u16();
+    if !check.accepts_status(status) {
+        debug!(url = %url, status, "Health probe got an unexpected status");
+        return false;
+    }
+    if !check.checks_body() {
+        return true;
+    }
+    match response.bytes().await {
+        Ok(body) if check.accepts_body(&body) => true,
+        Ok(_) => {
+            debug!(url = %url, "Health probe body did not match");
+            false
+        }
+        Err(_) => false,
+    }
 }
 
 fn default_interval() -> String {
@@ -29,17 +97,27 @@ pub struct BackendHealth {
 
 pub struct HealthChecker {
     status: Arc<DashMap<String, BackendHealth>>,
+    probe_timeout: Duration,
 }
 
 impl HealthChecker {
     pub fn new() -> Self {
+        Self::with_timeout(parse_duration(crate::constants::DEFAULT_HEALTH_CHECK_TIMEOUT))
+    }
+
+    pub fn with_timeout(probe_timeout: Duration) -> Self {
         Self {
             status: Arc::new(DashMap::new()),
+            probe_timeout,
         }
     }
 
+    pub fn probe_timeout(&self) -> Duration {
+        self.probe_timeout
+    }
+
     pub fn is_healthy(&self, url: &str) -> bool {
-        self.status.get(url).map(|h| h.healthy).unwrap_or(true) // assume healthy if not checked yet
+        self.status.get(url).is_none_or(|h| h.healthy) // assume healthy if not checked yet
     }
 
     pub fn filter_healthy<'a>(&self, destinations: &[&'a str]) -> Vec<&'a str> {
@@ -59,18 +137,19 @@ impl HealthChecker {
     pub fn start_checker(
         &self,
         client: reqwest::Client,
-        routes: Vec<(String, String, Duration)>, // (url, health_path, interval)
+        routes: Vec<(String, HealthCheckConfig)>, // (url, check)
     ) {
         let status = self.status.clone();
+        let probe_timeout = self.probe_timeout;
 
         tokio::spawn(async move {
             // Group by interval for efficient checking
-            let mut by_interval: HashMap<u64, Vec<(String, String)>> = HashMap::new();
-            for (url, path, interval) in &routes {
+            let mut by_interval: HashMap<u64, Vec<(String, HealthCheckConfig)>> = HashMap::new();
+            for (url, check) in routes {
                 by_interval
-                    .entry(interval.as_secs())
+                    .entry(parse_duration(&check.interval).as_secs())
                     .or_default()
-                    .push((url.clone(), path.clone()));
+                    .push((url, check));
             }
 
             for (secs, targets) in by_interval {
@@ -80,15 +159,9 @@ impl HealthChecker {
 
                 tokio::spawn(async move {
                     loop {
-                        for (url, path) in &targets {
-                            let check_url = format!("{}{}", url, path);
-                            let healthy = client
-                                .get(&check_url)
-                                .timeout(Duration::from_secs(3))
-                                .send()
-                                .await
-                                .map(|r| r.status().is_success())
-                                .unwrap_or(false);
+                        for (url, check) in &targets {
+                            let check_url = format!("{}{}", url, check.path);
+                            let healthy = probe(&client, &check_url, probe_timeout, check).await;
 
                             let prev = status.get(url).map(|h| h.healthy);
                             status.insert(
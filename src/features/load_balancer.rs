@@ -1,5 +1,12 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
 
+use dashmap::DashMap;
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize, Clone, Default)]
@@ -8,32 +15,106 @@ pub enum LoadBalanceStrategy {
     #[default]
     RoundRobin,
     Random,
+    /// The destination with the fewest requests in flight; ties rotate.
+    LeastConnections,
 }
 
+/// Round-robin position per route, so traffic on one route doesn't skew
+/// the rotation of another, and requests in flight per destination for
+/// `least_connections`.
 pub struct LoadBalancer {
-    counter: AtomicUsize,
+    counters: DashMap<String, AtomicUsize>,
+    in_flight: DashMap<String, Arc<AtomicUsize>>,
+}
+
+/// Counts a request as in flight to a destination until dropped, so the
+/// count comes back down however the request ends.
+pub struct InFlightGuard(Arc<AtomicUsize>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
 }
 
 impl LoadBalancer {
     pub fn new() -> Self {
         Self {
-            counter: AtomicUsize::new(0),
+            counters: DashMap::new(),
+            in_flight: DashMap::new(),
+        }
+    }
+
+    /// Index into `destinations` for the next request to `route`. With
+    /// `weights` (unlisted destinations weigh 1), round-robin and random pick
+    /// each destination in proportion to its weight.
+    pub fn pick(
+        &self,
+        route: &str,
+        destinations: &[&str],
+        strategy: &LoadBalanceStrategy,
+        weights: &HashMap<String, u32>,
+    ) -> Option<usize> {
+        if !matches!(strategy, LoadBalanceStrategy::LeastConnections) {
+            if weights.is_empty() {
+                return self.next_index(route, destinations.len(), strategy);
+            }
+            let weight_of = |d: &str| weights.get(d).copied().unwrap_or(1) as usize;
+            let total: usize = destinations.iter().map(|d| weight_of(d)).sum();
+            let mut slot = self.next_index(route, total, strategy)?;
+            return destinations.iter().position(|d| {
+                let weight = weight_of(d);
+                if slot < weight {
+                    return true;
+                }
+                slot -= weight;
+                false
+            });
         }
+        let counts: Vec<usize> = destinations.iter().map(|d| self.in_flight(d)).collect();
+        let fewest = *counts.iter().min()?;
+        let tied: Vec<usize> = (0..counts.len()).filter(|&i| counts[i] == fewest).collect();
+        let turn = self.next_index(route, tied.len(), &LoadBalanceStrategy::RoundRobin)?;
+        Some(tied[turn])
+    }
+
+    /// Call before sending a request to `destination`; hold the guard until
+    /// the response is read.
+    pub fn track(&self, destination: &str) -> InFlightGuard {
+        let counter = match self.in_flight.get(destination) {
+            Some(c) => c.clone(),
+            None => self.in_flight.entry(destination.to_string()).or_default().clone(),
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        InFlightGuard(counter)
+    }
+
+    pub fn in_flight(&self, destination: &str) -> usize {
+        self.in_flight.get(destination).map_or(0, |c| c.load(Ordering::Relaxed))
     }
 
-    pub fn next_index(&self, count: usize, strategy: &LoadBalanceStrategy) -> Option<usize> {
+    pub fn next_index(&self, route: &str, count: usize, strategy: &LoadBalanceStrategy) -> Option<usize> {
         if count == 0 {
             return None;
         }
+        let next = || match self.counters.get(route) {
+            Some(counter) => counter.fetch_add(1, Ordering::Relaxed),
+            None => self
+                .counters
+                .entry(route.to_string())
+                .or_default()
+                .fetch_add(1, Ordering::Relaxed),
+        };
         Some(match strategy {
-            LoadBalanceStrategy::RoundRobin => self.counter.fetch_add(1, Ordering::Relaxed) % count,
+            // Without destinations to compare, least-connections rotates
+            LoadBalanceStrategy::RoundRobin | LoadBalanceStrategy::LeastConnections => next() % count,
             LoadBalanceStrategy::Random => {
                 use std::collections::hash_map::RandomState;
                 use std::hash::{BuildHasher, Hasher};
                 let s = RandomState::new();
                 let mut hasher = s.build_hasher();
-                hasher.write_usize(self.counter.fetch_add(1, Ordering::Relaxed));
-                hasher.finish() as usize % count
+                hasher.write_usize(next());
+                usize::try_from(hasher.finish() % count as u64).unwrap_or(0)
             }
         })
     }
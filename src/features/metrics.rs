@@ -0,0 +1,51 @@
+use dashmap::DashMap;
+
+/// Label used for requests that match no configured route.
+pub const UNMATCHED_LABEL: &str = "unmatched";
+/// Label used once the distinct route label budget is exhausted.
+pub const OVERFLOW_LABEL: &str = "other";
+
+/// Fallback for the Prometheus endpoint label when axum has no matched path,
+/// so raw request paths never become label values.
+pub fn unmatched_endpoint_label(_path: &str) -> String {
+    UNMATCHED_LABEL.to_string()
+}
+
+/// Bounds the number of distinct `route` label values emitted by gateway metrics.
+/// Under heavy concurrency the budget can be overshot by a handful of entries,
+/// never by an unbounded amount.
+pub struct RouteLabels {
+    max_labels: usize,
+    seen: DashMap<String, ()>,
+}
+
+impl RouteLabels {
+    pub fn new(max_labels: usize) -> Self {
+        Self {
+            max_labels,
+            seen: DashMap::new(),
+        }
+    }
+
+    pub fn label_for(&self, route_name: Option<&str>) -> String {
+        let Some(name) = route_name else {
+            return UNMATCHED_LABEL.to_string();
+        };
+        if self.seen.contains_key(name) {
+            return name.to_string();
+        }
+        if self.seen.len() < self.max_labels {
+            self.seen.insert(name.to_string(), ());
+            return name.to_string();
+        }
+        OVERFLOW_LABEL.to_string()
+    }
+
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+}
@@ -0,0 +1,65 @@
+//! Push exporters for `observability.metrics.exporter`. Every metric goes
+//! through the `metrics` facade, so the names are the same whichever
+//! exporter is installed; Prometheus is set up in `serve`.
+pub mod otlp;
+pub mod statsd;
+
+use anyhow::{Result, anyhow};
+use axum_prometheus::metrics::set_global_recorder;
+use reqwest::Client;
+
+use crate::{
+    config::{MetricsConfig, MetricsExporter},
+    middleware::rate_limiter::rate_limit::parse_duration,
+};
+
+/// Metrics an exporter still holds, pushed once more at shutdown.
+pub struct MetricsFlush {
+    recorder: otlp::OtlpRecorder,
+    endpoint: String,
+    client: Client,
+}
+
+impl MetricsFlush {
+    pub async fn flush(&self) {
+        otlp::push(&self.recorder, &self.endpoint, &self.client).await;
+    }
+}
+
+/// Installs the global recorder for a push exporter. No-op for Prometheus.
+/// Returns a flush handle for exporters that buffer between pushes.
+pub fn install(config: &MetricsConfig, client: Client) -> Result<Option<MetricsFlush>> {
+    match config.exporter {
+        MetricsExporter::Prometheus => Ok(None),
+        MetricsExporter::Statsd => {
+            let statsd = config
+                .statsd
+                .as_ref()
+                .ok_or_else(|| anyhow!("metrics.statsd is not configured"))?;
+            let recorder = statsd::StatsdRecorder::new(statsd)?;
+            set_global_recorder(recorder).map_err(|_| anyhow!("a metrics recorder is already installed"))?;
+            // Every update is sent as it happens
+            Ok(None)
+        }
+        MetricsExporter::Otlp => {
+            let otlp = config
+                .otlp
+                .as_ref()
+                .ok_or_else(|| anyhow!("metrics.otlp is not configured"))?;
+            let interval = parse_duration(&otlp.interval).map_err(|e| anyhow!(e))?;
+            let recorder = otlp::OtlpRecorder::new();
+            set_global_recorder(recorder.clone()).map_err(|_| anyhow!("a metrics recorder is already installed"))?;
+            tokio::spawn(otlp::export_loop(
+                recorder.clone(),
+                otlp.endpoint.clone(),
+                interval,
+                client.clone(),
+            ));
+            Ok(Some(MetricsFlush {
+                recorder,
+                endpoint: otlp.endpoint.clone(),
+                client,
+            }))
+        }
+    }
+}
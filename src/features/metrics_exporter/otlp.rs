@@ -0,0 +1,207 @@
+use std::{
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use axum_prometheus::metrics::{
+    Counter, Gauge, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+};
+use dashmap::DashMap;
+use reqwest::Client;
+use serde_json::{Value, json};
+use tracing::warn;
+
+/// Histogram bucket bounds, in the unit recorded (seconds for durations).
+const BUCKET_BOUNDS: [f64; 11] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Aggregates metrics in memory; `export_loop` pushes them to an OTLP/HTTP
+/// collector as cumulative sums, gauges and histograms.
+#[derive(Clone)]
+pub struct OtlpRecorder {
+    inner: Arc<Registry>,
+}
+
+struct Registry {
+    started_at_nanos: u64,
+    counters: DashMap<Key, Arc<AtomicU64>>,
+    /// f64 bits, as `metrics` stores gauges in an `AtomicU64`
+    gauges: DashMap<Key, Arc<AtomicU64>>,
+    histograms: DashMap<Key, Arc<Mutex<HistogramData>>>,
+}
+
+struct HistogramData {
+    count: u64,
+    sum: f64,
+    /// One per bound plus the overflow bucket.
+    buckets: [u64; BUCKET_BOUNDS.len() + 1],
+}
+
+struct OtlpHistogram(Arc<Mutex<HistogramData>>);
+
+impl OtlpRecorder {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Registry {
+                started_at_nanos: now_nanos(),
+                counters: DashMap::new(),
+                gauges: DashMap::new(),
+                histograms: DashMap::new(),
+            }),
+        }
+    }
+
+    /// The current values as an OTLP `ExportMetricsServiceRequest` in JSON.
+    pub fn payload(&self) -> Value {
+        let start = self.inner.started_at_nanos.to_string();
+        let now = now_nanos().to_string();
+        let mut metrics: Vec<Value> = Vec::new();
+
+        for entry in &self.inner.counters {
+            let point = data_point(
+                entry.key(),
+                &start,
+                &now,
+                json!({"asInt": entry.load(Ordering::Relaxed).to_string()}),
+            );
+            metrics.push(json!({
+                "name": entry.key().name(),
+                "sum": {"aggregationTemporality": 2, "isMonotonic": true, "dataPoints": [point]},
+            }));
+        }
+        for entry in &self.inner.gauges {
+            let value = f64::from_bits(entry.load(Ordering::Relaxed));
+            let point = data_point(entry.key(), &start, &now, json!({"asDouble": value}));
+            metrics.push(json!({"name": entry.key().name(), "gauge": {"dataPoints": [point]}}));
+        }
+        for entry in &self.inner.histograms {
+            let data = match entry.lock() {
+                Ok(d) => d,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            let point = data_point(
+                entry.key(),
+                &start,
+                &now,
+                json!({
+                    "count": data.count.to_string(),
+                    "sum": data.sum,
+                    "bucketCounts": data.buckets.iter().map(u64::to_string).collect::<Vec<_>>(),
+                    "explicitBounds": BUCKET_BOUNDS,
+                }),
+            );
+            metrics.push(json!({
+                "name": entry.key().name(),
+                "histogram": {"aggregationTemporality": 2, "dataPoints": [point]},
+            }));
+        }
+
+        json!({
+            "resourceMetrics": [{
+                "resource": {"attributes": [{"key": "service.name", "value": {"stringValue": "rustygw"}}]},
+                "scopeMetrics": [{"scope": {"name": "rustygw"}, "metrics": metrics}],
+            }]
+        })
+    }
+}
+
+impl Default for OtlpRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn data_point(key: &Key, start: &str, now: &str, value: Value) -> Value {
+    let attributes: Vec<Value> = key
+        .labels()
+        .map(|l| json!({"key": l.key(), "value": {"stringValue": l.value()}}))
+        .collect();
+    let mut point = json!({
+        "attributes": attributes,
+        "startTimeUnixNano": start,
+        "timeUnixNano": now,
+    });
+    if let (Some(point), Value::Object(value)) = (point.as_object_mut(), value) {
+        point.extend(value);
+    }
+    point
+}
+
+/// POSTs the recorder's metrics to `{endpoint}/v1/metrics` every `interval`.
+pub async fn export_loop(recorder: OtlpRecorder, endpoint: String, interval: Duration, client: Client) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        push(&recorder, &endpoint, &client).await;
+    }
+}
+
+/// POSTs the recorder's current metrics to `{endpoint}/v1/metrics` once.
+pub async fn push(recorder: &OtlpRecorder, endpoint: &str, client: &Client) {
+    let url = format!("{}/v1/metrics", endpoint.trim_end_matches('/'));
+    let result = client.post(&url).json(&recorder.payload()).send().await;
+    match result {
+        Ok(resp) if !resp.status().is_success() => {
+            warn!(url = %url, status = %resp.status(), "OTLP collector rejected metrics");
+        }
+        Ok(_) => {}
+        Err(e) => warn!(url = %url, "Failed to export metrics over OTLP: {}", e),
+    }
+}
+
+fn now_nanos() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_nanos()).unwrap_or(u64::MAX))
+}
+
+impl HistogramFn for OtlpHistogram {
+    fn record(&self, value: f64) {
+        let mut data = match self.0.lock() {
+            Ok(d) => d,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        data.count += 1;
+        data.sum += value;
+        let bucket = BUCKET_BOUNDS
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(BUCKET_BOUNDS.len());
+        data.buckets[bucket] += 1;
+    }
+}
+
+impl Recorder for OtlpRecorder {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        Counter::from_arc(self.inner.counters.entry(key.clone()).or_default().clone())
+    }
+
+    fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::from_arc(self.inner.gauges.entry(key.clone()).or_default().clone())
+    }
+
+    fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+        let data = self
+            .inner
+            .histograms
+            .entry(key.clone())
+            .or_insert_with(|| {
+                Arc::new(Mutex::new(HistogramData {
+                    count: 0,
+                    sum: 0.0,
+                    buckets: [0; BUCKET_BOUNDS.len() + 1],
+                }))
+            })
+            .clone();
+        Histogram::from_arc(Arc::new(OtlpHistogram(data)))
+    }
+}
@@ -0,0 +1,129 @@
+use std::{
+    fmt::Display,
+    io,
+    net::{ToSocketAddrs, UdpSocket},
+    sync::Arc,
+};
+
+use axum_prometheus::metrics::{
+    Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder, SharedString, Unit,
+};
+
+use crate::config::StatsdConfig;
+
+/// Sends every metric update to a StatsD agent as a UDP line, e.g.
+/// `gateway_route_requests_total:1|c|#route:users,status:200`. Sends are
+/// fire-and-forget; a missing agent never slows a request down.
+pub struct StatsdRecorder {
+    socket: Arc<UdpSocket>,
+    prefix: String,
+    tags: bool,
+}
+
+impl StatsdRecorder {
+    pub fn new(config: &StatsdConfig) -> io::Result<Self> {
+        let addr = config
+            .addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("cannot resolve {}", config.addr)))?;
+        let bind_addr = if addr.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(bind_addr)?;
+        socket.connect(addr)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket: Arc::new(socket),
+            prefix: config.prefix.clone(),
+            tags: config.tags,
+        })
+    }
+
+    fn metric(&self, key: &Key) -> Arc<StatsdMetric> {
+        let labels: Vec<String> = key
+            .labels()
+            .map(|l| format!("{}:{}", sanitize(l.key()), sanitize(l.value())))
+            .collect();
+        let tags = if self.tags && !labels.is_empty() {
+            format!("|#{}", labels.join(","))
+        } else {
+            String::new()
+        };
+        Arc::new(StatsdMetric {
+            socket: self.socket.clone(),
+            name: format!("{}{}", self.prefix, sanitize(key.name())),
+            tags,
+        })
+    }
+}
+
+/// These characters delimit fields in the line format.
+fn sanitize(s: &str) -> String {
+    s.replace([':', '|', ',', '#', '@'], "_")
+}
+
+struct StatsdMetric {
+    socket: Arc<UdpSocket>,
+    name: String,
+    tags: String,
+}
+
+impl StatsdMetric {
+    fn send(&self, value: impl Display, kind: &str) {
+        let line = format!("{}:{}|{}{}", self.name, value, kind, self.tags);
+        let _ = self.socket.send(line.as_bytes());
+    }
+}
+
+impl CounterFn for StatsdMetric {
+    fn increment(&self, value: u64) {
+        self.send(value, "c");
+    }
+
+    fn absolute(&self, value: u64) {
+        self.send(value, "g");
+    }
+}
+
+impl GaugeFn for StatsdMetric {
+    fn increment(&self, value: f64) {
+        self.send(format!("+{}", value), "g");
+    }
+
+    fn decrement(&self, value: f64) {
+        self.send(format!("-{}", value), "g");
+    }
+
+    fn set(&self, value: f64) {
+        // A leading sign means a delta, so negative values are set from zero
+        if value < 0.0 {
+            self.send(0, "g");
+        }
+        self.send(value, "g");
+    }
+}
+
+impl HistogramFn for StatsdMetric {
+    fn record(&self, value: f64) {
+        self.send(value, "h");
+    }
+}
+
+impl Recorder for StatsdRecorder {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        Counter::from_arc(self.metric(key))
+    }
+
+    fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::from_arc(self.metric(key))
+    }
+
+    fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+        Histogram::from_arc(self.metric(key))
+    }
+}
@@ -1,5 +1,23 @@
+pub mod adaptive_timeout;
 pub mod auth;
+pub mod blue_green;
+pub mod cache;
+pub mod canary;
+pub mod capture;
 pub mod circuit_breaker;
+pub mod coalesce;
+pub mod degradation;
+pub mod drain;
 pub mod health_check;
 pub mod load_balancer;
+pub mod metrics;
+pub mod metrics_exporter;
+pub mod qos;
 pub mod rate_limiter;
+pub mod retry_budget;
+pub mod shutdown;
+pub mod stats;
+pub mod store_cleanup;
+pub mod tls;
+pub mod traffic;
+pub mod warmup;
@@ -0,0 +1,174 @@
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    sync::{Mutex, MutexGuard},
+    time::Duration,
+};
+
+use http::{HeaderMap, HeaderName};
+use tokio::sync::oneshot;
+
+use crate::{
+    config::{QosClass, QosConfig},
+    middleware::rate_limiter::rate_limit::parse_duration,
+};
+
+/// Name reported for requests that match no configured class.
+pub const DEFAULT_CLASS: &str = "default";
+
+/// Why a request was not admitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rejection {
+    QueueFull,
+    TimedOut,
+}
+
+impl Rejection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Rejection::QueueFull => "queue_full",
+            Rejection::TimedOut => "timed_out",
+        }
+    }
+}
+
+/// Admission control for `server.qos`. Up to `max_concurrent` requests hold a
+/// slot; the rest wait, and each freed slot goes straight to the waiting
+/// request with the highest priority, oldest first within a priority.
+pub struct PriorityGate {
+    max_concurrent: usize,
+    max_queue: usize,
+    queue_timeout: Duration,
+    class_header: Option<HeaderName>,
+    /// Highest priority first, so the first match wins.
+    classes: Vec<QosClass>,
+    slots: Mutex<Slots>,
+}
+
+/// Waiters are keyed by (priority descending, arrival), so the first entry
+/// is the next to admit.
+type WaitKey = (Reverse<u8>, u64);
+
+struct Slots {
+    in_flight: usize,
+    next_seq: u64,
+    waiting: BTreeMap<WaitKey, oneshot::Sender<()>>,
+}
+
+impl PriorityGate {
+    pub fn new(config: &QosConfig) -> Self {
+        let mut classes = config.classes.clone();
+        classes.sort_by_key(|c| Reverse(c.priority));
+        Self {
+            max_concurrent: config.max_concurrent.max(1),
+            max_queue: config.max_queue,
+            queue_timeout: parse_duration(&config.queue_timeout).unwrap_or(Duration::from_secs(5)),
+            class_header: config
+                .class_header
+                .as_deref()
+                .and_then(|h| HeaderName::from_bytes(h.as_bytes()).ok()),
+            classes,
+            slots: Mutex::new(Slots {
+                in_flight: 0,
+                next_seq: 0,
+                waiting: BTreeMap::new(),
+            }),
+        }
+    }
+
+    /// The class for a caller with these auth `roles`, by role or by
+    /// `class_header`, as (name, priority).
+    pub fn classify(&self, roles: &[String], headers: &HeaderMap) -> (&str, u8) {
+        let header_class = self
+            .class_header
+            .as_ref()
+            .and_then(|name| headers.get(name))
+            .and_then(|v| v.to_str().ok());
+        self.classes
+            .iter()
+            .find(|c| header_class == Some(c.name.as_str()) || c.roles.iter().any(|r| roles.contains(r)))
+            .map_or((DEFAULT_CLASS, 0), |c| (c.name.as_str(), c.priority))
+    }
+
+    /// Waits for a slot. The slot is held until the permit is dropped.
+    pub async fn admit(&self, priority: u8) -> Result<QosPermit<'_>, Rejection> {
+        let (key, mut rx) = {
+            let mut slots = self.lock();
+            if slots.in_flight < self.max_concurrent && slots.waiting.is_empty() {
+                slots.in_flight += 1;
+                return Ok(QosPermit { gate: self });
+            }
+            if slots.waiting.len() >= self.max_queue {
+                return Err(Rejection::QueueFull);
+            }
+            let key = (Reverse(priority), slots.next_seq);
+            slots.next_seq += 1;
+            let (tx, rx) = oneshot::channel();
+            slots.waiting.insert(key, tx);
+            (key, rx)
+        };
+
+        // Declared after `rx` so it drops first, while a handed-over slot can still be detected
+        let mut ticket = Ticket {
+            gate: self,
+            key,
+            admitted: false,
+        };
+        match tokio::time::timeout(self.queue_timeout, &mut rx).await {
+            Ok(Ok(())) => {
+                ticket.admitted = true;
+                Ok(QosPermit { gate: self })
+            }
+            _ => Err(Rejection::TimedOut),
+        }
+    }
+
+    /// Hands a freed slot to the next waiter, or returns it to the pool.
+    fn release(&self) {
+        let mut slots = self.lock();
+        while let Some((_, tx)) = slots.waiting.pop_first() {
+            if tx.send(()).is_ok() {
+                return;
+            }
+        }
+        slots.in_flight = slots.in_flight.saturating_sub(1);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Slots> {
+        match self.slots.lock() {
+            Ok(slots) => slots,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// A held slot; dropping it admits the next waiter.
+pub struct QosPermit<'a> {
+    gate: &'a PriorityGate,
+}
+
+impl Drop for QosPermit<'_> {
+    fn drop(&mut self) {
+        self.gate.release();
+    }
+}
+
+/// A place in the queue. Dropped without being admitted (timeout or client
+/// gone), it leaves the queue, passing on a slot it was handed meanwhile.
+struct Ticket<'a> {
+    gate: &'a PriorityGate,
+    key: WaitKey,
+    admitted: bool,
+}
+
+impl Drop for Ticket<'_> {
+    fn drop(&mut self) {
+        if self.admitted {
+            return;
+        }
+        let still_queued = self.gate.lock().waiting.remove(&self.key).is_some();
+        if !still_queued {
+            self.gate.release();
+        }
+    }
+}
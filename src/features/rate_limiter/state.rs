@@ -1,21 +1,67 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use dashmap::DashMap;
 use tokio::{sync::RwLock, time::Instant};
 
+use crate::{
+    errors::StoreUnavailable,
+    features::store_cleanup::{SweepQueue, SweepStats},
+};
+
+/// Whether a rate limit store let a request through.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateLimitDecision {
+    Allowed,
+    /// The bucket is empty; it holds a token again after `retry_after`.
+    Limited {
+        retry_after: Duration,
+    },
+}
+
+impl RateLimitDecision {
+    pub fn is_allowed(&self) -> bool {
+        matches!(self, RateLimitDecision::Allowed)
+    }
+}
+
 #[async_trait]
 pub trait RateLimitState: Send + Sync {
-    async fn check_and_update(&self, key: &str, capacity: u64, refill_rate: f64) -> bool;
+    /// Takes a token from `key`'s bucket, or says how long until it has one.
+    /// `capacity` and `refill_rate` are the limit in force now: a bucket
+    /// holding more than `capacity`, because a reload lowered the limit, is
+    /// cut down to it before the token is taken.
+    async fn check_and_update(
+        &self,
+        key: &str,
+        capacity: u64,
+        refill_rate: f64,
+    ) -> Result<RateLimitDecision, StoreUnavailable>;
+
+    /// Drops buckets that have refilled completely, checking at most `batch`
+    /// (all when `None`). Stores that expire keys themselves keep the default.
+    fn sweep_idle(&self, _batch: Option<usize>) -> SweepStats {
+        SweepStats::default()
+    }
 }
 
 struct Bucket {
     tokens: f64,
     last_refill: Instant,
+    capacity: f64,
+    refill_rate: f64,
+}
+
+impl Bucket {
+    /// Full again, so a new bucket would behave the same.
+    fn is_idle(&self) -> bool {
+        self.tokens + self.last_refill.elapsed().as_secs_f64() * self.refill_rate >= self.capacity
+    }
 }
 
 pub struct InMemoryRateLimitState {
     clients: DashMap<String, Arc<RwLock<Bucket>>>,
+    sweep_queue: SweepQueue,
 }
 
 impl Default for InMemoryRateLimitState {
@@ -28,31 +74,59 @@ impl InMemoryRateLimitState {
     pub fn new() -> Self {
         Self {
             clients: DashMap::new(),
+            sweep_queue: SweepQueue::new(),
         }
     }
+
+    pub fn bucket_count(&self) -> usize {
+        self.clients.len()
+    }
 }
 
 #[async_trait]
 impl RateLimitState for InMemoryRateLimitState {
-    async fn check_and_update(&self, key: &str, capacity: u64, refill_rate: f64) -> bool {
+    async fn check_and_update(
+        &self,
+        key: &str,
+        capacity: u64,
+        refill_rate: f64,
+    ) -> Result<RateLimitDecision, StoreUnavailable> {
         let entry = self.clients.entry(key.to_string()).or_insert_with(|| {
+            self.sweep_queue.push(key.to_string());
             Arc::new(RwLock::new(Bucket {
                 tokens: capacity as f64,
                 last_refill: Instant::now(),
+                capacity: capacity as f64,
+                refill_rate,
             }))
         });
 
         let mut bucket = entry.write().await;
 
+        // Adopt the limit first, so tokens over a lowered capacity are
+        // dropped rather than spent
+        bucket.capacity = capacity as f64;
+        bucket.refill_rate = refill_rate;
         let elapsed = bucket.last_refill.elapsed().as_secs_f64();
-        bucket.tokens = (bucket.tokens + elapsed * refill_rate).min(capacity as f64);
+        bucket.tokens = (bucket.tokens + elapsed * refill_rate).min(bucket.capacity);
         bucket.last_refill = Instant::now();
 
         if bucket.tokens >= 1.0 {
             bucket.tokens -= 1.0;
-            true
+            Ok(RateLimitDecision::Allowed)
         } else {
-            false
+            // A bucket that never refills has no time to give
+            let retry_after = Duration::try_from_secs_f64((1.0 - bucket.tokens) / refill_rate).unwrap_or(Duration::MAX);
+            Ok(RateLimitDecision::Limited { retry_after })
         }
     }
+
+    fn sweep_idle(&self, batch: Option<usize>) -> SweepStats {
+        self.sweep_queue.sweep(batch, |key| {
+            // A bucket being updated right now is in use
+            self.clients
+                .remove_if(key, |_, bucket| bucket.try_read().is_ok_and(|b| b.is_idle()))
+                .is_some()
+        })
+    }
 }
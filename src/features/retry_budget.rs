@@ -0,0 +1,47 @@
+use std::{sync::Mutex, time::Instant};
+
+use crate::{config::RetryBudgetConfig, middleware::rate_limiter::rate_limit::parse_duration};
+
+/// Gateway-wide allowance of upstream retries (`server.retry_budget`). First
+/// attempts are never charged; only retries draw from it.
+pub struct RetryBudget {
+    capacity: f64,
+    refill_per_sec: f64,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RetryBudget {
+    pub fn new(config: &RetryBudgetConfig) -> Self {
+        let capacity = f64::from(config.retries);
+        // Validated at load
+        let period = parse_duration(&config.period).map_or(1.0, |p| p.as_secs_f64());
+        Self {
+            capacity,
+            refill_per_sec: capacity / period,
+            bucket: Mutex::new(Bucket {
+                tokens: capacity,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Takes a token for one retry; false once the budget is spent.
+    pub fn try_acquire(&self) -> bool {
+        let mut bucket = self.bucket.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let elapsed = bucket.last_refill.elapsed().as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        bucket.last_refill = Instant::now();
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
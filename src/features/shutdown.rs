@@ -0,0 +1,59 @@
+use tracing::{info, warn};
+
+use crate::{features::metrics_exporter::MetricsFlush, state::AppState};
+
+/// Completes on Ctrl-C or, on Unix, SIGTERM.
+pub async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        () = ctrl_c => {},
+        () = terminate => {},
+    }
+    info!("Shutdown signal received, draining connections");
+}
+
+/// Runs once in-flight requests have drained: logs what the gateway served
+/// and flushes whatever is still buffered (captured requests, pushed metrics).
+pub async fn finish(state: &AppState, metrics_flush: Option<MetricsFlush>) {
+    let summary = state.stats.summary();
+    info!(
+        uptime_secs = summary.uptime_secs,
+        requests = summary.requests,
+        client_errors = summary.client_errors,
+        server_errors = summary.server_errors,
+        cache_hits = summary.cache_hits,
+        cache_misses = summary.cache_misses,
+        cache_hit_rate = summary.cache_hit_rate,
+        peak_concurrency = summary.peak_concurrency,
+        "Shutdown summary"
+    );
+
+    if let Some(capture) = &state.request_capture {
+        capture.flush().await;
+    }
+    if let Some(metrics) = metrics_flush {
+        metrics.flush().await;
+    }
+    info!("Gateway stopped");
+}
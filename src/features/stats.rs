@@ -0,0 +1,93 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::Instant,
+};
+
+use http::StatusCode;
+
+/// Process-wide request totals for the shutdown summary. Kept whether or
+/// not metrics are enabled.
+pub struct GatewayStats {
+    started_at: Instant,
+    requests: AtomicU64,
+    client_errors: AtomicU64,
+    server_errors: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    in_flight: AtomicU64,
+    peak_in_flight: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatsSummary {
+    pub uptime_secs: u64,
+    pub requests: u64,
+    pub client_errors: u64,
+    pub server_errors: u64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    /// Hits over cache lookups; 0 when nothing was looked up.
+    pub cache_hit_rate: f64,
+    pub peak_concurrency: u64,
+}
+
+impl GatewayStats {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            requests: AtomicU64::new(0),
+            client_errors: AtomicU64::new(0),
+            server_errors: AtomicU64::new(0),
+            cache_hits: AtomicU64::new(0),
+            cache_misses: AtomicU64::new(0),
+            in_flight: AtomicU64::new(0),
+            peak_in_flight: AtomicU64::new(0),
+        }
+    }
+
+    pub fn request_started(&self) {
+        let in_flight = self.in_flight.fetch_add(1, Ordering::Relaxed) + 1;
+        self.peak_in_flight.fetch_max(in_flight, Ordering::Relaxed);
+    }
+
+    pub fn request_finished(&self, status: StatusCode) {
+        self.in_flight.fetch_sub(1, Ordering::Relaxed);
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        if status.is_client_error() {
+            self.client_errors.fetch_add(1, Ordering::Relaxed);
+        } else if status.is_server_error() {
+            self.server_errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn record_cache_lookup(&self, hit: bool) {
+        let counter = if hit { &self.cache_hits } else { &self.cache_misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn summary(&self) -> StatsSummary {
+        let cache_hits = self.cache_hits.load(Ordering::Relaxed);
+        let cache_misses = self.cache_misses.load(Ordering::Relaxed);
+        let lookups = cache_hits + cache_misses;
+        StatsSummary {
+            uptime_secs: self.started_at.elapsed().as_secs(),
+            requests: self.requests.load(Ordering::Relaxed),
+            client_errors: self.client_errors.load(Ordering::Relaxed),
+            server_errors: self.server_errors.load(Ordering::Relaxed),
+            cache_hits,
+            cache_misses,
+            cache_hit_rate: if lookups == 0 {
+                0.0
+            } else {
+                cache_hits as f64 / lookups as f64
+            },
+            peak_concurrency: self.peak_in_flight.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Default for GatewayStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,90 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tracing::debug;
+
+use crate::state::AppState;
+
+/// Outcome of one sweep.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SweepStats {
+    pub examined: usize,
+    pub removed: usize,
+}
+
+/// Keys of an in-memory store in the order they were added, so each sweep
+/// resumes where the last one stopped instead of rescanning the whole map.
+#[derive(Default)]
+pub struct SweepQueue {
+    keys: Mutex<VecDeque<String>>,
+}
+
+impl SweepQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call once for each key newly added to the store.
+    pub fn push(&self, key: String) {
+        self.lock().push_back(key);
+    }
+
+    /// Offers up to `batch` keys (every key when `None`) to `remove_if_idle`,
+    /// which removes the entry from the store and returns true when it is
+    /// idle. Keys still in use go to the back of the queue; no key is offered
+    /// twice in one sweep.
+    pub fn sweep(&self, batch: Option<usize>, mut remove_if_idle: impl FnMut(&str) -> bool) -> SweepStats {
+        let mut stats = SweepStats::default();
+        let queued = self.lock().len();
+        let limit = batch.map_or(queued, |batch| batch.min(queued));
+        while stats.examined < limit {
+            // Not held while checking, so requests adding keys aren't blocked
+            let Some(key) = self.lock().pop_front() else {
+                break;
+            };
+            stats.examined += 1;
+            if remove_if_idle(&key) {
+                stats.removed += 1;
+            } else {
+                self.lock().push_back(key);
+            }
+        }
+        stats
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<String>> {
+        self.keys.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Every `interval`, drops idle rate limit buckets and circuits: ones a fresh
+/// entry would be indistinguishable from. `batch` bounds the entries checked
+/// per tick (`tuning.store_cleanup_batch`); unset checks them all.
+pub async fn run(state: Arc<AppState>, interval: Duration, batch: Option<usize>) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    ticker.tick().await;
+    loop {
+        ticker.tick().await;
+        let buckets = state.rate_limit_store.sweep_idle(batch);
+        let circuits = state.circuit_breaker_store.sweep_idle(batch);
+        debug!(
+            buckets_examined = buckets.examined,
+            buckets_removed = buckets.removed,
+            circuits_examined = circuits.examined,
+            circuits_removed = circuits.removed,
+            "Store cleanup tick"
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use std::{
+    io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use axum::{Router, extract::ConnectInfo};
+use hyper::body::Incoming;
+use hyper_util::{
+    rt::{TokioExecutor, TokioIo},
+    server::conn::auto::Builder as ConnectionBuilder,
+    service::TowerToHyperService,
+};
+use rustls::{
+    ServerConfig as RustlsServerConfig, SupportedProtocolVersion,
+    crypto::{CryptoProvider, ring},
+    pki_types::{CertificateDer, PrivateKeyDer, pem::PemObject},
+    server::{ClientHello, ResolvesServerCert},
+    sign::CertifiedKey,
+    version,
+};
+use serde::Deserialize;
+use tokio::{net::TcpListener, sync::watch, task::JoinSet};
+use tokio_rustls::TlsAcceptor;
+use tower::ServiceBuilder;
+use tracing::warn;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TlsConfig {
+    pub cert_path: String,
+    pub key_path: String,
+    /// Lowest protocol version accepted; handshakes offering less fail.
+    #[serde(default)]
+    pub min_version: TlsVersion,
+    /// Cipher suites offered, by IANA name (e.g. `TLS13_AES_256_GCM_SHA384`).
+    /// Empty: rustls' defaults.
+    #[serde(default)]
+    pub cipher_suites: Vec<String>,
+}
+
+/// TLS versions rustls speaks; anything older is always refused.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TlsVersion {
+    #[default]
+    Tls12,
+    Tls13,
+}
+
+/// Accepts `1.2` / `1.3`, quoted or not: YAML reads the bare form as a number.
+impl<'de> Deserialize<'de> for TlsVersion {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Text(String),
+            Number(f64),
+        }
+        let text = match Raw::deserialize(deserializer)? {
+            Raw::Text(text) => text,
+            Raw::Number(number) => number.to_string(),
+        };
+        match text.trim() {
+            "1.2" => Ok(TlsVersion::Tls12),
+            "1.3" => Ok(TlsVersion::Tls13),
+            other => Err(serde::de::Error::custom(format!(
+                "unsupported TLS version '{}': expected 1.2 or 1.3",
+                other
+            ))),
+        }
+    }
+}
+
+static TLS12_UP: &[&SupportedProtocolVersion] = &[&version::TLS13, &version::TLS12];
+static TLS13_ONLY: &[&SupportedProtocolVersion] = &[&version::TLS13];
+
+impl TlsVersion {
+    fn protocol_versions(self) -> &'static [&'static SupportedProtocolVersion] {
+        match self {
+            TlsVersion::Tls12 => TLS12_UP,
+            TlsVersion::Tls13 => TLS13_ONLY,
+        }
+    }
+}
+
+impl TlsConfig {
+    /// The ring provider, narrowed to `cipher_suites` when set.
+    fn crypto_provider(&self) -> Result<CryptoProvider, TlsError> {
+        let mut provider = ring::default_provider();
+        if self.cipher_suites.is_empty() {
+            return Ok(provider);
+        }
+        let available = std::mem::take(&mut provider.cipher_suites);
+        for name in &self.cipher_suites {
+            let suite = available
+                .iter()
+                .find(|s| s.suite().as_str().is_some_and(|n| n.eq_ignore_ascii_case(name)))
+                .ok_or_else(|| TlsError::UnknownCipherSuite(name.clone()))?;
+            provider.cipher_suites.push(*suite);
+        }
+        Ok(provider)
+    }
+
+    /// Checks the version and cipher suite settings can make a server config.
+    pub fn check(&self) -> Result<(), TlsError> {
+        RustlsServerConfig::builder_with_provider(Arc::new(self.crypto_provider()?))
+            .with_protocol_versions(self.min_version.protocol_versions())?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum TlsError {
+    #[error("Failed to read PEM: {0}")]
+    Pem(#[from] rustls::pki_types::pem::Error),
+    #[error("No certificates found in {0}")]
+    NoCertificates(String),
+    #[error("TLS error: {0}")]
+    Rustls(#[from] rustls::Error),
+    #[error("Unknown cipher suite '{0}'")]
+    UnknownCipherSuite(String),
+}
+
+/// Serves whatever certificate is currently loaded. Each handshake asks the
+/// resolver, so a reload only affects new connections.
+#[derive(Debug)]
+pub struct ReloadableCertResolver {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    current: RwLock<Arc<CertifiedKey>>,
+}
+
+impl ReloadableCertResolver {
+    pub fn from_files(cert_path: impl AsRef<Path>, key_path: impl AsRef<Path>) -> Result<Self, TlsError> {
+        let cert_path = cert_path.as_ref().to_path_buf();
+        let key_path = key_path.as_ref().to_path_buf();
+        let key = load_certified_key(&cert_path, &key_path)?;
+        Ok(Self {
+            cert_path,
+            key_path,
+            current: RwLock::new(Arc::new(key)),
+        })
+    }
+
+    /// Re-reads the cert and key from disk. On failure the old pair stays active.
+    pub fn reload(&self) -> Result<(), TlsError> {
+        let key = Arc::new(load_certified_key(&self.cert_path, &self.key_path)?);
+        match self.current.write() {
+            Ok(mut current) => *current = key,
+            Err(poisoned) => *poisoned.into_inner() = key,
+        }
+        Ok(())
+    }
+
+    pub fn current(&self) -> Arc<CertifiedKey> {
+        match self.current.read() {
+            Ok(current) => current.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    pub fn cert_path(&self) -> &Path {
+        &self.cert_path
+    }
+
+    pub fn key_path(&self) -> &Path {
+        &self.key_path
+    }
+}
+
+impl ResolvesServerCert for ReloadableCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current())
+    }
+}
+
+pub fn load_certified_key(cert_path: &Path, key_path: &Path) -> Result<CertifiedKey, TlsError> {
+    let certs = CertificateDer::pem_file_iter(cert_path)?.collect::<Result<Vec<_>, _>>()?;
+    if certs.is_empty() {
+        return Err(TlsError::NoCertificates(cert_path.display().to_string()));
+    }
+    let key = PrivateKeyDer::from_pem_file(key_path)?;
+    Ok(CertifiedKey::from_der(certs, key, &ring::default_provider())?)
+}
+
+pub fn server_config(
+    resolver: Arc<ReloadableCertResolver>,
+    tls: &TlsConfig,
+) -> Result<Arc<RustlsServerConfig>, TlsError> {
+    let mut config = RustlsServerConfig::builder_with_provider(Arc::new(tls.crypto_provider()?))
+        .with_protocol_versions(tls.min_version.protocol_versions())?
+        .with_no_client_auth()
+        .with_cert_resolver(resolver);
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    Ok(Arc::new(config))
+}
+
+/// How long a client gets to finish the TLS handshake before it's dropped.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Accept loop for TLS termination. Mirrors `axum::serve` with connect info,
+/// so the client address keeps working behind TLS. Once `shutdown` completes it stops
+/// accepting and returns when open connections have finished.
+pub async fn serve_tls(
+    listener: TcpListener,
+    app: Router,
+    tls_config: Arc<RustlsServerConfig>,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> io::Result<()> {
+    let acceptor = TlsAcceptor::from(tls_config);
+    let (draining_tx, draining) = watch::channel(false);
+    let mut connections = JoinSet::new();
+    tokio::pin!(shutdown);
+    loop {
+        let accepted = tokio::select! {
+            accepted = listener.accept() => accepted,
+            Some(_) = connections.join_next() => continue,
+            () = &mut shutdown => break,
+        };
+        let (stream, remote_addr) = match accepted {
+            Ok(conn) => conn,
+            Err(e) => {
+                warn!("Failed to accept connection: {}", e);
+                continue;
+            }
+        };
+        let acceptor = acceptor.clone();
+        let app = app.clone();
+        let mut draining = draining.clone();
+
+        connections.spawn(async move {
+            let tls_stream = tokio::select! {
+                handshake = tokio::time::timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)) => match handshake {
+                    Ok(Ok(s)) => s,
+                    Ok(Err(e)) => {
+                        warn!(remote = %remote_addr, "TLS handshake failed: {}", e);
+                        return;
+                    }
+                    Err(_) => {
+                        warn!(remote = %remote_addr, "TLS handshake timed out");
+                        return;
+                    }
+                },
+                // No request has started yet, so there's nothing to finish
+                _ = draining.changed() => return,
+            };
+
+            let service = ServiceBuilder::new()
+                .map_request(move |mut req: http::Request<Incoming>| {
+                    req.extensions_mut().insert(ConnectInfo::<SocketAddr>(remote_addr));
+                    req
+                })
+                .service(app);
+
+            let builder = ConnectionBuilder::new(TokioExecutor::new());
+            let conn =
+                builder.serve_connection_with_upgrades(TokioIo::new(tls_stream), TowerToHyperService::new(service));
+            tokio::pin!(conn);
+            let result = tokio::select! {
+                result = conn.as_mut() => result,
+                _ = draining.changed() => {
+                    // Finish the request in progress, then close
+                    conn.as_mut().graceful_shutdown();
+                    conn.await
+                }
+            };
+            if let Err(e) = result {
+                warn!(remote = %remote_addr, "TLS connection error: {}", e);
+            }
+        });
+    }
+
+    // Stop accepting, let open connections finish their requests
+    drop(listener);
+    let _ = draining_tx.send(true);
+    while connections.join_next().await.is_some() {}
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use dashmap::DashMap;
+use serde::Serialize;
+
+#[derive(Default)]
+struct Counters {
+    requests: AtomicU64,
+    /// Unix time in milliseconds; 0 means never.
+    last_request_ms: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct RouteTrafficSnapshot {
+    pub requests: u64,
+    /// Unix time in milliseconds of the most recent request, if any.
+    pub last_request_ms: Option<u64>,
+}
+
+/// Requests proxied per route and when each route was last hit, for
+/// spotting dead routes. Counts reset on restart.
+pub struct RouteTraffic {
+    routes: DashMap<String, Arc<Counters>>,
+}
+
+impl RouteTraffic {
+    pub fn new() -> Self {
+        Self { routes: DashMap::new() }
+    }
+
+    pub fn record(&self, route: &str) {
+        let counters = match self.routes.get(route) {
+            Some(c) => c.clone(),
+            None => self.routes.entry(route.to_string()).or_default().clone(),
+        };
+        counters.requests.fetch_add(1, Ordering::Relaxed);
+        counters.last_request_ms.fetch_max(now_ms(), Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self, route: &str) -> RouteTrafficSnapshot {
+        let Some(counters) = self.routes.get(route) else {
+            return RouteTrafficSnapshot {
+                requests: 0,
+                last_request_ms: None,
+            };
+        };
+        let last_request_ms = counters.last_request_ms.load(Ordering::Relaxed);
+        RouteTrafficSnapshot {
+            requests: counters.requests.load(Ordering::Relaxed),
+            last_request_ms: (last_request_ms > 0).then_some(last_request_ms),
+        }
+    }
+}
+
+impl Default for RouteTraffic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+}
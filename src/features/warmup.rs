@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use futures::future::join_all;
+use reqwest::{Client, Method};
+use tracing::{debug, info, warn};
+
+/// What a startup warm-up achieved.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WarmupStats {
+    /// Connections opened; any response counts, even an error status.
+    pub connections: usize,
+    /// Connection attempts that failed (unreachable backend, timeout).
+    pub failures: usize,
+}
+
+/// Opens `connections` pooled connections to each destination by sending
+/// that many concurrent `OPTIONS` requests, so the first client requests
+/// don't pay for connect and TLS setup. Unreachable destinations are logged
+/// and skipped.
+pub async fn warm_up(targets: &[(Client, String)], connections: usize, timeout: Duration) -> WarmupStats {
+    let attempts = targets.iter().flat_map(|(client, destination)| {
+        (0..connections).map(move |_| async move {
+            let result = client
+                .request(Method::OPTIONS, destination)
+                .timeout(timeout)
+                .send()
+                .await;
+            if let Err(e) = &result {
+                debug!(destination = %destination, "Warm-up request failed: {}", e);
+            }
+            (destination.as_str(), result.is_ok())
+        })
+    });
+
+    let mut stats = WarmupStats::default();
+    let mut unreachable = Vec::new();
+    for (destination, ok) in join_all(attempts).await {
+        if ok {
+            stats.connections += 1;
+        } else {
+            stats.failures += 1;
+            if !unreachable.contains(&destination) {
+                unreachable.push(destination);
+            }
+        }
+    }
+    for destination in unreachable {
+        warn!(destination = %destination, "Skipping warm-up of unreachable destination");
+    }
+    info!(
+        destinations = targets.len(),
+        connections = stats.connections,
+        failures = stats.failures,
+        "Connection warm-up finished"
+    );
+    stats
+}
@@ -33,17 +33,19 @@ pub async fn grpc_proxy_handler(
     let destination = {
         let config = state.config.read().await;
         config.find_route_for_path(&request_path).and_then(|route| {
-            let dest_path = request_path.strip_prefix(&route.path).unwrap_or("");
-            let destinations = route.all_destinations();
+            let dest_path = route.strip_path_prefix(&request_path).unwrap_or("");
+            let destinations = state.drained.filter(state.blue_green.destinations(&route));
             let healthy = state.health_checker.filter_healthy(&destinations);
-            let idx = state.load_balancer.next_index(healthy.len(), &route.load_balance)?;
-            Some(format!("{}{}", healthy[idx], dest_path))
+            let idx =
+                state
+                    .load_balancer
+                    .pick(&route.name, &healthy, &route.load_balance, &route.destination_weights)?;
+            Some((healthy[idx].to_string(), format!("{}{}", healthy[idx], dest_path)))
         })
     };
 
-    let dest_url = match destination {
-        Some(url) => url,
-        None => return grpc_error(StatusCode::NOT_FOUND),
+    let Some((base, dest_url)) = destination else {
+        return grpc_error(StatusCode::NOT_FOUND);
     };
 
     info!(destination = %dest_url, "Proxying gRPC request");
@@ -57,7 +59,7 @@ pub async fn grpc_proxy_handler(
     };
 
     let mut req_builder = http::Request::builder().method(method).uri(&uri);
-    for (key, value) in headers.iter() {
+    for (key, value) in &headers {
         req_builder = req_builder.header(key, value);
     }
 
@@ -71,6 +73,7 @@ pub async fn grpc_proxy_handler(
 
     let client = Client::builder(TokioExecutor::new()).http2_only(true).build_http();
 
+    let _in_flight = state.load_balancer.track(&base);
     match client.request(request).await {
         Ok(resp) => {
             let (parts, body) = resp.into_parts();
@@ -82,7 +85,7 @@ pub async fn grpc_proxy_handler(
                 }
             };
             let mut response = Response::builder().status(parts.status);
-            for (key, value) in parts.headers.iter() {
+            for (key, value) in &parts.headers {
                 response = response.header(key, value);
             }
             response
@@ -1,6 +1,8 @@
+pub mod admin;
 pub mod aggregate;
 pub mod app;
 pub mod config;
+pub mod constants;
 pub mod errors;
 pub mod features;
 pub mod grpc_proxy;
@@ -8,40 +10,85 @@ pub mod middleware;
 pub mod plugins;
 pub mod proxy;
 pub mod state;
+pub mod static_file;
 pub mod utils;
 pub mod ws_proxy;
 
-use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+use std::{net::SocketAddr, sync::Arc};
 
 use anyhow::Result;
-use axum_prometheus::PrometheusMetricLayer;
+use axum::Router;
+use axum_prometheus::{EndpointLabel, PrometheusMetricLayerBuilder, metrics_exporter_prometheus::PrometheusHandle};
 use dotenvy::dotenv;
 use moka::future::Cache;
 use reqwest::Client;
-use tokio::{net::TcpListener, sync::RwLock};
-use tracing::{Level, info};
+use tokio::{net::TcpListener, runtime::Runtime, sync::RwLock};
+use tracing::{Level, info, warn};
+use tracing_subscriber::{Layer, layer::SubscriberExt, util::SubscriberInitExt};
 
-use crate::state::{AppState, CachedResponse};
+use crate::state::AppState;
 use crate::{
-    config::{ApiKeyStore, GatewayConfig, SecretsConfig},
+    config::{ApiKeyStore, GatewayConfig, MetricsExporter, SecretsConfig},
     features::{
         circuit_breaker::circuit_breaker::CircuitBreakerStore,
         rate_limiter::state::{InMemoryRateLimitState, RateLimitState},
+        tls::ReloadableCertResolver,
+    },
+    utils::{
+        config_source::{ConfigSource, HttpConfigSource},
+        hot_reload, logging,
     },
-    utils::hot_reload,
 };
 
-pub async fn run(config_path: PathBuf) -> Result<()> {
+/// Builds the Tokio runtime from `server.runtime` before the gateway starts.
+/// A config that fails to load here falls back to defaults; `run` reports the error.
+pub fn build_runtime(source: &ConfigSource) -> Result<Runtime> {
+    dotenv().ok();
+
+    let loaded = match source {
+        ConfigSource::File(path) => GatewayConfig::load(path),
+        // Fetching needs a runtime of its own until the configured one exists,
+        // and a client of its own so no pooled connection outlives that runtime
+        ConfigSource::Http(remote) => {
+            let mut bootstrap = ConfigSource::Http(HttpConfigSource::new(
+                remote.url(),
+                remote.poll_interval(),
+                Client::new(),
+            ));
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?
+                .block_on(bootstrap.load())
+        }
+    };
+    let runtime_config = loaded
+        .map(|cfg| cfg.server.runtime)
+        .unwrap_or_default()
+        .with_env_overrides();
+
+    Ok(runtime_config.build()?)
+}
+
+pub async fn run(mut source: ConfigSource) -> Result<()> {
     dotenv().ok();
 
-    tracing_subscriber::fmt().with_max_level(Level::INFO).init();
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer().with_filter(logging::route_level_filter(Level::INFO)))
+        .init();
 
     info!("Loading secrets...");
-    let secrets = Arc::new(SecretsConfig::from_env()?);
+    let secrets = SecretsConfig::from_env()?;
 
     info!("Loading gateway configuration...");
-    let config = Arc::new(RwLock::new(GatewayConfig::load(config_path.clone())?));
+    let config = Arc::new(RwLock::new(source.load().await?));
     info!("Configuration loaded successfully.");
+    let estimate = config.read().await.resource_estimate();
+    info!(
+        routes = estimate.routes,
+        regexes = estimate.regexes,
+        approx_kib = estimate.approx_bytes.div_ceil(1024),
+        "Estimated route table footprint"
+    );
 
     let key_store_path = config.read().await.identity.api_key_store_path.clone();
 
@@ -49,42 +96,106 @@ pub async fn run(config_path: PathBuf) -> Result<()> {
 
     let key_store = Arc::new(RwLock::new(ApiKeyStore::load(&key_store_path)?));
 
-    let cache: Arc<Cache<String, Arc<CachedResponse>>> = Arc::new(
-        Cache::builder()
-            .max_capacity(10_000) // Default 5 minute TTL
-            .build(),
-    );
+    // start hot reloader
+    let reload_channel_buffer = config.read().await.tuning.reload_channel_buffer;
+    let config_path = match source {
+        ConfigSource::File(path) => Some(path),
+        ConfigSource::Http(remote) => {
+            tokio::spawn(hot_reload::watch_remote_config(remote, config.clone()));
+            None
+        }
+    };
+    tokio::spawn(hot_reload::watch_config_files(
+        config_path,
+        config.clone(),
+        key_store.clone(), // Clone for the watcher task
+        reload_channel_buffer,
+    ));
 
-    let rate_limit_store: Arc<dyn RateLimitState> = Arc::new(InMemoryRateLimitState::new());
+    let addr = config.read().await.server.addr.clone();
+    let listener = TcpListener::bind(&addr).await?;
+
+    serve(
+        config,
+        Arc::new(secrets),
+        key_store,
+        listener,
+        features::shutdown::shutdown_signal(),
+    )
+    .await
+}
 
+/// Runs the gateway on an already-bound listener with an in-memory config.
+/// Used to start the gateway in-process (tests, benchmarks) on an ephemeral port;
+/// no config file is watched. Enabling metrics installs a global recorder, so
+/// only one such gateway per process may have metrics on.
+pub async fn run_with_config(
+    config: GatewayConfig,
+    secrets: SecretsConfig,
+    key_store: ApiKeyStore,
+    listener: TcpListener,
+) -> Result<()> {
+    run_with_shutdown(
+        config,
+        secrets,
+        key_store,
+        listener,
+        features::shutdown::shutdown_signal(),
+    )
+    .await
+}
+
+/// Like `run_with_config`, but stops when `shutdown` completes rather than on
+/// Ctrl-C/SIGTERM. Returns once in-flight requests have drained and the
+/// shutdown summary is logged.
+pub async fn run_with_shutdown(
+    config: GatewayConfig,
+    secrets: SecretsConfig,
+    key_store: ApiKeyStore,
+    listener: TcpListener,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> Result<()> {
+    serve(
+        Arc::new(RwLock::new(config)),
+        Arc::new(secrets),
+        Arc::new(RwLock::new(key_store)),
+        listener,
+        shutdown,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_lines)]
+async fn serve(
+    config: Arc<RwLock<GatewayConfig>>,
+    secrets: Arc<SecretsConfig>,
+    key_store: Arc<RwLock<ApiKeyStore>>,
+    listener: TcpListener,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> Result<()> {
+    let mut metrics_flush = None;
     let (prometheus_layer, prometheus_handle) = {
         let config_guard = config.read().await;
-        if config_guard.observability.metrics.enabled {
+        let metrics = &config_guard.observability.metrics;
+        if metrics.enabled && metrics.exporter != MetricsExporter::Prometheus {
+            info!(exporter = ?metrics.exporter, "Metrics reporting is enabled");
+            metrics_flush = features::metrics_exporter::install(metrics, Client::new())?;
+            (None, None)
+        } else if metrics.enabled {
             info!("Metrics reporting is enabled");
-            let (layer, handle) = PrometheusMetricLayer::pair();
+            let (layer, handle) = PrometheusMetricLayerBuilder::new()
+                .with_endpoint_label_type(EndpointLabel::MatchedPathWithFallbackFn(
+                    features::metrics::unmatched_endpoint_label,
+                ))
+                .with_default_metrics()
+                .build_pair();
             (Some(layer), Some(handle))
         } else {
             (None, None)
         }
     };
 
-    let circuit_breaker_store = Arc::new(CircuitBreakerStore::new());
-
-    let plugin_registry = Arc::new(plugins::PluginRegistry::new());
-
-    let health_checker = Arc::new(features::health_check::HealthChecker::new());
-
-    let http_client = {
-        let cfg = config.read().await;
-        let pool = &cfg.server.pool;
-        Client::builder()
-            .connect_timeout(features::health_check::parse_duration(&pool.connect_timeout))
-            .timeout(features::health_check::parse_duration(&pool.request_timeout))
-            .pool_idle_timeout(features::health_check::parse_duration(&pool.idle_timeout))
-            .pool_max_idle_per_host(pool.max_idle_per_host)
-            .build()
-            .expect("Failed to build HTTP client")
-    };
+    let app_state = build_state(config.clone(), secrets, key_store, prometheus_handle).await?;
 
     // Collect health check targets from routes
     {
@@ -92,63 +203,220 @@ pub async fn run(config_path: PathBuf) -> Result<()> {
         let mut targets = Vec::new();
         for route in &cfg.routes {
             if let Some(hc) = &route.health_check {
-                let interval = features::health_check::parse_duration(&hc.interval);
                 for dest in route.all_destinations() {
-                    targets.push((dest.to_string(), hc.path.clone(), interval));
+                    targets.push((dest.to_string(), hc.clone()));
                 }
             }
         }
         if !targets.is_empty() {
-            health_checker.start_checker(http_client.clone(), targets);
+            app_state
+                .health_checker
+                .start_checker(app_state.http_client.clone(), targets);
             info!("Health checks started");
         }
     }
 
-    let app_state = Arc::new(AppState {
-        config: config.clone(),
-        secrets,
-        key_store: key_store.clone(),
-        rate_limit_store,
-        cache,
-        http_client: http_client.clone(),
-        http_client_insecure: Client::builder()
-            .danger_accept_invalid_certs(true)
-            .connect_timeout(std::time::Duration::from_secs(5))
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .expect("Failed to build insecure HTTP client"),
-        prometheus_handle,
-        circuit_breaker_store,
-        load_balancer: features::load_balancer::LoadBalancer::new(),
-        health_checker,
-        plugin_registry,
-    });
+    // Prime the connection pools before the listener starts serving
+    let (warmup_targets, warmup_connections, warmup_timeout) = {
+        let cfg = config.read().await;
+        let pool = &cfg.server.pool;
+        let mut targets: Vec<(Client, String)> = Vec::new();
+        for route in &cfg.routes {
+            let client = if route.tls_skip_verify {
+                &app_state.http_client_insecure
+            } else {
+                &app_state.http_client
+            };
+            for dest in route.all_destinations() {
+                if !targets.iter().any(|(_, d)| d == dest) {
+                    targets.push((client.clone(), dest.to_string()));
+                }
+            }
+        }
+        (
+            targets,
+            pool.warmup_connections.min(pool.max_idle_per_host),
+            features::health_check::parse_duration(&pool.connect_timeout),
+        )
+    };
+    if warmup_connections > 0 {
+        features::warmup::warm_up(&warmup_targets, warmup_connections, warmup_timeout).await;
+    }
 
-    // start hot reloader
-    tokio::spawn(hot_reload::watch_config_files(
-        config_path,
-        config.clone(),
-        key_store.clone(), // Clone for the watcher task
+    // Key sets are fetched up front so the first tokens don't wait on them;
+    // a failure here is retried when a token needs the set
+    let jwks_urls: Vec<(String, std::time::Duration)> = {
+        let cfg = config.read().await;
+        let admin_auth = cfg.server.admin.iter().flat_map(|admin| admin.auth.iter());
+        let mut urls: Vec<(String, std::time::Duration)> = Vec::new();
+        for auth in cfg.routes.iter().flat_map(|r| r.auth.iter()).chain(admin_auth) {
+            if let Some(url) = &auth.jwks_url
+                && !urls.iter().any(|(u, _)| u == url)
+            {
+                urls.push((url.clone(), features::health_check::parse_duration(&auth.timeout)));
+            }
+        }
+        urls
+    };
+    for (url, timeout) in &jwks_urls {
+        if let Err(e) = app_state.jwks.refresh(&app_state.http_client, url, *timeout).await {
+            warn!(url = %url, "Initial JWKS fetch failed: {:?}", e);
+        }
+    }
+
+    let (cleanup_interval, cleanup_batch) = {
+        let tuning = &config.read().await.tuning;
+        (
+            features::health_check::parse_duration(&tuning.store_cleanup_interval),
+            tuning.store_cleanup_batch,
+        )
+    };
+    tokio::spawn(features::store_cleanup::run(
+        app_state.clone(),
+        cleanup_interval,
+        cleanup_batch,
     ));
 
-    let (cors_config, body_limit) = {
+    let (tls, tls_reload_debounce) = {
         let cfg = config.read().await;
-        let bl = features::health_check::parse_body_limit(&cfg.server.pool.body_limit);
-        (cfg.cors.clone(), bl)
+        (
+            cfg.server.tls.clone(),
+            features::health_check::parse_duration(&cfg.tuning.tls_reload_debounce),
+        )
     };
-    let mut app = app::create_app(app_state, &cors_config, body_limit)?;
+    let mut app = build_app(app_state.clone()).await?;
 
     if let Some(layer) = prometheus_layer {
         app = app.layer(layer);
     }
 
-    let config_guard = config.read().await;
+    let addr = listener.local_addr()?;
 
-    let addr = config_guard.server.addr.clone();
-
-    let listener = TcpListener::bind(&addr).await?;
-    info!("Gateway listening on {}", &addr);
-    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
+    if let Some(tls) = tls {
+        let resolver = Arc::new(ReloadableCertResolver::from_files(&tls.cert_path, &tls.key_path)?);
+        tokio::spawn(hot_reload::watch_tls_files(resolver.clone(), tls_reload_debounce));
+        info!("Gateway listening on {} (TLS)", addr);
+        features::tls::serve_tls(listener, app, features::tls::server_config(resolver, &tls)?, shutdown).await?;
+    } else {
+        info!("Gateway listening on {}", addr);
+        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+            .with_graceful_shutdown(shutdown)
+            .await?;
+    }
 
+    features::shutdown::finish(&app_state, metrics_flush).await;
     Ok(())
 }
+
+/// Builds the full router, every middleware included, for an already-built state.
+/// The metrics layer is added by the caller since it installs a global recorder.
+pub async fn build_app(state: Arc<AppState>) -> Result<Router> {
+    let (cors_config, body_limit) = {
+        let cfg = state.config.read().await;
+        let bl = features::health_check::parse_body_limit(&cfg.server.pool.body_limit);
+        (cfg.cors.clone(), bl)
+    };
+    app::create_app(state, &cors_config, body_limit)
+}
+
+/// Builds the shared state from an already-loaded config.
+/// Background tasks (health checks, hot reload) are left to the caller.
+pub async fn build_state(
+    config: Arc<RwLock<GatewayConfig>>,
+    secrets: Arc<SecretsConfig>,
+    key_store: Arc<RwLock<ApiKeyStore>>,
+    prometheus_handle: Option<PrometheusHandle>,
+) -> Result<Arc<AppState>> {
+    let cache = {
+        let cfg = config.read().await;
+        features::cache::build_response_cache(&cfg.server.cache).await?
+    };
+
+    let rate_limit_store: Arc<dyn RateLimitState> = Arc::new(InMemoryRateLimitState::new());
+
+    let circuit_breaker_events = config.read().await.tuning.circuit_breaker_events;
+    let circuit_breaker_store = Arc::new(CircuitBreakerStore::new().with_events(circuit_breaker_events));
+
+    let plugin_registry = Arc::new(plugins::PluginRegistry::new());
+
+    let tuning = config.read().await.tuning.clone();
+
+    let health_checker = Arc::new(features::health_check::HealthChecker::with_timeout(
+        features::health_check::parse_duration(&tuning.health_check_timeout),
+    ));
+
+    let (http_client, max_route_labels, request_id, global_circuit_breaker, qos_gate, retry_budget) = {
+        let cfg = config.read().await;
+        let pool = &cfg.server.pool;
+        // Redirects are relayed to the caller; routes opt in to following them (see proxy)
+        let client = Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .connect_timeout(features::health_check::parse_duration(&pool.connect_timeout))
+            .timeout(features::health_check::parse_duration(&pool.request_timeout))
+            .pool_idle_timeout(features::health_check::parse_duration(&pool.idle_timeout))
+            .pool_max_idle_per_host(pool.max_idle_per_host)
+            .build()?;
+        (
+            client,
+            cfg.observability.metrics.max_route_labels,
+            cfg.observability.request_id.clone(),
+            cfg.server
+                .circuit_breaker
+                .as_ref()
+                .map(features::circuit_breaker::global::GlobalCircuitBreaker::new),
+            cfg.server.qos.as_ref().map(features::qos::PriorityGate::new),
+            cfg.server
+                .retry_budget
+                .as_ref()
+                .map(features::retry_budget::RetryBudget::new),
+        )
+    };
+
+    let capture_config = config.read().await.observability.capture.clone();
+    let request_capture = match capture_config {
+        Some(capture) => Some(features::capture::RequestCapture::start(&capture).await?),
+        None => None,
+    };
+
+    Ok(Arc::new(AppState {
+        config,
+        secrets,
+        key_store,
+        rate_limit_store,
+        cache,
+        static_cache: Cache::builder()
+            .max_capacity(tuning.static_cache_capacity)
+            .time_to_live(features::health_check::parse_duration(&tuning.static_cache_ttl))
+            .build(),
+        http_client,
+        http_client_insecure: Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .danger_accept_invalid_certs(true)
+            .connect_timeout(std::time::Duration::from_secs(5))
+            .timeout(std::time::Duration::from_secs(30))
+            .build()?,
+        prometheus_handle,
+        route_labels: features::metrics::RouteLabels::new(max_route_labels),
+        request_id_generator: middleware::request_id::generator::RequestIdGenerator::new(request_id.format)
+            .with_policy(request_id.policy),
+        jwks: features::auth::jwks::JwksCache::new(features::health_check::parse_duration(&tuning.jwks_miss_cooldown)),
+        circuit_breaker_store,
+        global_circuit_breaker,
+        request_capture,
+        qos_gate,
+        retry_budget,
+        load_balancer: features::load_balancer::LoadBalancer::new(),
+        canary_tracker: features::canary::CanaryTracker::new(),
+        blue_green: features::blue_green::BlueGreenSwitch::new(),
+        drained: features::drain::DrainedDestinations::new(),
+        route_traffic: features::traffic::RouteTraffic::new(),
+        latency_tracker: features::adaptive_timeout::LatencyTracker::new(),
+        stats: features::stats::GatewayStats::new(),
+        request_coalescer: match tuning.coalesce_shards {
+            Some(shards) => features::coalesce::RequestCoalescer::with_shards(shards),
+            None => features::coalesce::RequestCoalescer::new(),
+        },
+        health_checker,
+        plugin_registry,
+    }))
+}
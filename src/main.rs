@@ -1,8 +1,38 @@
-use rustway::run;
-use std::path::PathBuf;
+use clap::Parser;
+use rustway::{
+    build_runtime,
+    config::hash_api_key,
+    features::{auth::basic::hash_password, capture::replay_file},
+    run,
+    utils::config_path::{Cli, Command},
+};
 
-#[tokio::main]
-async fn main() -> Result<(), anyhow::Error> {
-    let config_path = PathBuf::from("gateway.yaml");
-    run(config_path).await
+fn main() -> Result<(), anyhow::Error> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        Some(Command::Replay { file, target }) => {
+            tracing_subscriber::fmt::init();
+            let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+            let summary = runtime.block_on(replay_file(&file, &target, &reqwest::Client::new()))?;
+            println!(
+                "Replayed {} requests ({} failed, {} skipped)",
+                summary.sent, summary.failed, summary.skipped
+            );
+            Ok(())
+        }
+        Some(Command::HashApiKey { key }) => {
+            println!("{}", hash_api_key(&key));
+            Ok(())
+        }
+        Some(Command::HashPassword { password, iterations }) => {
+            println!("{}", hash_password(&password, iterations)?);
+            Ok(())
+        }
+        None => {
+            let source = cli.config_source()?;
+            let runtime = build_runtime(&source)?;
+            runtime.block_on(run(source))
+        }
+    }
 }
@@ -1,26 +1,97 @@
-use axum::{extract::Request, middleware::Next, response::Response};
-use std::time::Instant;
-use tracing::info;
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use http::HeaderMap;
+use std::{sync::Arc, time::Instant};
+use tracing::{info, warn};
+
+use crate::{
+    app::REQUEST_ID_HEADER,
+    features::capture::{REDACTED, SENSITIVE_HEADERS},
+    middleware::route_match::matched_route,
+    state::AppState,
+    utils::logging::with_route_log_level,
+};
 
 /// Structured access log middleware.
-/// Logs method, path, status, duration for every request.
-pub async fn layer(req: Request, next: Next) -> Response {
+/// Logs method, path, status, duration for every request, and warns about
+/// requests slower than `observability.slow_request_threshold`.
+/// A matched route's `log_level` applies to everything logged for the request,
+/// and its `log_headers` are added to the access line.
+pub async fn layer(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Response {
     let method = req.method().clone();
     let path = req.uri().path().to_string();
-    let start = Instant::now();
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("unknown")
+        .to_string();
+    let route = matched_route(&req);
+    let route_log_level = route
+        .as_ref()
+        .and_then(|r| r.log_level.as_deref().and_then(|l| l.parse().ok()));
+    let log_headers = route.map(|r| r.log_headers.clone()).unwrap_or_default();
+    let request_headers = logged_headers(req.headers(), &log_headers);
+    let slow_threshold = state.config.read().await.observability.slow_request_threshold;
+
+    with_route_log_level(route_log_level, async move {
+        let start = Instant::now();
+
+        let response = next.run(req).await;
 
-    let response = next.run(req).await;
+        let duration = start.elapsed();
+        let status = response.status().as_u16();
+        let response_headers = logged_headers(response.headers(), &log_headers);
 
-    let duration = start.elapsed();
-    let status = response.status().as_u16();
+        info!(
+            method = %method,
+            path = %path,
+            status = status,
+            duration_ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+            request_headers = request_headers.as_deref(),
+            response_headers = response_headers.as_deref(),
+            "access"
+        );
 
-    info!(
-        method = %method,
-        path = %path,
-        status = status,
-        duration_ms = duration.as_millis() as u64,
-        "access"
-    );
+        if let Some(threshold) = slow_threshold
+            && duration > threshold
+        {
+            warn!(
+                method = %method,
+                path = %path,
+                status = status,
+                latency_ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX),
+                threshold_ms = u64::try_from(threshold.as_millis()).unwrap_or(u64::MAX),
+                request_id = %request_id,
+                "slow request"
+            );
+        }
+
+        response
+    })
+    .await
+}
 
-    response
+/// `name=value` pairs for the listed headers present in `headers`, or `None`
+/// when the route lists none.
+fn logged_headers(headers: &HeaderMap, names: &[String]) -> Option<String> {
+    if names.is_empty() {
+        return None;
+    }
+    let pairs: Vec<String> = names
+        .iter()
+        .filter_map(|name| {
+            let value = headers.get(name.as_str())?;
+            let value = if SENSITIVE_HEADERS.iter().any(|h| h.eq_ignore_ascii_case(name)) {
+                REDACTED.into()
+            } else {
+                String::from_utf8_lossy(value.as_bytes())
+            };
+            Some(format!("{}={}", name.to_ascii_lowercase(), value))
+        })
+        .collect();
+    Some(pairs.join(" "))
 }
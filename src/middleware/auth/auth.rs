@@ -1,46 +1,86 @@
 use std::sync::Arc;
 
 use axum::{
+    body::Body,
     extract::{Request, State},
     middleware::Next,
     response::Response,
 };
 
-use http::Uri;
+use http_body_util::BodyExt;
 
 use crate::{
-    config::RouteConfig,
+    config::AuthType,
     errors::AppError,
-    features::auth::auth::{check_roles, verify_token},
+    features::auth::{
+        auth::{AuthOutcome, authenticate, check_claims, check_roles},
+        basic::basic_challenge,
+        trusted_header::is_trusted_source,
+    },
+    middleware::route_match::{matched_route, request_context},
+    proxy::request_body_error,
     state::AppState,
 };
 
 // axum middleware layer for authentication
 pub async fn layer(State(state): State<Arc<AppState>>, mut req: Request, next: Next) -> Result<Response, AppError> {
-    let route = find_route_for_uri(req.uri(), state.clone()).await?;
-
-    if let Some(auth_config) = &route.auth {
-        let claims = {
-            // Acquire read lock on the key store for API key checks
-            let key_store_guard = state.key_store.read().await;
-            // Pass all necessary configs to the verification function
-            verify_token(req.headers(), auth_config, &state.secrets, &key_store_guard)?
+    let route = matched_route(&req).ok_or(AppError::RouteNotFound)?;
+    let client_ip = request_context(&req).and_then(|ctx| ctx.client_ip);
+
+    if !route.middleware.auth {
+        return Ok(next.run(req).await);
+    }
+
+    if !route.auth.is_empty() {
+        // Signatures cover the raw body, so it is buffered here and put back
+        let body = if route.auth.iter().any(|m| m.auth_type == AuthType::HmacSignature) {
+            let (parts, body) = req.into_parts();
+            let bytes = body
+                .collect()
+                .await
+                .map_err(|e| request_body_error(&route.name, &e))?
+                .to_bytes();
+            req = Request::from_parts(parts, Body::from(bytes.clone()));
+            Some(bytes)
+        } else {
+            None
+        };
+        let outcome = match authenticate(
+            req.headers(),
+            body.as_deref(),
+            client_ip,
+            &route.auth,
+            &state.secrets,
+            &state.key_store,
+            &state.jwks,
+            &state.http_client,
+        )
+        .await
+        {
+            Ok(outcome) => outcome,
+            Err(e) if route.auth.iter().any(|m| m.auth_type == AuthType::Basic) => {
+                return Ok(basic_challenge(e, &route.name));
+            }
+            Err(e) => return Err(e),
         };
 
-        if let Some(required_roles) = &auth_config.roles {
-            check_roles(&claims.roles, required_roles)?;
+        if let AuthOutcome::Authenticated(claims, auth_config) = outcome {
+            if let Some(required_roles) = &auth_config.roles {
+                check_roles(&claims.roles, required_roles)?;
+            }
+            check_claims(&claims, &auth_config.required_claims)?;
+            req.extensions_mut().insert(claims);
         }
 
-        req.extensions_mut().insert(claims);
+        // Another method let the request in; identity headers from an
+        // untrusted peer must not reach the backend either
+        for method in route.auth.iter().filter(|m| m.auth_type == AuthType::TrustedHeader) {
+            if !is_trusted_source(method, client_ip) {
+                req.headers_mut().remove(method.user_header.as_str());
+                req.headers_mut().remove(method.roles_header.as_str());
+            }
+        }
     }
 
     Ok(next.run(req).await)
 }
-
-async fn find_route_for_uri(uri: &Uri, state: Arc<AppState>) -> Result<Arc<RouteConfig>, AppError> {
-    let config_guard = state.config.read().await;
-
-    config_guard
-        .find_route_for_path(uri.path())
-        .ok_or(AppError::RouteNotFound)
-}
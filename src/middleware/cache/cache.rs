@@ -1,59 +1,123 @@
-use std::{
-    sync::Arc,
-    time::{Duration, Instant},
-};
+use std::{net::IpAddr, sync::Arc};
 
 use axum::{body::Body, extract::State, middleware::Next, response::Response};
-use http::Request;
+use http::{
+    HeaderMap, HeaderValue, Method, Request, Uri,
+    header::{ACCEPT_ENCODING, CACHE_CONTROL, CONTENT_LENGTH},
+};
 use http_body_util::BodyExt;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{
     errors::AppError,
-    middleware::rate_limiter::rate_limit::parse_duration,
+    features::{cache::jittered_ttl, degradation::on_store_error, health_check::parse_body_limit},
+    middleware::{rate_limiter::rate_limit::parse_duration, route_match::request_context},
     state::{AppState, CachedResponse},
+    utils::ip_range::ip_in_ranges,
+    ws_proxy::is_websocket_upgrade,
 };
 
-pub async fn layer(State(state): State<Arc<AppState>>, req: Request<Body>, next: Next) -> Result<Response, AppError> {
-    let config_guard = state.config.read().await;
+/// Set on responses served from a stale entry because the backend failed.
+pub const X_CACHE_HEADER: &str = "x-cache";
 
-    let route = config_guard.find_route_for_path(req.uri().path());
+#[allow(clippy::too_many_lines)]
+pub async fn layer(State(state): State<Arc<AppState>>, req: Request<Body>, next: Next) -> Result<Response, AppError> {
+    let (route, client_ip) = request_context(&req).map_or((None, None), |ctx| (ctx.route.clone(), ctx.client_ip));
+    let route = route.filter(|r| r.middleware.cache);
 
-    let cache_config = match route.and_then(|r| r.cache.clone()) {
-        Some(c) => c,
-        None => return Ok(next.run(req).await),
+    let Some(cache_config) = route.and_then(|r| r.cache.clone()) else {
+        return Ok(next.run(req).await);
     };
 
-    if !req.method().is_safe() {
+    let is_head = req.method() == Method::HEAD;
+    // A WebSocket upgrade is a GET, but nothing about it can be cached
+    if (req.method() != Method::GET && !is_head) || is_websocket_upgrade(req.headers()) {
         return Ok(next.run(req).await);
     }
 
-    let cache_key = req.uri().to_string();
-    let ttl = parse_duration(&cache_config.ttl).unwrap_or(Duration::MAX); // item will be explicitly removed by cache algo
+    // Backends may answer pre-compressed; the entry is kept as sent, so it
+    // only replays to clients accepting the same codings
+    let cache_key = format!(
+        "{}{}",
+        cache_key(req.uri(), cache_config.key_query_params.as_deref()),
+        encoding_variant(req.headers())
+    );
+    // without a valid ttl the item lives until evicted
+    let ttl = parse_duration(&cache_config.ttl)
+        .ok()
+        .map(|ttl| jittered_ttl(ttl, cache_config.ttl_jitter));
+
+    // A trusted client may skip the cache read; the fresh response still repopulates it.
+    let bypass = client_ip.filter(|ip| should_bypass_cache(req.headers(), *ip, &cache_config.bypass_trusted_ips));
 
     //1. check if a valid response is already in the cache.
-    if let Some(cached_response) = state.cache.get(&cache_key).await {
-        if cached_response.inserted_at.elapsed() < ttl {
-            info!(key = %cache_key, "Cache HIT");
-            let mut builder = Response::builder().status(cached_response.status);
-            if let Some(headers) = builder.headers_mut() {
-                *headers = cached_response.headers.clone();
+    let cached = match bypass {
+        Some(_) => None,
+        None => match state.cache.get(&cache_key).await {
+            Ok(cached) => cached,
+            Err(e) => {
+                // Failing open, an unreachable store counts as a miss
+                let policy = state.config.read().await.degradation.cache;
+                on_store_error("cache", policy, &e)?;
+                None
             }
-            return Ok(builder
-                .body(Body::from(cached_response.body.clone()))
-                .unwrap_or_else(|_| Response::new(Body::empty())));
-        } else {
-            info!(key = %cache_key, "Cache STALE (expired)");
-            state.cache.invalidate(&cache_key).await;
-        }
+        },
+    };
+    if let Some(client_ip) = bypass {
+        info!(key = %cache_key, client_ip = %client_ip, "Cache BYPASS requested by trusted client");
+    } else if let Some(cached_response) = cached {
+        info!(key = %cache_key, head = is_head, "Cache HIT");
+        state.stats.record_cache_lookup(true);
+        return Ok(from_cached(&cached_response, is_head));
     }
 
     info!(key = %cache_key, "Cache MISS");
+    if bypass.is_none() {
+        state.stats.record_cache_lookup(false);
+    }
 
     // 2. If not in cache, call the next middleware (and eventually the proxy handler).
     let response = next.run(req).await;
 
-    if response.status().is_success() {
+    // 3. A failing backend is papered over with the expired copy, if still kept
+    let stale_window = cache_config
+        .stale_if_error
+        .as_deref()
+        .and_then(|w| parse_duration(w).ok());
+    if response.status().is_server_error()
+        && stale_window.is_some()
+        && let Ok(Some(stale)) = state.cache.get(&stale_key(&cache_key)).await
+    {
+        warn!(key = %cache_key, status = %response.status(), "Backend failed, serving stale cached response");
+        let mut stale_response = from_cached(&stale, is_head);
+        stale_response
+            .headers_mut()
+            .insert(X_CACHE_HEADER, HeaderValue::from_static("STALE-ERROR"));
+        return Ok(stale_response);
+    }
+
+    // A HEAD response has no body, so it can't stand in for the GET entry
+    if response.status().is_success() && !is_head {
+        let max_entry_size = cache_config.max_entry_size.as_deref().map(parse_body_limit);
+        let min_entry_size = cache_config.min_entry_size.as_deref().map(parse_body_limit);
+        let declared_len = response
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<usize>().ok());
+        if let (Some(max), Some(len)) = (max_entry_size, declared_len)
+            && len > max
+        {
+            info!(key = %cache_key, size = len, max_entry_size = max, "Response too large to cache");
+            return Ok(response);
+        }
+        if let (Some(min), Some(len)) = (min_entry_size, declared_len)
+            && len < min
+        {
+            info!(key = %cache_key, size = len, min_entry_size = min, "Response too small to cache");
+            return Ok(response);
+        }
+
         let (parts, body) = response.into_parts();
         let bytes = body
             .collect()
@@ -61,17 +125,136 @@ pub async fn layer(State(state): State<Arc<AppState>>, req: Request<Body>, next:
             .map_err(|_| AppError::InternalServerError)?
             .to_bytes();
 
+        if let Some(max) = max_entry_size
+            && bytes.len() > max
+        {
+            info!(key = %cache_key, size = bytes.len(), max_entry_size = max, "Response too large to cache");
+            return Ok(Response::from_parts(parts, Body::from(bytes)));
+        }
+        if let Some(min) = min_entry_size
+            && bytes.len() < min
+        {
+            info!(key = %cache_key, size = bytes.len(), min_entry_size = min, "Response too small to cache");
+            return Ok(Response::from_parts(parts, Body::from(bytes)));
+        }
+
         let cached_response = Arc::new(CachedResponse {
             status: parts.status,
             headers: parts.headers.clone(),
             body: bytes.clone(),
-            inserted_at: Instant::now(),
         });
 
-        state.cache.insert(cache_key, cached_response).await;
+        if let Some(window) = stale_window {
+            // Outlives the fresh entry by the window, for step 3
+            let stale_ttl = ttl.map(|ttl| ttl + window);
+            state
+                .cache
+                .insert(stale_key(&cache_key), cached_response.clone(), stale_ttl)
+                .await;
+        }
+        state.cache.insert(cache_key, cached_response, ttl).await;
 
         return Ok(Response::from_parts(parts, Body::from(bytes)));
     }
 
     Ok(response)
 }
+
+/// Builds the response for a cache entry. HEAD gets the GET's headers, with
+/// the length of the body it would have had.
+fn from_cached(cached: &CachedResponse, is_head: bool) -> Response {
+    let mut builder = Response::builder().status(cached.status);
+    if let Some(headers) = builder.headers_mut() {
+        *headers = cached.headers.clone();
+        if is_head {
+            headers.insert(CONTENT_LENGTH, HeaderValue::from(cached.body.len()));
+        }
+    }
+    let body = if is_head {
+        Body::empty()
+    } else {
+        Body::from(cached.body.clone())
+    };
+    builder.body(body).unwrap_or_else(|_| Response::new(Body::empty()))
+}
+
+/// Where the copy kept for `stale_if_error` lives, next to the fresh entry.
+fn stale_key(cache_key: &str) -> String {
+    format!("stale-if-error:{}", cache_key)
+}
+
+/// Key for a cacheable request. GET and HEAD share it, so HEAD can be
+/// answered from the GET entry. With `key_query_params`, only those
+/// parameters are kept, sorted so their order in the request doesn't matter.
+pub fn cache_key(uri: &Uri, key_query_params: Option<&[String]>) -> String {
+    let key = uri.to_string();
+    let Some(allowed) = key_query_params else {
+        return key;
+    };
+    let (base, query) = key.split_once('?').unwrap_or((key.as_str(), ""));
+    let mut kept: Vec<&str> = query
+        .split('&')
+        .filter(|pair| {
+            let name = pair.split_once('=').map_or(*pair, |(name, _)| name);
+            allowed.iter().any(|p| p == name)
+        })
+        .collect();
+    if kept.is_empty() {
+        return base.to_string();
+    }
+    kept.sort_unstable();
+    format!("{}?{}", base, kept.join("&"))
+}
+
+/// Content codings a backend might compress with, in key order.
+const CONTENT_CODINGS: [&str; 4] = ["br", "deflate", "gzip", "zstd"];
+
+/// Cache key suffix for the request's `Accept-Encoding`, the header backends
+/// name in `Vary`: the known codings it accepts, e.g. `#ae=br,gzip`. Empty
+/// when it accepts none of them, so identity-only clients share the plain key.
+pub fn encoding_variant(headers: &HeaderMap) -> String {
+    let mut accepted = [false; CONTENT_CODINGS.len()];
+    for entry in headers
+        .get_all(ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+    {
+        let mut parts = entry.split(';');
+        let coding = parts.next().unwrap_or("").trim();
+        let refused = parts
+            .filter_map(|p| p.trim().strip_prefix("q="))
+            .any(|q| q.trim().parse::<f32>().is_ok_and(|q| q == 0.0));
+        if refused {
+            continue;
+        }
+        for (i, known) in CONTENT_CODINGS.iter().enumerate() {
+            if coding == "*" || coding.eq_ignore_ascii_case(known) {
+                accepted[i] = true;
+            }
+        }
+    }
+    let codings: Vec<&str> = CONTENT_CODINGS
+        .iter()
+        .zip(accepted)
+        .filter_map(|(coding, accepted)| accepted.then_some(*coding))
+        .collect();
+    if codings.is_empty() {
+        String::new()
+    } else {
+        format!("#ae={}", codings.join(","))
+    }
+}
+
+/// Honor `Cache-Control: no-cache` only from clients inside the trusted ranges,
+/// so untrusted callers cannot bust the cache at will.
+pub fn should_bypass_cache(headers: &HeaderMap, client_ip: IpAddr, trusted_ips: &[String]) -> bool {
+    let no_cache = headers
+        .get_all(CACHE_CONTROL)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .any(|directive| directive.trim().eq_ignore_ascii_case("no-cache"));
+
+    no_cache && ip_in_ranges(client_ip, trusted_ips)
+}
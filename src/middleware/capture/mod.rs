@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use http::header::CONTENT_LENGTH;
+
+use crate::{
+    errors::AppError, features::metrics::UNMATCHED_LABEL, middleware::route_match::matched_route,
+    proxy::request_body_error, state::AppState,
+};
+
+/// Records sampled requests to the capture file before passing them on.
+/// Bodies are only buffered when `content-length` is within `max_body_bytes`.
+pub async fn layer(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Result<Response, AppError> {
+    let capture = match &state.request_capture {
+        Some(c) if c.should_sample() => c,
+        _ => return Ok(next.run(req).await),
+    };
+
+    let uri = req
+        .uri()
+        .path_and_query()
+        .map_or("/", http::uri::PathAndQuery::as_str)
+        .to_string();
+    let content_length = req
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+
+    let req = match content_length {
+        Some(len) if len <= capture.max_body_bytes() => {
+            let route = matched_route(&req).map_or_else(|| UNMATCHED_LABEL.to_string(), |r| r.name.clone());
+            let (parts, body) = req.into_parts();
+            let bytes = axum::body::to_bytes(body, len)
+                .await
+                .map_err(|e| request_body_error(&route, &e))?;
+            capture.record(&parts.method, &uri, &parts.headers, Some(&bytes));
+            Request::from_parts(parts, Body::from(bytes))
+        }
+        None if req.method() == http::Method::GET || req.method() == http::Method::HEAD => {
+            capture.record(req.method(), &uri, req.headers(), Some(&bytes::Bytes::new()));
+            req
+        }
+        _ => {
+            capture.record(req.method(), &uri, req.headers(), None);
+            req
+        }
+    };
+
+    Ok(next.run(req).await)
+}
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use http::{Method, header::AUTHORIZATION};
+use http_body_util::BodyExt;
+use tracing::debug;
+
+use crate::{
+    errors::AppError,
+    features::{auth::auth::Claims, coalesce::Flight},
+    middleware::{cache::cache::encoding_variant, route_match::matched_route},
+    state::{AppState, CachedResponse},
+    ws_proxy::is_websocket_upgrade,
+};
+
+/// Collapses identical in-flight GET/HEAD requests on routes with
+/// `coalesce: true` into one upstream call.
+pub async fn layer(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Result<Response, AppError> {
+    let route = match matched_route(&req) {
+        Some(r)
+            if r.coalesce
+                && (req.method() == Method::GET || req.method() == Method::HEAD)
+                && !is_websocket_upgrade(req.headers()) =>
+        {
+            r
+        }
+        _ => return Ok(next.run(req).await),
+    };
+
+    // Callers with different identities or accepted encodings may get
+    // different responses. Without a gateway identity, the backend may still
+    // tell callers apart by the Authorization header it's forwarded.
+    let identity = match req.extensions().get::<Claims>() {
+        Some(claims) => format!("identity:{:?}:{:?}", claims.sub, claims.roles),
+        None => format!(
+            "authorization:{}",
+            req.headers()
+                .get(AUTHORIZATION)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("")
+        ),
+    };
+    let key = format!(
+        "{} {}{} {}",
+        req.method(),
+        req.uri(),
+        encoding_variant(req.headers()),
+        identity
+    );
+
+    match state.request_coalescer.join(&route.name, key) {
+        Flight::Follower(mut leader) => match leader.recv().await {
+            Ok(shared) => {
+                debug!(route = %route.name, "Serving coalesced response");
+                let mut builder = Response::builder().status(shared.status);
+                if let Some(headers) = builder.headers_mut() {
+                    *headers = shared.headers.clone();
+                }
+                Ok(builder
+                    .body(Body::from(shared.body.clone()))
+                    .unwrap_or_else(|_| Response::new(Body::empty())))
+            }
+            // The leader was cancelled; go upstream ourselves
+            Err(_) => Ok(next.run(req).await),
+        },
+        Flight::Leader(leader) => {
+            let response = next.run(req).await;
+            let (parts, body) = response.into_parts();
+            let bytes = body
+                .collect()
+                .await
+                .map_err(|_| AppError::InternalServerError)?
+                .to_bytes();
+            leader.complete(Arc::new(CachedResponse {
+                status: parts.status,
+                headers: parts.headers.clone(),
+                body: bytes.clone(),
+            }));
+            Ok(Response::from_parts(parts, Body::from(bytes)))
+        }
+    }
+}
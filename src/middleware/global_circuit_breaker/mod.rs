@@ -0,0 +1,31 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use tracing::warn;
+
+use crate::{errors::AppError, proxy::upstream_status, state::AppState};
+
+/// Paths that keep answering while the gateway sheds load.
+const EXEMPT_PATHS: &[&str] = &["/health", "/metrics"];
+
+/// Gateway-wide load shedding. Rejects every route with 503 while the global
+/// breaker is open; each response's status feeds the aggregate error rate.
+pub async fn layer(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Result<Response, AppError> {
+    let breaker = match &state.global_circuit_breaker {
+        Some(b) if !EXEMPT_PATHS.contains(&req.uri().path()) => b,
+        _ => return Ok(next.run(req).await),
+    };
+
+    if !breaker.allow_request().await {
+        warn!(path = %req.uri().path(), "Global circuit breaker is OPEN, shedding request");
+        return Err(AppError::ServiceUnavailable);
+    }
+
+    let response = next.run(req).await;
+    breaker.record(upstream_status(&response).is_server_error()).await;
+    Ok(response)
+}
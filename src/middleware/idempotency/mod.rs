@@ -0,0 +1,109 @@
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use http::{HeaderValue, Method};
+use http_body_util::BodyExt;
+use tracing::{debug, info};
+
+use crate::{
+    errors::AppError,
+    features::{auth::auth::Claims, coalesce::Flight, degradation::on_store_error},
+    middleware::{rate_limiter::rate_limit::parse_duration, route_match::matched_route},
+    state::{AppState, CachedResponse},
+};
+
+/// Set on responses replayed for a repeated idempotency key.
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// On routes with `idempotency`, answers a request whose key was already seen
+/// within the window with the first request's response, from the response
+/// cache. Duplicates arriving while the first is still in flight wait for it.
+/// Keys are scoped to the route, the caller's `sub` and the method and path,
+/// so one client's key can't replay another's response.
+pub async fn layer(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Result<Response, AppError> {
+    let Some(route) = matched_route(&req) else {
+        return Ok(next.run(req).await);
+    };
+    let Some(config) = route.idempotency.as_ref() else {
+        return Ok(next.run(req).await);
+    };
+    // Safe methods can be repeated as they are
+    if matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS) {
+        return Ok(next.run(req).await);
+    }
+    let Some(idempotency_key) = req.headers().get(&config.header).and_then(|v| v.to_str().ok()) else {
+        return Ok(next.run(req).await);
+    };
+
+    let key = format!(
+        "idempotency:{}:{}:{} {}:{}",
+        route.name,
+        req.extensions()
+            .get::<Claims>()
+            .map_or("", |claims| claims.sub.as_str()),
+        req.method(),
+        req.uri().path(),
+        idempotency_key
+    );
+    let window = parse_duration(&config.window).ok();
+
+    match state.cache.get(&key).await {
+        Ok(Some(first)) => {
+            info!(route = %route.name, key = %idempotency_key, "Replaying response for repeated idempotency key");
+            return Ok(replay(&first));
+        }
+        Ok(None) => {}
+        Err(e) => {
+            // Failing open, the request goes to the backend as if the key were new
+            let policy = state.config.read().await.degradation.cache;
+            on_store_error("cache", policy, &e)?;
+        }
+    }
+
+    let leader = match state.request_coalescer.join(&route.name, key.clone()) {
+        Flight::Follower(mut leader) => match leader.recv().await {
+            Ok(first) => {
+                debug!(route = %route.name, key = %idempotency_key, "Duplicate waited for the first request");
+                return Ok(replay(&first));
+            }
+            // The first request was cancelled; this one takes its place
+            Err(_) => return Ok(next.run(req).await),
+        },
+        Flight::Leader(leader) => leader,
+    };
+
+    let response = next.run(req).await;
+    let (parts, body) = response.into_parts();
+    let bytes = body
+        .collect()
+        .await
+        .map_err(|_| AppError::InternalServerError)?
+        .to_bytes();
+    let first = Arc::new(CachedResponse {
+        status: parts.status,
+        headers: parts.headers.clone(),
+        body: bytes.clone(),
+    });
+    // A failed attempt may be retried with the same key
+    if !parts.status.is_server_error() {
+        state.cache.insert(key, first.clone(), window).await;
+    }
+    leader.complete(first);
+    Ok(Response::from_parts(parts, Body::from(bytes)))
+}
+
+fn replay(first: &CachedResponse) -> Response {
+    let mut builder = Response::builder().status(first.status);
+    if let Some(headers) = builder.headers_mut() {
+        *headers = first.headers.clone();
+        headers.insert(IDEMPOTENT_REPLAYED_HEADER, HeaderValue::from_static("true"));
+    }
+    builder
+        .body(Body::from(first.body.clone()))
+        .unwrap_or_else(|_| Response::new(Body::empty()))
+}
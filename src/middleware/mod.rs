@@ -1,7 +1,16 @@
 pub mod access_log;
 pub mod auth;
 pub mod cache;
+pub mod capture;
 pub mod circuit_breaker;
+pub mod coalesce;
+pub mod global_circuit_breaker;
+pub mod idempotency;
+pub mod qos;
 pub mod rate_limiter;
 pub mod request_id;
+pub mod route_match;
+pub mod route_metrics;
+pub mod stats;
 pub mod tracing_ctx;
+pub mod uri_limit;
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use axum_prometheus::metrics::counter;
+use tracing::warn;
+
+use crate::{errors::AppError, features::auth::auth::Claims, state::AppState};
+
+/// Holds each proxied request to a `server.qos` slot. Runs after auth, so the
+/// caller's roles pick its class, and after the cache, so hits skip the queue.
+pub async fn layer(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Result<Response, AppError> {
+    let Some(gate) = &state.qos_gate else {
+        return Ok(next.run(req).await);
+    };
+
+    let roles = req
+        .extensions()
+        .get::<Claims>()
+        .map(|claims| claims.roles.as_slice())
+        .unwrap_or_default();
+    let (class, priority) = gate.classify(roles, req.headers());
+
+    let _permit = match gate.admit(priority).await {
+        Ok(permit) => permit,
+        Err(rejection) => {
+            warn!(class = %class, reason = rejection.as_str(), path = %req.uri().path(), "QoS queue rejected request");
+            counter!("gateway_qos_rejected_total", "class" => class.to_string(), "reason" => rejection.as_str())
+                .increment(1);
+            return Err(AppError::ServiceUnavailable);
+        }
+    };
+    Ok(next.run(req).await)
+}
@@ -3,52 +3,138 @@ use std::{sync::Arc, time::Duration};
 use axum::{
     extract::{Request, State},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
 };
-use axum_client_ip::ClientIp;
+use http::{HeaderValue, header::RETRY_AFTER};
 use tracing::{info, warn};
 
-use crate::{errors::AppError, state::AppState};
+use crate::{
+    errors::AppError,
+    features::{auth::auth::Claims, degradation::on_store_error, rate_limiter::state::RateLimitDecision},
+    middleware::route_match::request_context,
+    state::AppState,
+};
 
-pub async fn layer(
-    State(state): State<Arc<AppState>>,
-    ClientIp(client_ip): ClientIp,
-    req: Request,
-    next: Next,
-) -> Result<Response, AppError> {
+pub async fn layer(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Result<Response, AppError> {
+    let (route, client_ip) = request_context(&req).map_or((None, None), |ctx| (ctx.route.clone(), ctx.client_ip));
     info!(client_ip = ?client_ip, "Client connected");
-    let config_guard = state.config.read().await;
-    let route = config_guard.find_route_for_path(req.uri().path());
 
     if let Some(route_config) = route
+        && route_config.middleware.rate_limit
         && let Some(rate_limit_config) = route_config.rate_limit.as_ref()
     {
-        let period = parse_duration(&rate_limit_config.period).unwrap_or_else(|_| Duration::from_secs(60));
-        let capacity = rate_limit_config.requests;
-        let refill_rate = rate_limit_config.requests as f64 / period.as_secs_f64();
-
-        // Use x-service-name header if present (BTB), otherwise client IP (BTF)
-        let key = req
-            .headers()
-            .get("x-service-name")
-            .and_then(|v| v.to_str().ok())
-            .map(|s| format!("svc:{}", s))
-            .unwrap_or_else(|| client_ip.to_string());
-        let allowed = state
+        // Auth runs first and leaves the caller's claims; without them the route default applies
+        let user_roles = req
+            .extensions()
+            .get::<Claims>()
+            .map(|claims| claims.roles.as_slice())
+            .unwrap_or_default();
+        let (role, requests, period) = rate_limit_config.limit_for_roles(user_roles);
+
+        let refill_period = parse_duration(period).unwrap_or_else(|_| Duration::from_secs(60));
+        let capacity = requests;
+        let refill_rate = requests as f64 / refill_period.as_secs_f64();
+
+        // A configured key (tenant header or claim) wins and falls back to the
+        // client IP only, so a caller can't pick its own bucket with
+        // x-service-name. Otherwise use x-service-name if present (BTB), then
+        // client IP (BTF)
+        let key = match rate_limit_config.key.as_deref().and_then(KeySource::parse) {
+            Some(source) => source.key_for(&req),
+            None => req
+                .headers()
+                .get("x-service-name")
+                .and_then(|v| v.to_str().ok())
+                .map(|s| format!("svc:{}", s)),
+        }
+        .or_else(|| client_ip.map(|ip| ip.to_string()))
+        .ok_or(AppError::InternalServerError)?;
+        // Separate buckets per role so a caller's limit changes cleanly with their role
+        let key = match role {
+            Some(role) => format!("{}:role:{}", key, role),
+            None => key,
+        };
+        // The limit in force is passed on every check, so after a reload the
+        // bucket is capped at the new capacity and refills at the new rate
+        // without starting over full
+        let decision = match state
             .rate_limit_store
             .check_and_update(&key, capacity, refill_rate)
-            .await;
+            .await
+        {
+            Ok(decision) => decision,
+            Err(e) => {
+                let policy = state.config.read().await.degradation.rate_limit;
+                on_store_error("rate_limit", policy, &e)?;
+                RateLimitDecision::Allowed
+            }
+        };
 
-        if !allowed {
+        if let RateLimitDecision::Limited { retry_after } = decision {
             warn!(ip=%key, path=%req.uri().path(),"Request rate-limited");
-            return Err(AppError::RateLimited);
+            let mut response = AppError::RateLimited.into_response();
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, retry_after_header(retry_after));
+            return Ok(response);
         }
     }
     Ok(next.run(req).await)
 }
 
+/// Whole seconds, rounded up so a client retrying on time finds a token.
+fn retry_after_header(retry_after: Duration) -> HeaderValue {
+    let secs = retry_after
+        .as_secs()
+        .saturating_add(u64::from(retry_after.subsec_nanos() > 0));
+    HeaderValue::from(secs.max(1))
+}
+
+/// Where `rate_limit.key` takes a request's bucket key from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySource<'a> {
+    Header(&'a str),
+    Claim(&'a str),
+}
+
+impl<'a> KeySource<'a> {
+    /// Parses `"header:<name>"` or `"claim:<name>"`.
+    pub fn parse(key: &'a str) -> Option<Self> {
+        let (kind, name) = key.split_once(':')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        match kind.trim() {
+            "header" => Some(KeySource::Header(name)),
+            "claim" => Some(KeySource::Claim(name)),
+            _ => None,
+        }
+    }
+
+    /// The bucket key for `req`, or `None` when it doesn't carry the header
+    /// or claim.
+    fn key_for(&self, req: &Request) -> Option<String> {
+        let (kind, name, value) = match *self {
+            KeySource::Header(name) => (
+                "header",
+                name,
+                req.headers().get(name)?.to_str().ok()?.trim().to_string(),
+            ),
+            KeySource::Claim(name) => ("claim", name, req.extensions().get::<Claims>()?.claim(name)?),
+        };
+        (!value.is_empty()).then(|| format!("{}:{}:{}", kind, name, value))
+    }
+}
+
 pub fn parse_duration(s: &str) -> Result<Duration, &'static str> {
     let s = s.trim();
+    if let Some(ms) = s.strip_suffix("ms") {
+        return ms
+            .parse()
+            .map(Duration::from_millis)
+            .map_err(|_| "Invalid number in duration");
+    }
     let unit = s.chars().last().ok_or("Empty durtion")?;
     let value: u64 = s[..s.len() - 1].parse().map_err(|_| "Invalid number in duration")?;
 
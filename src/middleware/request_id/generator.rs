@@ -0,0 +1,123 @@
+use std::{
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use uuid::Uuid;
+
+use crate::config::{RequestIdFormat, RequestIdPolicy};
+
+const CROCKFORD_BASE32: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const NANOID_ALPHABET: &[u8; 64] = b"_-0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const NANOID_LEN: usize = 21;
+const ULID_RANDOM_MASK: u128 = (1 << 80) - 1;
+
+/// Generates request ids in the configured format. Every format only uses
+/// `[0-9A-Za-z_-]`, so ids are always valid header values.
+pub struct RequestIdGenerator {
+    format: RequestIdFormat,
+    policy: RequestIdPolicy,
+    /// Last (timestamp ms, random part) handed out, for monotonic ULIDs.
+    last_ulid: Mutex<(u64, u128)>,
+}
+
+impl RequestIdGenerator {
+    pub fn new(format: RequestIdFormat) -> Self {
+        Self {
+            format,
+            policy: RequestIdPolicy::default(),
+            last_ulid: Mutex::new((0, 0)),
+        }
+    }
+
+    #[must_use]
+    pub fn with_policy(mut self, policy: RequestIdPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub fn format(&self) -> RequestIdFormat {
+        self.format
+    }
+
+    pub fn policy(&self) -> RequestIdPolicy {
+        self.policy
+    }
+
+    /// Whether the client-supplied `id` is used instead of a generated one.
+    pub fn accepts(&self, id: &str) -> bool {
+        match self.policy {
+            RequestIdPolicy::AlwaysGenerate => false,
+            RequestIdPolicy::TrustIfValid => is_safe_request_id(id),
+            RequestIdPolicy::Trust => true,
+        }
+    }
+
+    pub fn generate(&self) -> String {
+        match self.format {
+            RequestIdFormat::UuidV4 => Uuid::new_v4().to_string(),
+            RequestIdFormat::Ulid => self.ulid(),
+            RequestIdFormat::Nanoid => nanoid(),
+        }
+    }
+
+    /// ULIDs from the same millisecond increment the random part, so ids sort
+    /// in generation order even under load.
+    fn ulid(&self) -> String {
+        let now_ms = u64::try_from(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis(),
+        )
+        .unwrap_or(u64::MAX);
+
+        let (timestamp, random) = {
+            let mut last = match self.last_ulid.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            let (last_ms, last_random) = *last;
+            *last = if now_ms <= last_ms && last_random < ULID_RANDOM_MASK {
+                (last_ms, last_random + 1)
+            } else {
+                (now_ms.max(last_ms), Uuid::new_v4().as_u128() & ULID_RANDOM_MASK)
+            };
+            *last
+        };
+
+        encode_ulid((u128::from(timestamp & 0xFFFF_FFFF_FFFF) << 80) | random)
+    }
+}
+
+/// 26 Crockford base32 characters, most significant first.
+fn encode_ulid(value: u128) -> String {
+    (0..26)
+        .rev()
+        .map(|i| CROCKFORD_BASE32[((value >> (i * 5)) & 0x1F) as usize] as char)
+        .collect()
+}
+
+fn nanoid() -> String {
+    // Two v4 UUIDs give 244 random bits; 21 characters need 126
+    let random = Uuid::new_v4().as_u128() ^ Uuid::new_v4().as_u128().rotate_left(64);
+    (0..NANOID_LEN)
+        .map(|i| NANOID_ALPHABET[((random >> (i * 6)) & 0x3F) as usize] as char)
+        .collect()
+}
+
+/// Longest client request id `trust_if_valid` keeps.
+pub const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// True if `id` is short and made of characters safe to log and forward.
+pub fn is_safe_request_id(id: &str) -> bool {
+    (1..=MAX_REQUEST_ID_LEN).contains(&id.len())
+        && id
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b':' | b'-'))
+}
+
+/// True if `id` is a well-formed ULID (26 Crockford base32 chars, no overflow).
+pub fn is_valid_ulid(id: &str) -> bool {
+    id.len() == 26 && id.bytes().all(|b| CROCKFORD_BASE32.contains(&b)) && id.as_bytes()[0] <= b'7'
+}
@@ -1,2 +1,3 @@
+pub mod generator;
 #[allow(clippy::module_inception)]
 pub mod request_id;
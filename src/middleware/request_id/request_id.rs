@@ -1,32 +1,39 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Instant};
 
-use crate::app::REQUEST_ID_HEADER;
+use crate::{app::REQUEST_ID_HEADER, state::AppState};
 use axum::{
     body::Body,
+    extract::State,
     http::{HeaderValue, Request},
     middleware::Next,
     response::Response,
 };
-use uuid::Uuid;
 
-pub async fn layer(mut req: Request<Body>, next: Next) -> Response {
+/// When the gateway received the request; inserted before any other
+/// middleware runs so deadlines account for all gateway processing.
+#[derive(Debug, Clone, Copy)]
+pub struct RequestStart(pub Instant);
+
+pub async fn layer(State(state): State<Arc<AppState>>, mut req: Request<Body>, next: Next) -> Response {
+    req.extensions_mut().insert(RequestStart(Instant::now()));
+
+    let generator = &state.request_id_generator;
     let id = req
         .headers()
         .get(REQUEST_ID_HEADER)
         .and_then(|value| value.to_str().ok())
-        .map(|s| s.to_string());
+        .filter(|id| generator.accepts(id))
+        .map(str::to_string);
 
-    let request_id = match id {
-        Some(id) => id,
-        None => {
-            let new_id = Uuid::new_v4().to_string();
-            req.headers_mut().insert(
-                REQUEST_ID_HEADER,
-                HeaderValue::from_str(&new_id).unwrap_or_else(|_| HeaderValue::from_static("unknown")),
-            );
-            new_id
-        }
-    };
+    let request_id = id.unwrap_or_else(|| {
+        // Also replaces a rejected client id, so nothing downstream sees it
+        let new_id = generator.generate();
+        req.headers_mut().insert(
+            REQUEST_ID_HEADER,
+            HeaderValue::from_str(&new_id).unwrap_or_else(|_| HeaderValue::from_static("unknown")),
+        );
+        new_id
+    });
 
     // Store the final request ID in the request extensions so it can be
     // accessed by other handlers, like our proxy handler.
@@ -0,0 +1,64 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::{config::RouteConfig, state::AppState};
+
+/// What the gateway knows about a request before its own layers run. Built
+/// once per request, so every layer and the proxy see the same route even if
+/// the config is reloaded mid-request.
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    pub route: Option<Arc<RouteConfig>>,
+    /// Parameters captured by the route path, e.g. `{id}`.
+    pub params: Vec<(String, String)>,
+    /// `None` when the server was not started with connect info.
+    pub client_ip: Option<IpAddr>,
+    pub request_id: Option<Arc<String>>,
+}
+
+/// Resolves the route once per request and stores a `RequestContext` in the
+/// request extensions, so the layers below neither repeat the lookup nor hold
+/// the config lock while the request is in flight. A context that is already
+/// present is kept.
+pub async fn layer(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Response {
+    if req.extensions().get::<RequestContext>().is_some() {
+        return next.run(req).await;
+    }
+
+    let (route, params) = {
+        let config_guard = state.config.read().await;
+        config_guard
+            .match_route_with_params(req.uri().path())
+            .map_or((None, Vec::new()), |(route, params)| (Some(route), params))
+    };
+    let (mut parts, body) = req.into_parts();
+    let client_ip = parts
+        .extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    let request_id = parts.extensions.get::<Arc<String>>().cloned();
+    parts.extensions.insert(RequestContext {
+        route,
+        params,
+        client_ip,
+        request_id,
+    });
+
+    next.run(Request::from_parts(parts, body)).await
+}
+
+pub fn request_context<B>(req: &http::Request<B>) -> Option<&RequestContext> {
+    req.extensions().get::<RequestContext>()
+}
+
+pub fn matched_route<B>(req: &http::Request<B>) -> Option<Arc<RouteConfig>> {
+    request_context(req).and_then(|ctx| ctx.route.clone())
+}
@@ -0,0 +1,30 @@
+use std::{sync::Arc, time::Instant};
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use axum_prometheus::metrics::{counter, histogram};
+
+use crate::{middleware::route_match::matched_route, state::AppState};
+
+/// Per-route request metrics labeled by route name.
+/// Unmatched paths collapse into a single label, see `RouteLabels`.
+/// Recorded through the `metrics` facade, so they reach any configured exporter.
+pub async fn layer(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Response {
+    if !state.config.read().await.observability.metrics.enabled {
+        return next.run(req).await;
+    }
+    let route_name = matched_route(&req).map(|r| r.name.clone());
+    let route = state.route_labels.label_for(route_name.as_deref());
+    let start = Instant::now();
+
+    let response = next.run(req).await;
+
+    let status = response.status().as_u16().to_string();
+    counter!("gateway_route_requests_total", "route" => route.clone(), "status" => status).increment(1);
+    histogram!("gateway_route_request_duration_seconds", "route" => route).record(start.elapsed().as_secs_f64());
+
+    response
+}
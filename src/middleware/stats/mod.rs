@@ -0,0 +1,17 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::state::AppState;
+
+/// Counts every request and how many run at once, for the shutdown summary.
+pub async fn layer(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Response {
+    state.stats.request_started();
+    let response = next.run(req).await;
+    state.stats.request_finished(response.status());
+    response
+}
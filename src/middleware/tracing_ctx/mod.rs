@@ -13,16 +13,13 @@ pub async fn layer(mut req: Request, next: Next) -> Response {
         .headers()
         .get(TRACEPARENT)
         .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string());
-
-    let traceparent = match traceparent {
-        Some(tp) => tp,
-        None => {
-            let trace_id = Uuid::new_v4().as_simple().to_string();
-            let span_id = &Uuid::new_v4().as_simple().to_string()[..16];
-            format!("00-{}-{}-01", trace_id, span_id)
-        }
-    };
+        .map(str::to_string);
+
+    let traceparent = traceparent.unwrap_or_else(|| {
+        let trace_id = Uuid::new_v4().as_simple().to_string();
+        let span_id = &Uuid::new_v4().as_simple().to_string()[..16];
+        format!("00-{}-{}-01", trace_id, span_id)
+    });
 
     if let Ok(v) = HeaderValue::from_str(&traceparent) {
         req.headers_mut().insert(TRACEPARENT, v);
@@ -0,0 +1,26 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use axum_prometheus::metrics::counter;
+use tracing::warn;
+
+use crate::{errors::AppError, state::AppState};
+
+/// Rejects request targets (path and query) longer than
+/// `security.max_uri_length` with 414, before the route lookup sees them.
+pub async fn layer(State(state): State<Arc<AppState>>, req: Request, next: Next) -> Result<Response, AppError> {
+    let max = state.config.read().await.security.max_uri_length;
+    let len = req.uri().path_and_query().map_or(0, |pq| pq.as_str().len());
+    if let Some(max) = max
+        && len > max
+    {
+        warn!(uri_length = len, max_uri_length = max, "Request URI too long");
+        counter!("gateway_uri_too_long_total").increment(1);
+        return Err(AppError::UriTooLong);
+    }
+    Ok(next.run(req).await)
+}
@@ -18,7 +18,7 @@ impl HeaderInjectorPlugin {
 
 #[async_trait]
 impl Plugin for HeaderInjectorPlugin {
-    fn name(&self) -> &str {
+    fn name(&self) -> &'static str {
         "header-injector"
     }
     fn phase(&self) -> PluginPhase {
@@ -42,7 +42,7 @@ pub struct RequestLoggerPlugin;
 
 #[async_trait]
 impl Plugin for RequestLoggerPlugin {
-    fn name(&self) -> &str {
+    fn name(&self) -> &'static str {
         "request-logger"
     }
     fn phase(&self) -> PluginPhase {
@@ -32,6 +32,7 @@ impl PluginContext {
         }
     }
 
+    #[must_use]
     pub fn with_client_ip(mut self, ip: Option<String>) -> Self {
         self.client_ip = ip;
         self
@@ -1,50 +1,139 @@
 use axum::{
     Extension,
     body::Body,
-    extract::{Path, State},
+    extract::{
+        Path, RawQuery, State,
+        ws::{WebSocketUpgrade, rejection::WebSocketUpgradeRejection},
+    },
     http::HeaderMap,
-    response::Response,
+    response::{IntoResponse, Response},
 };
+use axum_prometheus::metrics::counter;
 use bytes::Bytes;
-use http::{HeaderValue, Method};
+use http::{HeaderValue, Method, StatusCode};
 use http_body_util::BodyExt;
-use std::sync::Arc;
+use std::{
+    fmt::Write,
+    net::IpAddr,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Instant,
+};
 use tracing::info;
 
-use crate::{app::REQUEST_ID_HEADER, errors::AppError, state::AppState};
+use crate::{
+    app::REQUEST_ID_HEADER,
+    config::{CircuitBreakerConfig, DestinationOverrideConfig, QueryParamsTransform, RouteConfig},
+    errors::AppError,
+    features::{
+        circuit_breaker::circuit_breaker::CircuitState, health_check::parse_body_limit, load_balancer::InFlightGuard,
+    },
+    middleware::{
+        rate_limiter::rate_limit::parse_duration, request_id::request_id::RequestStart, route_match::RequestContext,
+    },
+    state::AppState,
+    utils::ip_range::ip_in_ranges,
+    ws_proxy::{is_websocket_upgrade, upgrade_to_backend},
+};
+
+/// Remaining `request_deadline` budget in milliseconds, sent to backends.
+pub const REQUEST_DEADLINE_HEADER: &str = "x-request-deadline";
+/// Lets a backend deduplicate a repeated POST or PATCH; forwarded as is.
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+/// Client identity headers set when `forward_client_ip` is on.
+pub const X_FORWARDED_FOR: &str = "x-forwarded-for";
+pub const X_FORWARDED_PROTO: &str = "x-forwarded-proto";
+pub const X_REAL_IP: &str = "x-real-ip";
+/// Client headers a route with a `forward_headers` allowlist passes on
+/// without listing them.
+pub const DEFAULT_FORWARD_HEADERS: &[&str] = &[
+    "accept",
+    "accept-encoding",
+    "accept-language",
+    "cache-control",
+    "content-encoding",
+    "content-length",
+    "content-type",
+    "if-match",
+    "if-modified-since",
+    "if-none-match",
+    "user-agent",
+    IDEMPOTENCY_KEY_HEADER,
+];
+
+/// The backend's own status, attached to proxied responses. Differs from the
+/// response status when the route's `status_map` remapped it.
+#[derive(Debug, Clone, Copy)]
+pub struct UpstreamStatus(pub StatusCode);
+
+/// Status to judge backend health by: the upstream status when the response
+/// was proxied, otherwise the response's own status.
+pub fn upstream_status(response: &Response) -> StatusCode {
+    response
+        .extensions()
+        .get::<UpstreamStatus>()
+        .map_or(response.status(), |s| s.0)
+}
 
 #[axum::debug_handler]
+#[allow(clippy::too_many_arguments, clippy::too_many_lines)]
 pub async fn proxy_handler(
     State(state): State<Arc<AppState>>,
     Extension(request_id): Extension<Arc<String>>,
+    Extension(RequestStart(started_at)): Extension<RequestStart>,
+    Extension(context): Extension<RequestContext>,
     Path(path): Path<String>,
+    RawQuery(query): RawQuery,
     method: Method,
     mut headers: HeaderMap,
+    ws: Result<WebSocketUpgrade, WebSocketUpgradeRejection>,
     body: Body,
 ) -> Result<Response, AppError> {
     let request_path = format!("/{}", path);
     info!("Received request for path: {}", request_path);
 
     let config_guard = state.config.read().await;
-    let matched = config_guard.match_route_with_params(&request_path);
-
-    let (route, params) = match matched {
-        Some((route, params)) => (route, params),
-        None => return Err(AppError::RouteNotFound),
-    };
+    let RequestContext {
+        route,
+        params,
+        client_ip,
+        ..
+    } = context;
+    let route = route.ok_or(AppError::RouteNotFound)?;
+    state.route_traffic.record(&route.name);
 
-    let destination_path = request_path.strip_prefix(&route.path).unwrap_or(&request_path);
+    let destination_path = route.strip_path_prefix(&request_path).unwrap_or(&request_path);
     // For parameterized routes, use the full request path as remainder is empty
     let destination_path = if params.is_empty() { destination_path } else { "" };
 
-    let destinations = route.all_destinations();
-    let healthy = state.health_checker.filter_healthy(&destinations);
-    let idx = match state.load_balancer.next_index(healthy.len(), &route.load_balance) {
-        Some(idx) => idx,
-        None => {
-            tracing::warn!(route = %route.name, "No healthy backends available");
-            return Err(AppError::ServiceUnavailable);
-        }
+    if let Some(root) = &route.static_file {
+        return crate::static_file::serve_static_file(&state.static_cache, root, destination_path).await;
+    }
+
+    let pinned = match &route.destination_override {
+        Some(over) => pinned_destination(&route, over, &mut headers, client_ip)?,
+        None => None,
+    };
+
+    let destinations = state.drained.filter(state.blue_green.destinations(&route));
+    // A pinned destination is used as is, healthy or not: it's for debugging
+    let healthy = match pinned {
+        Some(destination) => vec![destination],
+        None => state.health_checker.filter_healthy(&destinations),
+    };
+    let Some(idx) = state
+        .load_balancer
+        .pick(&route.name, &healthy, &route.load_balance, &route.destination_weights)
+    else {
+        tracing::warn!(route = %route.name, "No healthy backends available");
+        return Err(AppError::ServiceUnavailable);
+    };
+
+    let destination_path = match &route.rewrite {
+        Some(rewrite) => rewrite.apply(destination_path),
+        None => destination_path.into(),
     };
 
     // Apply path rewrite if configured
@@ -52,23 +141,76 @@ pub async fn proxy_handler(
         .transform
         .as_ref()
         .and_then(|t| t.rewrite_path.as_ref())
-        .map(|rewrite| rewrite.replace("{path}", destination_path))
-        .unwrap_or_else(|| destination_path.to_string());
+        .map_or_else(
+            || destination_path.to_string(),
+            |rewrite| rewrite.replace("{path}", &destination_path),
+        );
 
-    let destination_url = {
-        let mut url = format!("{}{}", healthy[idx], final_path);
+    let query = match route.transform.as_ref().map(|t| &t.query_params) {
+        Some(qp) if !qp.is_empty() => apply_query_transform(query.as_deref(), qp),
+        _ => query.filter(|q| !q.is_empty()),
+    };
+    let destination_url_for = |destination: &str| {
+        let mut url = format!("{}{}", destination, final_path);
         for (key, value) in &params {
             url = url.replace(&format!("{{{}}}", key), value);
         }
+        if let Some(query) = &query {
+            url.push(if url.contains('?') { '&' } else { '?' });
+            url.push_str(query);
+        }
         url
     };
 
-    let route_timeout = route
-        .timeout
+    // Upgrades go to the picked destination as a WebSocket; proxying one as
+    // plain HTTP only produces confusing backend errors
+    if is_websocket_upgrade(&headers) {
+        return match ws {
+            Ok(ws) => upgrade_to_backend(ws, &destination_url_for(healthy[idx])).await,
+            Err(rejection) => {
+                tracing::warn!(route = %route.name, "WebSocket upgrade rejected: {}", rejection);
+                Ok(rejection.into_response())
+            }
+        };
+    }
+
+    // The load-balanced pick goes first; the other healthy destinations are failovers
+    let mut candidates: Vec<&str> = healthy.iter().cycle().skip(idx).take(healthy.len()).copied().collect();
+    // A request routed to the canary fails over to the stable destinations
+    let canary = route
+        .canary
         .as_ref()
-        .map(|t| crate::features::health_check::parse_duration(t));
+        .filter(|c| pinned.is_none() && state.canary_tracker.routes_to_canary(&route.name, c));
+    if let Some(canary) = canary {
+        candidates.insert(0, canary.destination.as_str());
+    }
+    let destination_breaker = route
+        .circuit_breaker
+        .as_ref()
+        .filter(|_| route.middleware.circuit_breaker && candidates.len() > 1);
 
-    info!(destination = %destination_url, strategy = ?route.load_balance, "Forwarding request to backend");
+    // Client headers first, so the route's allowlist never drops the ones
+    // the gateway adds below
+    if route.forward_headers.is_some() {
+        let mut forwarded = HeaderMap::new();
+        for (name, value) in headers.iter().filter(|(name, _)| route.request_header_forwarded(name)) {
+            forwarded.append(name.clone(), value.clone());
+        }
+        headers = forwarded;
+    }
+
+    if route
+        .forward_client_ip
+        .unwrap_or(config_guard.security.forward_client_ip)
+        && let Some(client_ip) = client_ip
+    {
+        let proto = if config_guard.server.tls.is_some() {
+            "https"
+        } else {
+            "http"
+        };
+        add_forwarded_headers(&mut headers, client_ip, proto);
+    }
 
     // Apply request header transformations
     if let Some(transform) = &route.transform {
@@ -91,16 +233,35 @@ pub async fn proxy_handler(
         HeaderValue::from_str(&request_id).unwrap_or_else(|_| HeaderValue::from_static("unknown")),
     );
 
-    let body_bytes: Bytes = body
-        .collect()
-        .await
-        .map_err(|e| {
-            tracing::error!("Failed to read request body: {}", e);
-            AppError::InternalServerError
-        })?
-        .to_bytes();
+    let body_limit_exceeded = Arc::new(AtomicBool::new(false));
+    let mut streamed_body = None;
+    let body_bytes: Bytes = if route.stream_request_body {
+        let limit = parse_body_limit(&config_guard.server.pool.body_limit);
+        let content_length = headers
+            .get(http::header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<usize>().ok());
+        if content_length.is_some_and(|len| len > limit) {
+            return Err(AppError::PayloadTooLarge);
+        }
+        streamed_body = Some(limited_body_stream(body, limit, body_limit_exceeded.clone()));
+        Bytes::new()
+    } else {
+        body.collect()
+            .await
+            .map_err(|e| request_body_error(&route.name, &e))?
+            .to_bytes()
+    };
 
-    let max_attempts = route.retry.as_ref().map(|r| r.count + 1).unwrap_or(1);
+    // A streamed body is consumed by the first attempt; POSTs without a key aren't repeated
+    let max_attempts = match &route.retry {
+        Some(retry)
+            if !route.stream_request_body && (retry.retry_non_idempotent || safe_to_retry(&method, &headers)) =>
+        {
+            retry.attempts()
+        }
+        _ => 1,
+    };
     let retry_on: Vec<u16> = route
         .retry
         .as_ref()
@@ -112,11 +273,11 @@ pub async fn proxy_handler(
             }
         })
         .unwrap_or_default();
-    let backoff = route
-        .retry
-        .as_ref()
-        .map(|r| crate::features::health_check::parse_duration(&r.backoff))
-        .unwrap_or(std::time::Duration::from_millis(100));
+    let backoff = route.retry.as_ref().map_or(std::time::Duration::from_millis(100), |r| {
+        crate::features::health_check::parse_duration(&r.backoff)
+    });
+
+    let deadline = route.request_deadline.as_deref().and_then(|d| parse_duration(d).ok());
 
     let client = if route.tls_skip_verify {
         &state.http_client_insecure
@@ -125,11 +286,56 @@ pub async fn proxy_handler(
     };
 
     let mut last_err = None;
+    let mut cursor = 0;
     for attempt in 0..max_attempts {
+        // Next destination whose circuit isn't open; give up once every one is
+        let Some((destination, circuit)) =
+            next_destination(&state, &route.name, &candidates, destination_breaker, &mut cursor).await
+        else {
+            tracing::warn!(route = %route.name, "All destinations are unavailable");
+            break;
+        };
+        let destination_url = destination_url_for(destination);
+        let mut route_timeout = match &route.adaptive_timeout {
+            Some(adaptive) => Some(state.latency_tracker.timeout_for(&route.name, adaptive)),
+            None => route
+                .timeout_for(destination)
+                .map(crate::features::health_check::parse_duration),
+        };
+        // What's left of the deadline bounds this attempt and is passed on to the backend
+        let mut attempt_headers = headers.clone();
+        if let Some(deadline) = deadline {
+            let remaining = deadline.saturating_sub(started_at.elapsed());
+            if remaining.is_zero() {
+                tracing::warn!(route = %route.name, attempt = attempt + 1, "Request deadline exceeded");
+                return Err(AppError::DeadlineExceeded);
+            }
+            route_timeout = Some(route_timeout.map_or(remaining, |t| t.min(remaining)));
+            attempt_headers.insert(
+                REQUEST_DEADLINE_HEADER,
+                HeaderValue::from(u64::try_from(remaining.as_millis()).unwrap_or(u64::MAX)),
+            );
+        }
+        let more_attempts = attempt + 1 < max_attempts;
+        // Only back off when coming back around to a destination already tried,
+        // doubling each time
+        let failover_backoff = |cursor: usize| {
+            if cursor.is_multiple_of(candidates.len()) {
+                backoff.saturating_mul(1 << attempt.min(16))
+            } else {
+                std::time::Duration::ZERO
+            }
+        };
+
+        info!(destination = %destination_url, strategy = ?route.load_balance, "Forwarding request to backend");
+
         let mut req_builder = client
             .request(method.clone(), &destination_url)
-            .headers(headers.clone())
-            .body(body_bytes.clone());
+            .headers(attempt_headers)
+            .body(match streamed_body.take() {
+                Some(stream) => stream,
+                None => reqwest::Body::from(body_bytes.clone()),
+            });
 
         if let Some(timeout) = route_timeout {
             req_builder = req_builder.timeout(timeout);
@@ -140,23 +346,70 @@ pub async fn proxy_handler(
             AppError::InvalidDestination(destination_url.clone())
         })?;
 
-        match client.execute(request).await {
+        let attempt_started = Instant::now();
+        // Released once the response is read or the attempt fails
+        let in_flight = state.load_balancer.track(destination);
+        match execute_with_redirects(client, request, &route).await {
             Ok(resp) => {
                 let status = resp.status();
-                if attempt + 1 < max_attempts && retry_on.contains(&status.as_u16()) {
-                    tracing::warn!(attempt = attempt + 1, status = %status, "Retrying request");
-                    tokio::time::sleep(backoff * (attempt + 1)).await;
+                if let Some(adaptive) = &route.adaptive_timeout {
+                    state
+                        .latency_tracker
+                        .record(&route.name, attempt_started.elapsed(), adaptive.window);
+                }
+                tracing::debug!(route = %route.name, destination = %destination_url, attempt = attempt + 1, status = %status, "Backend responded");
+                if let (Some(circuit), Some(cb)) = (&circuit, destination_breaker) {
+                    circuit.record(destination, status.is_server_error(), cb).await;
+                }
+                if let Some(canary) = canary.filter(|c| c.destination == destination) {
+                    state
+                        .canary_tracker
+                        .record(&route.name, canary, status.is_server_error());
+                }
+                if more_attempts && retry_on.contains(&status.as_u16()) && retry_budget_allows(&state, &route.name) {
+                    cursor += 1;
+                    tracing::warn!(attempt = attempt + 1, status = %status, destination = %destination, "Retrying request");
+                    counter!("gateway_retries_total", "route" => route.name.clone()).increment(1);
+                    tokio::time::sleep(failover_backoff(cursor)).await;
                     continue;
                 }
                 let resp_headers = resp.headers().clone();
-                let bytes = resp.bytes().await.map_err(AppError::from)?;
-                let body = Body::from(bytes);
+                let header_bytes: usize = resp_headers
+                    .iter()
+                    .map(|(name, value)| name.as_str().len() + value.len())
+                    .sum();
+                let max_header_bytes = config_guard.tuning.max_response_header_bytes;
+                if header_bytes > max_header_bytes {
+                    tracing::warn!(
+                        route = %route.name,
+                        destination = %destination,
+                        header_bytes = header_bytes,
+                        max_header_bytes = max_header_bytes,
+                        "Backend response headers too large, returning 502"
+                    );
+                    return Err(AppError::UpstreamHeadersTooLarge);
+                }
+                let body = upstream_body(resp, in_flight);
 
-                let mut response_builder = Response::builder().status(status);
-                for (name, value) in resp_headers.iter() {
-                    response_builder = response_builder.header(name, value);
+                let client_status = route
+                    .status_map
+                    .get(&status.as_u16())
+                    .and_then(|s| StatusCode::from_u16(*s).ok())
+                    .unwrap_or(status);
+                let mut response_builder = Response::builder().status(client_status);
+                for (name, value) in &resp_headers {
+                    if route.response_header_allowed(name) {
+                        response_builder = response_builder.header(name, value);
+                    }
                 }
                 let mut response = response_builder.body(body).map_err(|_| AppError::InternalServerError)?;
+                response.extensions_mut().insert(UpstreamStatus(status));
+                if let Some(default) = &route.default_content_type
+                    && !response.headers().contains_key(http::header::CONTENT_TYPE)
+                    && let Ok(value) = HeaderValue::from_str(default)
+                {
+                    response.headers_mut().insert(http::header::CONTENT_TYPE, value);
+                }
                 response.headers_mut().insert(
                     REQUEST_ID_HEADER,
                     HeaderValue::from_str(&request_id).unwrap_or_else(|_| HeaderValue::from_static("unknown")),
@@ -178,14 +431,288 @@ pub async fn proxy_handler(
                 return Ok(response);
             }
             Err(e) => {
-                if attempt + 1 < max_attempts {
-                    tracing::warn!(attempt = attempt + 1, "Request failed, retrying: {}", e);
-                    tokio::time::sleep(backoff * (attempt + 1)).await;
+                // The client's upload was cut off, not the backend's fault
+                if body_limit_exceeded.load(Ordering::Relaxed) {
+                    tracing::warn!(route = %route.name, "Streamed request body exceeded body_limit");
+                    return Err(AppError::PayloadTooLarge);
+                }
+                if let (Some(circuit), Some(cb)) = (&circuit, destination_breaker) {
+                    circuit.record(destination, true, cb).await;
+                }
+                if let Some(canary) = canary.filter(|c| c.destination == destination) {
+                    state.canary_tracker.record(&route.name, canary, true);
                 }
+                if !more_attempts || !retry_budget_allows(&state, &route.name) {
+                    last_err = Some(e);
+                    break;
+                }
+                cursor += 1;
+                tracing::warn!(attempt = attempt + 1, destination = %destination, "Request failed, retrying: {}", e);
+                counter!("gateway_retries_total", "route" => route.name.clone()).increment(1);
+                tokio::time::sleep(failover_backoff(cursor)).await;
                 last_err = Some(e);
             }
         }
     }
 
-    Err(last_err.map(AppError::from).unwrap_or(AppError::InternalServerError))
+    if deadline.is_some_and(|d| started_at.elapsed() >= d) {
+        tracing::warn!(route = %route.name, "Request deadline exceeded");
+        return Err(AppError::DeadlineExceeded);
+    }
+    Err(last_err.map_or(AppError::ServiceUnavailable, AppError::from))
+}
+
+/// Streams the backend's body to the client as it arrives, so memory stays
+/// flat however large it is. `in_flight` is held until the body is done so
+/// `least_connections` still counts a long download.
+fn upstream_body(resp: reqwest::Response, in_flight: InFlightGuard) -> Body {
+    Body::from_stream(futures::StreamExt::map(resp.bytes_stream(), move |chunk| {
+        let _held = &in_flight;
+        chunk
+    }))
+}
+
+/// The destination a trusted caller pinned with the override header, which
+/// is removed either way. Untrusted callers' headers are ignored; a trusted
+/// caller naming a backend the route doesn't use is refused.
+fn pinned_destination<'a>(
+    route: &'a RouteConfig,
+    over: &DestinationOverrideConfig,
+    headers: &mut HeaderMap,
+    client_ip: Option<IpAddr>,
+) -> Result<Option<&'a str>, AppError> {
+    let Some(value) = headers.remove(over.header.as_str()) else {
+        return Ok(None);
+    };
+    if !client_ip.is_some_and(|ip| ip_in_ranges(ip, &over.trusted_ips)) {
+        tracing::debug!(route = %route.name, client_ip = ?client_ip, "Ignoring destination override from untrusted client");
+        return Ok(None);
+    }
+    let requested = value.to_str().unwrap_or_default();
+    match route.own_destination(requested) {
+        Some(destination) => {
+            info!(route = %route.name, destination = %destination, "Destination pinned by override header");
+            Ok(Some(destination))
+        }
+        None => Err(AppError::DestinationNotAllowed(requested.to_string())),
+    }
+}
+
+/// POST and PATCH may take effect twice, so they are only retried when they
+/// carry an `Idempotency-Key` the backend can deduplicate on.
+fn safe_to_retry(method: &Method, headers: &HeaderMap) -> bool {
+    !matches!(*method, Method::POST | Method::PATCH) || headers.contains_key(IDEMPOTENCY_KEY_HEADER)
+}
+
+/// Spends a token from `server.retry_budget`, if configured. When it's empty
+/// the caller returns the failure it has instead of retrying.
+fn retry_budget_allows(state: &AppState, route: &str) -> bool {
+    match &state.retry_budget {
+        Some(budget) if !budget.try_acquire() => {
+            tracing::warn!(route = %route, "Retry budget exhausted, not retrying");
+            counter!("gateway_retry_budget_exhausted_total", "route" => route.to_string()).increment(1);
+            false
+        }
+        _ => true,
+    }
+}
+
+/// A failure reading the client's request body. That's the client's doing,
+/// so it gets a 400 and its own counter rather than counting as a 500.
+pub fn request_body_error(route: &str, error: &axum::Error) -> AppError {
+    tracing::warn!(route = %route, "Failed to read request body: {}", error);
+    counter!("gateway_request_body_errors_total", "route" => route.to_string()).increment(1);
+    AppError::RequestBodyRead
+}
+
+/// Streams `body` to the backend, failing the upload once more than `limit`
+/// bytes have arrived. Sets `exceeded` when that happens.
+fn limited_body_stream(body: Body, limit: usize, exceeded: Arc<AtomicBool>) -> reqwest::Body {
+    let mut received = 0;
+    reqwest::Body::wrap_stream(futures::StreamExt::map(body.into_data_stream(), move |chunk| {
+        let chunk = chunk.map_err(std::io::Error::other)?;
+        received += chunk.len();
+        if received > limit {
+            exceeded.store(true, Ordering::Relaxed);
+            return Err(std::io::Error::other("request body exceeds body_limit"));
+        }
+        Ok(chunk)
+    }))
+}
+
+/// Sends `request`, following up to `route.max_redirects` backend redirects.
+/// A redirect whose target host isn't allowed is returned as-is so the
+/// caller relays the 3xx instead of the gateway fetching an arbitrary host.
+async fn execute_with_redirects(
+    client: &reqwest::Client,
+    mut request: reqwest::Request,
+    route: &RouteConfig,
+) -> Result<reqwest::Response, reqwest::Error> {
+    let origin_hosts: Vec<String> = route
+        .all_destinations()
+        .into_iter()
+        .chain(route.canary.as_ref().map(|c| c.destination.as_str()))
+        .filter_map(|d| reqwest::Url::parse(d).ok()?.host_str().map(str::to_string))
+        .collect();
+
+    let mut hops = 0;
+    loop {
+        let next_copy = (hops < route.max_redirects).then(|| request.try_clone()).flatten();
+        let current_url = request.url().clone();
+        let resp = client.execute(request).await?;
+
+        let Some(mut next) = next_copy else {
+            return Ok(resp);
+        };
+        if !resp.status().is_redirection() {
+            return Ok(resp);
+        }
+        let Some(target) = resp
+            .headers()
+            .get(http::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|location| current_url.join(location).ok())
+        else {
+            return Ok(resp);
+        };
+        let allowed = target
+            .host_str()
+            .is_some_and(|host| redirect_host_allowed(host, &route.allowed_domains, &origin_hosts));
+        if !allowed || !matches!(target.scheme(), "http" | "https") {
+            tracing::warn!(route = %route.name, target = %target, "Not following redirect to disallowed host");
+            return Ok(resp);
+        }
+
+        // 303, and 301/302 after a POST, continue as a body-less GET like browsers do
+        let status = resp.status();
+        if status == http::StatusCode::SEE_OTHER
+            || (matches!(status.as_u16(), 301 | 302) && next.method() == Method::POST)
+        {
+            *next.method_mut() = Method::GET;
+            *next.body_mut() = None;
+            next.headers_mut().remove(http::header::CONTENT_LENGTH);
+            next.headers_mut().remove(http::header::CONTENT_TYPE);
+        }
+        if target.host_str() != current_url.host_str() {
+            next.headers_mut().remove(http::header::AUTHORIZATION);
+        }
+        next.headers_mut().remove(http::header::HOST);
+        info!(route = %route.name, from = %current_url, to = %target, "Following backend redirect");
+        *next.url_mut() = target;
+        request = next;
+        hops += 1;
+    }
+}
+
+/// `allowed` entries match exactly, or as a suffix when they start with `.`
+/// (`.example.com` matches `api.example.com`). With no entries, only the
+/// route's own destination hosts are allowed.
+pub fn redirect_host_allowed(host: &str, allowed: &[String], origin_hosts: &[String]) -> bool {
+    let host = host.to_ascii_lowercase();
+    if allowed.is_empty() {
+        return origin_hosts.iter().any(|o| o.eq_ignore_ascii_case(&host));
+    }
+    allowed.iter().any(|entry| {
+        let entry = entry.trim().to_ascii_lowercase();
+        match entry.strip_prefix('.') {
+            Some(suffix) => host == suffix || host.ends_with(&entry),
+            None => host == entry,
+        }
+    })
+}
+
+/// Picks the destination at `cursor` (wrapping), skipping any whose
+/// per-destination circuit is open. Returns `None` when all are open.
+async fn next_destination<'a>(
+    state: &AppState,
+    route_name: &str,
+    candidates: &[&'a str],
+    breaker: Option<&CircuitBreakerConfig>,
+    cursor: &mut usize,
+) -> Option<(&'a str, Option<Arc<CircuitState>>)> {
+    if breaker.is_none() {
+        return Some((candidates[*cursor % candidates.len()], None));
+    }
+    for _ in 0..candidates.len() {
+        let destination = candidates[*cursor % candidates.len()];
+        let circuit = state.circuit_breaker_store.for_destination(route_name, destination);
+        if circuit.allow_request(destination).await {
+            return Some((destination, Some(circuit)));
+        }
+        *cursor += 1;
+    }
+    None
+}
+
+/// Appends the client to `X-Forwarded-For` and sets `X-Real-IP` and
+/// `X-Forwarded-Proto`, replacing whatever the client sent for those two.
+fn add_forwarded_headers(headers: &mut HeaderMap, client_ip: IpAddr, proto: &'static str) {
+    let forwarded_for = match headers.get(X_FORWARDED_FOR).and_then(|v| v.to_str().ok()) {
+        Some(existing) if !existing.trim().is_empty() => format!("{}, {}", existing, client_ip),
+        _ => client_ip.to_string(),
+    };
+    if let Ok(value) = HeaderValue::from_str(&forwarded_for) {
+        headers.insert(X_FORWARDED_FOR, value);
+    }
+    if let Ok(value) = HeaderValue::from_str(&client_ip.to_string()) {
+        headers.insert(X_REAL_IP, value);
+    }
+    headers.insert(X_FORWARDED_PROTO, HeaderValue::from_static(proto));
+}
+
+/// Applies the route's query parameter transform to a raw query string.
+/// Existing pairs keep their original encoding; added values are percent-encoded.
+/// Returns `None` when nothing is left to forward.
+pub fn apply_query_transform(query: Option<&str>, transform: &QueryParamsTransform) -> Option<String> {
+    let mut pairs: Vec<(String, Option<String>)> = query
+        .unwrap_or("")
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((k, v)) => (k.to_string(), Some(v.to_string())),
+            None => (pair.to_string(), None),
+        })
+        .collect();
+
+    pairs.retain(|(k, _)| !transform.remove.contains(k));
+
+    for (key, _) in &mut pairs {
+        if let Some(new_key) = transform.rename.get(key.as_str()) {
+            key.clone_from(new_key);
+        }
+    }
+
+    let mut added: Vec<_> = transform.add.iter().collect();
+    added.sort();
+    for (key, value) in added {
+        pairs.retain(|(k, _)| k != key);
+        pairs.push((encode_query_component(key), Some(encode_query_component(value))));
+    }
+
+    if pairs.is_empty() {
+        return None;
+    }
+    Some(
+        pairs
+            .into_iter()
+            .map(|(k, v)| match v {
+                Some(v) => format!("{}={}", k, v),
+                None => k,
+            })
+            .collect::<Vec<_>>()
+            .join("&"),
+    )
+}
+
+fn encode_query_component(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for byte in s.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(byte as char),
+            _ => {
+                let _ = write!(out, "%{:02X}", byte);
+            }
+        }
+    }
+    out
 }
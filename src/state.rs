@@ -3,24 +3,81 @@ use bytes::Bytes;
 use http::{HeaderMap, StatusCode};
 use moka::future::Cache;
 use reqwest::Client;
-use std::{sync::Arc, time::Instant};
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Arc};
 
 use crate::{
     config::{ApiKeyStore, GatewayConfig, SecretsConfig},
     features::{
-        circuit_breaker::circuit_breaker::CircuitBreakerStore, health_check::HealthChecker,
-        load_balancer::LoadBalancer, rate_limiter::state::RateLimitState,
+        adaptive_timeout::LatencyTracker,
+        auth::jwks::JwksCache,
+        blue_green::BlueGreenSwitch,
+        cache::ResponseCache,
+        canary::CanaryTracker,
+        capture::RequestCapture,
+        circuit_breaker::{circuit_breaker::CircuitBreakerStore, global::GlobalCircuitBreaker},
+        coalesce::RequestCoalescer,
+        drain::DrainedDestinations,
+        health_check::HealthChecker,
+        load_balancer::LoadBalancer,
+        metrics::RouteLabels,
+        qos::PriorityGate,
+        rate_limiter::state::RateLimitState,
+        retry_budget::RetryBudget,
+        stats::GatewayStats,
+        traffic::RouteTraffic,
     },
+    middleware::request_id::generator::RequestIdGenerator,
     plugins::PluginRegistry,
 };
 
 use tokio::sync::RwLock;
 
+/// Serializable so it can be stored in a shared (Redis) cache.
+/// Expiry is owned by the cache backend, not the entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CachedResponse {
+    #[serde(with = "status_code_serde")]
     pub status: StatusCode,
+    #[serde(with = "header_map_serde")]
     pub headers: HeaderMap,
     pub body: Bytes,
-    pub inserted_at: Instant,
+}
+
+mod status_code_serde {
+    use http::StatusCode;
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    // serde's `with` calls this with a reference
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn serialize<S: Serializer>(status: &StatusCode, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(status.as_u16())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<StatusCode, D::Error> {
+        StatusCode::from_u16(u16::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+mod header_map_serde {
+    use http::{HeaderMap, HeaderName, HeaderValue};
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S: Serializer>(headers: &HeaderMap, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(headers.iter().map(|(name, value)| (name.as_str(), value.as_bytes())))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HeaderMap, D::Error> {
+        let pairs = Vec::<(String, Vec<u8>)>::deserialize(deserializer)?;
+        let mut headers = HeaderMap::with_capacity(pairs.len());
+        for (name, value) in pairs {
+            headers.append(
+                HeaderName::from_bytes(name.as_bytes()).map_err(D::Error::custom)?,
+                HeaderValue::from_bytes(&value).map_err(D::Error::custom)?,
+            );
+        }
+        Ok(headers)
+    }
 }
 
 pub struct AppState {
@@ -28,12 +85,32 @@ pub struct AppState {
     pub secrets: Arc<SecretsConfig>,
     pub key_store: Arc<RwLock<ApiKeyStore>>,
     pub rate_limit_store: Arc<dyn RateLimitState>,
-    pub cache: Arc<Cache<String, Arc<CachedResponse>>>,
+    pub cache: Arc<dyn ResponseCache>,
+    pub static_cache: Cache<PathBuf, Arc<CachedResponse>>,
     pub http_client: Client,
     pub http_client_insecure: Client,
     pub prometheus_handle: Option<PrometheusHandle>,
+    pub route_labels: RouteLabels,
+    pub request_id_generator: RequestIdGenerator,
+    /// Key sets of the `jwks_url`s auth methods verify JWTs against.
+    pub jwks: JwksCache,
     pub circuit_breaker_store: Arc<CircuitBreakerStore>,
+    /// Set when `server.circuit_breaker` is configured; read at startup.
+    pub global_circuit_breaker: Option<GlobalCircuitBreaker>,
+    /// Set when `observability.capture` is configured; read at startup.
+    pub request_capture: Option<RequestCapture>,
+    /// Set when `server.qos` is configured; read at startup.
+    pub qos_gate: Option<PriorityGate>,
+    /// Set when `server.retry_budget` is configured; read at startup.
+    pub retry_budget: Option<RetryBudget>,
     pub load_balancer: LoadBalancer,
+    pub canary_tracker: CanaryTracker,
+    pub blue_green: BlueGreenSwitch,
+    pub drained: DrainedDestinations,
+    pub route_traffic: RouteTraffic,
+    pub latency_tracker: LatencyTracker,
+    pub stats: GatewayStats,
+    pub request_coalescer: RequestCoalescer,
     pub health_checker: Arc<HealthChecker>,
     pub plugin_registry: Arc<PluginRegistry>,
 }
@@ -0,0 +1,99 @@
+use std::{
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+};
+
+use axum::{body::Body, response::Response};
+use bytes::Bytes;
+use http::{HeaderMap, HeaderValue, StatusCode, header::CONTENT_TYPE};
+use moka::future::Cache;
+use tracing::{info, warn};
+
+use crate::{errors::AppError, state::CachedResponse};
+
+/// Serves a route's `static_file` without contacting a backend.
+/// `root` is either a single file or a directory; for a directory, `remainder`
+/// (the request path after the route prefix) selects the file inside it.
+pub async fn serve_static_file(
+    cache: &Cache<PathBuf, Arc<CachedResponse>>,
+    root: &str,
+    remainder: &str,
+) -> Result<Response, AppError> {
+    let Some(path) = resolve_static_path(Path::new(root), remainder) else {
+        warn!(root = %root, remainder = %remainder, "Static file not found or outside root");
+        return Err(AppError::StaticFileNotFound);
+    };
+
+    let cached = if let Some(cached) = cache.get(&path).await {
+        cached
+    } else {
+        let body = tokio::fs::read(&path).await.map_err(|e| {
+            warn!(path = ?path, "Failed to read static file: {}", e);
+            AppError::StaticFileNotFound
+        })?;
+        let mut headers = HeaderMap::new();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type_for(&path)));
+        let entry = Arc::new(CachedResponse {
+            status: StatusCode::OK,
+            headers,
+            body: Bytes::from(body),
+        });
+        info!(path = ?path, "Loaded static file");
+        cache.insert(path, entry.clone()).await;
+        entry
+    };
+
+    let mut builder = Response::builder().status(cached.status);
+    if let Some(headers) = builder.headers_mut() {
+        *headers = cached.headers.clone();
+    }
+    builder
+        .body(Body::from(cached.body.clone()))
+        .map_err(|_| AppError::InternalServerError)
+}
+
+/// Resolves the file to serve, refusing anything that escapes `root`
+/// (`..`, absolute paths, or symlinks pointing outside).
+pub fn resolve_static_path(root: &Path, remainder: &str) -> Option<PathBuf> {
+    let root = root.canonicalize().ok()?;
+    let relative = remainder.trim_start_matches('/');
+
+    if root.is_file() {
+        return relative.is_empty().then_some(root);
+    }
+    if relative.is_empty() {
+        return None;
+    }
+
+    let relative = Path::new(relative);
+    if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+        return None;
+    }
+
+    let candidate = root.join(relative).canonicalize().ok()?;
+    (candidate.starts_with(&root) && candidate.is_file()).then_some(candidate)
+}
+
+pub fn content_type_for(path: &Path) -> &'static str {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase)
+        .unwrap_or_default();
+    match ext.as_str() {
+        "json" => "application/json",
+        "html" | "htm" => "text/html; charset=utf-8",
+        "txt" => "text/plain; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "js" => "application/javascript",
+        "yaml" | "yml" => "application/yaml",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "ico" => "image/x-icon",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream",
+    }
+}
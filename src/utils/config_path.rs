@@ -1,9 +1,67 @@
-use clap::Parser;
-use std::path::PathBuf;
+use anyhow::anyhow;
+use clap::{Parser, Subcommand};
+use reqwest::Client;
+use std::{path::PathBuf, time::Duration};
+
+use crate::{
+    features::auth::basic::DEFAULT_PASSWORD_HASH_ITERATIONS,
+    middleware::rate_limiter::rate_limit::parse_duration,
+    utils::config_source::{ConfigSource, HttpConfigSource},
+};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub struct Cli {
-    #[arg(short, long, value_name = "FILE", default_value = "gateway_config.yaml")]
+    #[arg(short, long, value_name = "FILE", default_value = "gateway.yaml")]
     pub config: PathBuf,
+    /// Fetch the config from this URL instead of `--config`, polling it for changes.
+    #[arg(long, value_name = "URL")]
+    pub config_url: Option<String>,
+    /// How often to poll `--config-url`.
+    #[arg(long, value_name = "DURATION", default_value = "30s")]
+    pub config_poll_interval: String,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Replay requests from a capture file against a target gateway or backend.
+    Replay {
+        /// JSONL file written by `observability.capture`.
+        #[arg(short, long, value_name = "FILE")]
+        file: PathBuf,
+        /// Base URL to send requests to, e.g. http://localhost:8094.
+        #[arg(short, long, value_name = "URL")]
+        target: String,
+    },
+    /// Print the SHA-256 digest of an API key for a `hashed: true` key store.
+    HashApiKey {
+        /// The API key to hash.
+        key: String,
+    },
+    /// Print a salted PBKDF2 hash of a password for a key store user of `Basic` auth.
+    HashPassword {
+        /// The password to hash.
+        password: String,
+        /// PBKDF2 rounds; more is slower to verify and to brute-force.
+        #[arg(long, default_value_t = DEFAULT_PASSWORD_HASH_ITERATIONS)]
+        iterations: u32,
+    },
+}
+
+impl Cli {
+    /// `--config-url` when given, otherwise the `--config` file.
+    pub fn config_source(&self) -> Result<ConfigSource, anyhow::Error> {
+        let Some(url) = &self.config_url else {
+            return Ok(ConfigSource::File(self.config.clone()));
+        };
+        let poll_interval = parse_duration(&self.config_poll_interval)
+            .map_err(|e| anyhow!("--config-poll-interval '{}': {}", self.config_poll_interval, e))?;
+        if poll_interval.is_zero() {
+            return Err(anyhow!("--config-poll-interval must be greater than zero"));
+        }
+        let client = Client::builder().timeout(Duration::from_secs(10)).build()?;
+        Ok(ConfigSource::Http(HttpConfigSource::new(url, poll_interval, client)))
+    }
 }
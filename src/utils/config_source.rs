@@ -0,0 +1,97 @@
+use std::{path::PathBuf, time::Duration};
+
+use http::{StatusCode, header};
+use reqwest::Client;
+
+use crate::{config::GatewayConfig, errors::ConfigError};
+
+/// Where the gateway config comes from: a local file (the default) or a
+/// remote endpoint polled for changes.
+#[derive(Debug, Clone)]
+pub enum ConfigSource {
+    File(PathBuf),
+    Http(HttpConfigSource),
+}
+
+impl ConfigSource {
+    pub async fn load(&mut self) -> Result<GatewayConfig, ConfigError> {
+        match self {
+            ConfigSource::File(path) => GatewayConfig::load(path),
+            ConfigSource::Http(source) => GatewayConfig::from_yaml(&source.fetch().await?),
+        }
+    }
+}
+
+/// Config served over HTTP as the same YAML a config file holds. Includes are
+/// not processed. A Consul KV key works too, via `/v1/kv/<key>?raw`.
+#[derive(Debug, Clone)]
+pub struct HttpConfigSource {
+    url: String,
+    poll_interval: Duration,
+    client: Client,
+    etag: Option<String>,
+    body: Option<String>,
+}
+
+impl HttpConfigSource {
+    pub fn new(url: impl Into<String>, poll_interval: Duration, client: Client) -> Self {
+        Self {
+            url: url.into(),
+            poll_interval,
+            client,
+            etag: None,
+            body: None,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn poll_interval(&self) -> Duration {
+        self.poll_interval
+    }
+
+    /// Fetches the config body.
+    pub async fn fetch(&mut self) -> Result<String, ConfigError> {
+        self.etag = None;
+        self.body = None;
+        self.poll()
+            .await?
+            .ok_or_else(|| self.fetch_error("no config returned".to_string()))
+    }
+
+    /// Fetches the config body, or `None` when it hasn't changed since the
+    /// last fetch (a 304 for the previous ETag, or an identical body).
+    pub async fn poll(&mut self) -> Result<Option<String>, ConfigError> {
+        let mut request = self.client.get(&self.url);
+        if let Some(etag) = &self.etag {
+            request = request.header(header::IF_NONE_MATCH, etag);
+        }
+        let response = request.send().await.map_err(|e| self.fetch_error(e.to_string()))?;
+        if response.status() == StatusCode::NOT_MODIFIED {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(self.fetch_error(format!("status {}", response.status())));
+        }
+        self.etag = response
+            .headers()
+            .get(header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let body = response.text().await.map_err(|e| self.fetch_error(e.to_string()))?;
+        if self.body.as_ref() == Some(&body) {
+            return Ok(None);
+        }
+        self.body = Some(body.clone());
+        Ok(Some(body))
+    }
+
+    fn fetch_error(&self, reason: String) -> ConfigError {
+        ConfigError::Fetch {
+            url: self.url.clone(),
+            reason,
+        }
+    }
+}
@@ -1,17 +1,32 @@
-// Watches the main config and API key files for changes and reloads them
+// Watches the main config (file or remote), API key and TLS certificate files for changes and reloads them
 
-use std::{fs, path::PathBuf, sync::Arc};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use axum_prometheus::metrics::counter;
 
 use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
 use tokio::sync::{RwLock, mpsc};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-use crate::config::{ApiKeyStore, GatewayConfig};
+use crate::{
+    config::{ApiKeyStore, GatewayConfig},
+    errors::{ConfigError, ReloadError},
+    features::tls::ReloadableCertResolver,
+    utils::config_source::HttpConfigSource,
+};
 
+/// Watches the config file and the API key store. `config_path` is `None` when
+/// the config comes from a remote source; only the key store is watched then.
 pub async fn watch_config_files(
-    config_path: PathBuf,
+    config_path: Option<PathBuf>,
     gateway_config: Arc<RwLock<GatewayConfig>>,
     api_key_store: Arc<RwLock<ApiKeyStore>>,
+    channel_buffer: usize,
 ) {
     info!("Starting Configuration file watcher...");
 
@@ -20,7 +35,7 @@ pub async fn watch_config_files(
         PathBuf::from(config_guard.identity.api_key_store_path.clone())
     };
 
-    let gateway_config_path = match fs::canonicalize(&config_path) {
+    let gateway_config_path = match config_path.as_ref().map(fs::canonicalize).transpose() {
         Ok(path) => path,
         Err(e) => {
             error!(path = ?config_path, "Failed to get absolute path for gateway config: {}", e);
@@ -41,7 +56,7 @@ pub async fn watch_config_files(
     let gateway_config_clone = gateway_config.clone();
     let api_key_store_clone = api_key_store.clone();
 
-    let (tx, mut rx) = mpsc::channel(1);
+    let (tx, mut rx) = mpsc::channel(channel_buffer.max(1));
 
     let mut watcher: RecommendedWatcher = match Watcher::new(
         move |res: Result<Event, notify::Error>| {
@@ -62,7 +77,9 @@ pub async fn watch_config_files(
     };
 
     // Watch both files
-    if let Err(e) = watcher.watch(&gateway_config_path, RecursiveMode::NonRecursive) {
+    if let Some(gateway_config_path) = &gateway_config_path
+        && let Err(e) = watcher.watch(gateway_config_path, RecursiveMode::NonRecursive)
+    {
         error!(path = ?gateway_config_path, "Failed to watch gateway config file: {}", e);
     }
     if let Err(e) = watcher.watch(&api_key_store_path, RecursiveMode::NonRecursive) {
@@ -73,17 +90,13 @@ pub async fn watch_config_files(
     while let Some(event) = rx.recv().await {
         info!("Detected change in config files: {:?}", event.paths);
 
-        if event.paths.contains(&gateway_config_path) {
-            match GatewayConfig::load(&gateway_config_path) {
-                Ok(new_config) => {
-                    let mut config_writer = gateway_config_clone.write().await;
-                    *config_writer = new_config;
-                    info!("Successfully reloaded gateway_config.yaml");
-                }
-                Err(e) => {
-                    error!("Failed to reload gateway_config.yaml: {}. Keeping old config.", e);
-                }
-            }
+        if let Some(gateway_config_path) = &gateway_config_path
+            && event.paths.contains(gateway_config_path)
+            && reload_gateway_config(gateway_config_path, &gateway_config_clone)
+                .await
+                .is_ok()
+        {
+            info!("Successfully reloaded gateway_config.yaml");
         }
         if event.paths.contains(&api_key_store_path) {
             match ApiKeyStore::load(&api_key_store_path) {
@@ -99,3 +112,119 @@ pub async fn watch_config_files(
         }
     }
 }
+
+/// Polls a remote config source every `poll_interval` and swaps in each
+/// changed config that validates. A config that fails is not retried until
+/// the source serves a different one.
+pub async fn watch_remote_config(mut source: HttpConfigSource, gateway_config: Arc<RwLock<GatewayConfig>>) {
+    info!(url = %source.url(), "Starting remote config poller...");
+
+    let mut interval = tokio::time::interval(source.poll_interval());
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    // The first tick completes immediately; the config was just loaded
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+        match source.poll().await {
+            Ok(Some(body)) => {
+                let url = source.url().to_string();
+                if apply_reload(GatewayConfig::from_yaml(&body), &url, &gateway_config)
+                    .await
+                    .is_ok()
+                {
+                    info!(url = %url, "Successfully reloaded remote config");
+                }
+            }
+            Ok(None) => {}
+            Err(e) => {
+                counter!("gateway_config_reload_failures_total", "reason" => "fetch_failed").increment(1);
+                warn!("{}. Keeping old config.", e);
+            }
+        }
+    }
+}
+
+/// Loads and validates the config at `path` and swaps it in. On failure the
+/// old config is kept, and the failure is logged and counted by category so
+/// route conflicts introduced by an edit stand out from other invalid configs.
+pub async fn reload_gateway_config(path: &Path, gateway_config: &RwLock<GatewayConfig>) -> Result<(), ReloadError> {
+    apply_reload(GatewayConfig::load(path), &path.display().to_string(), gateway_config).await
+}
+
+async fn apply_reload(
+    loaded: Result<GatewayConfig, ConfigError>,
+    source: &str,
+    gateway_config: &RwLock<GatewayConfig>,
+) -> Result<(), ReloadError> {
+    match loaded {
+        Ok(new_config) => {
+            *gateway_config.write().await = new_config;
+            Ok(())
+        }
+        Err(e) => {
+            let e = ReloadError::from(e);
+            counter!("gateway_config_reload_failures_total", "reason" => e.category()).increment(1);
+            error!(source = %source, category = e.category(), "{}. Keeping old config.", e);
+            Err(e)
+        }
+    }
+}
+
+pub async fn watch_tls_files(resolver: Arc<ReloadableCertResolver>, debounce: Duration) {
+    info!("Starting TLS certificate watcher...");
+
+    // Watch the parent directories rather than the files: cert-manager and most
+    // deploy tools replace certs by rename/symlink swap, which a file watch loses.
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for path in [resolver.cert_path(), resolver.key_path()] {
+        let dir = match fs::canonicalize(path) {
+            Ok(abs) => abs.parent().map(PathBuf::from).unwrap_or(abs),
+            Err(e) => {
+                error!(path = ?path, "Failed to get absolute path for TLS file: {}", e);
+                return;
+            }
+        };
+        if !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+
+    let (tx, mut rx) = mpsc::channel(1);
+
+    let mut watcher: RecommendedWatcher = match Watcher::new(
+        move |res: Result<Event, notify::Error>| {
+            if let Ok(event) = res
+                && (event.kind.is_modify() || event.kind.is_create() || event.kind.is_remove())
+            {
+                // A full channel means a reload is already pending
+                let _ = tx.try_send(event);
+            }
+        },
+        notify::Config::default(),
+    ) {
+        Ok(w) => w,
+        Err(e) => {
+            error!("Failed to create TLS file watcher: {}", e);
+            return;
+        }
+    };
+
+    for dir in &dirs {
+        if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+            error!(path = ?dir, "Failed to watch TLS directory: {}", e);
+        }
+    }
+
+    while let Some(event) = rx.recv().await {
+        // Cert and key are usually written separately; let both land before reloading
+        tokio::time::sleep(debounce).await;
+        while rx.try_recv().is_ok() {}
+
+        info!("Detected change in TLS files: {:?}", event.paths);
+        match resolver.reload() {
+            Ok(()) => info!("Successfully reloaded TLS certificate"),
+            Err(e) => error!("Failed to reload TLS certificate: {}. Keeping old certificate.", e),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use std::net::IpAddr;
+
+/// Returns true if `ip` falls inside any of the given ranges.
+/// Ranges are CIDR blocks ("10.0.0.0/8", "::1/128") or single addresses.
+/// Unparseable entries are ignored.
+pub fn ip_in_ranges(ip: IpAddr, ranges: &[String]) -> bool {
+    ranges.iter().any(|range| ip_in_range(ip, range))
+}
+
+/// Whether `range` is a CIDR block or single address `ip_in_range` understands.
+pub fn is_valid_range(range: &str) -> bool {
+    let (addr, prefix) = match range.trim().split_once('/') {
+        Some((addr, prefix)) => (addr, Some(prefix)),
+        None => (range.trim(), None),
+    };
+    let Ok(addr) = addr.parse::<IpAddr>() else {
+        return false;
+    };
+    let max_prefix = if addr.is_ipv4() { 32 } else { 128 };
+    prefix.is_none_or(|p| p.parse::<u32>().is_ok_and(|p| p <= max_prefix))
+}
+
+pub fn ip_in_range(ip: IpAddr, range: &str) -> bool {
+    let (addr, prefix) = match range.trim().split_once('/') {
+        Some((addr, prefix)) => (addr, prefix.parse::<u32>().ok()),
+        None => (range.trim(), None),
+    };
+    let Ok(network) = addr.parse::<IpAddr>() else {
+        return false;
+    };
+
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(net)) => {
+            let prefix = prefix.unwrap_or(32).min(32);
+            let mask = u32::MAX.checked_shl(32 - prefix).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(net) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(net)) => {
+            let prefix = prefix.unwrap_or(128).min(128);
+            let mask = u128::MAX.checked_shl(128 - prefix).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(net) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V4(_)) => ip.to_ipv4_mapped().is_some_and(|v4| ip_in_range(IpAddr::V4(v4), range)),
+        (IpAddr::V4(_), IpAddr::V6(_)) => false,
+    }
+}
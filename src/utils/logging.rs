@@ -0,0 +1,28 @@
+use std::future::Future;
+
+use tracing::{Level, Metadata, Subscriber};
+use tracing_subscriber::{
+    filter::dynamic_filter_fn,
+    layer::{Context, Filter},
+};
+
+tokio::task_local! {
+    static ROUTE_LOG_LEVEL: Level;
+}
+
+/// Runs `fut` with a route's `log_level` override in effect for the current task.
+pub async fn with_route_log_level<F: Future>(level: Option<Level>, fut: F) -> F::Output {
+    match level {
+        Some(level) => ROUTE_LOG_LEVEL.scope(level, fut).await,
+        None => fut.await,
+    }
+}
+
+/// Enables events up to `global`, or up to the route's level instead when the
+/// event comes from a request running under `with_route_log_level`.
+pub fn route_level_filter<S: Subscriber>(global: Level) -> impl Filter<S> {
+    dynamic_filter_fn(move |metadata: &Metadata<'_>, _: &Context<'_, S>| {
+        let max_level = ROUTE_LOG_LEVEL.try_with(|level| *level).unwrap_or(global);
+        *metadata.level() <= max_level
+    })
+}
@@ -1,3 +1,6 @@
 pub mod config_path;
+pub mod config_source;
 pub mod hot_reload;
+pub mod ip_range;
+pub mod logging;
 pub mod metric_handler;
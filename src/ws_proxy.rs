@@ -6,11 +6,13 @@ use axum::{
     response::Response,
 };
 use futures::{SinkExt, StreamExt};
+use http::HeaderMap;
 use std::sync::Arc;
-use tokio_tungstenite::connect_async;
+use tokio::net::TcpStream;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream, connect_async};
 use tracing::{error, info};
 
-use crate::state::AppState;
+use crate::{errors::AppError, state::AppState};
 
 pub async fn ws_proxy_handler(
     State(state): State<Arc<AppState>>,
@@ -22,10 +24,13 @@ pub async fn ws_proxy_handler(
     let destination = {
         let config = state.config.read().await;
         config.find_route_for_path(&request_path).and_then(|route| {
-            let dest_path = request_path.strip_prefix(&route.path).unwrap_or("");
-            let destinations = route.all_destinations();
+            let dest_path = route.strip_path_prefix(&request_path).unwrap_or("");
+            let destinations = state.drained.filter(state.blue_green.destinations(&route));
             let healthy = state.health_checker.filter_healthy(&destinations);
-            let idx = state.load_balancer.next_index(healthy.len(), &route.load_balance)?;
+            let idx =
+                state
+                    .load_balancer
+                    .pick(&route.name, &healthy, &route.load_balance, &route.destination_weights)?;
             let base = healthy[idx].replace("http://", "ws://").replace("https://", "wss://");
             Some(format!("{}{}", base, dest_path))
         })
@@ -41,9 +46,32 @@ pub async fn ws_proxy_handler(
     })
 }
 
-async fn proxy_websocket(client_ws: WebSocket, backend_url: String) {
-    info!(backend = %backend_url, "Proxying WebSocket connection");
+/// Whether the request asks to upgrade to a WebSocket.
+pub fn is_websocket_upgrade(headers: &HeaderMap) -> bool {
+    headers
+        .get(http::header::UPGRADE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.split(',').any(|p| p.trim().eq_ignore_ascii_case("websocket")))
+}
+
+/// Upgrades a request on a regular route, after its auth and rate limit ran.
+/// The backend connection is opened first so a backend that's down gets a
+/// 502 instead of a socket that closes straight away.
+pub async fn upgrade_to_backend(ws: WebSocketUpgrade, destination_url: &str) -> Result<Response, AppError> {
+    let backend_url = destination_url
+        .replacen("http://", "ws://", 1)
+        .replacen("https://", "wss://", 1);
+    let backend = match connect_async(&backend_url).await {
+        Ok((backend, _)) => backend,
+        Err(e) => {
+            error!(backend = %backend_url, "Failed to connect to backend WebSocket: {}", e);
+            return Err(AppError::WebSocketBackendFailed);
+        }
+    };
+    Ok(ws.on_upgrade(move |socket| pump(socket, backend, backend_url)))
+}
 
+async fn proxy_websocket(client_ws: WebSocket, backend_url: String) {
     let backend = match connect_async(&backend_url).await {
         Ok((ws, _)) => ws,
         Err(e) => {
@@ -51,6 +79,12 @@ async fn proxy_websocket(client_ws: WebSocket, backend_url: String) {
             return;
         }
     };
+    pump(client_ws, backend, backend_url).await;
+}
+
+/// Relays frames both ways until either side closes.
+async fn pump(client_ws: WebSocket, backend: WebSocketStream<MaybeTlsStream<TcpStream>>, backend_url: String) {
+    info!(backend = %backend_url, "Proxying WebSocket connection");
 
     let (mut client_tx, mut client_rx) = client_ws.split();
     let (mut backend_tx, mut backend_rx) = backend.split();
@@ -78,7 +112,7 @@ async fn proxy_websocket(client_ws: WebSocket, backend_url: String) {
                 tokio_tungstenite::tungstenite::Message::Ping(p) => Message::Ping(p),
                 tokio_tungstenite::tungstenite::Message::Pong(p) => Message::Pong(p),
                 tokio_tungstenite::tungstenite::Message::Close(_) => break,
-                _ => continue,
+                tokio_tungstenite::tungstenite::Message::Frame(_) => continue,
             };
             if client_tx.send(axum_msg).await.is_err() {
                 break;
@@ -87,8 +121,8 @@ async fn proxy_websocket(client_ws: WebSocket, backend_url: String) {
     };
 
     tokio::select! {
-        _ = client_to_backend => {},
-        _ = backend_to_client => {},
+        () = client_to_backend => {},
+        () = backend_to_client => {},
     }
 
     info!(backend = %backend_url, "WebSocket connection closed");
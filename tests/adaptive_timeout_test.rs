@@ -0,0 +1,119 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use axum::{Router, body::Body};
+use http::{Request, StatusCode};
+use rustway::{config::AdaptiveTimeoutConfig, features::adaptive_timeout::LatencyTracker};
+use tokio::net::TcpListener;
+
+async fn app() -> Router {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: api
+    path: /api
+    destination: "{backend}"
+    timeout: 10s
+    adaptive_timeout:
+      multiplier: 3
+      min: 100ms
+      max: 5s
+      min_samples: 5
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend
+    ))
+    .await;
+    app
+}
+
+fn config(min_samples: usize) -> AdaptiveTimeoutConfig {
+    AdaptiveTimeoutConfig {
+        multiplier: 3.0,
+        min: "100ms".to_string(),
+        max: "2s".to_string(),
+        window: 10,
+        min_samples,
+    }
+}
+
+#[tokio::test]
+async fn test_slow_response_is_cut_off_after_fast_ones() {
+    let app = app().await;
+    for _ in 0..10 {
+        let response = common::send(&app, Request::builder().uri("/api/echo").body(Body::empty()).unwrap()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    // Fast responses pin the timeout to `min`, far below the static 10s
+    let started = Instant::now();
+    let response = common::send(
+        &app,
+        Request::builder().uri("/api/delay/2000").body(Body::empty()).unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    assert!(started.elapsed() < Duration::from_secs(1), "{:?}", started.elapsed());
+}
+
+#[test]
+fn test_timeout_stays_within_bounds() {
+    let tracker = LatencyTracker::new();
+    let config = config(5);
+
+    // Not enough samples yet
+    tracker.record("fast", Duration::from_millis(1), config.window);
+    assert_eq!(tracker.timeout_for("fast", &config), Duration::from_secs(2));
+
+    for _ in 0..10 {
+        tracker.record("fast", Duration::from_millis(1), config.window);
+        tracker.record("slow", Duration::from_secs(3), config.window);
+        tracker.record("steady", Duration::from_millis(200), config.window);
+    }
+    assert_eq!(tracker.timeout_for("fast", &config), Duration::from_millis(100));
+    assert_eq!(tracker.timeout_for("slow", &config), Duration::from_secs(2));
+    assert_eq!(tracker.timeout_for("steady", &config), Duration::from_millis(600));
+}
+
+#[test]
+fn test_only_the_window_counts() {
+    let tracker = LatencyTracker::new();
+    let config = config(1);
+    for _ in 0..10 {
+        tracker.record("api", Duration::from_secs(1), config.window);
+    }
+    for _ in 0..10 {
+        tracker.record("api", Duration::from_millis(50), config.window);
+    }
+    assert_eq!(tracker.p99("api", 1), Some(Duration::from_millis(50)));
+}
+
+#[test]
+fn test_invalid_adaptive_timeout_fails_validation() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: api
+    path: /api
+    destination: http://localhost:9001
+    adaptive_timeout: {min: 5s, max: 1s, window: 10, min_samples: 20}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    );
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(err.contains("min '5s' is above max '1s'"), "{}", err);
+    assert!(err.contains("at least min_samples (20)"), "{}", err);
+}
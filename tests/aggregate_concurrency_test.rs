@@ -0,0 +1,116 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::{Json, Router, body::Body, routing::get};
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use rustway::{config::GatewayConfig, errors::ConfigError};
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+
+#[derive(Default)]
+struct InFlight {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+/// A backend that takes 50ms per request and remembers the most requests
+/// it had in flight at once.
+async fn start_backend(in_flight: Arc<InFlight>) -> String {
+    let app = Router::new().route(
+        "/item",
+        get(move || {
+            let in_flight = in_flight.clone();
+            async move {
+                let now = in_flight.current.fetch_add(1, Ordering::SeqCst) + 1;
+                in_flight.peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                in_flight.current.fetch_sub(1, Ordering::SeqCst);
+                Json(json!({ "ok": true }))
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
+
+fn config(backend: &str, max_concurrent: Option<usize>) -> String {
+    let sources: String = (0..8)
+        .map(|i| format!("      - {{service: s{i}, path: \"{backend}/item\", field: f{i}}}\n"))
+        .collect::<Vec<_>>()
+        .concat();
+    let cap = max_concurrent.map_or(String::new(), |max| {
+        format!("    max_concurrent_subrequests: {}\n", max)
+    });
+    format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: dashboard
+    path: /dashboard
+    destination: http://localhost
+{cap}    aggregate:
+{sources}identity:
+  api_key_store_path: ./api_keys.yaml
+"#
+    )
+}
+
+async fn aggregate(max_concurrent: Option<usize>) -> (Value, usize) {
+    let in_flight = Arc::new(InFlight::default());
+    let backend = start_backend(in_flight.clone()).await;
+    let (app, _) = common::test_app(&config(&backend, max_concurrent)).await;
+
+    let response = common::send(
+        &app,
+        Request::builder().uri("/agg/dashboard").body(Body::empty()).unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (
+        serde_json::from_slice(&body).unwrap(),
+        in_flight.peak.load(Ordering::SeqCst),
+    )
+}
+
+#[tokio::test]
+async fn test_concurrency_cap_bounds_sub_requests_in_flight() {
+    let (body, peak) = aggregate(Some(2)).await;
+
+    assert_eq!(peak, 2);
+    for i in 0..8 {
+        assert_eq!(body[format!("f{}", i)], json!({ "ok": true }));
+    }
+}
+
+#[tokio::test]
+async fn test_without_cap_sub_requests_run_together() {
+    let (_, peak) = aggregate(None).await;
+    assert!(peak > 2, "{}", peak);
+}
+
+#[test]
+fn test_zero_concurrency_is_rejected() {
+    let result = GatewayConfig::from_yaml(&config("http://backend:8000", Some(0)));
+    let errors = result.unwrap_err().into_errors();
+    assert!(
+        errors
+            .iter()
+            .any(|e| matches!(e, ConfigError::ZeroAggregateConcurrency { route } if route == "/dashboard")),
+        "{:?}",
+        errors
+    );
+}
@@ -0,0 +1,144 @@
+//! API keys read from a dedicated header instead of `Authorization`.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::response::IntoResponse;
+use http::{HeaderMap, StatusCode};
+use jsonwebtoken::{EncodingKey, Header, encode};
+use rustway::{
+    config::{ApiKeyDetails, ApiKeyStore, AuthConfig, SecretsConfig},
+    errors::AppError,
+    features::auth::auth::{Claims, verify_token},
+};
+
+const SECRET: &str = "api-key-header-test-secret";
+
+fn key_store() -> ApiKeyStore {
+    let details = ApiKeyDetails {
+        user_id: "svc-billing".to_string(),
+        roles: vec!["service".to_string()],
+        status: "active".to_string(),
+    };
+    ApiKeyStore {
+        keys: HashMap::from([("key-123".to_string(), details)]),
+        ..Default::default()
+    }
+}
+
+fn secrets() -> SecretsConfig {
+    SecretsConfig {
+        jwt_secret: SECRET.to_string(),
+        ..Default::default()
+    }
+}
+
+fn jwt() -> String {
+    let exp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 3600;
+    let claims = Claims {
+        sub: "alice".to_string(),
+        roles: vec!["user".to_string()],
+        exp: usize::try_from(exp).unwrap(),
+        iss: None,
+        aud: None,
+        extra: HashMap::new(),
+    };
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(SECRET.as_ref())).unwrap()
+}
+
+fn verify(headers: &[(&'static str, &str)], auth_yaml: &str) -> Result<Claims, AppError> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        map.insert(*name, value.parse().unwrap());
+    }
+    let auth: AuthConfig = serde_yaml::from_str(auth_yaml).unwrap();
+    verify_token(&map, &auth, &secrets(), &key_store())
+}
+
+#[test]
+fn test_api_key_read_from_custom_header() {
+    let claims = verify(&[("x-service-key", "key-123")], "type: ApiKey\nheader: X-Service-Key").unwrap();
+    assert_eq!(claims.sub, "svc-billing");
+
+    // With a header configured, `Authorization` is left to other methods
+    let err = verify(
+        &[("authorization", "Bearer key-123")],
+        "type: ApiKey\nheader: X-Service-Key",
+    )
+    .unwrap_err();
+    assert!(
+        matches!(err, AppError::MissingApiKey(ref h) if h == "X-Service-Key"),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn test_api_key_read_from_default_header() {
+    assert_eq!(
+        verify(&[("x-api-key", "key-123")], "type: ApiKey").unwrap().sub,
+        "svc-billing"
+    );
+    // Clients still sending it as a bearer token keep working
+    assert_eq!(
+        verify(&[("authorization", "Bearer key-123")], "type: ApiKey")
+            .unwrap()
+            .sub,
+        "svc-billing"
+    );
+}
+
+#[test]
+fn test_missing_api_key_header_rejected() {
+    let err = verify(&[], "type: ApiKey\nheader: X-Service-Key").unwrap_err();
+    assert!(matches!(err, AppError::MissingApiKey(_)), "{:?}", err);
+    assert_eq!(err.into_response().status(), StatusCode::UNAUTHORIZED);
+
+    let err = verify(&[], "type: ApiKey").unwrap_err();
+    assert!(
+        matches!(err, AppError::MissingApiKey(ref h) if h == "x-api-key"),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn test_jwt_still_read_from_authorization() {
+    let bearer = format!("Bearer {}", jwt());
+    let headers = [("authorization", bearer.as_str()), ("x-service-key", "key-123")];
+
+    assert_eq!(verify(&headers, "type: Jwt").unwrap().sub, "alice");
+    assert_eq!(
+        verify(&headers, "type: ApiKey\nheader: X-Service-Key").unwrap().sub,
+        "svc-billing"
+    );
+    assert!(matches!(
+        verify(&[("x-service-key", "key-123")], "type: Jwt"),
+        Err(AppError::MissingAuthToken)
+    ));
+}
+
+#[test]
+fn test_header_only_valid_for_api_key() {
+    let err = rustway::config::GatewayConfig::from_yaml(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: api
+    path: /api
+    destination: http://localhost:9001
+    auth:
+      type: Jwt
+      header: X-Service-Key
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("header only applies to ApiKey"), "{}", err);
+}
@@ -0,0 +1,78 @@
+//! API keys are verified against the live key store, one lookup per request.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use axum::{Router, body::Body};
+use http::{Request, StatusCode};
+use rustway::config::{ApiKeyDetails, ApiKeyStore};
+use tokio::net::TcpListener;
+
+const KEYS: usize = 5_000;
+
+fn details(user: &str, status: &str) -> ApiKeyDetails {
+    ApiKeyDetails {
+        user_id: user.to_string(),
+        roles: vec!["service".to_string()],
+        status: status.to_string(),
+    }
+}
+
+fn large_store() -> ApiKeyStore {
+    let mut keys: std::collections::HashMap<_, _> = (0..KEYS)
+        .map(|i| (format!("key-{}", i), details(&format!("svc{}", i), "active")))
+        .collect();
+    keys.insert("revoked-key".to_string(), details("old", "revoked"));
+    ApiKeyStore {
+        keys,
+        hashed: false,
+        users: std::collections::HashMap::new(),
+    }
+}
+
+async fn get(app: &Router, key: &str) -> StatusCode {
+    let request = Request::builder()
+        .uri("/api/orders")
+        .header("X-API-Key", key)
+        .body(Body::empty())
+        .unwrap();
+    common::send(app, request).await.status()
+}
+
+#[tokio::test]
+async fn test_large_store_lookups_and_live_updates() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+    let (app, state) = common::test_app_with_keys(
+        &format!(
+            r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: orders
+    path: /api/orders
+    destination: "{backend}/echo"
+    auth: {{type: ApiKey, roles: [service]}}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#
+        ),
+        large_store(),
+    )
+    .await;
+
+    assert_eq!(get(&app, "key-0").await, StatusCode::OK);
+    assert_eq!(get(&app, &format!("key-{}", KEYS - 1)).await, StatusCode::OK);
+    assert_eq!(get(&app, "key-unknown").await, StatusCode::UNAUTHORIZED);
+    assert_eq!(get(&app, "revoked-key").await, StatusCode::UNAUTHORIZED);
+
+    // A reloaded store applies from the next request; nothing is held on to
+    {
+        let mut store = state.key_store.write().await;
+        store.keys.remove("key-0");
+        store.keys.insert("new-key".to_string(), details("new", "active"));
+    }
+    assert_eq!(get(&app, "key-0").await, StatusCode::UNAUTHORIZED);
+    assert_eq!(get(&app, "new-key").await, StatusCode::OK);
+}
@@ -0,0 +1,121 @@
+//! `auth.on_error` decides what happens when an auth dependency is down,
+//! simulated here with an introspection endpoint nobody listens on.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::path::{Path, PathBuf};
+
+use axum::{Form, Json, Router, body::Body, routing::post};
+use http::{Request, StatusCode};
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+
+fn static_root(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustygw-auth-on-error-{}-{}", test, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("status.json"), r#"{"ok":true}"#).unwrap();
+    dir
+}
+
+fn config(root: &Path, introspection_url: &str, on_error: &str) -> String {
+    format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: status
+    path: /api/status
+    static_file: "{root}"
+    auth:
+      type: Introspection
+      introspection_url: "{introspection_url}"
+      timeout: 500ms
+      on_error: {on_error}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        root = root.display(),
+        introspection_url = introspection_url,
+        on_error = on_error,
+    )
+}
+
+/// An introspection endpoint that treats `good-token` as active.
+async fn introspection_server() -> String {
+    let app = Router::new().route(
+        "/introspect",
+        post(|Form(form): Form<Vec<(String, String)>>| async move {
+            let active = form.iter().any(|(k, v)| k == "token" && v == "good-token");
+            Json::<Value>(json!({"active": active, "sub": "svc", "roles": ["reader"]}))
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}/introspect", addr)
+}
+
+/// A URL that refuses connections: the auth dependency is down.
+async fn unreachable_url() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    format!("http://{}/introspect", addr)
+}
+
+fn get(token: &str) -> Request<Body> {
+    Request::builder()
+        .uri("/api/status/status.json")
+        .header("Authorization", format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_outage_fails_closed_by_default() {
+    let root = static_root("closed");
+    let (app, _) = common::test_app(&config(&root, &unreachable_url().await, "fail_closed")).await;
+
+    let resp = common::send(&app, get("good-token")).await;
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_outage_fails_open_when_configured() {
+    let root = static_root("open");
+    let (app, _) = common::test_app(&config(&root, &unreachable_url().await, "fail_open")).await;
+
+    let resp = common::send(&app, get("good-token")).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_fail_open_still_rejects_invalid_tokens() {
+    let root = static_root("reachable");
+    let (app, _) = common::test_app(&config(&root, &introspection_server().await, "fail_open")).await;
+
+    assert_eq!(common::send(&app, get("good-token")).await.status(), StatusCode::OK);
+    assert_eq!(
+        common::send(&app, get("bad-token")).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[test]
+fn test_introspection_requires_url() {
+    let yaml = r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: status
+    path: /api/status
+    destination: http://localhost:9001
+    auth:
+      type: Introspection
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#;
+    let err = rustway::config::GatewayConfig::from_yaml(yaml).unwrap_err().to_string();
+    assert!(err.contains("Introspection requires introspection_url"), "{}", err);
+}
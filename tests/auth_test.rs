@@ -1,3 +1,5 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
 use rustway::config::GatewayConfig;
 
 #[tokio::test]
@@ -0,0 +1,120 @@
+//! An auth method's `timeout` bounds the whole auth step, so a slow
+//! dependency can't stall requests.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use axum::{Json, Router, body::Body, routing::post};
+use http::{Request, StatusCode};
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+
+fn static_root(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustygw-auth-timeout-{}-{}", test, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("status.json"), r#"{"ok":true}"#).unwrap();
+    dir
+}
+
+/// An introspection endpoint that takes `delay` to call every token active.
+async fn slow_introspection(delay: Duration) -> String {
+    let app = Router::new().route(
+        "/introspect",
+        post(move || async move {
+            tokio::time::sleep(delay).await;
+            Json::<Value>(json!({"active": true, "sub": "svc", "roles": []}))
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}/introspect", addr)
+}
+
+async fn app(test: &str, delay: Duration, on_error: &str) -> Router {
+    let root = static_root(test);
+    let url = slow_introspection(delay).await;
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: status
+    path: /api/status
+    static_file: "{root}"
+    auth:
+      type: Introspection
+      introspection_url: "{url}"
+      timeout: 200ms
+      on_error: {on_error}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        root = root.display(),
+    ))
+    .await;
+    app
+}
+
+async fn timed_get(app: &Router) -> (StatusCode, Duration) {
+    let request = Request::builder()
+        .uri("/api/status/status.json")
+        .header("Authorization", "Bearer some-token")
+        .body(Body::empty())
+        .unwrap();
+    let started = Instant::now();
+    let status = common::send(app, request).await.status();
+    (status, started.elapsed())
+}
+
+#[tokio::test]
+async fn test_slow_auth_times_out_within_bound() {
+    let app = app("closed", Duration::from_secs(5), "fail_closed").await;
+
+    let (status, elapsed) = timed_get(&app).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert!(elapsed < Duration::from_secs(1), "auth took {:?}", elapsed);
+}
+
+#[tokio::test]
+async fn test_slow_auth_fails_open_when_configured() {
+    let app = app("open", Duration::from_secs(5), "fail_open").await;
+
+    let (status, elapsed) = timed_get(&app).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(elapsed < Duration::from_secs(1), "auth took {:?}", elapsed);
+}
+
+#[tokio::test]
+async fn test_auth_within_timeout_succeeds() {
+    let app = app("fast", Duration::from_millis(20), "fail_closed").await;
+
+    assert_eq!(timed_get(&app).await.0, StatusCode::OK);
+}
+
+#[test]
+fn test_invalid_auth_timeout_rejected() {
+    let err = rustway::config::GatewayConfig::from_yaml(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: api
+    path: /api
+    destination: http://localhost:9001
+    auth:
+      type: Jwt
+      timeout: forever
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("timeout 'forever'"), "{}", err);
+}
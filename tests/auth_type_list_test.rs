@@ -0,0 +1,143 @@
+//! `type: [Jwt, ApiKey]` shorthand for a route accepting either credential,
+//! and which error is returned when neither is accepted.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::body::Body;
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use jsonwebtoken::{EncodingKey, Header, encode};
+use rustway::{
+    config::{ApiKeyDetails, ApiKeyStore, AuthType},
+    features::auth::auth::Claims,
+};
+
+fn static_root(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustygw-auth-type-list-{}-{}", test, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("orders.json"), "[]").unwrap();
+    dir
+}
+
+fn config(root: &Path) -> String {
+    format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: orders
+    path: /api/orders
+    static_file: "{root}"
+    auth:
+      type: [Jwt, ApiKey]
+      roles: [orders]
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        root = root.display()
+    )
+}
+
+fn key_store() -> ApiKeyStore {
+    ApiKeyStore {
+        keys: HashMap::from([(
+            "billing-key".to_string(),
+            ApiKeyDetails {
+                user_id: "billing".to_string(),
+                roles: vec!["orders".to_string()],
+                status: "active".to_string(),
+            },
+        )]),
+        ..Default::default()
+    }
+}
+
+fn jwt(expires_in: i64) -> String {
+    let now = i64::try_from(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()).unwrap();
+    let claims = Claims {
+        sub: "alice".to_string(),
+        roles: vec!["orders".to_string()],
+        exp: usize::try_from(now + expires_in).unwrap(),
+        iss: None,
+        aud: None,
+        extra: HashMap::new(),
+    };
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(common::TEST_JWT_SECRET.as_bytes()),
+    )
+    .unwrap()
+}
+
+fn get(headers: &[(&str, &str)]) -> Request<Body> {
+    let mut builder = Request::builder().uri("/api/orders/orders.json");
+    for (name, value) in headers {
+        builder = builder.header(*name, *value);
+    }
+    builder.body(Body::empty()).unwrap()
+}
+
+async fn message(response: axum::response::Response) -> String {
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[test]
+fn test_type_list_expands_to_one_method_per_type() {
+    let config = common::parse_config(&config(&static_root("parse")));
+    let auth = &config.routes[0].auth;
+    assert_eq!(auth.len(), 2);
+    assert_eq!(auth[0].auth_type, AuthType::Jwt);
+    assert_eq!(auth[1].auth_type, AuthType::ApiKey);
+    // Settings beside the list apply to every method
+    assert!(auth.iter().all(|method| method.roles == Some(vec!["orders".to_string()])));
+}
+
+#[tokio::test]
+async fn test_each_credential_authenticates_on_its_own() {
+    let (app, _) = common::test_app_with_keys(&config(&static_root("each")), key_store()).await;
+
+    let token = format!("Bearer {}", jwt(3600));
+    let resp = common::send(&app, get(&[("Authorization", &token)])).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = common::send(&app, get(&[("X-API-Key", "billing-key")])).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = common::send(&app, get(&[("Authorization", "Bearer billing-key")])).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_rejected_api_key_reported_over_missing_jwt() {
+    let (app, _) = common::test_app_with_keys(&config(&static_root("bad-key")), key_store()).await;
+
+    let resp = common::send(&app, get(&[("X-API-Key", "revoked-key")])).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert!(message(resp).await.starts_with("Authentication failed"));
+}
+
+#[tokio::test]
+async fn test_expired_jwt_reported_over_missing_api_key() {
+    let (app, _) = common::test_app_with_keys(&config(&static_root("expired")), key_store()).await;
+
+    let token = format!("Bearer {}", jwt(-3600));
+    let resp = common::send(&app, get(&[("Authorization", &token)])).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(message(resp).await, "Token has expired");
+}
+
+#[tokio::test]
+async fn test_no_credential_is_first_methods_error() {
+    let (app, _) = common::test_app_with_keys(&config(&static_root("none")), key_store()).await;
+
+    let resp = common::send(&app, get(&[])).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    assert_eq!(message(resp).await, "Missing 'Authorization' header");
+}
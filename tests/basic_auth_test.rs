@@ -0,0 +1,213 @@
+//! HTTP Basic auth against the key store's `users`.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{collections::HashMap, path::PathBuf, time::Instant};
+
+use axum::body::Body;
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use http::{HeaderMap, Request, StatusCode, header::WWW_AUTHENTICATE};
+use rustway::{
+    config::{ApiKeyStore, AuthConfig, BasicUser, SecretsConfig},
+    errors::AppError,
+    features::auth::{
+        auth::{Claims, verify_token},
+        basic::{PasswordHash, authenticate_basic, hash_password},
+    },
+};
+use tokio::sync::RwLock;
+
+// Far fewer rounds than the default, to keep the tests fast
+const ITERATIONS: u32 = 1_000;
+
+fn user(password: &str, roles: &[&str], status: &str) -> BasicUser {
+    BasicUser {
+        password_hash: hash_password(password, ITERATIONS).unwrap(),
+        roles: roles.iter().map(ToString::to_string).collect(),
+        status: status.to_string(),
+    }
+}
+
+fn key_store() -> ApiKeyStore {
+    ApiKeyStore {
+        keys: HashMap::new(),
+        hashed: false,
+        users: HashMap::from([
+            ("reports".to_string(), user("s3cret", &["legacy"], "active")),
+            ("retired".to_string(), user("0ld", &["legacy"], "disabled")),
+        ]),
+    }
+}
+
+fn basic(username: &str, password: &str) -> String {
+    format!("Basic {}", BASE64.encode(format!("{}:{}", username, password)))
+}
+
+fn verify(authorization: &str) -> Result<Claims, AppError> {
+    let mut headers = HeaderMap::new();
+    headers.insert("Authorization", authorization.parse().unwrap());
+    let auth: AuthConfig = serde_yaml::from_str("type: Basic").unwrap();
+    verify_token(&headers, &auth, &SecretsConfig::default(), &key_store())
+}
+
+#[test]
+fn test_valid_credentials() {
+    let claims = verify(&basic("reports", "s3cret")).unwrap();
+    assert_eq!(claims.sub, "reports");
+    assert_eq!(claims.roles, vec!["legacy".to_string()]);
+    // The scheme is case-insensitive
+    assert!(verify(&basic("reports", "s3cret").replacen("Basic", "basic", 1)).is_ok());
+}
+
+#[test]
+fn test_password_may_contain_colons() {
+    let mut store = key_store();
+    store.users.insert("colon".to_string(), user("a:b:c", &[], "active"));
+    let mut headers = HeaderMap::new();
+    headers.insert("Authorization", basic("colon", "a:b:c").parse().unwrap());
+    let auth: AuthConfig = serde_yaml::from_str("type: Basic").unwrap();
+    assert!(verify_token(&headers, &auth, &SecretsConfig::default(), &store).is_ok());
+}
+
+#[test]
+fn test_invalid_credentials() {
+    for (username, password) in [("reports", "wrong"), ("nobody", "s3cret"), ("reports", "")] {
+        assert!(
+            matches!(verify(&basic(username, password)), Err(AppError::AuthFailed(_))),
+            "{}:{} was accepted",
+            username,
+            password
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_missing_user_is_hashed_like_a_wrong_password() {
+    // "s3cret" at the default strength, which a missing user is checked against
+    const DEFAULT_STRENGTH_HASH: &str = "pbkdf2-sha256$600000$ca101532ef20a568cb717836c9c339b5$\
+                                         2df2d5c493b4716a0cbcfcc471f0d6b7eaff9e9c07ee3b0da538dc8a81cf26ee";
+    let mut store = key_store();
+    store.users.insert(
+        "strong".to_string(),
+        BasicUser {
+            password_hash: DEFAULT_STRENGTH_HASH.to_string(),
+            roles: Vec::new(),
+            status: "active".to_string(),
+        },
+    );
+    let store = RwLock::new(store);
+    let rejected_in = async |username: &str| {
+        let mut headers = HeaderMap::new();
+        headers.insert("Authorization", basic(username, "wrong").parse().unwrap());
+        let started = Instant::now();
+        let result = authenticate_basic(&headers, &store).await;
+        assert!(
+            matches!(result, Err(AppError::AuthFailed(_))),
+            "{} was accepted",
+            username
+        );
+        started.elapsed()
+    };
+
+    let wrong_password = rejected_in("strong").await;
+    let missing_user = rejected_in("nobody").await;
+    // Without the hash a missing user is rejected in microseconds
+    assert!(
+        missing_user * 2 >= wrong_password,
+        "missing user took {:?}, wrong password {:?}",
+        missing_user,
+        wrong_password
+    );
+}
+
+#[test]
+fn test_disabled_user_rejected() {
+    match verify(&basic("retired", "0ld")) {
+        Err(AppError::AuthFailed(reason)) => assert!(reason.contains("disabled")),
+        other => panic!("expected AuthFailed, got {:?}", other.map(|c| c.sub)),
+    }
+}
+
+#[test]
+fn test_malformed_header() {
+    assert!(matches!(
+        verify("Bearer abc"),
+        Err(AppError::InvalidAuthHeader("Basic"))
+    ));
+    assert!(matches!(
+        verify("Basic not-base64!"),
+        Err(AppError::InvalidAuthHeader("Basic"))
+    ));
+    let no_colon = format!("Basic {}", BASE64.encode("reports"));
+    assert!(matches!(verify(&no_colon), Err(AppError::InvalidAuthHeader("Basic"))));
+}
+
+#[test]
+fn test_password_hash_format() {
+    let hash = hash_password("s3cret", ITERATIONS).unwrap();
+    assert!(hash.starts_with("pbkdf2-sha256$1000$"));
+    let parsed = PasswordHash::parse(&hash).unwrap();
+    assert!(parsed.verify("s3cret"));
+    assert!(!parsed.verify("S3cret"));
+    // Salted, so the same password hashes differently each time
+    assert_ne!(hash, hash_password("s3cret", ITERATIONS).unwrap());
+    assert!(PasswordHash::parse("s3cret").is_none());
+    assert!(hash_password("s3cret", 0).is_err());
+}
+
+#[test]
+fn test_store_with_bad_password_hash_fails_to_load() {
+    let path = std::env::temp_dir().join(format!("rustygw-basic-users-{}.yaml", std::process::id()));
+    std::fs::write(&path, "users:\n  reports:\n    password_hash: s3cret\n").unwrap();
+    let error = ApiKeyStore::load(&path).unwrap_err().to_string();
+    std::fs::remove_file(&path).unwrap();
+    assert!(error.contains("reports"));
+    assert!(!error.contains("s3cret"));
+}
+
+fn static_root() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustygw-basic-auth-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("report.csv"), "a,b\n").unwrap();
+    dir
+}
+
+fn get(authorization: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder().uri("/legacy/reports/report.csv");
+    if let Some(authorization) = authorization {
+        builder = builder.header("Authorization", authorization);
+    }
+    builder.body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn test_route_challenges_for_basic_credentials() {
+    let config = format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: reports
+    path: /legacy/reports
+    static_file: "{root}"
+    auth:
+      type: Basic
+      roles: [legacy]
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        root = static_root().display()
+    );
+    let (app, _) = common::test_app_with_keys(&config, key_store()).await;
+
+    let ok = common::send(&app, get(Some(&basic("reports", "s3cret")))).await;
+    assert_eq!(ok.status(), StatusCode::OK);
+    assert!(ok.headers().get(WWW_AUTHENTICATE).is_none());
+
+    for authorization in [None, Some(basic("reports", "wrong"))] {
+        let denied = common::send(&app, get(authorization.as_deref())).await;
+        assert_eq!(denied.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(denied.headers()[WWW_AUTHENTICATE], "Basic realm=\"reports\"");
+    }
+}
@@ -0,0 +1,179 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{collections::HashMap, time::Duration};
+
+use axum::{Router, body::Body};
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use rustway::config::{ApiKeyDetails, ApiKeyStore};
+use serde_json::Value;
+use tokio::net::TcpListener;
+
+async fn start_backend() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+    url
+}
+
+fn key_store() -> ApiKeyStore {
+    let key = |user: &str, roles: &[&str]| ApiKeyDetails {
+        user_id: user.to_string(),
+        roles: roles.iter().map(ToString::to_string).collect(),
+        status: "active".to_string(),
+    };
+    ApiKeyStore {
+        keys: HashMap::from([
+            ("ops-key".to_string(), key("ops", &["admin"])),
+            ("dev-key".to_string(), key("dev", &["user"])),
+        ]),
+        ..Default::default()
+    }
+}
+
+async fn test_app(blue: &str) -> Router {
+    let backend = start_backend().await;
+    let (app, _state) = common::test_app_with_keys(
+        &format!(
+            r#"
+server:
+  addr: "127.0.0.1:8094"
+  admin:
+    auth: {{type: ApiKey, roles: [admin]}}
+routes:
+  - name: shop
+    path: /api/shop
+    blue_green:
+      blue: ["{backend}{blue}"]
+      green: ["{backend}/green"]
+      active: blue
+  - name: plain
+    path: /api/plain
+    destination: "{backend}/plain"
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+            backend = backend,
+            blue = blue
+        ),
+        key_store(),
+    )
+    .await;
+    app
+}
+
+async fn get_json(app: &Router, uri: &str) -> Value {
+    let response = common::send(app, Request::builder().uri(uri).body(Body::empty()).unwrap()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes()).unwrap()
+}
+
+async fn switch(app: &Router, route: &str, key: &str) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("/admin/routes/{}/switch", route))
+        .header("Authorization", format!("Bearer {}", key))
+        .body(Body::empty())
+        .unwrap();
+    let response = common::send(app, request).await;
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_switch_routes_subsequent_requests_to_green() {
+    let app = test_app("/blue").await;
+    assert_eq!(get_json(&app, "/api/shop").await["path"], "/blue");
+
+    let (status, body) = switch(&app, "shop", "ops-key").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["active"], "green");
+    for _ in 0..3 {
+        assert_eq!(get_json(&app, "/api/shop").await["path"], "/green");
+    }
+
+    // Switching again goes back to blue
+    let (_, body) = switch(&app, "shop", "ops-key").await;
+    assert_eq!(body["active"], "blue");
+    assert_eq!(get_json(&app, "/api/shop").await["path"], "/blue");
+}
+
+#[tokio::test]
+async fn test_in_flight_request_to_old_group_completes() {
+    let app = test_app("/delay/300").await;
+    let in_flight = tokio::spawn({
+        let app = app.clone();
+        async move { get_json(&app, "/api/shop").await }
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    assert_eq!(switch(&app, "shop", "ops-key").await.0, StatusCode::OK);
+    assert_eq!(get_json(&app, "/api/shop").await["path"], "/green");
+    assert_eq!(in_flight.await.unwrap()["delayed_ms"], 300);
+}
+
+#[tokio::test]
+async fn test_switch_requires_admin() {
+    let app = test_app("/blue").await;
+    assert_eq!(switch(&app, "shop", "dev-key").await.0, StatusCode::FORBIDDEN);
+    assert_eq!(switch(&app, "shop", "bogus").await.0, StatusCode::UNAUTHORIZED);
+    assert_eq!(get_json(&app, "/api/shop").await["path"], "/blue");
+}
+
+#[tokio::test]
+async fn test_switch_unknown_or_plain_route() {
+    let app = test_app("/blue").await;
+    assert_eq!(switch(&app, "missing", "ops-key").await.0, StatusCode::NOT_FOUND);
+    assert_eq!(switch(&app, "plain", "ops-key").await.0, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_admin_endpoint_disabled_without_admin_config() {
+    let backend = start_backend().await;
+    let (app, _state) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: shop
+    path: /api/shop
+    blue_green:
+      blue: ["{backend}/blue"]
+      green: ["{backend}/green"]
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend
+    ))
+    .await;
+    assert_eq!(switch(&app, "shop", "ops-key").await.0, StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn test_invalid_blue_green_fails_validation() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: shop
+    path: /api/shop
+    destination: http://shop:8000
+    blue_green:
+      blue: [http://shop-blue:8000]
+      green: []
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    );
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(
+        err.contains("blue and green must each have at least one destination"),
+        "{}",
+        err
+    );
+    assert!(err.contains("cannot be combined with destination"), "{}", err);
+}
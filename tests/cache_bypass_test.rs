@@ -0,0 +1,59 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use http::{HeaderMap, HeaderValue, header::CACHE_CONTROL};
+use rustway::{middleware::cache::cache::should_bypass_cache, utils::ip_range::ip_in_range};
+use std::net::IpAddr;
+
+fn no_cache_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static("no-cache"));
+    headers
+}
+
+fn trusted() -> Vec<String> {
+    vec!["10.0.0.0/8".to_string(), "127.0.0.1".to_string()]
+}
+
+#[test]
+fn test_trusted_client_no_cache_bypasses() {
+    let ip: IpAddr = "10.1.2.3".parse().unwrap();
+    assert!(should_bypass_cache(&no_cache_headers(), ip, &trusted()));
+
+    let loopback: IpAddr = "127.0.0.1".parse().unwrap();
+    assert!(should_bypass_cache(&no_cache_headers(), loopback, &trusted()));
+}
+
+#[test]
+fn test_untrusted_client_no_cache_ignored() {
+    let ip: IpAddr = "203.0.113.7".parse().unwrap();
+    assert!(!should_bypass_cache(&no_cache_headers(), ip, &trusted()));
+}
+
+#[test]
+fn test_no_bypass_without_trusted_ranges() {
+    let ip: IpAddr = "10.1.2.3".parse().unwrap();
+    assert!(!should_bypass_cache(&no_cache_headers(), ip, &[]));
+}
+
+#[test]
+fn test_trusted_client_without_directive_uses_cache() {
+    let ip: IpAddr = "10.1.2.3".parse().unwrap();
+    assert!(!should_bypass_cache(&HeaderMap::new(), ip, &trusted()));
+
+    let mut headers = HeaderMap::new();
+    headers.insert(CACHE_CONTROL, HeaderValue::from_static("max-age=0, No-Cache"));
+    assert!(should_bypass_cache(&headers, ip, &trusted()));
+}
+
+#[test]
+fn test_ip_in_range_cidr() {
+    let ip: IpAddr = "192.168.1.42".parse().unwrap();
+    assert!(ip_in_range(ip, "192.168.1.0/24"));
+    assert!(!ip_in_range(ip, "192.168.2.0/24"));
+    assert!(ip_in_range(ip, "0.0.0.0/0"));
+    assert!(!ip_in_range(ip, "not-an-ip"));
+
+    let v6: IpAddr = "::1".parse().unwrap();
+    assert!(ip_in_range(v6, "::1/128"));
+    assert!(!ip_in_range(v6, "10.0.0.0/8"));
+}
@@ -0,0 +1,142 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use axum::{Router, body::Body, http::StatusCode, response::IntoResponse, routing::get};
+use http::{HeaderMap, Request, header};
+use http_body_util::BodyExt;
+use rustway::middleware::cache::cache::encoding_variant;
+use tokio::net::TcpListener;
+
+const PLAIN: &str = r#"{"catalog":["alpha","beta","gamma"]}"#;
+/// `PLAIN`, gzip-compressed.
+const GZIPPED: [u8; 53] = [
+    0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xab, 0x56, 0x4a, 0x4e, 0x2c, 0x49, 0xcc, 0xc9, 0x4f,
+    0x57, 0xb2, 0x8a, 0x56, 0x4a, 0xcc, 0x29, 0xc8, 0x48, 0x54, 0xd2, 0x51, 0x4a, 0x4a, 0x2d, 0x01, 0x51, 0xe9, 0x89,
+    0xb9, 0xb9, 0x89, 0x4a, 0xb1, 0xb5, 0x00, 0x13, 0xef, 0xf4, 0xe7, 0x24, 0x00, 0x00, 0x00,
+];
+
+/// Serves the catalog gzip-encoded to clients accepting gzip, counting calls.
+async fn compressing_backend() -> (String, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let app = Router::new().route(
+        "/catalog",
+        get(move |headers: HeaderMap| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                let gzip = headers
+                    .get(header::ACCEPT_ENCODING)
+                    .and_then(|v| v.to_str().ok())
+                    .is_some_and(|v| v.contains("gzip"));
+                if gzip {
+                    (
+                        [
+                            (header::CONTENT_TYPE, "application/json"),
+                            (header::CONTENT_ENCODING, "gzip"),
+                            (header::VARY, "Accept-Encoding"),
+                        ],
+                        GZIPPED.to_vec(),
+                    )
+                        .into_response()
+                } else {
+                    (
+                        [
+                            (header::CONTENT_TYPE, "application/json"),
+                            (header::VARY, "Accept-Encoding"),
+                        ],
+                        PLAIN,
+                    )
+                        .into_response()
+                }
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, calls)
+}
+
+async fn app() -> (Router, Arc<AtomicUsize>) {
+    let (backend, calls) = compressing_backend().await;
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: catalog
+    path: /api/catalog
+    destination: "{backend}/catalog"
+    cache: {{ttl: 60s}}
+    response_header_allowlist: [content-type]
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#
+    ))
+    .await;
+    (app, calls)
+}
+
+async fn fetch(app: &Router, accept_encoding: Option<&str>) -> (StatusCode, HeaderMap, Vec<u8>) {
+    let mut request = Request::builder().uri("/api/catalog");
+    if let Some(accept_encoding) = accept_encoding {
+        request = request.header(header::ACCEPT_ENCODING, accept_encoding);
+    }
+    let response = common::send(app, request.body(Body::empty()).unwrap()).await;
+    let (parts, body) = response.into_parts();
+    let body = body.collect().await.unwrap().to_bytes().to_vec();
+    (parts.status, parts.headers, body)
+}
+
+#[tokio::test]
+async fn test_gzip_response_is_cached_and_replayed_byte_identical() {
+    let (app, calls) = app().await;
+
+    let (status, headers, first) = fetch(&app, Some("gzip")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers[header::CONTENT_ENCODING], "gzip");
+    assert_eq!(first, GZIPPED);
+
+    let (status, headers, second) = fetch(&app, Some("gzip")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers[header::CONTENT_ENCODING], "gzip");
+    assert_eq!(second, first);
+    assert_eq!(calls.load(Ordering::SeqCst), 1, "second request should be a cache hit");
+}
+
+#[tokio::test]
+async fn test_encoded_entry_is_not_replayed_to_identity_clients() {
+    let (app, calls) = app().await;
+    fetch(&app, Some("gzip, deflate")).await;
+
+    let (_, headers, body) = fetch(&app, None).await;
+    assert!(headers.get(header::CONTENT_ENCODING).is_none());
+    assert_eq!(body, PLAIN.as_bytes());
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // Each variant is now cached on its own
+    assert_eq!(fetch(&app, Some("deflate, gzip")).await.2, GZIPPED);
+    assert_eq!(fetch(&app, None).await.2, PLAIN.as_bytes());
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_encoding_variant_normalizes_accept_encoding() {
+    let variant = |value: &str| {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT_ENCODING, value.parse().unwrap());
+        encoding_variant(&headers)
+    };
+
+    assert_eq!(variant("gzip, br"), "#ae=br,gzip");
+    assert_eq!(variant("br;q=0.5, GZIP"), "#ae=br,gzip");
+    assert_eq!(variant("gzip;q=0, identity"), "");
+    assert_eq!(variant("*"), "#ae=br,deflate,gzip,zstd");
+    assert_eq!(encoding_variant(&HeaderMap::new()), "");
+}
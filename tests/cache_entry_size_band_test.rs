@@ -0,0 +1,93 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use axum::{Router, body::Body, routing::get};
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use rustway::{config::GatewayConfig, errors::ConfigError};
+use tokio::net::TcpListener;
+
+const TINY: usize = 16;
+const MEDIUM: usize = 2 * 1024;
+const LARGE: usize = 8 * 1024;
+
+/// Serves `/{len}` as a body of that many bytes, counting hits.
+async fn counting_backend() -> (String, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let app = Router::new().route(
+        "/{len}",
+        get(move |axum::extract::Path(len): axum::extract::Path<usize>| {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                "x".repeat(len)
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, hits)
+}
+
+fn config(backend: &str) -> String {
+    format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: files
+    path: /api/files
+    destination: "{backend}"
+    cache: {{ttl: 60s, min_entry_size: 1KB, max_entry_size: 4KB}}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#
+    )
+}
+
+/// Fetches a body of `len` bytes twice and returns how many of those reached the backend.
+async fn backend_hits_for_two_gets(len: usize) -> usize {
+    let (backend, hits) = counting_backend().await;
+    let (app, _) = common::test_app(&config(&backend)).await;
+    for _ in 0..2 {
+        let request = Request::builder()
+            .uri(format!("/api/files/{}", len))
+            .body(Body::empty())
+            .unwrap();
+        let response = common::send(&app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body.len(), len);
+    }
+    hits.load(Ordering::SeqCst)
+}
+
+#[tokio::test]
+async fn test_response_below_min_entry_size_is_not_cached() {
+    assert_eq!(backend_hits_for_two_gets(TINY).await, 2);
+}
+
+#[tokio::test]
+async fn test_response_within_band_is_cached() {
+    assert_eq!(backend_hits_for_two_gets(MEDIUM).await, 1);
+}
+
+#[tokio::test]
+async fn test_response_above_max_entry_size_is_not_cached() {
+    assert_eq!(backend_hits_for_two_gets(LARGE).await, 2);
+}
+
+#[test]
+fn test_min_above_max_is_rejected() {
+    let yaml = config("http://backend:8000").replace("min_entry_size: 1KB", "min_entry_size: 8KB");
+    let result = GatewayConfig::from_yaml(&yaml);
+    assert!(matches!(result, Err(ConfigError::InvalidCache { route, .. }) if route == "/api/files"));
+}
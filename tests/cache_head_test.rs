@@ -0,0 +1,90 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use axum::{Router, body::Body, routing::any};
+use http::{Method, Request, StatusCode, header::CONTENT_LENGTH};
+use http_body_util::BodyExt;
+use tokio::net::TcpListener;
+
+const BODY: &str = r#"{"products":["a","b","c"]}"#;
+
+/// A backend that counts the requests it receives.
+async fn counting_backend() -> (String, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let app = Router::new().fallback(any(move || {
+        let counter = counter.clone();
+        async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            BODY
+        }
+    }));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, hits)
+}
+
+async fn app() -> (Router, Arc<AtomicUsize>) {
+    let (backend, hits) = counting_backend().await;
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: products
+    path: /api/products
+    destination: "{backend}"
+    cache: {{ttl: 60s}}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend
+    ))
+    .await;
+    (app, hits)
+}
+
+fn request(method: Method) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri("/api/products/list")
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_head_is_served_from_cached_get() {
+    let (app, hits) = app().await;
+
+    let get = common::send(&app, request(Method::GET)).await;
+    assert_eq!(get.status(), StatusCode::OK);
+    assert_eq!(get.into_body().collect().await.unwrap().to_bytes(), BODY.as_bytes());
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    let head = common::send(&app, request(Method::HEAD)).await;
+    assert_eq!(head.status(), StatusCode::OK);
+    assert_eq!(head.headers()[CONTENT_LENGTH], BODY.len().to_string().as_str());
+    assert_eq!(head.headers().get_all(CONTENT_LENGTH).iter().count(), 1);
+    assert!(head.into_body().collect().await.unwrap().to_bytes().is_empty());
+    assert_eq!(hits.load(Ordering::SeqCst), 1, "HEAD must not reach the backend");
+}
+
+#[tokio::test]
+async fn test_uncached_head_does_not_populate_get_entry() {
+    let (app, hits) = app().await;
+
+    common::send(&app, request(Method::HEAD)).await;
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    // The GET still goes upstream and gets the full body
+    let get = common::send(&app, request(Method::GET)).await;
+    assert_eq!(get.into_body().collect().await.unwrap().to_bytes(), BODY.as_bytes());
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,80 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use axum::{Router, body::Body, routing::any};
+use http::{Request, StatusCode, Uri};
+use rustway::middleware::cache::cache::cache_key;
+use tokio::net::TcpListener;
+
+/// A backend that counts the requests it receives.
+async fn counting_backend() -> (String, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let app = Router::new().fallback(any(move || {
+        let counter = counter.clone();
+        async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            "results"
+        }
+    }));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, hits)
+}
+
+async fn app() -> (Router, Arc<AtomicUsize>) {
+    let (backend, hits) = counting_backend().await;
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: search
+    path: /search
+    destination: "{backend}"
+    cache:
+      ttl: 60s
+      key_query_params: [q]
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend
+    ))
+    .await;
+    (app, hits)
+}
+
+async fn get(app: &Router, uri: &str) {
+    let response = common::send(app, Request::builder().uri(uri).body(Body::empty()).unwrap()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_unlisted_params_share_an_entry() {
+    let (app, hits) = app().await;
+
+    get(&app, "/search?q=shoes&session=a").await;
+    get(&app, "/search?session=b&q=shoes").await;
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+    get(&app, "/search?q=boots&session=a").await;
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_key_keeps_only_listed_params_in_sorted_order() {
+    let allowed = ["q".to_string(), "page".to_string()];
+    let uri: Uri = "/search?session=x&q=shoes&page=2".parse().unwrap();
+    assert_eq!(cache_key(&uri, Some(&allowed)), "/search?page=2&q=shoes");
+
+    let uri: Uri = "/search?session=x".parse().unwrap();
+    assert_eq!(cache_key(&uri, Some(&allowed)), "/search");
+    assert_eq!(cache_key(&uri, None), "/search?session=x");
+}
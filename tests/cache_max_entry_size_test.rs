@@ -0,0 +1,105 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use axum::{Router, body::Body, routing::get};
+use futures::stream;
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use tokio::net::TcpListener;
+
+const LARGE: usize = 8 * 1024;
+
+/// Serves `/small`, `/large` and `/large-chunked` (no content-length), counting hits.
+async fn counting_backend() -> (String, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let (small, large, chunked) = (hits.clone(), hits.clone(), hits.clone());
+    let app = Router::new()
+        .route(
+            "/small",
+            get(move || async move {
+                small.fetch_add(1, Ordering::SeqCst);
+                "small"
+            }),
+        )
+        .route(
+            "/large",
+            get(move || async move {
+                large.fetch_add(1, Ordering::SeqCst);
+                "x".repeat(LARGE)
+            }),
+        )
+        .route(
+            "/large-chunked",
+            get(move || async move {
+                chunked.fetch_add(1, Ordering::SeqCst);
+                let chunks = (0..8).map(|_| Ok::<_, std::io::Error>(vec![b'x'; LARGE / 8]));
+                Body::from_stream(stream::iter(chunks))
+            }),
+        );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, hits)
+}
+
+async fn app() -> (Router, Arc<AtomicUsize>) {
+    let (backend, hits) = counting_backend().await;
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: files
+    path: /api/files
+    destination: "{backend}"
+    cache: {{ttl: 60s, max_entry_size: 4KB}}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend
+    ))
+    .await;
+    (app, hits)
+}
+
+/// Fetches `path` twice and returns how many of those reached the backend.
+async fn backend_hits_for_two_gets(app: &Router, hits: &AtomicUsize, path: &str, expected_len: usize) -> usize {
+    let before = hits.load(Ordering::SeqCst);
+    for _ in 0..2 {
+        let response = common::send(app, Request::builder().uri(path).body(Body::empty()).unwrap()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(body.len(), expected_len);
+    }
+    hits.load(Ordering::SeqCst) - before
+}
+
+#[tokio::test]
+async fn test_response_within_max_entry_size_is_cached() {
+    let (app, hits) = app().await;
+    assert_eq!(backend_hits_for_two_gets(&app, &hits, "/api/files/small", 5).await, 1);
+}
+
+#[tokio::test]
+async fn test_response_over_max_entry_size_is_served_but_not_cached() {
+    let (app, hits) = app().await;
+    assert_eq!(
+        backend_hits_for_two_gets(&app, &hits, "/api/files/large", LARGE).await,
+        2
+    );
+}
+
+#[tokio::test]
+async fn test_chunked_response_over_max_entry_size_is_not_cached() {
+    let (app, hits) = app().await;
+    assert_eq!(
+        backend_hits_for_two_gets(&app, &hits, "/api/files/large-chunked", LARGE).await,
+        2
+    );
+}
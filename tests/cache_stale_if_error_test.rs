@@ -0,0 +1,127 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU8, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::{Router, body::Body, http::StatusCode, routing::get};
+use http::Request;
+use http_body_util::BodyExt;
+use tokio::net::TcpListener;
+
+const HEALTHY: u8 = 0;
+const FAILING: u8 = 1;
+const HANGING: u8 = 2;
+
+/// A backend whose behaviour the test switches: healthy, answering 503, or
+/// too slow for the route's timeout.
+async fn switchable_backend() -> (String, Arc<AtomicU8>) {
+    let mode = Arc::new(AtomicU8::new(HEALTHY));
+    let current = mode.clone();
+    let app = Router::new().route(
+        "/data",
+        get(move || {
+            let current = current.clone();
+            async move {
+                match current.load(Ordering::SeqCst) {
+                    HEALTHY => (StatusCode::OK, "fresh-body"),
+                    FAILING => (StatusCode::SERVICE_UNAVAILABLE, "down"),
+                    _ => {
+                        tokio::time::sleep(Duration::from_secs(2)).await;
+                        (StatusCode::OK, "too-late")
+                    }
+                }
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, mode)
+}
+
+async fn app(stale_if_error: &str) -> (Router, Arc<AtomicU8>) {
+    let (backend, mode) = switchable_backend().await;
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: data
+    path: /api/data
+    destination: "{backend}/data"
+    timeout: 200ms
+    cache: {{ttl: 100ms, stale_if_error: {stale_if_error}}}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#
+    ))
+    .await;
+    (app, mode)
+}
+
+async fn get_data(app: &Router) -> (StatusCode, Option<String>, String) {
+    let response = common::send(app, Request::builder().uri("/api/data").body(Body::empty()).unwrap()).await;
+    let status = response.status();
+    let x_cache = response
+        .headers()
+        .get("x-cache")
+        .map(|v| v.to_str().unwrap().to_string());
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, x_cache, String::from_utf8_lossy(&body).into_owned())
+}
+
+#[tokio::test]
+async fn test_backend_5xx_serves_stale_entry() {
+    let (app, mode) = app("10s").await;
+    assert_eq!(get_data(&app).await.2, "fresh-body");
+
+    mode.store(FAILING, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let (status, x_cache, body) = get_data(&app).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(x_cache.as_deref(), Some("STALE-ERROR"));
+    assert_eq!(body, "fresh-body");
+}
+
+#[tokio::test]
+async fn test_unresponsive_backend_serves_stale_entry() {
+    let (app, mode) = app("10s").await;
+    get_data(&app).await;
+
+    mode.store(HANGING, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let (status, x_cache, body) = get_data(&app).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(x_cache.as_deref(), Some("STALE-ERROR"));
+    assert_eq!(body, "fresh-body");
+}
+
+#[tokio::test]
+async fn test_error_passes_through_once_window_is_over() {
+    let (app, mode) = app("100ms").await;
+    get_data(&app).await;
+
+    mode.store(FAILING, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(400)).await;
+
+    let (status, x_cache, _) = get_data(&app).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(x_cache, None);
+}
+
+#[tokio::test]
+async fn test_error_without_cached_entry_passes_through() {
+    let (app, mode) = app("10s").await;
+    mode.store(FAILING, Ordering::SeqCst);
+
+    assert_eq!(get_data(&app).await.0, StatusCode::SERVICE_UNAVAILABLE);
+}
@@ -0,0 +1,122 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use common::harness::TestGateway;
+use rustway::{config::CanaryConfig, features::canary::CanaryTracker};
+use serde_json::Value;
+
+fn canary(weight: u8) -> CanaryConfig {
+    CanaryConfig {
+        destination: "http://canary:8000".to_string(),
+        weight,
+        error_rate_threshold: 0.5,
+        min_requests: 4,
+        window: "60s".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn test_failing_canary_is_drained_back_to_stable() {
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: checkout
+    path: /api/checkout
+    destination: "{backend}/echo"
+    canary:
+      destination: "{backend}/status/500"
+      weight: 50
+      error_rate_threshold: 0.5
+      min_requests: 4
+"#,
+    )
+    .await;
+    let url = format!("{}/api/checkout", gateway.base_url);
+
+    // Half the traffic hits the 500ing canary until four canary requests are seen
+    let mut canary_errors = 0;
+    for _ in 0..60 {
+        if reqwest::get(&url).await.unwrap().status() == 500 {
+            canary_errors += 1;
+        }
+    }
+    assert_eq!(
+        canary_errors, 4,
+        "canary should be rolled back after min_requests failures"
+    );
+
+    // After rollback every request goes to the stable destination
+    for _ in 0..20 {
+        let resp = reqwest::get(&url).await.unwrap();
+        assert_eq!(resp.status(), 200);
+        let body: Value = resp.json().await.unwrap();
+        assert_eq!(body["path"], "/echo");
+    }
+}
+
+#[test]
+fn test_canary_rolls_back_only_past_error_budget() {
+    let tracker = CanaryTracker::new();
+    let config = canary(100);
+
+    // 1 failure in 4 is under the 50% budget
+    for failed in [false, false, true, false] {
+        tracker.record("orders", &config, failed);
+    }
+    assert!(!tracker.is_rolled_back("orders", &config));
+    assert!(tracker.routes_to_canary("orders", &config));
+
+    for _ in 0..4 {
+        tracker.record("orders", &config, true);
+    }
+    assert!(tracker.is_rolled_back("orders", &config));
+    assert!(!tracker.routes_to_canary("orders", &config));
+}
+
+#[test]
+fn test_new_canary_destination_starts_fresh() {
+    let tracker = CanaryTracker::new();
+    let old = canary(100);
+    for _ in 0..4 {
+        tracker.record("orders", &old, true);
+    }
+    assert!(tracker.is_rolled_back("orders", &old));
+
+    let new = CanaryConfig {
+        destination: "http://canary-v2:8000".to_string(),
+        ..canary(100)
+    };
+    assert!(!tracker.is_rolled_back("orders", &new));
+    assert!(tracker.routes_to_canary("orders", &new));
+}
+
+#[test]
+fn test_zero_weight_never_routes_to_canary() {
+    let tracker = CanaryTracker::new();
+    let config = canary(0);
+    assert!((0..100).all(|_| !tracker.routes_to_canary("orders", &config)));
+}
+
+#[test]
+fn test_invalid_canary_fails_validation() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: checkout
+    path: /api/checkout
+    destination: http://stable:8000
+    canary:
+      destination: http://canary:8000
+      weight: 150
+      error_rate_threshold: 2.0
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    );
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(err.contains("weight must be at most 100"), "{}", err);
+    assert!(err.contains("error_rate_threshold must be in (0, 1]"), "{}", err);
+}
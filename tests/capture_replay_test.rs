@@ -0,0 +1,169 @@
+//! Captures requests through the full router and replays them against a
+//! recording backend.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{Router, body::Body, extract::Request, routing::any};
+use http::{HeaderMap, Method};
+use rustway::features::capture::{CapturedRequest, REDACTED, replay_file};
+
+fn capture_path(test: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("rustygw-capture-{}-{}.jsonl", test, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn config(path: &Path, sample_rate: f64, max_requests: u64) -> String {
+    format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: docs
+    path: /api/docs
+    static_file: "{root}"
+observability:
+  capture:
+    path: "{path}"
+    sample_rate: {sample_rate}
+    max_requests: {max_requests}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        root = std::env::temp_dir().display(),
+        path = path.display(),
+        sample_rate = sample_rate,
+        max_requests = max_requests,
+    )
+}
+
+async fn read_records(path: &Path, expected: usize) -> Vec<CapturedRequest> {
+    for _ in 0..50 {
+        let contents = tokio::fs::read_to_string(path).await.unwrap_or_default();
+        let records: Vec<CapturedRequest> = contents.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        if records.len() >= expected {
+            return records;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("capture file never reached {} records", expected);
+}
+
+#[derive(Debug, Clone)]
+struct Received {
+    method: Method,
+    uri: String,
+    headers: HeaderMap,
+    body: Vec<u8>,
+}
+
+async fn recording_backend() -> (String, Arc<Mutex<Vec<Received>>>) {
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let sink = received.clone();
+    let app = Router::new().fallback(any(move |req: Request| {
+        let sink = sink.clone();
+        async move {
+            let (parts, body) = req.into_parts();
+            let body = axum::body::to_bytes(body, usize::MAX).await.unwrap().to_vec();
+            sink.lock().unwrap().push(Received {
+                method: parts.method,
+                uri: parts.uri.to_string(),
+                headers: parts.headers,
+                body,
+            });
+            "ok"
+        }
+    }));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}", addr), received)
+}
+
+#[tokio::test]
+async fn captured_request_replays_with_same_method_path_headers_and_body() {
+    let path = capture_path("replay");
+    let (app, _state) = common::test_app(&config(&path, 1.0, 100)).await;
+
+    let request = Request::builder()
+        .method(Method::POST)
+        .uri("/api/docs/orders?source=mobile")
+        .header("content-type", "application/json")
+        .header("content-length", "17")
+        .header("x-tenant", "acme")
+        .header("authorization", "Bearer secret-token")
+        .body(Body::from(r#"{"sku":"abc-123"}"#))
+        .unwrap();
+    common::send(&app, request).await;
+
+    let records = read_records(&path, 1).await;
+    assert_eq!(records.len(), 1);
+    let auth = records[0].headers.iter().find(|(k, _)| k == "authorization").unwrap();
+    assert_eq!(auth.1, REDACTED);
+
+    let (target, received) = recording_backend().await;
+    let summary = replay_file(&path, &target, &reqwest::Client::new()).await.unwrap();
+    assert_eq!(summary.sent, 1);
+    assert_eq!(summary.failed, 0);
+
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 1);
+    let replayed = &received[0];
+    assert_eq!(replayed.method, Method::POST);
+    assert_eq!(replayed.uri, "/api/docs/orders?source=mobile");
+    assert_eq!(replayed.body, br#"{"sku":"abc-123"}"#);
+    assert_eq!(replayed.headers["x-tenant"], "acme");
+    assert_eq!(replayed.headers["content-type"], "application/json");
+    assert!(
+        replayed.headers.get("authorization").is_none(),
+        "redacted headers must not be replayed"
+    );
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn capture_stops_at_max_requests() {
+    let path = capture_path("bounded");
+    let (app, _state) = common::test_app(&config(&path, 1.0, 2)).await;
+
+    for i in 0..5 {
+        let request = Request::builder()
+            .uri(format!("/api/docs/item-{}", i))
+            .body(Body::empty())
+            .unwrap();
+        common::send(&app, request).await;
+    }
+
+    let records = read_records(&path, 2).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let records_after = read_records(&path, 2).await;
+    assert_eq!(records.len(), 2);
+    assert_eq!(records_after.len(), 2);
+    assert_eq!(records[0].uri, "/api/docs/item-0");
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn zero_sample_rate_captures_nothing() {
+    let path = capture_path("disabled");
+    let (app, _state) = common::test_app(&config(&path, 0.0, 100)).await;
+
+    let request = Request::builder()
+        .uri("/api/docs/anything")
+        .body(Body::empty())
+        .unwrap();
+    common::send(&app, request).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    assert_eq!(std::fs::read_to_string(&path).unwrap_or_default(), "");
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,101 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use axum::{Router, body::Body};
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tokio::net::TcpListener;
+
+async fn app(case_insensitive: bool) -> Router {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+  case_insensitive_paths: {case_insensitive}
+routes:
+  - name: public
+    path: /test/public
+    destination: "{backend}/echo"
+  - name: user
+    path: /users/{{userId}}
+    destination: "{backend}/profiles/{{userId}}"
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend,
+        case_insensitive = case_insensitive
+    ))
+    .await;
+    app
+}
+
+/// Status and the path the echo backend received.
+async fn backend_path(app: &Router, uri: &str) -> (StatusCode, Option<String>) {
+    let response = common::send(app, Request::builder().uri(uri).body(Body::empty()).unwrap()).await;
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let path = serde_json::from_slice::<Value>(&body)
+        .ok()
+        .and_then(|v| v["path"].as_str().map(str::to_string));
+    (status, path)
+}
+
+#[tokio::test]
+async fn test_mixed_case_path_matches_and_keeps_original_casing() {
+    let app = app(true).await;
+
+    let (status, path) = backend_path(&app, "/TEST/Public/Docs/ReadMe").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(path.as_deref(), Some("/echo/Docs/ReadMe"));
+
+    let (status, path) = backend_path(&app, "/TEST/Public").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(path.as_deref(), Some("/echo"));
+}
+
+#[tokio::test]
+async fn test_path_params_keep_their_casing() {
+    let app = app(true).await;
+
+    let (status, path) = backend_path(&app, "/USERS/AbC123").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(path.as_deref(), Some("/profiles/AbC123"));
+}
+
+#[tokio::test]
+async fn test_paths_are_case_sensitive_by_default() {
+    let app = app(false).await;
+
+    let (status, _) = backend_path(&app, "/TEST/Public").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = backend_path(&app, "/test/public/docs").await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[test]
+fn test_paths_differing_only_in_case_are_duplicates() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+  case_insensitive_paths: true
+routes:
+  - name: lower
+    path: /api/users
+    destination: http://localhost:9001
+  - name: upper
+    path: /API/Users
+    destination: http://localhost:9002
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    );
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(err.contains("Route 'upper' duplicates path"), "{}", err);
+}
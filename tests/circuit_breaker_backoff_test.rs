@@ -0,0 +1,173 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::time::Duration;
+
+use rustway::{
+    config::CircuitBreakerConfig,
+    features::circuit_breaker::circuit_breaker::{CircuitState, State},
+};
+
+fn backoff_config(max_open_duration: Option<&str>) -> CircuitBreakerConfig {
+    CircuitBreakerConfig {
+        failure_threshold: 1,
+        success_threshold: 1,
+        open_duration: "50ms".to_string(),
+        max_open_duration: max_open_duration.map(str::to_string),
+        backoff_multiplier: 2.0,
+        backoff_reset_after: None,
+    }
+}
+
+async fn open_for(circuit: &CircuitState) -> Duration {
+    match *circuit.state.read().await {
+        State::Open { open_for, .. } => open_for,
+        ref other => panic!("expected an open circuit, got {:?}", other),
+    }
+}
+
+/// Waits out the open window, then fails the half-open trial request.
+async fn fail_trial(circuit: &CircuitState, config: &CircuitBreakerConfig) {
+    tokio::time::sleep(open_for(circuit).await + Duration::from_millis(20)).await;
+    assert!(circuit.allow_request("backend").await);
+    circuit.record("backend", true, config).await;
+}
+
+#[tokio::test]
+async fn test_repeated_trips_back_off() {
+    let config = backoff_config(Some("200ms"));
+    let circuit = CircuitState::new();
+
+    circuit.record("backend", true, &config).await;
+    assert_eq!(open_for(&circuit).await, Duration::from_millis(50));
+
+    fail_trial(&circuit, &config).await;
+    assert_eq!(open_for(&circuit).await, Duration::from_millis(100));
+
+    fail_trial(&circuit, &config).await;
+    assert_eq!(open_for(&circuit).await, Duration::from_millis(200));
+
+    // Capped at max_open_duration
+    fail_trial(&circuit, &config).await;
+    assert_eq!(open_for(&circuit).await, Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn test_open_circuit_rejects_until_backed_off_duration_passes() {
+    let config = backoff_config(Some("1s"));
+    let circuit = CircuitState::new();
+
+    circuit.record("backend", true, &config).await;
+    fail_trial(&circuit, &config).await;
+
+    // Second trip is open for 100ms, so the original 50ms no longer lets a trial through
+    tokio::time::sleep(Duration::from_millis(70)).await;
+    assert!(!circuit.allow_request("backend").await);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(circuit.allow_request("backend").await);
+}
+
+#[tokio::test]
+async fn test_backoff_resets_after_staying_closed() {
+    let mut config = backoff_config(Some("1s"));
+    config.backoff_reset_after = Some("50ms".to_string());
+    let circuit = CircuitState::new();
+
+    circuit.record("backend", true, &config).await;
+    fail_trial(&circuit, &config).await;
+    assert_eq!(open_for(&circuit).await, Duration::from_millis(100));
+
+    // Trial succeeds and the circuit closes
+    tokio::time::sleep(Duration::from_millis(120)).await;
+    assert!(circuit.allow_request("backend").await);
+    circuit.record("backend", false, &config).await;
+    assert!(matches!(*circuit.state.read().await, State::Closed { .. }));
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    circuit.record("backend", true, &config).await;
+    assert_eq!(open_for(&circuit).await, Duration::from_millis(50));
+}
+
+#[tokio::test]
+async fn test_backoff_grows_when_flapping_after_a_recovery() {
+    let mut config = backoff_config(Some("1s"));
+    config.failure_threshold = 2;
+    config.backoff_reset_after = Some("50ms".to_string());
+    let circuit = CircuitState::new();
+
+    circuit.record("backend", true, &config).await;
+    circuit.record("backend", true, &config).await;
+    assert_eq!(open_for(&circuit).await, Duration::from_millis(50));
+
+    // Recovers and stays closed past the reset window
+    tokio::time::sleep(Duration::from_millis(70)).await;
+    assert!(circuit.allow_request("backend").await);
+    circuit.record("backend", false, &config).await;
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    circuit.record("backend", true, &config).await;
+    circuit.record("backend", true, &config).await;
+    assert_eq!(open_for(&circuit).await, Duration::from_millis(50));
+
+    // A failed trial falls back to closed below the threshold, then trips again
+    fail_trial(&circuit, &config).await;
+    assert!(matches!(
+        *circuit.state.read().await,
+        State::Closed {
+            consecutive_failures: 1
+        }
+    ));
+    circuit.record("backend", true, &config).await;
+    assert_eq!(open_for(&circuit).await, Duration::from_millis(100));
+
+    fail_trial(&circuit, &config).await;
+    circuit.record("backend", true, &config).await;
+    assert_eq!(open_for(&circuit).await, Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn test_without_max_open_duration_stays_fixed() {
+    let config = backoff_config(None);
+    let circuit = CircuitState::new();
+
+    circuit.record("backend", true, &config).await;
+    fail_trial(&circuit, &config).await;
+    fail_trial(&circuit, &config).await;
+    assert_eq!(open_for(&circuit).await, Duration::from_millis(50));
+}
+
+#[test]
+fn test_open_duration_for() {
+    let mut config = backoff_config(Some("1s"));
+    config.backoff_multiplier = 3.0;
+    assert_eq!(config.open_duration_for(1), Duration::from_millis(50));
+    assert_eq!(config.open_duration_for(2), Duration::from_millis(150));
+    assert_eq!(config.open_duration_for(3), Duration::from_millis(450));
+    assert_eq!(config.open_duration_for(4), Duration::from_secs(1));
+    assert_eq!(config.open_duration_for(100), Duration::from_secs(1));
+}
+
+#[test]
+fn test_invalid_backoff_fails_validation() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: api
+    path: /api
+    destination: http://localhost:9000
+    circuit_breaker:
+      failure_threshold: 1
+      success_threshold: 1
+      open_duration: 10s
+      max_open_duration: soon
+      backoff_multiplier: 0.5
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    );
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(err.contains("max_open_duration 'soon'"), "{}", err);
+    assert!(err.contains("backoff_multiplier must be at least 1"), "{}", err);
+}
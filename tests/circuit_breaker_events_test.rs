@@ -0,0 +1,126 @@
+//! The circuit breaker event log behind `GET /admin/circuit-breakers/events`.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{collections::HashMap, time::Duration};
+
+use axum::body::Body;
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use rustway::{
+    config::{ApiKeyDetails, ApiKeyStore, CircuitBreakerConfig},
+    features::circuit_breaker::circuit_breaker::{CircuitBreakerStore, CircuitState},
+};
+use serde_json::Value;
+
+fn config() -> CircuitBreakerConfig {
+    CircuitBreakerConfig {
+        failure_threshold: 2,
+        success_threshold: 1,
+        open_duration: "50ms".to_string(),
+        max_open_duration: None,
+        backoff_multiplier: 2.0,
+        backoff_reset_after: None,
+    }
+}
+
+/// Trips the circuit, waits out the open window and passes the trial request.
+async fn trip_and_recover(circuit: &CircuitState, name: &str) {
+    let config = config();
+    circuit.record(name, true, &config).await;
+    circuit.record(name, true, &config).await;
+    tokio::time::sleep(Duration::from_millis(70)).await;
+    assert!(circuit.allow_request(name).await);
+    circuit.record(name, false, &config).await;
+}
+
+#[tokio::test]
+async fn test_trip_and_recovery_recorded_in_order() {
+    let store = CircuitBreakerStore::new().with_events(16);
+    trip_and_recover(&store.get_or_insert("orders"), "orders").await;
+
+    let events = store.events();
+    let transitions: Vec<(&str, &str, &str)> = events.iter().map(|e| (e.circuit.as_str(), e.from, e.to)).collect();
+    assert_eq!(
+        transitions,
+        [
+            ("orders", "closed", "open"),
+            ("orders", "open", "half_open"),
+            ("orders", "half_open", "closed"),
+        ]
+    );
+    assert!(
+        events[0].reason.contains("2 consecutive failures"),
+        "{}",
+        events[0].reason
+    );
+    assert!(events.windows(2).all(|pair| pair[0].at_ms <= pair[1].at_ms));
+}
+
+#[tokio::test]
+async fn test_event_log_is_bounded() {
+    let store = CircuitBreakerStore::new().with_events(2);
+    trip_and_recover(&store.get_or_insert("orders"), "orders").await;
+
+    let kept: Vec<(&str, &str)> = store.events().iter().map(|e| (e.from, e.to)).collect();
+    assert_eq!(kept, [("open", "half_open"), ("half_open", "closed")]);
+}
+
+#[tokio::test]
+async fn test_no_events_kept_without_log() {
+    let store = CircuitBreakerStore::new().with_events(0);
+    trip_and_recover(&store.get_or_insert("orders"), "orders").await;
+    assert!(store.events().is_empty());
+}
+
+#[tokio::test]
+async fn test_admin_endpoint_lists_events() {
+    let key_store = ApiKeyStore {
+        keys: HashMap::from([(
+            "ops-key".to_string(),
+            ApiKeyDetails {
+                user_id: "ops".to_string(),
+                roles: vec!["admin".to_string()],
+                status: "active".to_string(),
+            },
+        )]),
+        ..Default::default()
+    };
+    let (app, state) = common::test_app_with_keys(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+  admin:
+    auth: {type: ApiKey, roles: [admin]}
+routes:
+  - name: orders
+    path: /api/orders
+    destination: http://localhost:9001
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        key_store,
+    )
+    .await;
+    trip_and_recover(&state.circuit_breaker_store.get_or_insert("orders"), "orders").await;
+
+    let request = Request::builder()
+        .uri("/admin/circuit-breakers/events")
+        .header("Authorization", "Bearer ops-key")
+        .body(Body::empty())
+        .unwrap();
+    let response = common::send(&app, request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes()).unwrap();
+
+    let events = body["events"].as_array().unwrap();
+    assert_eq!(events.len(), 3);
+    assert_eq!(events[0]["circuit"], "orders");
+    assert_eq!(
+        (events[0]["from"].as_str(), events[0]["to"].as_str()),
+        (Some("closed"), Some("open"))
+    );
+    assert_eq!(events[2]["to"], "closed");
+    assert!(events[0]["at_ms"].as_u64().unwrap() > 0);
+}
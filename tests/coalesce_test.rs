@@ -0,0 +1,256 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use axum::body::Body;
+use futures::future::join_all;
+use http::{Request, StatusCode};
+use jsonwebtoken::{EncodingKey, Header, encode};
+use rustway::{
+    features::{
+        auth::auth::Claims,
+        coalesce::{Flight, RequestCoalescer},
+    },
+    state::AppState,
+};
+use tokio::net::TcpListener;
+
+async fn start_backend() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+    url
+}
+
+async fn app(coalesce: bool) -> (axum::Router, std::sync::Arc<AppState>) {
+    let backend = start_backend().await;
+    common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: report
+    path: /api/report
+    destination: "{backend}/delay/300"
+    coalesce: {coalesce}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend,
+        coalesce = coalesce
+    ))
+    .await
+}
+
+async fn concurrent_gets(app: &axum::Router, count: usize) -> Vec<StatusCode> {
+    let requests = (0..count).map(|_| {
+        let req = Request::builder().uri("/api/report").body(Body::empty()).unwrap();
+        common::send(app, req)
+    });
+    join_all(requests).await.into_iter().map(|r| r.status()).collect()
+}
+
+#[tokio::test]
+async fn test_concurrent_identical_requests_are_coalesced() {
+    let (app, state) = app(true).await;
+
+    let statuses = concurrent_gets(&app, 5).await;
+    assert!(statuses.iter().all(|s| *s == StatusCode::OK), "{:?}", statuses);
+
+    // One leader went upstream; the other four waited on it
+    assert_eq!(state.request_coalescer.coalesced_count(), 4);
+    assert_eq!(state.request_coalescer.leaders_in_flight(), 0);
+}
+
+/// A coalescing route behind `auth`, then two concurrent GETs from each of
+/// the callers identified by `headers`.
+async fn coalesced_as(auth: &str, headers: &[(&str, &str)]) -> u64 {
+    let backend = start_backend().await;
+    let (app, state) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: report
+    path: /api/report
+    destination: "{backend}/delay/300"
+    coalesce: true
+    auth: {auth}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend,
+        auth = auth
+    ))
+    .await;
+
+    let requests = headers
+        .iter()
+        .flat_map(|(name, value)| [(name, value), (name, value)])
+        .map(|(name, value)| {
+            let req = Request::builder()
+                .uri("/api/report")
+                .header(*name, *value)
+                .body(Body::empty())
+                .unwrap();
+            common::send(&app, req)
+        });
+    let statuses: Vec<_> = join_all(requests).await.into_iter().map(|r| r.status()).collect();
+    assert!(statuses.iter().all(|s| *s == StatusCode::OK), "{:?}", statuses);
+    state.request_coalescer.coalesced_count()
+}
+
+fn jwt(sub: &str) -> String {
+    let exp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 3600;
+    let claims = Claims {
+        sub: sub.to_string(),
+        roles: vec![],
+        exp: usize::try_from(exp).unwrap(),
+        iss: None,
+        aud: None,
+        extra: HashMap::new(),
+    };
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(common::TEST_JWT_SECRET.as_bytes()),
+    )
+    .unwrap();
+    format!("Bearer {}", token)
+}
+
+#[tokio::test]
+async fn test_different_callers_are_not_coalesced_together() {
+    let (alice, bob) = (jwt("alice"), jwt("bob"));
+    let coalesced = coalesced_as(
+        "{type: Jwt}",
+        &[("authorization", alice.as_str()), ("authorization", bob.as_str())],
+    )
+    .await;
+    // Each caller's second request waits on their own first one
+    assert_eq!(coalesced, 2);
+}
+
+#[tokio::test]
+async fn test_different_trusted_identities_are_not_coalesced_together() {
+    let coalesced = coalesced_as(
+        "{type: TrustedHeader, trusted_ips: [127.0.0.1/32]}",
+        &[("x-authenticated-user", "alice"), ("x-authenticated-user", "bob")],
+    )
+    .await;
+    assert_eq!(coalesced, 2);
+}
+
+#[tokio::test]
+async fn test_coalescing_is_off_unless_enabled() {
+    let (app, state) = app(false).await;
+
+    let statuses = concurrent_gets(&app, 3).await;
+    assert!(statuses.iter().all(|s| *s == StatusCode::OK));
+    assert_eq!(state.request_coalescer.coalesced_count(), 0);
+}
+
+#[tokio::test]
+async fn test_cancelled_leader_releases_followers() {
+    let coalescer = RequestCoalescer::new();
+
+    let Flight::Leader(leader) = coalescer.join("report", "GET /a".to_string()) else {
+        panic!("first caller should lead");
+    };
+    let Flight::Follower(mut follower) = coalescer.join("report", "GET /a".to_string()) else {
+        panic!("second caller should follow");
+    };
+    assert_eq!(coalescer.leaders_in_flight(), 1);
+
+    drop(leader);
+    assert!(follower.recv().await.is_err());
+    assert_eq!(coalescer.leaders_in_flight(), 0);
+    assert!(matches!(
+        coalescer.join("report", "GET /a".to_string()),
+        Flight::Leader(_)
+    ));
+}
+
+#[tokio::test]
+async fn test_concurrent_requests_for_different_keys_do_not_serialize() {
+    let backend = start_backend().await;
+    let (app, state) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: report
+    path: /api/report
+    destination: "{backend}/delay/300"
+    coalesce: true
+identity:
+  api_key_store_path: ./api_keys.yaml
+tuning:
+  coalesce_shards: 2
+"#,
+        backend = backend
+    ))
+    .await;
+
+    let started = Instant::now();
+    let requests = (0..8).map(|page| {
+        let req = Request::builder()
+            .uri(format!("/api/report?page={}", page))
+            .body(Body::empty())
+            .unwrap();
+        common::send(&app, req)
+    });
+    let statuses: Vec<_> = join_all(requests).await.into_iter().map(|r| r.status()).collect();
+    assert!(statuses.iter().all(|s| *s == StatusCode::OK), "{:?}", statuses);
+
+    // Each leads its own flight; one after another would take 8 x 300ms
+    assert_eq!(state.request_coalescer.coalesced_count(), 0);
+    assert!(
+        started.elapsed() < Duration::from_millis(1200),
+        "{:?}",
+        started.elapsed()
+    );
+}
+
+#[test]
+fn test_leaders_of_different_keys_share_shards_without_blocking() {
+    let coalescer = RequestCoalescer::with_shards(2);
+
+    // More leaders held at once than there are shards
+    let leaders: Vec<_> = (0..16)
+        .map(|i| match coalescer.join("report", format!("GET /{}", i)) {
+            Flight::Leader(leader) => leader,
+            Flight::Follower(_) => panic!("key {} should get its own leader", i),
+        })
+        .collect();
+    assert_eq!(coalescer.leaders_in_flight(), 16);
+
+    drop(leaders);
+    assert_eq!(coalescer.leaders_in_flight(), 0);
+}
+
+#[test]
+fn test_coalesce_shards_must_be_a_power_of_two() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes: []
+identity:
+  api_key_store_path: ./api_keys.yaml
+tuning:
+  coalesce_shards: 12
+"#,
+    );
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(
+        err.contains("coalesce_shards must be a power of two greater than 1, got 12"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,170 @@
+//! Starts the gateway and an example backend in-process on ephemeral ports.
+//! Shared by integration tests and benchmarks so neither depends on
+//! externally started services.
+#![allow(dead_code)]
+
+use std::{collections::HashMap, net::SocketAddr, time::Duration};
+
+use axum::{
+    Json, Router,
+    extract::{
+        Path, Query, RawQuery,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    http::{HeaderMap, Method, StatusCode, header::LOCATION},
+    response::IntoResponse,
+    routing::{any, get},
+};
+use rustway::{
+    config::{ApiKeyStore, GatewayConfig, SecretsConfig},
+    run_with_shutdown,
+};
+use serde_json::{Value, json};
+use tokio::{net::TcpListener, task::JoinHandle};
+
+pub const HARNESS_JWT_SECRET: &str = "harness-secret";
+
+/// A running gateway plus backend. Both are stopped when this is dropped;
+/// call `shutdown` to wait for them to actually stop.
+pub struct TestGateway {
+    pub base_url: String,
+    pub backend_url: String,
+    pub gateway_addr: SocketAddr,
+    gateway: JoinHandle<()>,
+    backend: JoinHandle<()>,
+}
+
+impl TestGateway {
+    /// Starts the gateway with the given `routes:` YAML. Every `{backend}`
+    /// in it is replaced with the example backend's base URL.
+    pub async fn start(routes_yaml: &str) -> Self {
+        let backend_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let backend_addr = backend_listener.local_addr().unwrap();
+        let backend_url = format!("http://{}", backend_addr);
+        let backend = tokio::spawn(async move {
+            axum::serve(backend_listener, example_backend()).await.unwrap();
+        });
+
+        let yaml = format!(
+            "server:\n  addr: \"127.0.0.1:0\"\nidentity:\n  api_key_store_path: \"./api_keys.yaml\"\n{}",
+            routes_yaml.replace("{backend}", &backend_url)
+        );
+        let config = GatewayConfig::from_yaml(&yaml).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let gateway_addr = listener.local_addr().unwrap();
+        let gateway = tokio::spawn(async move {
+            run_with_shutdown(
+                config,
+                SecretsConfig {
+                    jwt_secret: HARNESS_JWT_SECRET.to_string(),
+                    ..Default::default()
+                },
+                ApiKeyStore::default(),
+                listener,
+                std::future::pending(),
+            )
+            .await
+            .unwrap();
+        });
+
+        let gateway = Self {
+            base_url: format!("http://{}", gateway_addr),
+            backend_url,
+            gateway_addr,
+            gateway,
+            backend,
+        };
+        gateway.wait_ready().await;
+        gateway
+    }
+
+    async fn wait_ready(&self) {
+        let client = reqwest::Client::new();
+        for _ in 0..50 {
+            if let Ok(resp) = client.get(format!("{}/health", self.base_url)).send().await
+                && resp.status().is_success()
+            {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("gateway at {} did not become ready", self.base_url);
+    }
+
+    pub async fn shutdown(mut self) {
+        self.gateway.abort();
+        self.backend.abort();
+        let _ = (&mut self.gateway).await;
+        let _ = (&mut self.backend).await;
+    }
+}
+
+impl Drop for TestGateway {
+    fn drop(&mut self) {
+        self.gateway.abort();
+        self.backend.abort();
+    }
+}
+
+/// Echoes back what it received (method, path, query, headers), like `tests/mock_service.py`.
+/// `/delay/{ms}` and `/slow?ms=` wait that long before answering; `/status/{code}` answers
+/// with that status; `/redirect/{code}?to=<url>` redirects there; `/ws-echo` is a WebSocket
+/// that echoes each message.
+pub fn example_backend() -> Router {
+    Router::new()
+        .route("/health", get(|| async { "OK" }))
+        .route("/delay/{ms}", any(delay))
+        .route("/slow", any(slow))
+        .route("/status/{code}", any(status))
+        .route("/redirect/{code}", any(redirect))
+        .route("/ws-echo", get(ws_echo))
+        .route("/{*path}", any(echo))
+}
+
+async fn echo(method: Method, Path(path): Path<String>, RawQuery(query): RawQuery, headers: HeaderMap) -> Json<Value> {
+    let headers: HashMap<&str, &str> = headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)))
+        .collect();
+    Json(json!({
+        "service": "example-backend",
+        "method": method.as_str(),
+        "path": format!("/{}", path),
+        "query": query,
+        "headers": headers,
+    }))
+}
+
+async fn ws_echo(ws: WebSocketUpgrade) -> impl IntoResponse {
+    ws.on_upgrade(|mut socket: WebSocket| async move {
+        while let Some(Ok(message)) = socket.recv().await {
+            if matches!(message, Message::Close(_)) || socket.send(message).await.is_err() {
+                break;
+            }
+        }
+    })
+}
+
+async fn delay(Path(ms): Path<u64>) -> Json<Value> {
+    tokio::time::sleep(Duration::from_millis(ms)).await;
+    Json(json!({
+        "service": "example-backend",
+        "delayed_ms": ms,
+    }))
+}
+
+async fn slow(Query(params): Query<HashMap<String, String>>) -> Json<Value> {
+    let ms = params.get("ms").and_then(|ms| ms.parse().ok()).unwrap_or(0);
+    delay(Path(ms)).await
+}
+
+async fn status(Path(code): Path<u16>) -> StatusCode {
+    StatusCode::from_u16(code).unwrap_or(StatusCode::BAD_REQUEST)
+}
+
+async fn redirect(Path(code): Path<u16>, Query(params): Query<HashMap<String, String>>) -> impl IntoResponse {
+    let status = StatusCode::from_u16(code).unwrap_or(StatusCode::FOUND);
+    let location = params.get("to").cloned().unwrap_or_else(|| "/".to_string());
+    (status, [(LOCATION, location)])
+}
@@ -0,0 +1,72 @@
+//! Shared fixtures for tests that exercise middleware against a real `AppState`.
+#![allow(dead_code)]
+
+pub mod harness;
+pub mod tls;
+
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{Router, body::Body, extract::ConnectInfo, response::Response};
+use http::Request;
+use rustway::{
+    build_app, build_state,
+    config::{ApiKeyStore, GatewayConfig, SecretsConfig},
+    state::AppState,
+};
+use tokio::sync::RwLock;
+use tower::ServiceExt;
+
+pub const TEST_JWT_SECRET: &str = "test-secret";
+
+pub fn parse_config(yaml: &str) -> GatewayConfig {
+    serde_yaml::from_str(yaml).unwrap()
+}
+
+pub async fn test_state(yaml: &str) -> Arc<AppState> {
+    test_state_with_keys(yaml, ApiKeyStore::default()).await
+}
+
+pub async fn test_state_with_keys(yaml: &str, key_store: ApiKeyStore) -> Arc<AppState> {
+    let mut config = parse_config(yaml);
+    config.build_route_tree_pub();
+    build_state(
+        Arc::new(RwLock::new(config)),
+        Arc::new(SecretsConfig {
+            jwt_secret: TEST_JWT_SECRET.to_string(),
+            ..Default::default()
+        }),
+        Arc::new(RwLock::new(key_store)),
+        None,
+    )
+    .await
+    .unwrap()
+}
+
+/// The full gateway router for `yaml`, driven in memory with `send`.
+pub async fn test_app(yaml: &str) -> (Router, Arc<AppState>) {
+    test_app_with_keys(yaml, ApiKeyStore::default()).await
+}
+
+pub async fn test_app_with_keys(yaml: &str, key_store: ApiKeyStore) -> (Router, Arc<AppState>) {
+    let config = GatewayConfig::from_yaml(yaml).unwrap();
+    let state = build_state(
+        Arc::new(RwLock::new(config)),
+        Arc::new(SecretsConfig {
+            jwt_secret: TEST_JWT_SECRET.to_string(),
+            ..Default::default()
+        }),
+        Arc::new(RwLock::new(key_store)),
+        None,
+    )
+    .await
+    .unwrap();
+    (build_app(state.clone()).await.unwrap(), state)
+}
+
+/// Sends one request through the router without a socket. The client address
+/// `axum::serve` would normally provide is filled in as 127.0.0.1.
+pub async fn send(app: &Router, mut req: Request<Body>) -> Response {
+    req.extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 40000))));
+    app.clone().oneshot(req).await.unwrap()
+}
@@ -1,4 +1,5 @@
 //! Concurrency tests for race conditions and thread safety.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
 
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -0,0 +1,251 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use rustway::{config::GatewayConfig, errors::ConfigError};
+
+fn validate(yaml: &str) -> Result<(), ConfigError> {
+    let mut cfg: GatewayConfig = serde_yaml::from_str(yaml).unwrap();
+    cfg.resolve_services_pub();
+    cfg.apply_defaults_pub();
+    cfg.validate_pub()
+}
+
+#[test]
+fn test_valid_config_passes() {
+    let result = validate(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: users
+    path: /api/users
+    destination: http://localhost:9001
+identity:
+  api_key_store_path: "./api_keys.yaml"
+"#,
+    );
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_duplicate_route_name() {
+    let result = validate(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: users
+    path: /api/users
+    destination: http://localhost:9001
+  - name: users
+    path: /api/people
+    destination: http://localhost:9001
+identity:
+  api_key_store_path: "./api_keys.yaml"
+"#,
+    );
+    assert!(matches!(result, Err(ConfigError::DuplicateRouteName(name)) if name == "users"));
+}
+
+#[test]
+fn test_duplicate_route_path() {
+    let result = validate(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: users
+    path: /api/users
+    destination: http://localhost:9001
+  - name: users_v2
+    path: /api/users
+    destination: http://localhost:9002
+identity:
+  api_key_store_path: "./api_keys.yaml"
+"#,
+    );
+    assert!(matches!(
+        result,
+        Err(ConfigError::DuplicateRoutePath { path, route, existing })
+            if path == "/api/users" && route == "users_v2" && existing == "users"
+    ));
+}
+
+#[test]
+fn test_missing_destination() {
+    let result = validate(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: broken
+    path: /broken
+identity:
+  api_key_store_path: "./api_keys.yaml"
+"#,
+    );
+    assert!(matches!(result, Err(ConfigError::MissingDestination(path)) if path == "/broken"));
+}
+
+#[test]
+fn test_unknown_service() {
+    let result = validate(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: broken
+    path: /broken
+    service: nonexistent
+identity:
+  api_key_store_path: "./api_keys.yaml"
+"#,
+    );
+    assert!(matches!(result, Err(ConfigError::UnknownService { service, .. }) if service == "nonexistent"));
+}
+
+#[test]
+fn test_invalid_rate_limit_zero_requests() {
+    let result = validate(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: limited
+    path: /limited
+    destination: http://localhost:9001
+    rate_limit:
+      requests: 0
+      period: 1m
+identity:
+  api_key_store_path: "./api_keys.yaml"
+"#,
+    );
+    assert!(matches!(result, Err(ConfigError::InvalidRateLimit { .. })));
+}
+
+#[test]
+fn test_invalid_rate_limit_bad_period() {
+    let result = validate(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: limited
+    path: /limited
+    destination: http://localhost:9001
+    rate_limit:
+      requests: 10
+      period: soon
+identity:
+  api_key_store_path: "./api_keys.yaml"
+"#,
+    );
+    assert!(matches!(result, Err(ConfigError::InvalidRateLimit { route, .. }) if route == "/limited"));
+}
+
+#[test]
+fn test_invalid_cache_bypass_trusted_ip() {
+    let result = validate(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: cached
+    path: /cached
+    destination: http://localhost:9001
+    cache:
+      ttl: 60s
+      bypass_trusted_ips: ["10.0.0.0/8x", "192.168.1.10"]
+identity:
+  api_key_store_path: "./api_keys.yaml"
+"#,
+    );
+    assert!(matches!(
+        result,
+        Err(ConfigError::InvalidCache { route, reason }) if route == "/cached" && reason.contains("'10.0.0.0/8x'")
+    ));
+}
+
+#[test]
+fn test_empty_aggregate_field_and_path() {
+    let result = validate(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: dashboard
+    path: /dashboard
+    aggregate:
+      - service: users
+        path: ""
+        field: ""
+identity:
+  api_key_store_path: "./api_keys.yaml"
+"#,
+    );
+    let errors = result.unwrap_err().into_errors();
+    assert_eq!(errors.len(), 2);
+    assert!(matches!(errors[0], ConfigError::EmptyAggregateField { .. }));
+    assert!(matches!(errors[1], ConfigError::EmptyAggregatePath { .. }));
+}
+
+#[test]
+fn test_config_error_converts_to_anyhow() {
+    let result = validate(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: broken
+    path: /broken
+identity:
+  api_key_store_path: "./api_keys.yaml"
+"#,
+    );
+    let err: anyhow::Error = result.unwrap_err().into();
+    assert!(err.to_string().contains("no destination"));
+}
+
+#[test]
+fn test_load_missing_file_is_io_error() {
+    let result = GatewayConfig::load("/nonexistent/gateway.yaml");
+    assert!(matches!(result, Err(ConfigError::Io(_))));
+}
+
+#[test]
+fn test_load_broken_yaml_reports_file_and_line() {
+    let dir = std::env::temp_dir().join(format!("rustygw-config-syntax-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("broken_gateway.yaml");
+    std::fs::write(
+        &path,
+        "server:\n  addr: \"127.0.0.1:8094\"\nroutes:\n  - name: users\n    path: [unclosed\nidentity:\n  api_key_store_path: \"./api_keys.yaml\"\n",
+    )
+    .unwrap();
+
+    let err = GatewayConfig::load(&path).unwrap_err();
+    let ConfigError::ParseFile { line, .. } = &err else {
+        panic!("expected ParseFile, got {:?}", err);
+    };
+    assert!(line.is_some_and(|l| l >= 5));
+
+    let message = err.to_string();
+    assert!(message.contains("broken_gateway.yaml"), "{}", message);
+    assert!(message.contains("line "), "{}", message);
+}
+
+#[test]
+fn test_load_wrong_type_reports_line() {
+    let dir = std::env::temp_dir().join(format!("rustygw-config-type-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("typed_gateway.yaml");
+    std::fs::write(
+        &path,
+        "server:\n  addr: \"127.0.0.1:8094\"\nroutes:\n  - name: users\n    path: /api/users\n    destination: http://localhost:9001\n    rate_limit:\n      requests: many\n      period: 1m\nidentity:\n  api_key_store_path: \"./api_keys.yaml\"\n",
+    )
+    .unwrap();
+
+    let message = GatewayConfig::load(&path).unwrap_err().to_string();
+    assert!(message.contains("typed_gateway.yaml"), "{}", message);
+    assert!(message.contains("line 8"), "{}", message);
+}
@@ -0,0 +1,105 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use axum::{Router, body::Body};
+use http::{Request, StatusCode, header::CONTENT_TYPE};
+use tokio::net::TcpListener;
+
+async fn app() -> Router {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: bare
+    path: /api/bare
+    destination: "{backend}/status/200"
+    default_content_type: text/plain; charset=utf-8
+  - name: typed
+    path: /api/typed
+    destination: "{backend}/echo"
+    default_content_type: text/plain; charset=utf-8
+  - name: cached
+    path: /api/cached
+    destination: "{backend}/status/200"
+    default_content_type: application/octet-stream
+    cache: {{ttl: 60s}}
+  - name: untouched
+    path: /api/untouched
+    destination: "{backend}/status/200"
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend
+    ))
+    .await;
+    app
+}
+
+async fn content_type(app: &Router, uri: &str) -> Option<String> {
+    let response = common::send(app, Request::builder().uri(uri).body(Body::empty()).unwrap()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .map(|v| v.to_str().unwrap().to_string())
+}
+
+#[tokio::test]
+async fn test_missing_content_type_gets_default() {
+    let app = app().await;
+    assert_eq!(
+        content_type(&app, "/api/bare").await.as_deref(),
+        Some("text/plain; charset=utf-8")
+    );
+}
+
+#[tokio::test]
+async fn test_backend_content_type_is_kept() {
+    let app = app().await;
+    assert_eq!(
+        content_type(&app, "/api/typed").await.as_deref(),
+        Some("application/json")
+    );
+}
+
+#[tokio::test]
+async fn test_cached_response_keeps_default() {
+    let app = app().await;
+    for _ in 0..2 {
+        assert_eq!(
+            content_type(&app, "/api/cached").await.as_deref(),
+            Some("application/octet-stream")
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_no_default_configured_leaves_header_absent() {
+    let app = app().await;
+    assert_eq!(content_type(&app, "/api/untouched").await, None);
+}
+
+#[test]
+fn test_invalid_default_content_type_fails_validation() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: bare
+    path: /api/bare
+    destination: http://localhost:9001
+    default_content_type: "text/plain\n"
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    );
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(err.contains("invalid default_content_type"), "{}", err);
+}
@@ -0,0 +1,158 @@
+//! `degradation` policies, with rate limit and cache stores that can't be reached.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use axum::{Router, body::Body};
+use http::{Request, StatusCode};
+use rustway::{
+    build_app, build_state,
+    config::{ApiKeyStore, FailurePolicy, GatewayConfig, SecretsConfig},
+    errors::StoreUnavailable,
+    features::{
+        cache::ResponseCache,
+        rate_limiter::state::{RateLimitDecision, RateLimitState},
+    },
+    state::CachedResponse,
+};
+use tokio::{net::TcpListener, sync::RwLock};
+
+struct UnreachableStore;
+
+#[async_trait]
+impl RateLimitState for UnreachableStore {
+    async fn check_and_update(
+        &self,
+        _key: &str,
+        _capacity: u64,
+        _refill_rate: f64,
+    ) -> Result<RateLimitDecision, StoreUnavailable> {
+        Err(StoreUnavailable("connection refused".to_string()))
+    }
+}
+
+#[async_trait]
+impl ResponseCache for UnreachableStore {
+    async fn get(&self, _key: &str) -> Result<Option<Arc<CachedResponse>>, StoreUnavailable> {
+        Err(StoreUnavailable("connection refused".to_string()))
+    }
+
+    async fn insert(&self, _key: String, _value: Arc<CachedResponse>, _ttl: Option<Duration>) {}
+
+    async fn invalidate(&self, _key: &str) {}
+}
+
+async fn app(policy: &str) -> Router {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+
+    let config = GatewayConfig::from_yaml(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+degradation:
+  rate_limit: {policy}
+  cache: {policy}
+routes:
+  - name: limited
+    path: /limited
+    destination: "{backend}/echo"
+    rate_limit:
+      requests: 1
+      period: 1m
+  - name: cached
+    path: /cached
+    destination: "{backend}/echo"
+    cache: {{ttl: 60s}}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend,
+        policy = policy
+    ))
+    .unwrap();
+    let state = build_state(
+        Arc::new(RwLock::new(config)),
+        Arc::new(SecretsConfig {
+            jwt_secret: common::TEST_JWT_SECRET.to_string(),
+            ..Default::default()
+        }),
+        Arc::new(RwLock::new(ApiKeyStore::default())),
+        None,
+    )
+    .await
+    .unwrap();
+    let mut state = Arc::into_inner(state).expect("state is not shared yet");
+    state.rate_limit_store = Arc::new(UnreachableStore);
+    state.cache = Arc::new(UnreachableStore);
+    build_app(Arc::new(state)).await.unwrap()
+}
+
+async fn status(app: &Router, uri: &str) -> StatusCode {
+    common::send(app, Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .status()
+}
+
+#[tokio::test]
+async fn test_fail_open_serves_without_the_store() {
+    let app = app("fail_open").await;
+
+    // Past the 1-per-minute limit, since nothing can be counted
+    for _ in 0..3 {
+        assert_eq!(status(&app, "/limited").await, StatusCode::OK);
+    }
+    assert_eq!(status(&app, "/cached").await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_fail_closed_returns_503() {
+    let app = app("fail_closed").await;
+
+    assert_eq!(status(&app, "/limited").await, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(status(&app, "/cached").await, StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[test]
+fn test_degradation_defaults_and_auth_fallback() {
+    let config = GatewayConfig::from_yaml(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+degradation:
+  auth: fail_open
+routes:
+  - name: inherits
+    path: /inherits
+    destination: http://localhost:9001
+    auth: {type: Jwt}
+  - name: explicit
+    path: /explicit
+    destination: http://localhost:9001
+    auth: {type: Jwt, on_error: fail_closed}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.degradation.rate_limit, FailurePolicy::FailOpen);
+    assert_eq!(config.degradation.cache, FailurePolicy::FailOpen);
+    assert_eq!(config.routes[0].auth[0].on_error, Some(FailurePolicy::FailOpen));
+    assert_eq!(config.routes[1].auth[0].on_error, Some(FailurePolicy::FailClosed));
+
+    let defaults = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes: []
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .degradation;
+    assert_eq!(defaults.auth, FailurePolicy::FailClosed);
+}
@@ -0,0 +1,137 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::net::SocketAddr;
+
+use axum::{Router, body::Body, extract::ConnectInfo, routing::get};
+use http::{HeaderMap, Request, StatusCode};
+use http_body_util::BodyExt;
+use tokio::net::TcpListener;
+use tower::ServiceExt;
+
+/// Answers with its name, flagging whether the override header leaked through.
+async fn named_backend(name: &'static str) -> String {
+    let app = Router::new().route(
+        "/{*path}",
+        get(move |headers: HeaderMap| async move {
+            if headers.contains_key("x-route-to") {
+                format!("{}+header", name)
+            } else {
+                name.to_string()
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
+
+async fn app() -> (Router, String, String) {
+    let (a, b) = (named_backend("a").await, named_backend("b").await);
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: orders
+    path: /api/orders
+    destinations: ["{a}", "{b}"]
+    destination_override:
+      trusted_ips: ["127.0.0.1/32"]
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#
+    ))
+    .await;
+    (app, a, b)
+}
+
+/// Sends from `client` with `x-route-to: target`.
+async fn send_from(app: &Router, client: [u8; 4], target: &str) -> (StatusCode, String) {
+    let mut request = Request::builder()
+        .uri("/api/orders/1")
+        .header("x-route-to", target)
+        .body(Body::empty())
+        .unwrap();
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((client, 40000))));
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8_lossy(&body).into_owned())
+}
+
+#[tokio::test]
+async fn test_trusted_client_pins_backend() {
+    let (app, _, b) = app().await;
+
+    for _ in 0..4 {
+        assert_eq!(
+            send_from(&app, [127, 0, 0, 1], &b).await,
+            (StatusCode::OK, "b".to_string())
+        );
+    }
+    // A trailing slash still names the same destination
+    assert_eq!(send_from(&app, [127, 0, 0, 1], &format!("{}/", b)).await.1, "b");
+}
+
+#[tokio::test]
+async fn test_untrusted_client_header_is_ignored() {
+    let (app, _, b) = app().await;
+
+    let mut seen = Vec::new();
+    for _ in 0..4 {
+        let (status, body) = send_from(&app, [10, 1, 2, 3], &b).await;
+        assert_eq!(status, StatusCode::OK);
+        seen.push(body);
+    }
+    assert!(
+        seen.contains(&"a".to_string()),
+        "round robin should still reach a: {:?}",
+        seen
+    );
+    assert!(seen.iter().all(|body| !body.ends_with("+header")));
+}
+
+#[tokio::test]
+async fn test_off_list_target_is_rejected() {
+    let (app, _, _) = app().await;
+
+    let (status, body) = send_from(&app, [127, 0, 0, 1], "http://169.254.169.254").await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert!(body.contains("169.254.169.254"), "{}", body);
+
+    // An untrusted client gets no hint that the header means anything
+    assert_eq!(
+        send_from(&app, [10, 1, 2, 3], "http://169.254.169.254").await.0,
+        StatusCode::OK
+    );
+}
+
+#[test]
+fn test_destination_override_requires_trusted_ips() {
+    let err = rustway::config::GatewayConfig::from_yaml(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: api
+    path: /api
+    destination: http://localhost:9001
+    destination_override:
+      trusted_ips: []
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(
+        err.contains("invalid destination_override: trusted_ips is required"),
+        "{}",
+        err
+    );
+}
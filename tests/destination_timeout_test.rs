@@ -0,0 +1,77 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use common::harness::TestGateway;
+use rustway::config::GatewayConfig;
+
+const CONFIG: &str = r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: mixed
+    path: /api/mixed
+    destinations:
+      - "http://slow:8000|timeout=10s"
+      - "http://fast:8000"
+    timeout: 2s
+    destination_timeouts:
+      "http://other:8000": 500ms
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#;
+
+#[test]
+fn test_inline_timeout_is_stripped_from_destination() {
+    let config = GatewayConfig::from_yaml(CONFIG).unwrap();
+    let route = &config.routes[0];
+
+    assert_eq!(route.all_destinations(), vec!["http://slow:8000", "http://fast:8000"]);
+    assert_eq!(route.timeout_for("http://slow:8000"), Some("10s"));
+    assert_eq!(route.timeout_for("http://fast:8000"), Some("2s"));
+    assert_eq!(route.timeout_for("http://other:8000"), Some("500ms"));
+}
+
+#[test]
+fn test_no_timeouts_configured() {
+    let config = GatewayConfig::from_yaml(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: plain
+    path: /api/plain
+    destination: "http://plain:8000"
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.routes[0].timeout_for("http://plain:8000"), None);
+}
+
+#[tokio::test]
+async fn test_selected_destination_uses_its_own_timeout() {
+    // Both destinations take 300ms; only the first is allowed that long.
+    // They must differ, since timeouts are looked up by destination URL.
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: delayed
+    path: /api/delayed
+    load_balance: round_robin
+    timeout: 100ms
+    destinations:
+      - "{backend}/delay/300|timeout=2s"
+      - "{backend}/slow?ms=300"
+"#,
+    )
+    .await;
+    let url = format!("{}/api/delayed", gateway.base_url);
+
+    let slow_with_long_timeout = reqwest::get(&url).await.unwrap();
+    assert_eq!(slow_with_long_timeout.status(), 200);
+
+    let slow_with_route_timeout = reqwest::get(&url).await.unwrap();
+    assert_eq!(slow_with_route_timeout.status(), 504);
+}
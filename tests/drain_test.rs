@@ -0,0 +1,108 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::collections::HashMap;
+
+use axum::{Router, body::Body};
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use rustway::config::{ApiKeyDetails, ApiKeyStore};
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+
+async fn start_backend() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+    url
+}
+
+fn key_store() -> ApiKeyStore {
+    ApiKeyStore {
+        keys: HashMap::from([(
+            "ops-key".to_string(),
+            ApiKeyDetails {
+                user_id: "ops".to_string(),
+                roles: vec!["admin".to_string()],
+                status: "active".to_string(),
+            },
+        )]),
+        ..Default::default()
+    }
+}
+
+/// Three destinations on one backend, told apart by path.
+async fn test_app() -> (Router, String) {
+    let backend = start_backend().await;
+    let (app, _state) = common::test_app_with_keys(
+        &format!(
+            r#"
+server:
+  addr: "127.0.0.1:8094"
+  admin:
+    auth: {{type: ApiKey, roles: [admin]}}
+routes:
+  - name: shop
+    path: /api/shop
+    destinations: ["{backend}/a", "{backend}/b", "{backend}/c"]
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+            backend = backend
+        ),
+        key_store(),
+    )
+    .await;
+    (app, backend)
+}
+
+async fn backend_path(app: &Router) -> String {
+    let response = common::send(app, Request::builder().uri("/api/shop").body(Body::empty()).unwrap()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    body["path"].as_str().unwrap().to_string()
+}
+
+async fn admin(app: &Router, action: &str, destination: &str) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("/admin/destinations/{}", action))
+        .header("Authorization", "Bearer ops-key")
+        .header("content-type", "application/json")
+        .body(Body::from(json!({ "destination": destination }).to_string()))
+        .unwrap();
+    let response = common::send(app, request).await;
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_drained_destination_gets_no_new_requests_until_undrained() {
+    let (app, backend) = test_app().await;
+    let drained = format!("{}/b", backend);
+
+    let (status, body) = admin(&app, "drain", &drained).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["changed"], true);
+    for _ in 0..6 {
+        assert_ne!(backend_path(&app).await, "/b");
+    }
+
+    let (status, body) = admin(&app, "undrain", &drained).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["drained"], false);
+    let mut paths = Vec::new();
+    for _ in 0..3 {
+        paths.push(backend_path(&app).await);
+    }
+    assert!(paths.contains(&"/b".to_string()), "{:?}", paths);
+}
+
+#[tokio::test]
+async fn test_unknown_destination_is_rejected() {
+    let (app, _) = test_app().await;
+    let (status, _) = admin(&app, "drain", "http://nowhere:9999").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,142 @@
+//! Routes that accept more than one auth method, tried in order.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::body::Body;
+use http::{Request, StatusCode};
+use jsonwebtoken::{EncodingKey, Header, encode};
+use rustway::{
+    config::{ApiKeyDetails, ApiKeyStore, AuthType},
+    features::auth::auth::Claims,
+};
+
+fn static_root(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustygw-dual-auth-{}-{}", test, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("orders.json"), "[]").unwrap();
+    dir
+}
+
+fn config(root: &Path) -> String {
+    format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: orders
+    path: /api/orders
+    static_file: "{root}"
+    auth:
+      - type: Jwt
+        roles: [user]
+      - type: ApiKey
+        roles: [service]
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        root = root.display()
+    )
+}
+
+fn key_store() -> ApiKeyStore {
+    let key = |user: &str, roles: &[&str]| ApiKeyDetails {
+        user_id: user.to_string(),
+        roles: roles.iter().map(ToString::to_string).collect(),
+        status: "active".to_string(),
+    };
+    ApiKeyStore {
+        keys: HashMap::from([
+            ("billing-key".to_string(), key("billing", &["service"])),
+            ("intern-key".to_string(), key("intern", &["user"])),
+        ]),
+        ..Default::default()
+    }
+}
+
+fn jwt(roles: &[&str]) -> String {
+    let exp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 3600;
+    let claims = Claims {
+        sub: "alice".to_string(),
+        roles: roles.iter().map(ToString::to_string).collect(),
+        exp: usize::try_from(exp).unwrap(),
+        iss: None,
+        aud: None,
+        extra: HashMap::new(),
+    };
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(common::TEST_JWT_SECRET.as_bytes()),
+    )
+    .unwrap()
+}
+
+fn get(token: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder().uri("/api/orders/orders.json");
+    if let Some(token) = token {
+        builder = builder.header("Authorization", format!("Bearer {}", token));
+    }
+    builder.body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn test_jwt_and_api_key_each_authenticate() {
+    let (app, _) = common::test_app_with_keys(&config(&static_root("both")), key_store()).await;
+
+    let with_jwt = common::send(&app, get(Some(&jwt(&["user"])))).await;
+    assert_eq!(with_jwt.status(), StatusCode::OK);
+    let with_key = common::send(&app, get(Some("billing-key"))).await;
+    assert_eq!(with_key.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_neither_method_is_401() {
+    let (app, _) = common::test_app_with_keys(&config(&static_root("neither")), key_store()).await;
+
+    let missing = common::send(&app, get(None)).await;
+    assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+    let unknown = common::send(&app, get(Some("not-a-jwt-or-key"))).await;
+    assert_eq!(unknown.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_roles_checked_against_matching_method() {
+    let (app, _) = common::test_app_with_keys(&config(&static_root("roles")), key_store()).await;
+
+    // The API key authenticates but only the JWT method accepts role `user`
+    let resp = common::send(&app, get(Some("intern-key"))).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    let resp = common::send(&app, get(Some(&jwt(&["service"])))).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[test]
+fn test_single_auth_method_still_parses() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: single
+    path: /api/single
+    destination: http://localhost:9001
+    auth:
+      type: ApiKey
+  - name: open
+    path: /api/open
+    destination: http://localhost:9002
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    );
+    assert_eq!(config.routes[0].auth.len(), 1);
+    assert_eq!(config.routes[0].auth[0].auth_type, AuthType::ApiKey);
+    assert!(config.routes[1].auth.is_empty());
+}
@@ -0,0 +1,80 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use axum::body::Body;
+use axum_prometheus::{metrics::with_local_recorder, metrics_exporter_prometheus::PrometheusBuilder};
+use http::{Request, StatusCode};
+use tokio::net::TcpListener;
+
+/// Runs the gateway on this thread so its counters land in the local recorder.
+#[test]
+fn test_errors_are_counted_by_kind() {
+    let recorder = PrometheusBuilder::new().build_recorder();
+    let handle = recorder.handle();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    with_local_recorder(&recorder, || {
+        runtime.block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let backend = format!("http://{}", listener.local_addr().unwrap());
+            tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+
+            let (app, _) = common::test_app(&format!(
+                r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: limited
+    path: /limited
+    destination: "{backend}/echo"
+    rate_limit: {{requests: 1, period: 1m}}
+  - name: private
+    path: /private
+    destination: "{backend}/echo"
+    auth: {{type: Jwt}}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+                backend = backend
+            ))
+            .await;
+            let status = |uri: &'static str| {
+                let app = app.clone();
+                async move {
+                    common::send(&app, Request::builder().uri(uri).body(Body::empty()).unwrap())
+                        .await
+                        .status()
+                }
+            };
+
+            assert_eq!(status("/limited").await, StatusCode::OK);
+            assert_eq!(status("/limited").await, StatusCode::TOO_MANY_REQUESTS);
+            assert_eq!(status("/private").await, StatusCode::UNAUTHORIZED);
+            assert_eq!(status("/private").await, StatusCode::UNAUTHORIZED);
+        });
+    });
+
+    let rendered = handle.render();
+    assert!(
+        rendered.contains(r#"gateway_errors_total{kind="RateLimited"} 1"#),
+        "{}",
+        rendered
+    );
+    assert!(
+        rendered.contains(r#"gateway_errors_total{kind="MissingAuthToken"} 2"#),
+        "{}",
+        rendered
+    );
+    assert!(!rendered.contains(r#"kind="ProxyError""#), "{}", rendered);
+}
+
+#[test]
+fn test_kind_is_the_variant_name() {
+    use rustway::errors::AppError;
+    assert_eq!(AppError::RateLimited.kind(), "RateLimited");
+    assert_eq!(AppError::AuthFailed("bad token".to_string()).kind(), "AuthFailed");
+}
@@ -0,0 +1,110 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use common::harness::TestGateway;
+use rustway::{config::CircuitBreakerConfig, features::circuit_breaker::circuit_breaker::CircuitBreakerStore};
+use serde_json::Value;
+
+#[tokio::test]
+async fn test_retry_fails_over_to_healthy_destination() {
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: failover
+    path: /api/failover
+    load_balance: round_robin
+    retry: {count: 1, backoff: 10ms}
+    destinations:
+      - "{backend}/status/503"
+      - "{backend}/echo"
+"#,
+    )
+    .await;
+    let url = format!("{}/api/failover", gateway.base_url);
+
+    // Round robin starts on the failing destination, then on the healthy one
+    for _ in 0..4 {
+        let resp = reqwest::get(&url).await.unwrap();
+        assert_eq!(resp.status(), 200);
+        let body: Value = resp.json().await.unwrap();
+        assert_eq!(body["path"], "/echo");
+    }
+}
+
+#[tokio::test]
+async fn test_failover_with_destination_circuits() {
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: failover
+    path: /api/failover
+    load_balance: round_robin
+    retry: {count: 1, backoff: 10ms}
+    circuit_breaker: {failure_threshold: 1, success_threshold: 1, open_duration: 60s}
+    destinations:
+      - "{backend}/status/503"
+      - "{backend}/echo"
+"#,
+    )
+    .await;
+    let url = format!("{}/api/failover", gateway.base_url);
+
+    // The failing destination's circuit opens after its first 503; the route's own
+    // circuit only sees the successful failover responses and stays closed
+    for _ in 0..4 {
+        assert_eq!(reqwest::get(&url).await.unwrap().status(), 200);
+    }
+}
+
+#[tokio::test]
+async fn test_destination_circuit_opens_and_blocks() {
+    let store = CircuitBreakerStore::new();
+    let config = CircuitBreakerConfig {
+        failure_threshold: 1,
+        success_threshold: 1,
+        open_duration: "60s".to_string(),
+        max_open_duration: None,
+        backoff_multiplier: 2.0,
+        backoff_reset_after: None,
+    };
+    let failing = store.for_destination("failover", "http://a");
+    failing.record("http://a", true, &config).await;
+
+    assert!(!failing.allow_request("http://a").await);
+    assert!(
+        store
+            .for_destination("failover", "http://b")
+            .allow_request("http://b")
+            .await
+    );
+    // Same destination on another route has its own circuit
+    assert!(
+        store
+            .for_destination("other", "http://a")
+            .allow_request("http://a")
+            .await
+    );
+}
+
+#[tokio::test]
+async fn test_gives_up_when_all_destinations_fail() {
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: failover
+    path: /api/failover
+    retry: {count: 3, backoff: 10ms}
+    destinations:
+      - "{backend}/status/503"
+      - "{backend}/status/502"
+"#,
+    )
+    .await;
+
+    let status = reqwest::get(format!("{}/api/failover", gateway.base_url))
+        .await
+        .unwrap()
+        .status();
+    assert!(status == 502 || status == 503);
+}
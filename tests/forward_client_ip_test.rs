@@ -0,0 +1,91 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use axum::{Router, body::Body};
+use http::Request;
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tokio::net::TcpListener;
+
+async fn app(forward_client_ip: bool) -> Router {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+security:
+  forward_client_ip: {forward_client_ip}
+routes:
+  - name: default
+    path: /api/default
+    destination: "{backend}/echo"
+  - name: always
+    path: /api/always
+    destination: "{backend}/echo"
+    forward_client_ip: true
+  - name: never
+    path: /api/never
+    destination: "{backend}/echo"
+    forward_client_ip: false
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#
+    ))
+    .await;
+    app
+}
+
+/// The headers the backend received.
+async fn backend_headers(app: &Router, path: &str, headers: &[(&str, &str)]) -> Value {
+    let mut request = Request::builder().uri(path);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    let response = common::send(app, request.body(Body::empty()).unwrap()).await;
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice::<Value>(&body).unwrap()["headers"].clone()
+}
+
+#[tokio::test]
+async fn test_client_ip_headers_reach_the_backend() {
+    let app = app(true).await;
+    let headers = backend_headers(&app, "/api/default", &[]).await;
+
+    assert_eq!(headers["x-forwarded-for"], "127.0.0.1");
+    assert_eq!(headers["x-real-ip"], "127.0.0.1");
+    assert_eq!(headers["x-forwarded-proto"], "http");
+}
+
+#[tokio::test]
+async fn test_forwarded_for_is_appended_and_real_ip_replaced() {
+    let app = app(true).await;
+    let headers = backend_headers(
+        &app,
+        "/api/default",
+        &[("x-forwarded-for", "203.0.113.7"), ("x-real-ip", "198.51.100.1")],
+    )
+    .await;
+
+    assert_eq!(headers["x-forwarded-for"], "203.0.113.7, 127.0.0.1");
+    assert_eq!(headers["x-real-ip"], "127.0.0.1");
+}
+
+#[tokio::test]
+async fn test_off_by_default_and_overridable_per_route() {
+    let off = app(false).await;
+
+    let headers = backend_headers(&off, "/api/default", &[]).await;
+    assert!(headers.get("x-forwarded-for").is_none());
+    assert!(headers.get("x-real-ip").is_none());
+
+    let headers = backend_headers(&off, "/api/always", &[]).await;
+    assert_eq!(headers["x-real-ip"], "127.0.0.1");
+
+    let on = app(true).await;
+    let headers = backend_headers(&on, "/api/never", &[]).await;
+    assert!(headers.get("x-forwarded-proto").is_none());
+}
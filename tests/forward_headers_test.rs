@@ -0,0 +1,109 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use axum::{Router, body::Body};
+use http::Request;
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tokio::net::TcpListener;
+
+async fn app() -> Router {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: open
+    path: /api/open
+    destination: "{backend}/echo"
+  - name: defaults-only
+    path: /api/defaults-only
+    destination: "{backend}/echo"
+    forward_headers: []
+  - name: tenant
+    path: /api/tenant
+    destination: "{backend}/echo"
+    forward_headers: [X-Tenant-Id]
+  - name: blocking
+    path: /api/blocking
+    destination: "{backend}/echo"
+    forward_headers: [x-tenant-id]
+    forward_headers_mode: block
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#
+    ))
+    .await;
+    app
+}
+
+/// The headers the backend received.
+async fn backend_headers(app: &Router, path: &str) -> Value {
+    let request = Request::builder()
+        .uri(path)
+        .header("x-tenant-id", "acme")
+        .header("x-debug-token", "internal")
+        .header("accept", "application/json")
+        .body(Body::empty())
+        .unwrap();
+    let response = common::send(app, request).await;
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    serde_json::from_slice::<Value>(&body).unwrap()["headers"].clone()
+}
+
+#[tokio::test]
+async fn test_custom_header_forwarded_only_when_allowlisted() {
+    let app = app().await;
+
+    let headers = backend_headers(&app, "/api/tenant").await;
+    assert_eq!(headers["x-tenant-id"], "acme");
+    assert_eq!(headers["accept"], "application/json");
+    assert!(headers.get("x-debug-token").is_none());
+
+    let headers = backend_headers(&app, "/api/defaults-only").await;
+    assert!(headers.get("x-tenant-id").is_none());
+    assert_eq!(headers["accept"], "application/json");
+    // The gateway's own headers aren't subject to the list
+    assert!(headers.get("x-request-id").is_some());
+}
+
+#[tokio::test]
+async fn test_routes_without_forward_headers_pass_everything() {
+    let headers = backend_headers(&app().await, "/api/open").await;
+
+    assert_eq!(headers["x-tenant-id"], "acme");
+    assert_eq!(headers["x-debug-token"], "internal");
+}
+
+#[tokio::test]
+async fn test_block_mode_drops_only_listed_headers() {
+    let headers = backend_headers(&app().await, "/api/blocking").await;
+
+    assert!(headers.get("x-tenant-id").is_none());
+    assert_eq!(headers["x-debug-token"], "internal");
+}
+
+#[test]
+fn test_invalid_forward_header_name_rejected() {
+    let err = rustway::config::GatewayConfig::from_yaml(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: api
+    path: /api
+    destination: http://localhost:9001
+    forward_headers: ["bad header"]
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("invalid forward_headers entry 'bad header'"), "{}", err);
+}
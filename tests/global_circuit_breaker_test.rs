@@ -0,0 +1,124 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::sync::Arc;
+
+use axum::{Router, body::Body, middleware::from_fn_with_state, routing::any};
+use http::{Request, StatusCode};
+use rustway::{
+    config::GlobalCircuitBreakerConfig, features::circuit_breaker::global::GlobalCircuitBreaker,
+    middleware::global_circuit_breaker::layer as global_circuit_breaker_layer, state::AppState,
+};
+use tower::ServiceExt;
+
+const CONFIG: &str = r#"
+server:
+  addr: "127.0.0.1:8094"
+  circuit_breaker:
+    error_rate_threshold: 0.5
+    min_requests: 4
+    window: 60s
+    open_duration: 60s
+routes:
+  - name: failing
+    path: /api/failing
+    destination: http://localhost:9001
+  - name: healthy
+    path: /api/healthy
+    destination: http://localhost:9002
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#;
+
+fn app(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/api/failing", any(|| async { StatusCode::BAD_GATEWAY }))
+        .route("/api/healthy", any(|| async { StatusCode::OK }))
+        .route("/health", any(|| async { StatusCode::OK }))
+        .layer(from_fn_with_state(state.clone(), global_circuit_breaker_layer))
+        .with_state(state)
+}
+
+async fn call(app: &Router, path: &str) -> StatusCode {
+    app.clone()
+        .oneshot(Request::builder().uri(path).body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+        .status()
+}
+
+fn breaker_config(open_duration: &str) -> GlobalCircuitBreakerConfig {
+    GlobalCircuitBreakerConfig {
+        error_rate_threshold: 0.5,
+        min_requests: 4,
+        window: "60s".to_string(),
+        open_duration: open_duration.to_string(),
+        success_threshold: 1,
+    }
+}
+
+#[tokio::test]
+async fn test_high_error_rate_trips_all_routes() {
+    let app = app(common::test_state(CONFIG).await);
+
+    // 3 failures out of 4 requests: 75% >= 50%
+    assert_eq!(call(&app, "/api/healthy").await, StatusCode::OK);
+    for _ in 0..3 {
+        assert_eq!(call(&app, "/api/failing").await, StatusCode::BAD_GATEWAY);
+    }
+
+    assert_eq!(call(&app, "/api/healthy").await, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(call(&app, "/api/failing").await, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(call(&app, "/health").await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_low_error_rate_keeps_breaker_closed() {
+    let app = app(common::test_state(CONFIG).await);
+
+    assert_eq!(call(&app, "/api/failing").await, StatusCode::BAD_GATEWAY);
+    for _ in 0..5 {
+        assert_eq!(call(&app, "/api/healthy").await, StatusCode::OK);
+    }
+    assert_eq!(call(&app, "/api/failing").await, StatusCode::BAD_GATEWAY);
+}
+
+#[tokio::test]
+async fn test_no_global_breaker_without_config() {
+    let state = common::test_state(&CONFIG.replace("  circuit_breaker:", "  unused:")).await;
+    assert!(state.global_circuit_breaker.is_none());
+}
+
+#[tokio::test]
+async fn test_breaker_recovers_after_open_duration() {
+    let breaker = GlobalCircuitBreaker::new(&breaker_config("0s"));
+    for _ in 0..4 {
+        breaker.record(true).await;
+    }
+    assert!(breaker.is_open().await);
+
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    assert!(breaker.allow_request().await);
+    breaker.record(false).await;
+    assert!(!breaker.is_open().await);
+}
+
+#[tokio::test]
+async fn test_failed_trial_reopens_breaker() {
+    let breaker = GlobalCircuitBreaker::new(&breaker_config("0s"));
+    for _ in 0..4 {
+        breaker.record(true).await;
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    assert!(breaker.allow_request().await);
+    breaker.record(true).await;
+    assert!(breaker.is_open().await);
+}
+
+#[test]
+fn test_invalid_threshold_fails_validation() {
+    let config = common::parse_config(&CONFIG.replace("error_rate_threshold: 0.5", "error_rate_threshold: 1.5"));
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(err.contains("error_rate_threshold"));
+}
@@ -0,0 +1,51 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use common::harness::TestGateway;
+use serde_json::Value;
+use tokio::net::TcpStream;
+
+const ROUTES: &str = r#"
+routes:
+  - name: echo
+    path: /api/echo
+    destination: "{backend}/echo"
+"#;
+
+#[tokio::test]
+async fn test_harness_starts_and_proxies() {
+    let gateway = TestGateway::start(ROUTES).await;
+
+    let health = reqwest::get(format!("{}/health", gateway.base_url)).await.unwrap();
+    assert_eq!(health.status(), 200);
+
+    let body: Value = reqwest::get(format!("{}/api/echo/hello", gateway.base_url))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["service"], "example-backend");
+    assert_eq!(body["path"], "/echo/hello");
+
+    gateway.shutdown().await;
+}
+
+#[tokio::test]
+async fn test_harness_tears_down_cleanly() {
+    let gateway = TestGateway::start(ROUTES).await;
+    let addr = gateway.gateway_addr;
+    assert!(TcpStream::connect(addr).await.is_ok());
+
+    gateway.shutdown().await;
+    assert!(TcpStream::connect(addr).await.is_err());
+}
+
+#[tokio::test]
+async fn test_harness_instances_use_distinct_ports() {
+    let a = TestGateway::start(ROUTES).await;
+    let b = TestGateway::start(ROUTES).await;
+    assert_ne!(a.base_url, b.base_url);
+    assert_ne!(a.backend_url, b.backend_url);
+}
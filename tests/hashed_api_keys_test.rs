@@ -0,0 +1,93 @@
+//! API key stores listing SHA-256 digests instead of the keys themselves.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::collections::HashMap;
+
+use http::HeaderMap;
+use rustway::{
+    config::{ApiKeyStore, AuthConfig, SecretsConfig, hash_api_key},
+    errors::AppError,
+    features::auth::auth::{Claims, verify_token},
+};
+
+fn store_file(test: &str, content: &str) -> std::path::PathBuf {
+    let path = std::env::temp_dir().join(format!("rustygw-hashed-keys-{}-{}.yaml", test, std::process::id()));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+fn verify(key: &str, store: &ApiKeyStore) -> Result<Claims, AppError> {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-api-key", key.parse().unwrap());
+    let auth: AuthConfig = serde_yaml::from_str("type: ApiKey").unwrap();
+    verify_token(&headers, &auth, &SecretsConfig::default(), store)
+}
+
+#[test]
+fn test_hash_api_key_is_sha256_hex() {
+    assert_eq!(
+        hash_api_key("abc"),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
+
+#[test]
+fn test_hashed_store_verifies_by_digest() {
+    let path = store_file(
+        "hashed",
+        &format!(
+            "hashed: true\nkeys:\n  \"{}\":\n    user_id: billing\n    roles: [service]\n",
+            hash_api_key("billing-key").to_uppercase()
+        ),
+    );
+    let store = ApiKeyStore::load(&path).unwrap();
+
+    assert_eq!(verify("billing-key", &store).unwrap().sub, "billing");
+    assert!(matches!(verify("wrong-key", &store), Err(AppError::AuthFailed(_))));
+    // The digest itself is not a credential
+    assert!(matches!(
+        verify(&hash_api_key("billing-key"), &store),
+        Err(AppError::AuthFailed(_))
+    ));
+}
+
+#[test]
+fn test_plaintext_store_still_verifies() {
+    let path = store_file(
+        "plain",
+        "keys:\n  \"billing-key\":\n    user_id: billing\n    roles: [service]\n",
+    );
+    let store = ApiKeyStore::load(&path).unwrap();
+
+    assert!(!store.hashed);
+    assert_eq!(verify("billing-key", &store).unwrap().sub, "billing");
+    assert!(verify(&hash_api_key("billing-key"), &store).is_err());
+}
+
+#[test]
+fn test_hashed_store_rejects_entries_that_are_not_digests() {
+    let path = store_file(
+        "not-digest",
+        "hashed: true\nkeys:\n  \"billing-key\":\n    user_id: billing\n    roles: [service]\n",
+    );
+    let err = ApiKeyStore::load(&path).unwrap_err().to_string();
+
+    assert!(err.contains("not a SHA-256 hex digest"), "{}", err);
+    assert!(
+        !err.contains("billing-key"),
+        "the raw entry must not be logged: {}",
+        err
+    );
+}
+
+#[test]
+fn test_lookup_in_memory_store() {
+    let details = serde_yaml::from_str("user_id: billing\nroles: [service]").unwrap();
+    let store = ApiKeyStore {
+        keys: HashMap::from([(hash_api_key("billing-key"), details)]),
+        hashed: true,
+        ..Default::default()
+    };
+    assert_eq!(store.lookup("billing-key").unwrap().user_id, "billing");
+    assert!(store.lookup("other").is_none());
+}
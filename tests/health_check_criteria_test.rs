@@ -0,0 +1,113 @@
+//! Health check success criteria beyond a 2xx status.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::time::Duration;
+
+use axum::{Json, Router, http::StatusCode, routing::get};
+use rustway::features::health_check::{HealthCheckConfig, HealthChecker, probe};
+use serde_json::json;
+use tokio::net::TcpListener;
+
+fn check(yaml: &str) -> HealthCheckConfig {
+    serde_yaml::from_str(yaml).unwrap()
+}
+
+async fn backend() -> String {
+    let app = Router::new()
+        .route("/degraded", get(|| async { Json(json!({"status": "degraded"})) }))
+        .route(
+            "/ok",
+            get(|| async { Json(json!({"status": "ok", "checks": {"db": "up"}})) }),
+        )
+        .route(
+            "/starting",
+            get(|| async { (StatusCode::SERVICE_UNAVAILABLE, "starting") }),
+        )
+        .route(
+            "/throttled",
+            get(|| async { (StatusCode::TOO_MANY_REQUESTS, "busy but alive") }),
+        );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
+
+#[test]
+fn test_default_criterion_is_2xx() {
+    let check = check("path: /health");
+    assert!(check.accepts_status(200));
+    assert!(check.accepts_status(204));
+    assert!(!check.accepts_status(301));
+    assert!(!check.accepts_status(503));
+    assert!(!check.checks_body());
+}
+
+#[test]
+fn test_body_criteria() {
+    let check = check("expected_body: ready\nexpected_json: {status: ok, checks.db: up}");
+    assert!(check.accepts_body(br#"{"status":"ok","checks":{"db":"up"},"note":"ready"}"#));
+    assert!(!check.accepts_body(br#"{"status":"ok","checks":{"db":"up"}}"#));
+    assert!(!check.accepts_body(br#"{"status":"ok","checks":{"db":"down"},"note":"ready"}"#));
+    assert!(!check.accepts_body(b"ready, but not JSON"));
+}
+
+#[tokio::test]
+async fn test_probe_applies_criteria() {
+    let url = backend().await;
+    let client = reqwest::Client::new();
+    let timeout = Duration::from_secs(2);
+    let status_ok = check("expected_json: {status: ok}");
+
+    assert!(probe(&client, &format!("{}/ok", url), timeout, &status_ok).await);
+    assert!(!probe(&client, &format!("{}/degraded", url), timeout, &status_ok).await);
+    assert!(!probe(&client, &format!("{}/starting", url), timeout, &check("{}")).await);
+
+    let throttled = check("expected_status: [200, 429]");
+    assert!(probe(&client, &format!("{}/throttled", url), timeout, &throttled).await);
+    assert!(!probe(&client, &format!("{}/throttled", url), timeout, &check("{}")).await);
+}
+
+#[tokio::test]
+async fn test_degraded_backend_marked_unhealthy() {
+    let url = backend().await;
+    let checker = HealthChecker::with_timeout(Duration::from_secs(2));
+    // A 200, but the body says degraded
+    checker.start_checker(
+        reqwest::Client::new(),
+        vec![(
+            url.clone(),
+            check("interval: 1s\npath: /degraded\nexpected_json: {status: ok}"),
+        )],
+    );
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+    while checker.is_healthy(&url) {
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "backend was never marked unhealthy"
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+#[test]
+fn test_invalid_expected_status_rejected() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: users
+    path: /api/users
+    destination: http://localhost:9001
+    health_check: {path: /health, expected_status: [200, 42]}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    );
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(err.contains("expected_status 42"), "{}", err);
+}
@@ -37,7 +37,7 @@ fn test_parse_body_limit_kb() {
 
 #[test]
 fn test_parse_body_limit_bytes() {
-    assert_eq!(parse_body_limit("1048576"), 1048576);
+    assert_eq!(parse_body_limit("1048576"), 1_048_576);
 }
 
 #[test]
@@ -0,0 +1,106 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use axum::{Router, body::Body, response::Response};
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use ring::hmac;
+use serde_json::Value;
+use tokio::net::TcpListener;
+
+const SECRET: &str = "webhook-secret";
+const PAYLOAD: &str = r#"{"event":"order.paid","id":42}"#;
+
+async fn app() -> Router {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: webhooks
+    path: /webhooks
+    destination: "{backend}/echo"
+    auth:
+      type: HmacSignature
+      signing_secret: {secret}
+      signature_header: x-hub-signature-256
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend,
+        secret = SECRET
+    ))
+    .await;
+    app
+}
+
+fn sign(body: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, SECRET.as_bytes());
+    let tag = hmac::sign(&key, body.as_bytes());
+    let hex = tag
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .concat();
+    format!("sha256={}", hex)
+}
+
+async fn post(app: &Router, body: &str, signature: Option<&str>) -> Response {
+    let mut request = Request::builder().method("POST").uri("/webhooks");
+    if let Some(signature) = signature {
+        request = request.header("x-hub-signature-256", signature);
+    }
+    common::send(app, request.body(Body::from(body.to_string())).unwrap()).await
+}
+
+#[tokio::test]
+async fn test_correctly_signed_payload_is_forwarded_intact() {
+    let app = app().await;
+
+    let response = post(&app, PAYLOAD, Some(&sign(PAYLOAD))).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes()).unwrap();
+    assert_eq!(body["headers"]["content-length"], PAYLOAD.len().to_string());
+}
+
+#[tokio::test]
+async fn test_tampered_or_unsigned_payload_is_rejected() {
+    let app = app().await;
+    let tampered = PAYLOAD.replace("42", "43");
+
+    assert_eq!(
+        post(&app, &tampered, Some(&sign(PAYLOAD))).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        post(&app, PAYLOAD, Some("sha256=zz")).await.status(),
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(post(&app, PAYLOAD, None).await.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[test]
+fn test_signature_auth_requires_secret() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: webhooks
+    path: /webhooks
+    destination: http://localhost:9001
+    auth:
+      type: HmacSignature
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    );
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(err.contains("HmacSignature requires signing_secret"), "{}", err);
+}
@@ -0,0 +1,89 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use axum::{Router, body::Body, routing::post};
+use http::{HeaderMap, Request, StatusCode};
+use tokio::net::TcpListener;
+
+/// Fails the first request with 503 and accepts the rest, recording the
+/// `Idempotency-Key` of each request it sees.
+async fn flaky_backend() -> (String, Arc<Mutex<Vec<Option<String>>>>) {
+    let seen: Arc<Mutex<Vec<Option<String>>>> = Arc::default();
+    let recorded = seen.clone();
+    let app = Router::new().route(
+        "/{*path}",
+        post(move |headers: HeaderMap| {
+            let recorded = recorded.clone();
+            async move {
+                let key = headers
+                    .get("idempotency-key")
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string);
+                let mut seen = recorded.lock().unwrap();
+                seen.push(key);
+                if seen.len() == 1 {
+                    StatusCode::SERVICE_UNAVAILABLE
+                } else {
+                    StatusCode::CREATED
+                }
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, seen)
+}
+
+async fn app(backend: &str) -> Router {
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: payments
+    path: /payments
+    destination: "{backend}"
+    retry: {{count: 2, backoff: 1ms, retry_on: [503]}}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend
+    ))
+    .await;
+    app
+}
+
+fn payment(key: Option<&str>) -> Request<Body> {
+    let mut request = Request::builder().method("POST").uri("/payments/charge");
+    if let Some(key) = key {
+        request = request.header("idempotency-key", key);
+    }
+    request.body(Body::from(r#"{"amount":100}"#)).unwrap()
+}
+
+#[tokio::test]
+async fn test_post_with_idempotency_key_is_retried() {
+    let (backend, seen) = flaky_backend().await;
+    let app = app(&backend).await;
+
+    let response = common::send(&app, payment(Some("pay-123"))).await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [Some("pay-123".to_string()), Some("pay-123".to_string())]
+    );
+}
+
+#[tokio::test]
+async fn test_post_without_idempotency_key_is_not_retried() {
+    let (backend, seen) = flaky_backend().await;
+    let app = app(&backend).await;
+
+    let response = common::send(&app, payment(None)).await;
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(seen.lock().unwrap().len(), 1);
+}
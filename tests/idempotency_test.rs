@@ -0,0 +1,142 @@
+//! Requests repeating an `Idempotency-Key` replayed from the first response.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use axum::{Router, body::Body, routing::post};
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use tokio::net::TcpListener;
+
+/// Answers each POST with the running call count, so a replay is recognisable.
+async fn counting_backend() -> (String, Arc<AtomicUsize>) {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let counter = calls.clone();
+    let app = Router::new().route(
+        "/orders",
+        post(move || {
+            let call = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            async move { (StatusCode::CREATED, format!("order-{}", call)) }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, calls)
+}
+
+async fn app(idempotency: &str) -> (Router, Arc<AtomicUsize>) {
+    let (backend, calls) = counting_backend().await;
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: orders
+    path: /api/orders
+    destination: "{backend}/orders"
+{idempotency}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#
+    ))
+    .await;
+    (app, calls)
+}
+
+async fn post_order(app: &Router, key: Option<&str>) -> (StatusCode, Option<String>, String) {
+    let mut builder = Request::builder().method("POST").uri("/api/orders");
+    if let Some(key) = key {
+        builder = builder.header("Idempotency-Key", key);
+    }
+    let response = common::send(app, builder.body(Body::from("{}")).unwrap()).await;
+    let status = response.status();
+    let replayed = response
+        .headers()
+        .get("idempotent-replayed")
+        .map(|v| v.to_str().unwrap().to_string());
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, replayed, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn test_duplicate_key_within_window_hits_backend_once() {
+    let (app, calls) = app("    idempotency:\n      window: 1m").await;
+
+    let first = post_order(&app, Some("order-abc")).await;
+    let second = post_order(&app, Some("order-abc")).await;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!((first.0, first.2.as_str()), (StatusCode::CREATED, "order-1"));
+    assert_eq!((second.0, second.2.as_str()), (first.0, first.2.as_str()));
+    assert_eq!(first.1, None);
+    assert_eq!(second.1.as_deref(), Some("true"));
+}
+
+#[tokio::test]
+async fn test_concurrent_duplicates_share_the_first_response() {
+    let (app, calls) = app("    idempotency:\n      window: 1m").await;
+
+    let (first, second) = tokio::join!(post_order(&app, Some("order-xyz")), post_order(&app, Some("order-xyz")));
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert_eq!(first.2, second.2);
+}
+
+#[tokio::test]
+async fn test_different_or_missing_keys_reach_backend() {
+    let (app, calls) = app("    idempotency:\n      header: X-Request-Key").await;
+
+    post_order(&app, None).await;
+    post_order(&app, None).await;
+    // The route reads its own header, not `Idempotency-Key`
+    post_order(&app, Some("order-abc")).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_window_expiry_lets_key_through_again() {
+    let (app, calls) = app("    idempotency:\n      window: 100ms").await;
+
+    post_order(&app, Some("order-abc")).await;
+    tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+    let again = post_order(&app, Some("order-abc")).await;
+
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(again.2, "order-2");
+}
+
+#[tokio::test]
+async fn test_routes_without_idempotency_are_unaffected() {
+    let (app, calls) = app("").await;
+
+    post_order(&app, Some("order-abc")).await;
+    post_order(&app, Some("order-abc")).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_invalid_window_rejected() {
+    let err = rustway::config::GatewayConfig::from_yaml(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: orders
+    path: /api/orders
+    destination: http://localhost:9001
+    idempotency:
+      window: soon
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("invalid idempotency"), "{}", err);
+}
@@ -0,0 +1,139 @@
+//! Drives the full router in memory: no listeners, no backends. Routes serve a
+//! static file so requests that pass the middleware never leave the process.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use axum::body::Body;
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use rustway::config::{ApiKeyDetails, ApiKeyStore};
+
+fn static_root(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustygw-in-memory-{}-{}", test, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("report.json"), r#"{"ok":true}"#).unwrap();
+    dir
+}
+
+fn config(root: &Path) -> String {
+    format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: admin
+    path: /api/admin
+    static_file: "{root}"
+    auth:
+      type: ApiKey
+      roles: [admin]
+  - name: limited
+    path: /api/limited
+    static_file: "{root}"
+    rate_limit:
+      requests: 2
+      period: 1m
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        root = root.display()
+    )
+}
+
+fn key_store() -> ApiKeyStore {
+    let key = |user: &str, roles: &[&str], status: &str| ApiKeyDetails {
+        user_id: user.to_string(),
+        roles: roles.iter().map(ToString::to_string).collect(),
+        status: status.to_string(),
+    };
+    ApiKeyStore {
+        keys: HashMap::from([
+            ("admin-key".to_string(), key("alice", &["admin"], "active")),
+            ("user-key".to_string(), key("bob", &["user"], "active")),
+            ("revoked-key".to_string(), key("carol", &["admin"], "revoked")),
+        ]),
+        ..Default::default()
+    }
+}
+
+fn get(path: &str, api_key: Option<&str>) -> Request<Body> {
+    let mut builder = Request::builder().uri(path);
+    if let Some(key) = api_key {
+        builder = builder.header("Authorization", format!("Bearer {}", key));
+    }
+    builder.body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn test_missing_token_is_401() {
+    let (app, _) = common::test_app_with_keys(&config(&static_root("missing")), key_store()).await;
+
+    let resp = common::send(&app, get("/api/admin/report.json", None)).await;
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_malformed_auth_header_is_401() {
+    let (app, _) = common::test_app_with_keys(&config(&static_root("malformed")), key_store()).await;
+
+    let req = Request::builder()
+        .uri("/api/admin/report.json")
+        .header("Authorization", "Token admin-key")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(common::send(&app, req).await.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_unknown_and_revoked_keys_are_401() {
+    let (app, _) = common::test_app_with_keys(&config(&static_root("unknown")), key_store()).await;
+
+    let unknown = common::send(&app, get("/api/admin/report.json", Some("nope"))).await;
+    assert_eq!(unknown.status(), StatusCode::UNAUTHORIZED);
+    let revoked = common::send(&app, get("/api/admin/report.json", Some("revoked-key"))).await;
+    assert_eq!(revoked.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_missing_role_is_403() {
+    let (app, _) = common::test_app_with_keys(&config(&static_root("forbidden")), key_store()).await;
+
+    let resp = common::send(&app, get("/api/admin/report.json", Some("user-key"))).await;
+    assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+}
+
+#[tokio::test]
+async fn test_authorized_request_reaches_handler() {
+    let (app, _) = common::test_app_with_keys(&config(&static_root("authorized")), key_store()).await;
+
+    let resp = common::send(&app, get("/api/admin/report.json", Some("admin-key"))).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..], br#"{"ok":true}"#);
+}
+
+#[tokio::test]
+async fn test_rate_limit_returns_429() {
+    let (app, _) = common::test_app(&config(&static_root("limited"))).await;
+
+    for _ in 0..2 {
+        let resp = common::send(&app, get("/api/limited/report.json", None)).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+    let resp = common::send(&app, get("/api/limited/report.json", None)).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_unknown_route_is_404() {
+    let (app, _) = common::test_app(&config(&static_root("unknown-route"))).await;
+
+    let resp = common::send(&app, get("/api/nowhere", None)).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
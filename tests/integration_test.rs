@@ -1,3 +1,8 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use common::harness::TestGateway;
 use rustway::config::GatewayConfig;
 use rustway::features::load_balancer::{LoadBalanceStrategy, LoadBalancer};
 
@@ -6,13 +11,13 @@ use rustway::features::load_balancer::{LoadBalanceStrategy, LoadBalancer};
 #[test]
 fn test_lb_zero_backends_round_robin() {
     let lb = LoadBalancer::new();
-    assert!(lb.next_index(0, &LoadBalanceStrategy::RoundRobin).is_none());
+    assert!(lb.next_index("users", 0, &LoadBalanceStrategy::RoundRobin).is_none());
 }
 
 #[test]
 fn test_lb_zero_backends_random() {
     let lb = LoadBalancer::new();
-    assert!(lb.next_index(0, &LoadBalanceStrategy::Random).is_none());
+    assert!(lb.next_index("users", 0, &LoadBalanceStrategy::Random).is_none());
 }
 
 // ==================== Config Validation Negative Tests ====================
@@ -141,3 +146,79 @@ fn test_check_roles_empty_user_roles() {
     let result = check_roles(&user_roles, &required);
     assert!(result.is_err());
 }
+
+// ==================== End-to-End Proxy Tests ====================
+
+#[tokio::test]
+async fn test_proxy_unknown_route_returns_404() {
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: echo
+    path: /api/echo
+    destination: "{backend}/echo"
+"#,
+    )
+    .await;
+
+    let resp = reqwest::get(format!("{}/api/missing", gateway.base_url)).await.unwrap();
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test]
+async fn test_proxy_forwards_method_and_query() {
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: echo
+    path: /api/echo
+    destination: "{backend}/echo"
+"#,
+    )
+    .await;
+
+    let body: serde_json::Value = reqwest::Client::new()
+        .post(format!("{}/api/echo/items?page=2", gateway.base_url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["method"], "POST");
+    assert_eq!(body["path"], "/echo/items");
+    assert_eq!(body["query"], "page=2");
+}
+
+#[tokio::test]
+async fn test_proxy_forwards_query_string_intact() {
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: users
+    path: /api/users
+    destination: "{backend}/echo"
+  - name: cached
+    path: /api/cached
+    destination: "{backend}/echo"
+    cache: {ttl: 60s}
+"#,
+    )
+    .await;
+    let query_for = |path: &str| {
+        let url = format!("{}{}", gateway.base_url, path);
+        async move {
+            let body: serde_json::Value = reqwest::get(url).await.unwrap().json().await.unwrap();
+            body["query"].clone()
+        }
+    };
+
+    assert_eq!(query_for("/api/users?page=2&limit=10").await, "page=2&limit=10");
+    assert_eq!(
+        query_for("/api/users/7?q=a%20b&tag=x&tag=y").await,
+        "q=a%20b&tag=x&tag=y"
+    );
+    // Cached responses are keyed by query, so each page reaches the backend with its own
+    assert_eq!(query_for("/api/cached?page=1&limit=10").await, "page=1&limit=10");
+    assert_eq!(query_for("/api/cached?page=2&limit=10").await, "page=2&limit=10");
+}
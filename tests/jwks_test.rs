@@ -0,0 +1,235 @@
+//! JWTs verified against a JWKS fetched from an identity provider.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use axum::{Json, Router, body::Body, http::StatusCode, response::IntoResponse, routing::get};
+use http::Request;
+use jsonwebtoken::{Algorithm, EncodingKey, Header, encode};
+use rustway::{
+    config::AuthConfig,
+    features::auth::{auth::Claims, jwks::JwksCache},
+};
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+
+const KEY_1_PRIVATE: &[u8] = include_bytes!("fixtures/jwt/rsa_private.pem");
+const KEY_1_JWK: &str = include_str!("fixtures/jwt/rsa_public.jwk.json");
+const KEY_2_PRIVATE: &[u8] = include_bytes!("fixtures/jwt/rsa2_private.pem");
+const KEY_2_JWK: &str = include_str!("fixtures/jwt/rsa2_public.jwk.json");
+
+/// Serves `/jwks.json` from `keys`, counting fetches; answers 500 while `down`.
+struct MockJwks {
+    url: String,
+    keys: Arc<Mutex<Vec<Value>>>,
+    fetches: Arc<AtomicUsize>,
+    down: Arc<AtomicBool>,
+}
+
+impl MockJwks {
+    async fn start(jwks: &[&str]) -> Self {
+        let keys = Arc::new(Mutex::new(
+            jwks.iter()
+                .map(|jwk| serde_json::from_str(jwk).unwrap())
+                .collect::<Vec<Value>>(),
+        ));
+        let fetches = Arc::new(AtomicUsize::new(0));
+        let down = Arc::new(AtomicBool::new(false));
+        let (served, counter, failing) = (keys.clone(), fetches.clone(), down.clone());
+        let app = Router::new().route(
+            "/jwks.json",
+            get(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                let response = if failing.load(Ordering::SeqCst) {
+                    StatusCode::INTERNAL_SERVER_ERROR.into_response()
+                } else {
+                    Json(json!({"keys": *served.lock().unwrap()})).into_response()
+                };
+                async move { response }
+            }),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/jwks.json", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        Self {
+            url,
+            keys,
+            fetches,
+            down,
+        }
+    }
+
+    fn fetches(&self) -> usize {
+        self.fetches.load(Ordering::SeqCst)
+    }
+}
+
+fn token(private_pem: &[u8], kid: &str) -> String {
+    let exp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 3600;
+    let claims = Claims {
+        sub: "auth0|alice".to_string(),
+        roles: vec!["user".to_string()],
+        exp: usize::try_from(exp).unwrap(),
+        iss: None,
+        aud: None,
+        extra: HashMap::new(),
+    };
+    let mut header = Header::new(Algorithm::RS256);
+    header.kid = Some(kid.to_string());
+    encode(&header, &claims, &EncodingKey::from_rsa_pem(private_pem).unwrap()).unwrap()
+}
+
+async fn app(jwks_url: &str, jwks_refresh: &str, miss_cooldown: &str) -> Router {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+tuning:
+  jwks_miss_cooldown: {miss_cooldown}
+routes:
+  - name: orders
+    path: /api/orders
+    destination: "{backend}/echo"
+    auth:
+      type: Jwt
+      jwks_url: "{jwks_url}"
+      jwks_refresh: {jwks_refresh}
+      roles: [user]
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#
+    ))
+    .await;
+    app
+}
+
+async fn status(app: &Router, token: &str) -> StatusCode {
+    let request = Request::builder()
+        .uri("/api/orders")
+        .header("Authorization", format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap();
+    common::send(app, request).await.status()
+}
+
+#[tokio::test]
+async fn test_token_verified_by_kid_and_key_set_cached() {
+    let jwks = MockJwks::start(&[KEY_1_JWK, KEY_2_JWK]).await;
+    let app = app(&jwks.url, "10m", "30s").await;
+
+    assert_eq!(status(&app, &token(KEY_1_PRIVATE, "key-1")).await, StatusCode::OK);
+    assert_eq!(status(&app, &token(KEY_2_PRIVATE, "key-2")).await, StatusCode::OK);
+    assert_eq!(status(&app, &token(KEY_1_PRIVATE, "key-1")).await, StatusCode::OK);
+    assert_eq!(jwks.fetches(), 1);
+}
+
+#[tokio::test]
+async fn test_token_signed_by_other_key_than_its_kid_rejected() {
+    let jwks = MockJwks::start(&[KEY_1_JWK, KEY_2_JWK]).await;
+    let app = app(&jwks.url, "10m", "30s").await;
+
+    assert_eq!(
+        status(&app, &token(KEY_2_PRIVATE, "key-1")).await,
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[tokio::test]
+async fn test_unknown_kid_triggers_refetch_after_rotation() {
+    let jwks = MockJwks::start(&[KEY_1_JWK]).await;
+    let app = app(&jwks.url, "10m", "0s").await;
+    assert_eq!(status(&app, &token(KEY_1_PRIVATE, "key-1")).await, StatusCode::OK);
+
+    // The provider rotates in a new key
+    jwks.keys.lock().unwrap().push(serde_json::from_str(KEY_2_JWK).unwrap());
+
+    assert_eq!(status(&app, &token(KEY_2_PRIVATE, "key-2")).await, StatusCode::OK);
+    assert_eq!(jwks.fetches(), 2);
+}
+
+#[tokio::test]
+async fn test_unknown_kids_refetch_at_most_once_per_cooldown() {
+    let jwks = MockJwks::start(&[KEY_1_JWK]).await;
+    let app = app(&jwks.url, "10m", "30s").await;
+    assert_eq!(status(&app, &token(KEY_1_PRIVATE, "key-1")).await, StatusCode::OK);
+
+    for kid in ["made-up-1", "made-up-2", "made-up-3"] {
+        assert_eq!(status(&app, &token(KEY_2_PRIVATE, kid)).await, StatusCode::UNAUTHORIZED);
+    }
+    assert_eq!(jwks.fetches(), 1);
+}
+
+#[tokio::test]
+async fn test_failed_refresh_keeps_last_good_key_set() {
+    let jwks = MockJwks::start(&[KEY_1_JWK]).await;
+    let app = app(&jwks.url, "100ms", "0s").await;
+    assert_eq!(status(&app, &token(KEY_1_PRIVATE, "key-1")).await, StatusCode::OK);
+
+    jwks.down.store(true, Ordering::SeqCst);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    assert_eq!(status(&app, &token(KEY_1_PRIVATE, "key-1")).await, StatusCode::OK);
+    assert_eq!(jwks.fetches(), 2, "the stale set should have been refreshed");
+}
+
+#[tokio::test]
+async fn test_unreachable_jwks_without_key_set_is_unavailable() {
+    let jwks = MockJwks::start(&[KEY_1_JWK]).await;
+    jwks.down.store(true, Ordering::SeqCst);
+    let app = app(&jwks.url, "10m", "0s").await;
+
+    assert_eq!(
+        status(&app, &token(KEY_1_PRIVATE, "key-1")).await,
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+}
+
+#[tokio::test]
+async fn test_refresh_reports_key_count() {
+    let jwks = MockJwks::start(&[KEY_1_JWK, KEY_2_JWK]).await;
+    let cache = JwksCache::new(Duration::from_secs(30));
+
+    let count = cache
+        .refresh(&reqwest::Client::new(), &jwks.url, Duration::from_secs(2))
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn test_jwks_url_only_valid_for_jwt() {
+    let err = rustway::config::GatewayConfig::from_yaml(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: api
+    path: /api
+    destination: http://localhost:9001
+    auth:
+      type: ApiKey
+      jwks_url: http://idp/jwks.json
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("jwks_url only applies to Jwt"), "{}", err);
+
+    let auth: AuthConfig = serde_yaml::from_str("type: Jwt\njwks_url: http://idp/jwks.json").unwrap();
+    assert_eq!(auth.jwks_refresh, "10m");
+}
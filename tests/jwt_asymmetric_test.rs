@@ -0,0 +1,136 @@
+//! JWTs verified against a configured public key instead of the shared secret.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use http::HeaderMap;
+use jsonwebtoken::{Algorithm, EncodingKey, Header, encode};
+use rustway::{
+    config::{ApiKeyStore, AuthConfig, SecretsConfig, public_key_from_pem},
+    errors::AppError,
+    features::auth::auth::{Claims, verify_token},
+};
+
+const RSA_PRIVATE: &[u8] = include_bytes!("fixtures/jwt/rsa_private.pem");
+const RSA_PUBLIC: &[u8] = include_bytes!("fixtures/jwt/rsa_public.pem");
+const EC_PRIVATE: &[u8] = include_bytes!("fixtures/jwt/ec_private.pem");
+const EC_PUBLIC: &[u8] = include_bytes!("fixtures/jwt/ec_public.pem");
+
+fn claims(exp_offset: i64) -> Claims {
+    let now = i64::try_from(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()).unwrap();
+    Claims {
+        sub: "alice".to_string(),
+        roles: vec!["user".to_string()],
+        exp: usize::try_from(now + exp_offset).unwrap(),
+        iss: None,
+        aud: None,
+        extra: HashMap::new(),
+    }
+}
+
+fn secrets(algorithm: Algorithm, public_pem: &[u8]) -> SecretsConfig {
+    SecretsConfig {
+        jwt_algorithm: algorithm,
+        jwt_public_key: Some(public_key_from_pem(algorithm, public_pem).unwrap()),
+        ..Default::default()
+    }
+}
+
+fn verify(token: &str, secrets: &SecretsConfig) -> Result<Claims, AppError> {
+    let mut headers = HeaderMap::new();
+    headers.insert("Authorization", format!("Bearer {}", token).parse().unwrap());
+    let auth: AuthConfig = serde_yaml::from_str("type: Jwt").unwrap();
+    verify_token(&headers, &auth, secrets, &ApiKeyStore::default())
+}
+
+#[test]
+fn test_rs256_token_verified_with_public_key() {
+    let token = encode(
+        &Header::new(Algorithm::RS256),
+        &claims(3600),
+        &EncodingKey::from_rsa_pem(RSA_PRIVATE).unwrap(),
+    )
+    .unwrap();
+
+    let verified = verify(&token, &secrets(Algorithm::RS256, RSA_PUBLIC)).unwrap();
+    assert_eq!(verified.sub, "alice");
+    assert_eq!(verified.roles, ["user"]);
+}
+
+#[test]
+fn test_es256_token_verified_with_public_key() {
+    let token = encode(
+        &Header::new(Algorithm::ES256),
+        &claims(3600),
+        &EncodingKey::from_ec_pem(EC_PRIVATE).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        verify(&token, &secrets(Algorithm::ES256, EC_PUBLIC)).unwrap().sub,
+        "alice"
+    );
+}
+
+#[test]
+fn test_forged_hs256_token_rejected_when_rs256_configured() {
+    let secrets = secrets(Algorithm::RS256, RSA_PUBLIC);
+
+    // The classic confusion attack: HMAC-sign with the public key as secret
+    let forged = encode(
+        &Header::new(Algorithm::HS256),
+        &claims(3600),
+        &EncodingKey::from_secret(RSA_PUBLIC),
+    )
+    .unwrap();
+    assert!(matches!(verify(&forged, &secrets), Err(AppError::AuthFailed(_))));
+
+    // Nor does a token signed with the old shared secret get through
+    let hs256 = encode(&Header::default(), &claims(3600), &EncodingKey::from_secret(b"secret")).unwrap();
+    assert!(matches!(verify(&hs256, &secrets), Err(AppError::AuthFailed(_))));
+}
+
+#[test]
+fn test_rs256_token_signed_by_another_key_rejected() {
+    let token = encode(
+        &Header::new(Algorithm::ES256),
+        &claims(3600),
+        &EncodingKey::from_ec_pem(EC_PRIVATE).unwrap(),
+    )
+    .unwrap();
+
+    assert!(matches!(
+        verify(&token, &secrets(Algorithm::RS256, RSA_PUBLIC)),
+        Err(AppError::AuthFailed(_))
+    ));
+}
+
+#[test]
+fn test_expired_rs256_token_reported_as_expired() {
+    let token = encode(
+        &Header::new(Algorithm::RS256),
+        &claims(-3600),
+        &EncodingKey::from_rsa_pem(RSA_PRIVATE).unwrap(),
+    )
+    .unwrap();
+
+    assert!(matches!(
+        verify(&token, &secrets(Algorithm::RS256, RSA_PUBLIC)),
+        Err(AppError::TokenExpired)
+    ));
+}
+
+#[test]
+fn test_hs256_remains_the_default() {
+    let secrets = SecretsConfig {
+        jwt_secret: "shared".to_string(),
+        ..Default::default()
+    };
+    let token = encode(&Header::default(), &claims(3600), &EncodingKey::from_secret(b"shared")).unwrap();
+
+    assert_eq!(secrets.jwt_algorithm, Algorithm::HS256);
+    assert_eq!(verify(&token, &secrets).unwrap().sub, "alice");
+}
@@ -0,0 +1,143 @@
+//! JWTs checked against the auth method's `issuer` and `audience`.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use http::HeaderMap;
+use jsonwebtoken::{EncodingKey, Header, encode};
+use rustway::{
+    config::{ApiKeyStore, AuthConfig, SecretsConfig},
+    errors::AppError,
+    features::auth::auth::{Audience, Claims, verify_token},
+};
+
+const SECRET: &str = "issuer-audience-test-secret";
+
+fn token(iss: Option<&str>, aud: Option<Audience>) -> String {
+    let exp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 3600;
+    let claims = Claims {
+        sub: "alice".to_string(),
+        roles: vec!["user".to_string()],
+        exp: usize::try_from(exp).unwrap(),
+        iss: iss.map(str::to_string),
+        aud,
+        extra: HashMap::new(),
+    };
+    encode(&Header::default(), &claims, &EncodingKey::from_secret(SECRET.as_ref())).unwrap()
+}
+
+fn verify(token: &str, auth_yaml: &str) -> Result<Claims, AppError> {
+    let mut headers = HeaderMap::new();
+    headers.insert("Authorization", format!("Bearer {}", token).parse().unwrap());
+    let auth: AuthConfig = serde_yaml::from_str(auth_yaml).unwrap();
+    let secrets = SecretsConfig {
+        jwt_secret: SECRET.to_string(),
+        ..Default::default()
+    };
+    verify_token(&headers, &auth, &secrets, &ApiKeyStore::default())
+}
+
+const CHECKED: &str = "type: Jwt\nissuer: https://idp.example.com/\naudience: orders-api";
+
+fn one(aud: &str) -> Audience {
+    Audience::One(aud.to_string())
+}
+
+#[test]
+fn test_matching_issuer_and_audience_accepted() {
+    let claims = verify(
+        &token(Some("https://idp.example.com/"), Some(one("orders-api"))),
+        CHECKED,
+    )
+    .unwrap();
+    assert_eq!(claims.claim("iss").as_deref(), Some("https://idp.example.com/"));
+    assert_eq!(claims.claim("aud").as_deref(), Some("orders-api"));
+
+    let many = Some(Audience::Many(vec![
+        "billing-api".to_string(),
+        "orders-api".to_string(),
+    ]));
+    assert!(verify(&token(Some("https://idp.example.com/"), many), CHECKED).is_ok());
+}
+
+#[test]
+fn test_wrong_issuer_rejected() {
+    let err = verify(
+        &token(Some("https://evil.example.com/"), Some(one("orders-api"))),
+        CHECKED,
+    )
+    .unwrap_err();
+    assert!(
+        matches!(&err, AppError::AuthFailed(reason) if reason.contains("issuer")),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn test_wrong_audience_rejected() {
+    let err = verify(
+        &token(Some("https://idp.example.com/"), Some(one("billing-api"))),
+        CHECKED,
+    )
+    .unwrap_err();
+    assert!(
+        matches!(&err, AppError::AuthFailed(reason) if reason.contains("audience")),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn test_missing_claims_rejected_when_configured() {
+    assert!(matches!(
+        verify(&token(None, Some(one("orders-api"))), CHECKED),
+        Err(AppError::AuthFailed(_))
+    ));
+    assert!(matches!(
+        verify(&token(Some("https://idp.example.com/"), None), CHECKED),
+        Err(AppError::AuthFailed(_))
+    ));
+}
+
+#[test]
+fn test_unconfigured_method_accepts_any_issuer() {
+    assert!(verify(&token(Some("https://anyone.example.com/"), None), "type: Jwt").is_ok());
+    assert!(verify(&token(None, None), "type: Jwt").is_ok());
+}
+
+#[test]
+fn test_unconfigured_method_still_rejects_tokens_with_an_audience() {
+    // jsonwebtoken's default, which routes without `audience` have always had
+    let err = verify(&token(None, Some(one("anything"))), "type: Jwt").unwrap_err();
+    assert!(
+        matches!(&err, AppError::AuthFailed(reason) if reason.contains("audience")),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn test_issuer_only_valid_for_jwt() {
+    let err = rustway::config::GatewayConfig::from_yaml(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: api
+    path: /api
+    destination: http://localhost:9001
+    auth:
+      type: ApiKey
+      audience: orders-api
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("issuer and audience only apply to Jwt"), "{}", err);
+}
@@ -1,10 +1,17 @@
-use rustway::features::load_balancer::{LoadBalanceStrategy, LoadBalancer};
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::collections::HashMap;
+
+use rustway::{
+    config::GatewayConfig,
+    features::load_balancer::{LoadBalanceStrategy, LoadBalancer},
+};
 
 #[test]
 fn test_round_robin_distribution() {
     let lb = LoadBalancer::new();
     let results: Vec<usize> = (0..6)
-        .map(|_| lb.next_index(3, &LoadBalanceStrategy::RoundRobin).unwrap())
+        .map(|_| lb.next_index("api", 3, &LoadBalanceStrategy::RoundRobin).unwrap())
         .collect();
     assert_eq!(results, vec![0, 1, 2, 0, 1, 2]);
 }
@@ -13,7 +20,7 @@ fn test_round_robin_distribution() {
 fn test_round_robin_two_backends() {
     let lb = LoadBalancer::new();
     let results: Vec<usize> = (0..4)
-        .map(|_| lb.next_index(2, &LoadBalanceStrategy::RoundRobin).unwrap())
+        .map(|_| lb.next_index("api", 2, &LoadBalanceStrategy::RoundRobin).unwrap())
         .collect();
     assert_eq!(results, vec![0, 1, 0, 1]);
 }
@@ -22,7 +29,7 @@ fn test_round_robin_two_backends() {
 fn test_round_robin_single_backend() {
     let lb = LoadBalancer::new();
     let results: Vec<usize> = (0..5)
-        .map(|_| lb.next_index(1, &LoadBalanceStrategy::RoundRobin).unwrap())
+        .map(|_| lb.next_index("api", 1, &LoadBalanceStrategy::RoundRobin).unwrap())
         .collect();
     assert_eq!(results, vec![0, 0, 0, 0, 0]);
 }
@@ -30,15 +37,15 @@ fn test_round_robin_single_backend() {
 #[test]
 fn test_zero_backends_returns_none() {
     let lb = LoadBalancer::new();
-    assert!(lb.next_index(0, &LoadBalanceStrategy::RoundRobin).is_none());
-    assert!(lb.next_index(0, &LoadBalanceStrategy::Random).is_none());
+    assert!(lb.next_index("api", 0, &LoadBalanceStrategy::RoundRobin).is_none());
+    assert!(lb.next_index("api", 0, &LoadBalanceStrategy::Random).is_none());
 }
 
 #[test]
 fn test_random_strategy_in_bounds() {
     let lb = LoadBalancer::new();
     for _ in 0..100 {
-        let idx = lb.next_index(3, &LoadBalanceStrategy::Random).unwrap();
+        let idx = lb.next_index("api", 3, &LoadBalanceStrategy::Random).unwrap();
         assert!(idx < 3);
     }
 }
@@ -55,7 +62,7 @@ fn test_load_balancer_thread_safe() {
         let lb = lb.clone();
         handles.push(thread::spawn(move || {
             for _ in 0..1000 {
-                let idx = lb.next_index(3, &LoadBalanceStrategy::RoundRobin).unwrap();
+                let idx = lb.next_index("api", 3, &LoadBalanceStrategy::RoundRobin).unwrap();
                 assert!(idx < 3);
             }
         }));
@@ -65,3 +72,138 @@ fn test_load_balancer_thread_safe() {
         h.join().unwrap();
     }
 }
+
+#[test]
+fn test_round_robin_is_even_per_route() {
+    let lb = LoadBalancer::new();
+    let mut hits = [0; 3];
+    for _ in 0..1000 {
+        hits[lb.next_index("api", 3, &LoadBalanceStrategy::RoundRobin).unwrap()] += 1;
+        // Traffic on another route doesn't shift this one's rotation
+        lb.next_index("other", 2, &LoadBalanceStrategy::RoundRobin).unwrap();
+    }
+    for count in hits {
+        assert!((333..=334).contains(&count), "{:?}", hits);
+    }
+}
+
+#[test]
+fn test_least_connections_picks_least_busy() {
+    let lb = LoadBalancer::new();
+    let destinations = ["http://a", "http://b", "http://c"];
+    let _a = lb.track("http://a");
+    let _a2 = lb.track("http://a");
+    let c = lb.track("http://c");
+
+    assert_eq!(
+        lb.pick(
+            "api",
+            &destinations,
+            &LoadBalanceStrategy::LeastConnections,
+            &HashMap::new()
+        ),
+        Some(1)
+    );
+
+    // b and c tie at one request each, so they take turns
+    let _b = lb.track("http://b");
+    let picks: Vec<usize> = (0..4)
+        .map(|_| {
+            lb.pick(
+                "api",
+                &destinations,
+                &LoadBalanceStrategy::LeastConnections,
+                &HashMap::new(),
+            )
+            .unwrap()
+        })
+        .collect();
+    assert!(picks.iter().all(|&i| i == 1 || i == 2), "{:?}", picks);
+    assert!(picks.contains(&1) && picks.contains(&2), "{:?}", picks);
+
+    drop(c);
+    assert_eq!(lb.in_flight("http://c"), 0);
+    assert_eq!(
+        lb.pick(
+            "api",
+            &destinations,
+            &LoadBalanceStrategy::LeastConnections,
+            &HashMap::new()
+        ),
+        Some(2)
+    );
+}
+
+#[test]
+fn test_in_flight_guard_releases_on_early_return() {
+    fn failing_request(lb: &LoadBalancer) -> Result<(), ()> {
+        let _in_flight = lb.track("http://a");
+        Err(())
+    }
+
+    let lb = LoadBalancer::new();
+    assert!(failing_request(&lb).is_err());
+    assert_eq!(lb.in_flight("http://a"), 0);
+}
+
+#[test]
+fn test_weighted_pick_follows_weights() {
+    let lb = LoadBalancer::new();
+    let destinations = ["http://a", "http://b"];
+    let weights = HashMap::from([("http://a".to_string(), 9), ("http://b".to_string(), 1)]);
+
+    for strategy in [LoadBalanceStrategy::RoundRobin, LoadBalanceStrategy::Random] {
+        let mut hits = [0; 2];
+        for _ in 0..10_000 {
+            hits[lb.pick("api", &destinations, &strategy, &weights).unwrap()] += 1;
+        }
+        assert!((8_700..=9_300).contains(&hits[0]), "{:?} {:?}", strategy, hits);
+    }
+}
+
+#[test]
+fn test_weighted_destinations_config() {
+    let config = GatewayConfig::from_yaml(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: rollout
+    path: /api/rollout
+    destinations:
+      - {url: "http://a:8000", weight: 9}
+      - "http://b:8000|weight=1"
+      - "http://c:8000"
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap();
+    let route = &config.routes[0];
+    assert_eq!(route.destinations, ["http://a:8000", "http://b:8000", "http://c:8000"]);
+    assert_eq!(route.destination_weights.get("http://a:8000"), Some(&9));
+    assert_eq!(route.destination_weights.get("http://b:8000"), Some(&1));
+    assert_eq!(route.destination_weights.get("http://c:8000"), None);
+}
+
+#[test]
+fn test_zero_weight_fails_validation() {
+    let err = GatewayConfig::from_yaml(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: rollout
+    path: /api/rollout
+    destinations:
+      - {url: "http://a:8000", weight: 0}
+      - "http://b:8000|weight=lots"
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("destination 'http://a:8000'"), "{}", err);
+    assert!(err.contains("destination 'http://b:8000'"), "{}", err);
+}
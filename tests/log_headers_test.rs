@@ -0,0 +1,118 @@
+//! Access log lines are checked by capturing log output on the test's own
+//! thread, so requests go through the in-memory router.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use axum::body::Body;
+use http::{Request, StatusCode};
+use tokio::net::TcpListener;
+use tracing_subscriber::fmt::MakeWriter;
+
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedLogs {
+    fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = CapturedLogs;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+/// Sends one request carrying a few headers to `path` and returns its access log line.
+async fn access_line(path: &str) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+    let (app, _state) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: traced
+    path: /api/traced
+    destination: "{backend}/echo"
+    log_headers: [X-Trace-Id, X-Backend, Authorization]
+    transform:
+      response_headers:
+        X-Backend: backend-7
+  - name: plain
+    path: /api/plain
+    destination: "{backend}/echo"
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#
+    ))
+    .await;
+
+    let logs = CapturedLogs::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(logs.clone())
+        .with_max_level(tracing::Level::INFO)
+        .with_ansi(false)
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let request = Request::builder()
+        .uri(path)
+        .header("x-trace-id", "trace-abc123")
+        .header("x-unlisted", "unlisted-value")
+        .header("authorization", "Bearer secret-token")
+        .body(Body::empty())
+        .unwrap();
+    assert_eq!(common::send(&app, request).await.status(), StatusCode::OK);
+
+    let logs = logs.contents();
+    logs.lines()
+        .find(|l| l.contains(" access"))
+        .unwrap_or_else(|| panic!("no access log line in:\n{}", logs))
+        .to_string()
+}
+
+#[tokio::test]
+async fn test_listed_headers_are_logged() {
+    let line = access_line("/api/traced").await;
+
+    assert!(line.contains("x-trace-id=trace-abc123"), "{}", line);
+    assert!(line.contains("x-backend=backend-7"), "{}", line);
+    assert!(!line.contains("unlisted-value"), "{}", line);
+}
+
+#[tokio::test]
+async fn test_credential_headers_are_redacted() {
+    let line = access_line("/api/traced").await;
+
+    assert!(line.contains("authorization=[REDACTED]"), "{}", line);
+    assert!(!line.contains("secret-token"), "{}", line);
+}
+
+#[tokio::test]
+async fn test_routes_without_log_headers_log_none() {
+    let line = access_line("/api/plain").await;
+
+    assert!(!line.contains("trace-abc123"), "{}", line);
+    assert!(!line.contains("request_headers"), "{}", line);
+}
@@ -0,0 +1,58 @@
+//! `server.max_routes` and the startup route table estimate.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use rustway::{config::GatewayConfig, errors::ConfigError};
+
+fn config(route_count: usize, max_routes: &str) -> String {
+    let routes: String = (0..route_count)
+        .map(|i| format!("  - name: r{i}\n    path: /api/r{i}\n    destination: http://localhost:9001\n"))
+        .collect::<Vec<_>>()
+        .concat();
+    format!(
+        "server:\n  addr: \"127.0.0.1:8094\"\n{max_routes}routes:\n{routes}identity:\n  api_key_store_path: ./api_keys.yaml\n"
+    )
+}
+
+#[test]
+fn test_config_over_max_routes_fails_to_load() {
+    let err = GatewayConfig::from_yaml(&config(4, "  max_routes: 3\n")).unwrap_err();
+
+    assert!(
+        matches!(err, ConfigError::TooManyRoutes { count: 4, max: 3 }),
+        "{:?}",
+        err
+    );
+    assert!(
+        err.to_string().contains("4 routes, more than server.max_routes (3)"),
+        "{}",
+        err
+    );
+}
+
+#[test]
+fn test_config_within_max_routes_loads() {
+    assert_eq!(
+        GatewayConfig::from_yaml(&config(3, "  max_routes: 3\n"))
+            .unwrap()
+            .routes
+            .len(),
+        3
+    );
+    assert_eq!(GatewayConfig::from_yaml(&config(50, "")).unwrap().routes.len(), 50);
+}
+
+#[test]
+fn test_resource_estimate_counts_routes_and_regexes() {
+    let plain = GatewayConfig::from_yaml(&config(10, "")).unwrap().resource_estimate();
+    assert_eq!((plain.routes, plain.regexes), (10, 0));
+
+    let mut yaml = config(10, "");
+    yaml = yaml.replacen(
+        "    destination: http://localhost:9001\n",
+        "    destination: http://localhost:9001\n    rewrite: {pattern: \"^/api/r0/(.*)$\", replacement: \"/v2/$1\"}\n",
+        1,
+    );
+    let with_regex = GatewayConfig::from_yaml(&yaml).unwrap().resource_estimate();
+    assert_eq!(with_regex.regexes, 1);
+    assert!(with_regex.approx_bytes > plain.approx_bytes);
+}
@@ -0,0 +1,90 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use axum::{Router, body::Body, routing::any};
+use http::{Request, StatusCode};
+use tokio::net::TcpListener;
+
+/// A backend that counts the requests it receives.
+async fn counting_backend() -> (String, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let app = Router::new().fallback(any(move || {
+        let counter = counter.clone();
+        async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            "ok"
+        }
+    }));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, hits)
+}
+
+async fn app(backend: &str) -> Router {
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: api
+    path: /api
+    destination: "{backend}"
+security:
+  max_uri_length: 1024
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend
+    ))
+    .await;
+    app
+}
+
+async fn status(app: &Router, uri: &str) -> StatusCode {
+    common::send(app, Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .status()
+}
+
+#[tokio::test]
+async fn test_long_uri_is_rejected_before_proxying() {
+    let (backend, hits) = counting_backend().await;
+    let app = app(&backend).await;
+
+    let long_path = format!("/api/{}", "a".repeat(8 * 1024));
+    assert_eq!(status(&app, &long_path).await, StatusCode::URI_TOO_LONG);
+    // Would be a 404 if it had reached the route lookup
+    let unrouted = format!("/missing/{}", "a".repeat(8 * 1024));
+    assert_eq!(status(&app, &unrouted).await, StatusCode::URI_TOO_LONG);
+    let long_query = format!("/api?q={}", "a".repeat(1024));
+    assert_eq!(status(&app, &long_query).await, StatusCode::URI_TOO_LONG);
+    assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+    assert_eq!(status(&app, "/api/short").await, StatusCode::OK);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_zero_max_uri_length_fails_validation() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes: []
+security:
+  max_uri_length: 0
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    );
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(err.contains("max_uri_length must be at least 1"), "{}", err);
+}
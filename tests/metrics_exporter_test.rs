@@ -0,0 +1,207 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    net::UdpSocket,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{Json, Router, body::Body, extract::State, routing::post};
+use axum_prometheus::metrics::{counter, gauge, histogram, with_local_recorder};
+use http::{Request, StatusCode};
+use rustway::{
+    config::StatsdConfig,
+    features::metrics_exporter::{self, otlp::OtlpRecorder, statsd::StatsdRecorder},
+};
+use serde_json::Value;
+use tokio::net::TcpListener;
+
+/// A UDP socket standing in for the StatsD agent.
+fn statsd_sink() -> (UdpSocket, String) {
+    let sink = UdpSocket::bind("127.0.0.1:0").unwrap();
+    sink.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+    let addr = sink.local_addr().unwrap().to_string();
+    (sink, addr)
+}
+
+/// Reads lines until one starts with `prefix`.
+fn receive_line(sink: &UdpSocket, prefix: &str) -> String {
+    let mut buf = [0u8; 1024];
+    let mut seen = Vec::new();
+    while let Ok(len) = sink.recv(&mut buf) {
+        let line = String::from_utf8_lossy(&buf[..len]).into_owned();
+        if line.starts_with(prefix) {
+            return line;
+        }
+        seen.push(line);
+    }
+    panic!("no line starting with {} in {:?}", prefix, seen);
+}
+
+fn statsd_config(addr: &str, prefix: &str) -> StatsdConfig {
+    StatsdConfig {
+        addr: addr.to_string(),
+        prefix: prefix.to_string(),
+        tags: true,
+    }
+}
+
+#[test]
+fn test_statsd_lines_keep_metric_names_and_labels() {
+    let (sink, addr) = statsd_sink();
+    let recorder = StatsdRecorder::new(&statsd_config(&addr, "")).unwrap();
+
+    with_local_recorder(&recorder, || {
+        counter!("gateway_canary_rollbacks_total", "route" => "checkout").increment(1);
+    });
+    assert_eq!(
+        receive_line(&sink, "gateway_canary_rollbacks_total"),
+        "gateway_canary_rollbacks_total:1|c|#route:checkout"
+    );
+
+    with_local_recorder(&recorder, || gauge!("gateway_coalesce_leaders_in_flight").set(2.0));
+    assert_eq!(
+        receive_line(&sink, "gateway_coalesce_leaders_in_flight"),
+        "gateway_coalesce_leaders_in_flight:2|g"
+    );
+
+    with_local_recorder(&recorder, || {
+        histogram!("gateway_route_request_duration_seconds", "route" => "users").record(0.25);
+    });
+    assert_eq!(
+        receive_line(&sink, "gateway_route_request_duration_seconds"),
+        "gateway_route_request_duration_seconds:0.25|h|#route:users"
+    );
+}
+
+#[test]
+fn test_statsd_plain_format_drops_tags() {
+    let (sink, addr) = statsd_sink();
+    let mut config = statsd_config(&addr, "");
+    config.tags = false;
+    let recorder = StatsdRecorder::new(&config).unwrap();
+
+    with_local_recorder(&recorder, || {
+        counter!("gateway_canary_rollbacks_total", "route" => "checkout").increment(3);
+    });
+    assert_eq!(
+        receive_line(&sink, "gateway_canary_rollbacks_total"),
+        "gateway_canary_rollbacks_total:3|c"
+    );
+}
+
+/// The only test in this binary that installs the global recorder.
+#[tokio::test]
+async fn test_gateway_requests_reach_statsd_sink() {
+    let (sink, addr) = statsd_sink();
+    let yaml = format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: users
+    path: /api/users
+    static_file: ./Cargo.toml
+observability:
+  metrics:
+    enabled: true
+    exporter: statsd
+    statsd:
+      addr: "{addr}"
+      prefix: rustygw.
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        addr = addr
+    );
+    let config = common::parse_config(&yaml);
+    metrics_exporter::install(&config.observability.metrics, reqwest::Client::new()).unwrap();
+
+    let (app, _) = common::test_app(&yaml).await;
+    let response = common::send(&app, Request::builder().uri("/api/users").body(Body::empty()).unwrap()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let sink = tokio::task::spawn_blocking(move || {
+        (
+            receive_line(&sink, "rustygw.gateway_route_requests_total"),
+            receive_line(&sink, "rustygw.gateway_route_request_duration_seconds"),
+        )
+    });
+    let (requests, duration) = sink.await.unwrap();
+    assert_eq!(
+        requests,
+        "rustygw.gateway_route_requests_total:1|c|#route:users,status:200"
+    );
+    assert!(duration.ends_with("|h|#route:users"), "{}", duration);
+}
+
+#[tokio::test]
+async fn test_otlp_exporter_pushes_metric_names() {
+    let received: Arc<Mutex<Vec<Value>>> = Arc::default();
+    let collector = Router::new()
+        .route(
+            "/v1/metrics",
+            post(
+                |State(received): State<Arc<Mutex<Vec<Value>>>>, Json(body): Json<Value>| async move {
+                    received.lock().unwrap().push(body);
+                    StatusCode::OK
+                },
+            ),
+        )
+        .with_state(received.clone());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, collector).await.unwrap() });
+
+    let recorder = OtlpRecorder::new();
+    with_local_recorder(&recorder, || {
+        counter!("gateway_route_requests_total", "route" => "users", "status" => "200").increment(2);
+        histogram!("gateway_route_request_duration_seconds", "route" => "users").record(0.02);
+    });
+    let export = tokio::spawn(metrics_exporter::otlp::export_loop(
+        recorder,
+        endpoint,
+        Duration::from_millis(50),
+        reqwest::Client::new(),
+    ));
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    export.abort();
+
+    let payload = received.lock().unwrap().first().cloned().expect("nothing exported");
+    let metrics = payload["resourceMetrics"][0]["scopeMetrics"][0]["metrics"]
+        .as_array()
+        .unwrap()
+        .clone();
+    let metric = |name: &str| metrics.iter().find(|m| m["name"] == name).unwrap().clone();
+
+    let requests = metric("gateway_route_requests_total");
+    assert_eq!(requests["sum"]["dataPoints"][0]["asInt"], "2");
+    assert_eq!(requests["sum"]["dataPoints"][0]["attributes"][0]["key"], "route");
+    let duration = metric("gateway_route_request_duration_seconds");
+    assert_eq!(duration["histogram"]["dataPoints"][0]["count"], "1");
+}
+
+#[test]
+fn test_exporter_without_settings_fails_validation() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes: []
+observability:
+  metrics:
+    enabled: true
+    exporter: statsd
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    );
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(
+        err.contains("metrics exporter 'statsd' requires metrics.statsd"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,81 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::collections::HashSet;
+
+use rustway::config::GatewayConfig;
+use rustway::features::metrics::{OVERFLOW_LABEL, RouteLabels, UNMATCHED_LABEL, unmatched_endpoint_label};
+
+#[test]
+fn test_unmatched_paths_collapse_to_single_label() {
+    let cfg: GatewayConfig = serde_yaml::from_str(
+        r#"
+server:
+  addr: "0.0.0.0:8094"
+routes:
+  - name: users
+    path: /api/users
+    destination: http://localhost:8080
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap();
+    let labels = RouteLabels::new(100);
+
+    let emitted: HashSet<String> = (0..5_000)
+        .map(|i| {
+            let path = format!("/random/{}", i);
+            let route = cfg.find_route_for_path(&path).map(|r| r.name.clone());
+            labels.label_for(route.as_deref())
+        })
+        .collect();
+
+    assert_eq!(emitted.len(), 1);
+    assert!(emitted.contains(UNMATCHED_LABEL));
+    assert!(labels.is_empty());
+}
+
+#[test]
+fn test_matched_route_uses_route_name() {
+    let labels = RouteLabels::new(10);
+    assert_eq!(labels.label_for(Some("users")), "users");
+    assert_eq!(labels.label_for(Some("users")), "users");
+    assert_eq!(labels.len(), 1);
+}
+
+#[test]
+fn test_route_label_budget_is_bounded() {
+    let labels = RouteLabels::new(3);
+    let emitted: HashSet<String> = (0..50)
+        .map(|i| labels.label_for(Some(&format!("route-{}", i))))
+        .collect();
+
+    assert_eq!(labels.len(), 3);
+    assert_eq!(emitted.len(), 4);
+    assert!(emitted.contains(OVERFLOW_LABEL));
+    // Labels admitted before the budget ran out keep reporting under their own name
+    assert_eq!(labels.label_for(Some("route-0")), "route-0");
+}
+
+#[test]
+fn test_prometheus_fallback_label_ignores_raw_path() {
+    assert_eq!(unmatched_endpoint_label("/some/random/path/123"), UNMATCHED_LABEL);
+}
+
+#[test]
+fn test_metrics_config_defaults() {
+    let cfg: GatewayConfig = serde_yaml::from_str(
+        r#"
+server:
+  addr: "0.0.0.0:8094"
+routes: []
+observability:
+  metrics:
+    enabled: true
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap();
+    assert_eq!(cfg.observability.metrics.max_route_labels, 100);
+}
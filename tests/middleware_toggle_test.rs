@@ -0,0 +1,84 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::sync::Arc;
+
+use axum::{Router, body::Body, middleware::from_fn_with_state, routing::any};
+use http::{Request, StatusCode};
+use rustway::{
+    middleware::{
+        circuit_breaker::circuit_breaker::layer as circuit_breaker_layer, route_match::layer as route_match_layer,
+    },
+    state::AppState,
+};
+use tower::ServiceExt;
+
+const CONFIG: &str = r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: guarded
+    path: /api/guarded
+    destination: http://localhost:9001
+    circuit_breaker:
+      failure_threshold: 2
+      success_threshold: 1
+      open_duration: 60s
+  - name: unguarded
+    path: /api/unguarded
+    destination: http://localhost:9001
+    circuit_breaker:
+      failure_threshold: 2
+      success_threshold: 1
+      open_duration: 60s
+    middleware:
+      circuit_breaker: false
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#;
+
+fn failing_backend(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/{*path}", any(|| async { StatusCode::INTERNAL_SERVER_ERROR }))
+        .route_layer(from_fn_with_state(state.clone(), circuit_breaker_layer))
+        .route_layer(from_fn_with_state(state.clone(), route_match_layer))
+        .with_state(state)
+}
+
+async fn call(app: &Router, path: &str) -> StatusCode {
+    app.clone()
+        .oneshot(Request::builder().uri(path).body(Body::empty()).unwrap())
+        .await
+        .unwrap()
+        .status()
+}
+
+#[tokio::test]
+async fn test_enabled_circuit_breaker_trips() {
+    let app = failing_backend(common::test_state(CONFIG).await);
+
+    assert_eq!(call(&app, "/api/guarded").await, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(call(&app, "/api/guarded").await, StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(call(&app, "/api/guarded").await, StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_disabled_circuit_breaker_never_trips() {
+    let app = failing_backend(common::test_state(CONFIG).await);
+
+    for _ in 0..10 {
+        assert_eq!(call(&app, "/api/unguarded").await, StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
+
+#[test]
+fn test_middleware_toggles_default_enabled() {
+    let cfg = common::parse_config(CONFIG);
+    let guarded = &cfg.routes[0].middleware;
+    assert!(guarded.auth && guarded.rate_limit && guarded.cache && guarded.circuit_breaker);
+
+    let unguarded = &cfg.routes[1].middleware;
+    assert!(!unguarded.circuit_breaker);
+    assert!(unguarded.auth && unguarded.rate_limit && unguarded.cache);
+}
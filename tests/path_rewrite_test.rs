@@ -0,0 +1,120 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use axum::{Router, body::Body};
+use http::Request;
+use http_body_util::BodyExt;
+use serde_json::Value;
+use tokio::net::TcpListener;
+
+async fn app() -> Router {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: strip
+    path: /strip
+    destination: "{backend}"
+    rewrite:
+      strip_prefix: /internal
+  - name: renamed
+    path: /v1
+    destination: "{backend}/v2"
+    rewrite:
+      pattern: "^/old-name(/.*)?$"
+      replacement: "/new-name$1"
+  - name: reordered
+    path: /users
+    destination: "{backend}"
+    rewrite:
+      strip_prefix: /by-id
+      pattern: "^/(?P<id>[0-9]+)/(?P<section>[a-z]+)$"
+      replacement: "/sections/${{section}}/users/${{id}}"
+  - name: templated
+    path: /legacy
+    destination: "{backend}"
+    rewrite:
+      pattern: "^/items"
+      replacement: "/products"
+    transform:
+      rewrite_path: "/api{{path}}"
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#
+    ))
+    .await;
+    app
+}
+
+/// The path the backend was asked for, with its query string.
+async fn backend_path(app: &Router, uri: &str) -> (String, Value) {
+    let response = common::send(app, Request::builder().uri(uri).body(Body::empty()).unwrap()).await;
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let echo = serde_json::from_slice::<Value>(&body).unwrap();
+    (echo["path"].as_str().unwrap().to_string(), echo["query"].clone())
+}
+
+#[tokio::test]
+async fn test_strip_prefix_removes_leading_segment() {
+    let app = app().await;
+
+    assert_eq!(backend_path(&app, "/strip/internal/orders/7").await.0, "/orders/7");
+    // Paths without the prefix go through unchanged
+    assert_eq!(backend_path(&app, "/strip/public/orders").await.0, "/public/orders");
+}
+
+#[tokio::test]
+async fn test_regex_rewrite_maps_old_name_to_new_name() {
+    let app = app().await;
+
+    assert_eq!(
+        backend_path(&app, "/v1/old-name/widgets/3").await.0,
+        "/v2/new-name/widgets/3"
+    );
+    assert_eq!(backend_path(&app, "/v1/old-name").await.0, "/v2/new-name");
+    assert_eq!(backend_path(&app, "/v1/other/widgets").await.0, "/v2/other/widgets");
+}
+
+#[tokio::test]
+async fn test_regex_rewrite_uses_named_capture_groups_after_strip() {
+    let app = app().await;
+
+    let (path, query) = backend_path(&app, "/users/by-id/42/billing?expand=true").await;
+    assert_eq!(path, "/sections/billing/users/42");
+    assert_eq!(query, "expand=true");
+}
+
+#[tokio::test]
+async fn test_rewrite_runs_before_rewrite_path_template() {
+    let app = app().await;
+
+    assert_eq!(backend_path(&app, "/legacy/items/9").await.0, "/api/products/9");
+}
+
+#[test]
+fn test_invalid_rewrite_pattern_fails_validation() {
+    let err = rustway::config::GatewayConfig::from_yaml(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: api
+    path: /api
+    destination: http://localhost:9001
+    rewrite:
+      pattern: "^/(unclosed"
+      replacement: /x
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("invalid rewrite: pattern '^/(unclosed'"), "{}", err);
+}
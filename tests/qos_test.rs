@@ -0,0 +1,206 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use axum::{Router, body::Body, extract::Path, routing::get};
+use http::{HeaderMap, Request, StatusCode};
+use jsonwebtoken::{EncodingKey, Header, encode};
+use rustway::features::{auth::auth::Claims, qos::PriorityGate};
+use tokio::{net::TcpListener, sync::Notify};
+
+/// Records the order requests arrive in. `/hold` answers only once `release` fires.
+async fn recording_backend(release: Arc<Notify>) -> (String, Arc<Mutex<Vec<String>>>) {
+    let arrivals: Arc<Mutex<Vec<String>>> = Arc::default();
+    let seen = arrivals.clone();
+    let app = Router::new().route(
+        "/{name}",
+        get(move |Path(name): Path<String>| {
+            let (seen, release) = (seen.clone(), release.clone());
+            async move {
+                seen.lock().unwrap().push(name.clone());
+                if name == "hold" {
+                    release.notified().await;
+                }
+                name
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, arrivals)
+}
+
+async fn app(backend: &str, max_queue: usize) -> Router {
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+  qos:
+    max_concurrent: 1
+    max_queue: {max_queue}
+    queue_timeout: 5s
+    class_header: x-qos-class
+    classes:
+      - name: premium
+        priority: 10
+        roles: [premium]
+routes:
+  - name: api
+    path: /api
+    destination: "{backend}"
+    auth:
+      type: Jwt
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend,
+        max_queue = max_queue
+    ))
+    .await;
+    app
+}
+
+fn token(roles: &[&str]) -> String {
+    let exp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 3600;
+    let claims = Claims {
+        sub: "tester".to_string(),
+        roles: roles.iter().map(ToString::to_string).collect(),
+        exp: usize::try_from(exp).unwrap(),
+        iss: None,
+        aud: None,
+        extra: HashMap::new(),
+    };
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(common::TEST_JWT_SECRET.as_bytes()),
+    )
+    .unwrap()
+}
+
+fn spawn_request(app: &Router, path: &str, roles: &[&str]) -> tokio::task::JoinHandle<StatusCode> {
+    let (app, request) = (
+        app.clone(),
+        Request::builder()
+            .uri(path)
+            .header("authorization", format!("Bearer {}", token(roles)))
+            .body(Body::empty())
+            .unwrap(),
+    );
+    tokio::spawn(async move { common::send(&app, request).await.status() })
+}
+
+async fn wait_for_arrivals(arrivals: &Mutex<Vec<String>>, count: usize) {
+    for _ in 0..100 {
+        if arrivals.lock().unwrap().len() >= count {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!(
+        "backend saw {:?}, expected {} requests",
+        arrivals.lock().unwrap(),
+        count
+    );
+}
+
+#[tokio::test]
+async fn test_premium_request_is_admitted_ahead_of_queued_ones() {
+    let release = Arc::new(Notify::new());
+    let (backend, arrivals) = recording_backend(release.clone()).await;
+    let app = app(&backend, 10).await;
+
+    // Saturate the only slot
+    let hold = spawn_request(&app, "/api/hold", &["user"]);
+    wait_for_arrivals(&arrivals, 1).await;
+
+    // Queued first, but lower priority
+    let low = spawn_request(&app, "/api/low", &["user"]);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let high = spawn_request(&app, "/api/high", &["premium"]);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(
+        *arrivals.lock().unwrap(),
+        ["hold"],
+        "queued requests must wait for a slot"
+    );
+
+    release.notify_one();
+    assert_eq!(hold.await.unwrap(), StatusCode::OK);
+    assert_eq!(high.await.unwrap(), StatusCode::OK);
+    assert_eq!(low.await.unwrap(), StatusCode::OK);
+    assert_eq!(*arrivals.lock().unwrap(), ["hold", "high", "low"]);
+}
+
+#[tokio::test]
+async fn test_full_queue_returns_503() {
+    let release = Arc::new(Notify::new());
+    let (backend, arrivals) = recording_backend(release.clone()).await;
+    let app = app(&backend, 1).await;
+
+    let hold = spawn_request(&app, "/api/hold", &["user"]);
+    wait_for_arrivals(&arrivals, 1).await;
+    let queued = spawn_request(&app, "/api/queued", &["user"]);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let rejected = spawn_request(&app, "/api/rejected", &["premium"]);
+    assert_eq!(rejected.await.unwrap(), StatusCode::SERVICE_UNAVAILABLE);
+
+    release.notify_one();
+    assert_eq!(hold.await.unwrap(), StatusCode::OK);
+    assert_eq!(queued.await.unwrap(), StatusCode::OK);
+}
+
+#[test]
+fn test_class_from_roles_or_header() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+  qos:
+    max_concurrent: 10
+    class_header: x-qos-class
+    classes:
+      - {name: batch, priority: 1}
+      - {name: premium, priority: 10, roles: [premium, admin]}
+routes: []
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    );
+    let gate = PriorityGate::new(config.server.qos.as_ref().unwrap());
+    let no_headers = HeaderMap::new();
+
+    assert_eq!(gate.classify(&["admin".to_string()], &no_headers), ("premium", 10));
+    assert_eq!(gate.classify(&["user".to_string()], &no_headers), ("default", 0));
+
+    let mut headers = HeaderMap::new();
+    headers.insert("x-qos-class", "batch".parse().unwrap());
+    assert_eq!(gate.classify(&[], &headers), ("batch", 1));
+}
+
+#[test]
+fn test_invalid_qos_fails_validation() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+  qos:
+    max_concurrent: 0
+    queue_timeout: soon
+routes: []
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    );
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(err.contains("max_concurrent must be at least 1"), "{}", err);
+    assert!(err.contains("queue_timeout 'soon'"), "{}", err);
+}
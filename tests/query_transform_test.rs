@@ -0,0 +1,77 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use rustway::{config::GatewayConfig, proxy::apply_query_transform};
+
+fn transform_for(yaml: &str) -> rustway::config::QueryParamsTransform {
+    let cfg: GatewayConfig = serde_yaml::from_str(yaml).unwrap();
+    cfg.routes[0].transform.as_ref().unwrap().query_params.clone()
+}
+
+const ECHO_ROUTE: &str = r#"
+server:
+  addr: "0.0.0.0:8094"
+routes:
+  - name: echo
+    path: /echo
+    destination: http://localhost:9001/echo
+    transform:
+      query_params:
+        add:
+          api_version: "2"
+        remove: [debug]
+        rename:
+          q: search
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#;
+
+#[test]
+fn test_injected_param_reaches_backend_and_stripped_does_not() {
+    let qp = transform_for(ECHO_ROUTE);
+    let query = apply_query_transform(Some("debug=1&page=3"), &qp).unwrap();
+    assert!(query.contains("api_version=2"));
+    assert!(query.contains("page=3"));
+    assert!(!query.contains("debug"));
+}
+
+#[test]
+fn test_rename_param() {
+    let qp = transform_for(ECHO_ROUTE);
+    let query = apply_query_transform(Some("q=rust"), &qp).unwrap();
+    assert_eq!(query, "search=rust&api_version=2");
+}
+
+#[test]
+fn test_add_overrides_client_value() {
+    let qp = transform_for(ECHO_ROUTE);
+    let query = apply_query_transform(Some("api_version=1"), &qp).unwrap();
+    assert_eq!(query, "api_version=2");
+}
+
+#[test]
+fn test_add_to_empty_query() {
+    let qp = transform_for(ECHO_ROUTE);
+    assert_eq!(apply_query_transform(None, &qp).unwrap(), "api_version=2");
+}
+
+#[test]
+fn test_all_params_removed_returns_none() {
+    let mut qp = transform_for(ECHO_ROUTE);
+    qp.add.clear();
+    assert!(apply_query_transform(Some("debug=true"), &qp).is_none());
+}
+
+#[test]
+fn test_added_values_are_encoded() {
+    let mut qp = transform_for(ECHO_ROUTE);
+    qp.add.insert("tag".to_string(), "a b&c".to_string());
+    let query = apply_query_transform(None, &qp).unwrap();
+    assert!(query.contains("tag=a%20b%26c"));
+}
+
+#[test]
+fn test_flag_params_preserved() {
+    let qp = transform_for(ECHO_ROUTE);
+    let query = apply_query_transform(Some("verbose&page=1"), &qp).unwrap();
+    assert!(query.starts_with("verbose&page=1"));
+}
@@ -1,5 +1,7 @@
 use std::time::Duration;
 
+use rustway::middleware::rate_limiter::rate_limit::parse_duration;
+
 #[tokio::test]
 async fn test_basic_duration_functionality() {
     // Test basic duration functionality
@@ -24,13 +26,13 @@ async fn test_rate_limit_constants() {
 async fn test_rate_limit_calculations() {
     // Test basic rate limit calculations
     let requests_per_minute = 60;
-    let requests_per_second = requests_per_minute as f64 / 60.0;
+    let requests_per_second = f64::from(requests_per_minute) / 60.0;
 
-    assert_eq!(requests_per_second, 1.0);
+    assert!((requests_per_second - 1.0).abs() < f64::EPSILON);
 
     let window_ms = 1000;
-    let refill_rate = requests_per_second * (window_ms as f64 / 1000.0);
-    assert_eq!(refill_rate, 1.0);
+    let refill_rate = requests_per_second * (f64::from(window_ms) / 1000.0);
+    assert!((refill_rate - 1.0).abs() < f64::EPSILON);
 }
 
 #[tokio::test]
@@ -41,3 +43,13 @@ async fn test_rate_limit_time_windows() {
 
     assert_eq!(minute.as_secs() / second.as_secs(), 60);
 }
+
+#[test]
+fn test_parse_duration_units() {
+    assert_eq!(parse_duration("100ms"), Ok(Duration::from_millis(100)));
+    assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+    assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+    assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+    assert!(parse_duration("fastms").is_err());
+    assert!(parse_duration("10d").is_err());
+}
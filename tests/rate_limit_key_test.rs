@@ -0,0 +1,144 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    net::SocketAddr,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{Router, body::Body, extract::ConnectInfo, middleware::from_fn_with_state, routing::any};
+use http::{Request, StatusCode};
+use jsonwebtoken::{EncodingKey, Header, encode};
+use rustway::{
+    config::GatewayConfig,
+    errors::ConfigError,
+    middleware::{
+        auth::auth::layer as auth_layer,
+        rate_limiter::rate_limit::{KeySource, layer as ratelimiter_layer},
+        route_match::layer as route_match_layer,
+    },
+    state::AppState,
+};
+use serde_json::json;
+use tower::ServiceExt;
+
+const CONFIG: &str = r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: by_header
+    path: /api/header
+    destination: http://localhost:9001
+    rate_limit: {requests: 1, period: 1m, key: "header:x-tenant-id"}
+  - name: by_claim
+    path: /api/claim
+    destination: http://localhost:9001
+    auth:
+      type: Jwt
+    rate_limit: {requests: 1, period: 1m, key: "claim:tenant"}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#;
+
+fn app(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/{*path}", any(|| async { StatusCode::OK }))
+        .route_layer(from_fn_with_state(state.clone(), ratelimiter_layer))
+        .route_layer(from_fn_with_state(state.clone(), auth_layer))
+        .route_layer(from_fn_with_state(state.clone(), route_match_layer))
+        .with_state(state)
+}
+
+fn token(tenant: &str) -> String {
+    let exp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 3600;
+    encode(
+        &Header::default(),
+        &json!({ "sub": "tester", "roles": [], "exp": exp, "tenant": tenant }),
+        &EncodingKey::from_secret(common::TEST_JWT_SECRET.as_bytes()),
+    )
+    .unwrap()
+}
+
+/// Sends one request from the same client IP with the given header.
+async fn status(app: &Router, uri: &str, header: Option<(&str, String)>) -> StatusCode {
+    let mut req = Request::builder().uri(uri);
+    if let Some((name, value)) = header {
+        req = req.header(name, value);
+    }
+    let mut req = req.body(Body::empty()).unwrap();
+    req.extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 4000))));
+    app.clone().oneshot(req).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn test_tenants_from_header_get_independent_buckets() {
+    let app = app(common::test_state(CONFIG).await);
+    let tenant = |id: &str| Some(("x-tenant-id", id.to_string()));
+
+    assert_eq!(status(&app, "/api/header", tenant("acme")).await, StatusCode::OK);
+    assert_eq!(
+        status(&app, "/api/header", tenant("acme")).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    assert_eq!(status(&app, "/api/header", tenant("globex")).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_missing_header_falls_back_to_client_ip() {
+    let app = app(common::test_state(CONFIG).await);
+
+    assert_eq!(status(&app, "/api/header", None).await, StatusCode::OK);
+    assert_eq!(status(&app, "/api/header", None).await, StatusCode::TOO_MANY_REQUESTS);
+    // A tenant's bucket isn't the IP's
+    assert_eq!(
+        status(&app, "/api/header", Some(("x-tenant-id", "acme".to_string()))).await,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn test_service_name_does_not_escape_a_configured_key() {
+    let app = app(common::test_state(CONFIG).await);
+    let service = |name: &str| Some(("x-service-name", name.to_string()));
+
+    assert_eq!(status(&app, "/api/header", service("a")).await, StatusCode::OK);
+    // A new service name each time still lands in the client IP's bucket
+    assert_eq!(
+        status(&app, "/api/header", service("b")).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+}
+
+#[tokio::test]
+async fn test_tenants_from_claim_get_independent_buckets() {
+    let app = app(common::test_state(CONFIG).await);
+    let bearer = |tenant: &str| Some(("Authorization", format!("Bearer {}", token(tenant))));
+
+    assert_eq!(status(&app, "/api/claim", bearer("acme")).await, StatusCode::OK);
+    assert_eq!(
+        status(&app, "/api/claim", bearer("acme")).await,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+    assert_eq!(status(&app, "/api/claim", bearer("globex")).await, StatusCode::OK);
+}
+
+#[test]
+fn test_key_source_parsing() {
+    assert_eq!(
+        KeySource::parse("header:X-Tenant-Id"),
+        Some(KeySource::Header("X-Tenant-Id"))
+    );
+    assert_eq!(KeySource::parse("claim:tenant"), Some(KeySource::Claim("tenant")));
+    assert_eq!(KeySource::parse("claim:"), None);
+    assert_eq!(KeySource::parse("ip"), None);
+}
+
+#[test]
+fn test_invalid_key_is_rejected() {
+    let yaml = CONFIG.replace("header:x-tenant-id", "cookie:tenant");
+    let result = GatewayConfig::from_yaml(&yaml);
+    assert!(matches!(result, Err(ConfigError::InvalidRateLimit { route, .. }) if route == "/api/header"));
+}
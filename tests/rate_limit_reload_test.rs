@@ -0,0 +1,97 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::path::PathBuf;
+
+use axum::{Router, body::Body};
+use http::{Request, StatusCode};
+use rustway::{
+    features::rate_limiter::state::{InMemoryRateLimitState, RateLimitDecision, RateLimitState},
+    utils::hot_reload::reload_gateway_config,
+};
+use tokio::net::TcpListener;
+
+fn config(backend: &str, requests: u32) -> String {
+    format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: orders
+    path: /orders
+    destination: "{backend}/echo"
+    rate_limit: {{requests: {requests}, period: 1m}}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend,
+        requests = requests
+    )
+}
+
+fn config_file(test: &str, contents: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustygw-rl-reload-{}-{}", test, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("gateway.yaml");
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+async fn start_backend() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+    url
+}
+
+async fn status(app: &Router) -> StatusCode {
+    common::send(app, Request::builder().uri("/orders").body(Body::empty()).unwrap())
+        .await
+        .status()
+}
+
+#[tokio::test]
+async fn test_tightened_limit_applies_on_next_request() {
+    let backend = start_backend().await;
+    let (app, state) = common::test_app(&config(&backend, 100)).await;
+    for _ in 0..3 {
+        assert_eq!(status(&app).await, StatusCode::OK);
+    }
+
+    let path = config_file("tighten", &config(&backend, 2));
+    reload_gateway_config(&path, &state.config).await.unwrap();
+
+    assert_eq!(status(&app).await, StatusCode::OK);
+    assert_eq!(status(&app).await, StatusCode::OK);
+    assert_eq!(status(&app).await, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_reload_does_not_refill_buckets() {
+    let backend = start_backend().await;
+    let (app, state) = common::test_app(&config(&backend, 1)).await;
+    assert_eq!(status(&app).await, StatusCode::OK);
+    assert_eq!(status(&app).await, StatusCode::TOO_MANY_REQUESTS);
+
+    // A higher limit refills faster but doesn't hand out a fresh burst
+    let path = config_file("loosen", &config(&backend, 5));
+    reload_gateway_config(&path, &state.config).await.unwrap();
+    assert_eq!(status(&app).await, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_lowered_capacity_caps_a_full_bucket_at_once() {
+    let store = InMemoryRateLimitState::new();
+    // Refills far too slowly to matter here
+    let rate = 0.001;
+    assert!(store.check_and_update("client", 100, rate).await.unwrap().is_allowed());
+
+    // 99 tokens left under the old limit; only 2 survive the lower one
+    assert!(store.check_and_update("client", 2, rate).await.unwrap().is_allowed());
+    assert!(store.check_and_update("client", 2, rate).await.unwrap().is_allowed());
+    assert!(matches!(
+        store.check_and_update("client", 2, rate).await.unwrap(),
+        RateLimitDecision::Limited { .. }
+    ));
+}
@@ -0,0 +1,118 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use common::harness::TestGateway;
+use reqwest::{Client, redirect::Policy};
+use rustway::proxy::redirect_host_allowed;
+use serde_json::Value;
+
+/// A client that shows us exactly what the gateway returned.
+fn client() -> Client {
+    Client::builder().redirect(Policy::none()).build().unwrap()
+}
+
+#[tokio::test]
+async fn test_redirect_is_relayed_by_default() {
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: moved
+    path: /api/moved
+    destination: "{backend}/redirect/302?to=http://127.0.0.1:1/"
+"#,
+    )
+    .await;
+
+    let resp = client()
+        .get(format!("{}/api/moved", gateway.base_url))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 302);
+    assert_eq!(resp.headers()["location"], "http://127.0.0.1:1/");
+}
+
+#[tokio::test]
+async fn test_redirect_to_disallowed_host_is_not_followed() {
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: moved
+    path: /api/moved
+    destination: "{backend}/redirect/302?to=http://internal.evil.test/admin"
+    max_redirects: 3
+    allowed_domains: ["127.0.0.1"]
+"#,
+    )
+    .await;
+
+    let resp = client()
+        .get(format!("{}/api/moved", gateway.base_url))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 302);
+    assert_eq!(resp.headers()["location"], "http://internal.evil.test/admin");
+}
+
+#[tokio::test]
+async fn test_redirect_to_allowed_host_is_followed() {
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: moved
+    path: /api/moved
+    destination: "{backend}/redirect/307?to=/echo/landed"
+    max_redirects: 1
+"#,
+    )
+    .await;
+
+    let resp = client()
+        .post(format!("{}/api/moved", gateway.base_url))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["path"], "/echo/landed");
+    // 307 keeps the method
+    assert_eq!(body["method"], "POST");
+}
+
+#[tokio::test]
+async fn test_redirect_hops_are_bounded() {
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: moved
+    path: /api/moved
+    destination: "{backend}/redirect/302?to=/redirect/302%3Fto%3D/echo/two-hops"
+    max_redirects: 1
+"#,
+    )
+    .await;
+
+    let resp = client()
+        .get(format!("{}/api/moved", gateway.base_url))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 302);
+    assert_eq!(resp.headers()["location"], "/echo/two-hops");
+}
+
+#[test]
+fn test_redirect_host_matching() {
+    let origins = vec!["backend.internal".to_string()];
+    assert!(redirect_host_allowed("backend.internal", &[], &origins));
+    assert!(!redirect_host_allowed("evil.test", &[], &origins));
+
+    let allowed = vec![".example.com".to_string(), "cdn.test".to_string()];
+    assert!(redirect_host_allowed("api.example.com", &allowed, &origins));
+    assert!(redirect_host_allowed("example.com", &allowed, &origins));
+    assert!(redirect_host_allowed("CDN.test", &allowed, &origins));
+    assert!(!redirect_host_allowed("badexample.com", &allowed, &origins));
+    assert!(!redirect_host_allowed("backend.internal", &allowed, &origins));
+}
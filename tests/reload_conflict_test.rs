@@ -0,0 +1,75 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::path::PathBuf;
+
+use rustway::{config::GatewayConfig, errors::ReloadError, utils::hot_reload::reload_gateway_config};
+use tokio::sync::RwLock;
+
+const ORIGINAL: &str = r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: users
+    path: /api/users
+    destination: http://localhost:9001
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#;
+
+fn config_file(test: &str, contents: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustygw-reload-{}-{}", test, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("gateway.yaml");
+    std::fs::write(&path, contents).unwrap();
+    path
+}
+
+#[tokio::test]
+async fn test_reload_with_duplicate_path_reports_conflicting_route() {
+    let path = config_file("conflict", ORIGINAL);
+    let config = RwLock::new(GatewayConfig::load(&path).unwrap());
+
+    std::fs::write(
+        &path,
+        ORIGINAL.replace(
+            "identity:",
+            "  - name: people\n    path: /api/users\n    destination: http://localhost:9002\nidentity:",
+        ),
+    )
+    .unwrap();
+
+    let err = reload_gateway_config(&path, &config).await.unwrap_err();
+    assert!(matches!(err, ReloadError::RouteConflict(_)), "{:?}", err);
+    assert_eq!(err.category(), "route_conflict");
+    let message = err.to_string();
+    assert!(message.contains("'people'"), "{}", message);
+    assert!(message.contains("'users'"), "{}", message);
+    assert!(message.contains("/api/users"), "{}", message);
+
+    // The old config stays active
+    assert_eq!(config.read().await.routes.len(), 1);
+}
+
+#[tokio::test]
+async fn test_reload_with_other_invalid_config_is_not_a_conflict() {
+    let path = config_file("invalid", ORIGINAL);
+    let config = RwLock::new(GatewayConfig::load(&path).unwrap());
+
+    std::fs::write(&path, ORIGINAL.replace("    destination: http://localhost:9001\n", "")).unwrap();
+
+    let err = reload_gateway_config(&path, &config).await.unwrap_err();
+    assert!(matches!(err, ReloadError::Invalid(_)), "{:?}", err);
+    assert_eq!(err.category(), "invalid_config");
+    assert_eq!(config.read().await.routes[0].destination, "http://localhost:9001");
+}
+
+#[tokio::test]
+async fn test_valid_reload_is_applied() {
+    let path = config_file("valid", ORIGINAL);
+    let config = RwLock::new(GatewayConfig::load(&path).unwrap());
+
+    std::fs::write(&path, ORIGINAL.replace("/api/users", "/api/v2/users")).unwrap();
+
+    reload_gateway_config(&path, &config).await.unwrap();
+    assert_eq!(config.read().await.routes[0].path, "/api/v2/users");
+}
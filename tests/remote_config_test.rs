@@ -0,0 +1,119 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{Router, body::Body, extract::State, routing::get};
+use http::{Request, StatusCode};
+use reqwest::Client;
+use rustway::{
+    build_app, build_state,
+    config::{ApiKeyStore, SecretsConfig},
+    utils::{
+        config_source::{ConfigSource, HttpConfigSource},
+        hot_reload::watch_remote_config,
+    },
+};
+use tokio::{net::TcpListener, sync::RwLock};
+
+fn gateway_yaml(backend: &str, routes: &[&str]) -> String {
+    let routes: String = routes
+        .iter()
+        .map(|name| format!("  - name: {name}\n    path: /{name}\n    destination: \"{backend}\"\n"))
+        .collect::<Vec<_>>()
+        .concat();
+    format!("server:\n  addr: \"127.0.0.1:8094\"\nroutes:\n{routes}identity:\n  api_key_store_path: ./api_keys.yaml\n")
+}
+
+/// Serves whatever is currently in the returned slot at `/gateway.yaml`.
+async fn config_server(initial: String) -> (String, Arc<Mutex<String>>) {
+    let served = Arc::new(Mutex::new(initial));
+    let app = Router::new()
+        .route(
+            "/gateway.yaml",
+            get(|State(served): State<Arc<Mutex<String>>>| async move { served.lock().unwrap().clone() }),
+        )
+        .with_state(served.clone());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/gateway.yaml", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, served)
+}
+
+async fn status(app: &Router, uri: &str) -> StatusCode {
+    common::send(app, Request::builder().uri(uri).body(Body::empty()).unwrap())
+        .await
+        .status()
+}
+
+async fn wait_for_status(app: &Router, uri: &str, expected: StatusCode) {
+    for _ in 0..100 {
+        if status(app, uri).await == expected {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!("{} never returned {}", uri, expected);
+}
+
+#[tokio::test]
+async fn test_changed_remote_config_updates_live_routes() {
+    let backend_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend = format!("http://{}", backend_listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(backend_listener, common::harness::example_backend())
+            .await
+            .unwrap();
+    });
+
+    let (url, served) = config_server(gateway_yaml(&backend, &["orders"])).await;
+    let mut source = ConfigSource::Http(HttpConfigSource::new(url, Duration::from_millis(20), Client::new()));
+    let config = Arc::new(RwLock::new(source.load().await.unwrap()));
+    let ConfigSource::Http(remote) = source else {
+        unreachable!()
+    };
+    let state = build_state(
+        config.clone(),
+        Arc::new(SecretsConfig {
+            jwt_secret: common::TEST_JWT_SECRET.to_string(),
+            ..Default::default()
+        }),
+        Arc::new(RwLock::new(ApiKeyStore::default())),
+        None,
+    )
+    .await
+    .unwrap();
+    let app = build_app(state).await.unwrap();
+    tokio::spawn(watch_remote_config(remote, config.clone()));
+
+    assert_eq!(status(&app, "/orders/1").await, StatusCode::OK);
+    assert_eq!(status(&app, "/invoices/1").await, StatusCode::NOT_FOUND);
+
+    *served.lock().unwrap() = gateway_yaml(&backend, &["orders", "invoices"]);
+    wait_for_status(&app, "/invoices/1", StatusCode::OK).await;
+
+    // An invalid config is rejected and the last good one stays live
+    *served.lock().unwrap() = gateway_yaml(&backend, &["orders", "orders"]);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(status(&app, "/invoices/1").await, StatusCode::OK);
+    assert_eq!(config.read().await.routes.len(), 2);
+}
+
+#[tokio::test]
+async fn test_unreachable_remote_config_fails_to_load() {
+    let mut source = ConfigSource::Http(HttpConfigSource::new(
+        "http://127.0.0.1:1/gateway.yaml",
+        Duration::from_secs(1),
+        Client::new(),
+    ));
+    let err = source.load().await.unwrap_err().to_string();
+    assert!(
+        err.starts_with("Failed to fetch config from http://127.0.0.1:1/gateway.yaml"),
+        "{}",
+        err
+    );
+}
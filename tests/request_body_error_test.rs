@@ -0,0 +1,68 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use axum::{Router, body::Body, routing::post};
+use bytes::Bytes;
+use http::{Request, StatusCode};
+use tokio::net::TcpListener;
+
+#[tokio::test]
+async fn test_aborted_request_body_is_a_client_error() {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let backend = Router::new().route(
+        "/upload",
+        post(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            async { "ok" }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, backend).await.unwrap() });
+
+    let (app, state) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: upload
+    path: /upload
+    destination: "{backend}/upload"
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend_url
+    ))
+    .await;
+
+    // The client sends part of the body, then the upload is cut off
+    let truncated = Body::from_stream(futures::stream::iter([
+        Ok(Bytes::from_static(b"partial")),
+        Err(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "connection reset by client",
+        )),
+    ]));
+    let response = common::send(
+        &app,
+        Request::builder()
+            .method("POST")
+            .uri("/upload")
+            .body(truncated)
+            .unwrap(),
+    )
+    .await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(hits.load(Ordering::SeqCst), 0, "nothing should be forwarded");
+    let summary = state.stats.summary();
+    assert_eq!(summary.client_errors, 1);
+    assert_eq!(summary.server_errors, 0);
+}
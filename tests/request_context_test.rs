@@ -0,0 +1,77 @@
+//! The route is matched once, by the outermost layer; everything below reads
+//! the `RequestContext` it leaves. A context already on the request is kept,
+//! so seeding one that disagrees with the path shows who looked it up again.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{net::IpAddr, sync::Arc};
+
+use axum::{Router, body::Body};
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use rustway::{config::GatewayConfig, middleware::route_match::RequestContext};
+use serde_json::Value;
+use tokio::net::TcpListener;
+
+/// Sends `uri` with `context` already attached.
+async fn send_with_context(app: &Router, uri: &str, context: RequestContext) -> (StatusCode, Value) {
+    let mut request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+    request.extensions_mut().insert(context);
+    let response = common::send(app, request).await;
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_layers_and_proxy_use_the_request_context() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+
+    let routes = |name: &str, destination: &str, rate_limit: &str| {
+        format!(
+            r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: {name}
+    path: /api/users/{{id}}
+    destination: "{backend}{destination}"
+    {rate_limit}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+            name = name,
+            backend = backend,
+            destination = destination,
+            rate_limit = rate_limit
+        )
+    };
+    // What the path matches: no rate limit
+    let (app, _) = common::test_app(&routes("users", "/users/{id}", "")).await;
+    // What the context says instead: another destination and one request a minute
+    let pinned = GatewayConfig::from_yaml(&routes(
+        "pinned",
+        "/pinned/{id}",
+        "rate_limit: {requests: 1, period: 1m}",
+    ))
+    .unwrap()
+    .routes[0]
+        .clone();
+    let context = || RequestContext {
+        route: Some(Arc::clone(&pinned)),
+        params: vec![("id".to_string(), "7".to_string())],
+        client_ip: Some(IpAddr::from([127, 0, 0, 1])),
+        request_id: None,
+    };
+
+    let (status, body) = send_with_context(&app, "/api/users/42", context()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["path"], "/pinned/7");
+
+    // Only the context's route is rate limited
+    let (status, _) = send_with_context(&app, "/api/users/42", context()).await;
+    assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+}
@@ -0,0 +1,98 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use common::harness::TestGateway;
+use serde_json::Value;
+
+fn deadline_ms(body: &Value) -> u64 {
+    body["headers"]["x-request-deadline"]
+        .as_str()
+        .expect("backend received no x-request-deadline")
+        .parse()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_backend_receives_remaining_deadline() {
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: slow
+    path: /api/slow
+    request_deadline: 2s
+    load_balance: round_robin
+    retry: {count: 1, backoff: 10ms}
+    destinations:
+      - "{backend}/delay/1000|timeout=300ms"
+      - "{backend}/echo"
+  - name: fast
+    path: /api/fast
+    request_deadline: 2s
+    destination: "{backend}/echo"
+"#,
+    )
+    .await;
+
+    // First request on a fresh gateway: round robin starts on the delay
+    // destination, which times out before failing over to the echo
+    let slow: Value = reqwest::get(format!("{}/api/slow", gateway.base_url))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let fast: Value = reqwest::get(format!("{}/api/fast", gateway.base_url))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    let (slow, fast) = (deadline_ms(&slow), deadline_ms(&fast));
+    assert!(fast <= 2000 && fast > 1700, "fast route budget {}", fast);
+    assert!(
+        slow <= 1700,
+        "slow route budget {} should reflect the failed attempt",
+        slow
+    );
+    assert!(slow < fast);
+}
+
+#[tokio::test]
+async fn test_no_deadline_header_without_request_deadline() {
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: plain
+    path: /api/plain
+    destination: "{backend}/echo"
+"#,
+    )
+    .await;
+
+    let body: Value = reqwest::get(format!("{}/api/plain", gateway.base_url))
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(body["headers"]["x-request-deadline"].is_null());
+}
+
+#[tokio::test]
+async fn test_exhausted_deadline_is_504() {
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: slow
+    path: /api/slow
+    request_deadline: 200ms
+    destination: "{backend}/delay/1000"
+"#,
+    )
+    .await;
+
+    let resp = reqwest::get(format!("{}/api/slow", gateway.base_url)).await.unwrap();
+    assert_eq!(resp.status(), 504);
+}
@@ -0,0 +1,187 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use axum::{Extension, Router, body::Body, middleware::from_fn_with_state, routing::get};
+use http::{HeaderValue, Request};
+use http_body_util::BodyExt;
+use rustway::{
+    config::{RequestIdFormat, RequestIdPolicy},
+    middleware::request_id::{
+        generator::{MAX_REQUEST_ID_LEN, RequestIdGenerator, is_safe_request_id, is_valid_ulid},
+        request_id::layer as request_id_layer,
+    },
+};
+use tower::ServiceExt;
+use uuid::Uuid;
+
+fn config(format: &str, policy: &str) -> String {
+    format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes: []
+observability:
+  request_id:
+    format: {}
+    policy: {}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        format, policy
+    )
+}
+
+async fn generated_id(format: &str) -> String {
+    resolved_id(format, "trust_if_valid", None).await
+}
+
+/// The request id the layer settles on for a request carrying `inbound`.
+async fn resolved_id(format: &str, policy: &str, inbound: Option<&str>) -> String {
+    let state = common::test_state(&config(format, policy)).await;
+    let app = Router::new()
+        .route(
+            "/",
+            get(|Extension(id): Extension<Arc<String>>| async move { id.to_string() }),
+        )
+        .layer(from_fn_with_state(state, request_id_layer));
+    let mut request = Request::builder().uri("/");
+    if let Some(inbound) = inbound {
+        request = request.header("x-request-id", inbound);
+    }
+    let resp = app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap();
+    String::from_utf8(resp.into_body().collect().await.unwrap().to_bytes().to_vec()).unwrap()
+}
+
+#[test]
+fn test_ulid_ids_are_valid_and_sort_by_creation_time() {
+    let generator = RequestIdGenerator::new(RequestIdFormat::Ulid);
+    let mut ids = Vec::new();
+    for batch in 0..5 {
+        if batch > 0 {
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        ids.extend((0..200).map(|_| generator.generate()));
+    }
+
+    assert!(ids.iter().all(|id| is_valid_ulid(id)));
+    let mut sorted = ids.clone();
+    sorted.sort();
+    assert_eq!(sorted, ids);
+    sorted.dedup();
+    assert_eq!(sorted.len(), ids.len());
+}
+
+#[test]
+fn test_every_format_is_a_valid_header_value() {
+    for format in [RequestIdFormat::UuidV4, RequestIdFormat::Ulid, RequestIdFormat::Nanoid] {
+        let generator = RequestIdGenerator::new(format);
+        for _ in 0..100 {
+            let id = generator.generate();
+            assert!(HeaderValue::from_str(&id).is_ok(), "{:?} produced {}", format, id);
+        }
+    }
+}
+
+#[test]
+fn test_nanoid_shape() {
+    let generator = RequestIdGenerator::new(RequestIdFormat::Nanoid);
+    let id = generator.generate();
+    assert_eq!(id.len(), 21);
+    assert!(id.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-'));
+    assert_ne!(id, generator.generate());
+}
+
+#[test]
+fn test_is_valid_ulid_rejects_malformed() {
+    assert!(is_valid_ulid("01ARZ3NDEKTSV4RRFFQ69G5FAV"));
+    assert!(!is_valid_ulid("01ARZ3NDEKTSV4RRFFQ69G5FA")); // too short
+    assert!(!is_valid_ulid("01ARZ3NDEKTSV4RRFFQ69G5FAU")); // U is not Crockford
+    assert!(!is_valid_ulid("81ARZ3NDEKTSV4RRFFQ69G5FAV")); // overflows 128 bits
+}
+
+#[tokio::test]
+async fn test_layer_uses_configured_format() {
+    assert!(Uuid::parse_str(&generated_id("uuid_v4").await).is_ok());
+    assert!(is_valid_ulid(&generated_id("ulid").await));
+    assert_eq!(generated_id("nanoid").await.len(), 21);
+}
+
+#[tokio::test]
+async fn test_default_format_is_uuid_v4() {
+    let state = common::test_state(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes: []
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .await;
+    assert_eq!(state.request_id_generator.format(), RequestIdFormat::UuidV4);
+}
+
+const VALID_ID: &str = "client-7f3a.9c:01";
+/// Tabs are the one control character a header value may carry.
+const CONTROL_ID: &str = "abc\tforged-log-line";
+
+fn too_long_id() -> String {
+    "a".repeat(MAX_REQUEST_ID_LEN + 1)
+}
+
+#[tokio::test]
+async fn test_always_generate_ignores_client_ids() {
+    for inbound in [VALID_ID, &too_long_id(), CONTROL_ID] {
+        let id = resolved_id("uuid_v4", "always_generate", Some(inbound)).await;
+        assert!(Uuid::parse_str(&id).is_ok(), "kept {:?}", inbound);
+    }
+}
+
+#[tokio::test]
+async fn test_trust_if_valid_keeps_only_safe_client_ids() {
+    assert_eq!(resolved_id("uuid_v4", "trust_if_valid", Some(VALID_ID)).await, VALID_ID);
+
+    let id = resolved_id("uuid_v4", "trust_if_valid", Some(&too_long_id())).await;
+    assert!(Uuid::parse_str(&id).is_ok());
+
+    let id = resolved_id("uuid_v4", "trust_if_valid", Some(CONTROL_ID)).await;
+    assert!(Uuid::parse_str(&id).is_ok());
+}
+
+#[tokio::test]
+async fn test_trust_keeps_client_ids_as_sent() {
+    assert_eq!(resolved_id("uuid_v4", "trust", Some(VALID_ID)).await, VALID_ID);
+    assert_eq!(
+        resolved_id("uuid_v4", "trust", Some(&too_long_id())).await,
+        too_long_id()
+    );
+    assert_eq!(resolved_id("uuid_v4", "trust", Some(CONTROL_ID)).await, CONTROL_ID);
+}
+
+#[tokio::test]
+async fn test_default_policy_is_trust_if_valid() {
+    let state = common::test_state(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes: []
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .await;
+    assert_eq!(state.request_id_generator.policy(), RequestIdPolicy::TrustIfValid);
+}
+
+#[test]
+fn test_is_safe_request_id() {
+    assert!(is_safe_request_id(VALID_ID));
+    assert!(is_safe_request_id(&"a".repeat(MAX_REQUEST_ID_LEN)));
+    assert!(!is_safe_request_id(&too_long_id()));
+    assert!(!is_safe_request_id(CONTROL_ID));
+    assert!(!is_safe_request_id("id with spaces"));
+    assert!(!is_safe_request_id(""));
+}
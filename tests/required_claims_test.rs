@@ -0,0 +1,141 @@
+//! Authorization on arbitrary claims via an auth method's `required_claims`.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::{Router, body::Body};
+use http::{Request, StatusCode};
+use jsonwebtoken::{EncodingKey, Header, encode};
+use rustway::{
+    config::AuthConfig,
+    errors::AppError,
+    features::auth::auth::{Claims, check_claims},
+};
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+
+fn claims(extra: Value) -> Claims {
+    Claims {
+        sub: "alice".to_string(),
+        roles: vec!["user".to_string()],
+        exp: 0,
+        iss: None,
+        aud: None,
+        extra: serde_json::from_value(extra).unwrap(),
+    }
+}
+
+fn check(auth_yaml: &str, extra: Value) -> Result<(), AppError> {
+    let auth: AuthConfig = serde_yaml::from_str(auth_yaml).unwrap();
+    check_claims(&claims(extra), &auth.required_claims)
+}
+
+#[test]
+fn test_equality_match() {
+    let auth = "type: Jwt\nrequired_claims: {tenant: acme}";
+    assert!(check(auth, json!({"tenant": "acme"})).is_ok());
+    assert!(matches!(
+        check(auth, json!({"tenant": "globex"})),
+        Err(AppError::InsufficientPermissions)
+    ));
+    assert!(matches!(check(auth, json!({})), Err(AppError::InsufficientPermissions)));
+
+    // Non-string values compare as JSON, and `sub` counts as a claim
+    assert!(
+        check(
+            "type: Jwt\nrequired_claims: {verified: true, level: 3}",
+            json!({"verified": true, "level": 3})
+        )
+        .is_ok()
+    );
+    assert!(
+        check(
+            "type: Jwt\nrequired_claims: {verified: true}",
+            json!({"verified": "true"})
+        )
+        .is_err()
+    );
+    assert!(check("type: Jwt\nrequired_claims: {sub: alice}", json!({})).is_ok());
+}
+
+#[test]
+fn test_membership_match() {
+    let auth = "type: Jwt\nrequired_claims: {plan: [pro, enterprise]}";
+    assert!(check(auth, json!({"plan": "pro"})).is_ok());
+    assert!(check(auth, json!({"plan": "enterprise"})).is_ok());
+    assert!(matches!(
+        check(auth, json!({"plan": "free"})),
+        Err(AppError::InsufficientPermissions)
+    ));
+}
+
+#[test]
+fn test_list_valued_claim_matches_on_any_item() {
+    let auth = "type: Jwt\nrequired_claims: {groups: admins}";
+    assert!(check(auth, json!({"groups": ["staff", "admins"]})).is_ok());
+    assert!(check(auth, json!({"groups": ["staff"]})).is_err());
+}
+
+#[test]
+fn test_every_required_claim_must_match() {
+    let auth = "type: Jwt\nrequired_claims: {tenant: acme, plan: [pro, enterprise]}";
+    assert!(check(auth, json!({"tenant": "acme", "plan": "pro"})).is_ok());
+    assert!(check(auth, json!({"tenant": "acme", "plan": "free"})).is_err());
+    assert!(check(auth, json!({"tenant": "globex", "plan": "pro"})).is_err());
+}
+
+async fn app() -> Router {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: reports
+    path: /api/reports
+    destination: "{backend}/echo"
+    auth:
+      type: Jwt
+      roles: [user]
+      required_claims:
+        tenant: acme
+        plan: [pro, enterprise]
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#
+    ))
+    .await;
+    app
+}
+
+fn request(extra: Value) -> Request<Body> {
+    let mut claims = claims(extra);
+    claims.exp = usize::try_from(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 3600).unwrap();
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(common::TEST_JWT_SECRET.as_bytes()),
+    )
+    .unwrap();
+    Request::builder()
+        .uri("/api/reports")
+        .header("Authorization", format!("Bearer {}", token))
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_route_rejects_mismatched_claims_with_403() {
+    let app = app().await;
+
+    let allowed = common::send(&app, request(json!({"tenant": "acme", "plan": "enterprise"}))).await;
+    assert_eq!(allowed.status(), StatusCode::OK);
+    let wrong_tenant = common::send(&app, request(json!({"tenant": "globex", "plan": "pro"}))).await;
+    assert_eq!(wrong_tenant.status(), StatusCode::FORBIDDEN);
+    let wrong_plan = common::send(&app, request(json!({"tenant": "acme", "plan": "free"}))).await;
+    assert_eq!(wrong_plan.status(), StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,167 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::{sync::Arc, time::Duration};
+
+use bytes::Bytes;
+use http::{HeaderMap, HeaderValue, StatusCode, header::CONTENT_TYPE};
+use rustway::{
+    features::cache::{ResponseCache, jittered_ttl, memory::MokaResponseCache},
+    state::CachedResponse,
+};
+
+fn response(body: &'static str) -> Arc<CachedResponse> {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    headers.append("x-multi", HeaderValue::from_static("a"));
+    headers.append("x-multi", HeaderValue::from_static("b"));
+    Arc::new(CachedResponse {
+        status: StatusCode::CREATED,
+        headers,
+        body: Bytes::from_static(body.as_bytes()),
+    })
+}
+
+async fn exercise_get_insert(cache: &dyn ResponseCache, key: &str) {
+    assert!(cache.get(key).await.unwrap().is_none());
+
+    cache
+        .insert(key.to_string(), response("{\"a\":1}"), Some(Duration::from_secs(60)))
+        .await;
+    let hit = cache.get(key).await.unwrap().unwrap();
+    assert_eq!(hit.status, StatusCode::CREATED);
+    assert_eq!(hit.body, Bytes::from_static(b"{\"a\":1}"));
+    assert_eq!(hit.headers.get_all("x-multi").iter().count(), 2);
+
+    cache.invalidate(key).await;
+    assert!(cache.get(key).await.unwrap().is_none());
+}
+
+async fn exercise_expiry(cache: &dyn ResponseCache, key: &str) {
+    cache
+        .insert(key.to_string(), response("short"), Some(Duration::from_millis(100)))
+        .await;
+    assert!(cache.get(key).await.unwrap().is_some());
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert!(cache.get(key).await.unwrap().is_none());
+}
+
+async fn exercise_overwrite(cache: &dyn ResponseCache, key: &str) {
+    cache
+        .insert(key.to_string(), response("old"), Some(Duration::from_secs(60)))
+        .await;
+    cache
+        .insert(key.to_string(), response("new"), Some(Duration::from_secs(60)))
+        .await;
+    assert_eq!(cache.get(key).await.unwrap().unwrap().body, Bytes::from_static(b"new"));
+    cache.invalidate(key).await;
+}
+
+#[test]
+fn test_cached_response_round_trips_through_json() {
+    let original = response("payload");
+    let encoded = serde_json::to_vec(original.as_ref()).unwrap();
+    let decoded: CachedResponse = serde_json::from_slice(&encoded).unwrap();
+    assert_eq!(decoded.status, original.status);
+    assert_eq!(decoded.headers, original.headers);
+    assert_eq!(decoded.body, original.body);
+}
+
+#[tokio::test]
+async fn test_moka_get_insert_invalidate() {
+    exercise_get_insert(&MokaResponseCache::new(100), "/api/users").await;
+}
+
+#[tokio::test]
+async fn test_moka_entries_expire() {
+    exercise_expiry(&MokaResponseCache::new(100), "/api/users").await;
+}
+
+#[tokio::test]
+async fn test_moka_insert_overwrites() {
+    exercise_overwrite(&MokaResponseCache::new(100), "/api/users").await;
+}
+
+#[tokio::test]
+async fn test_moka_entry_without_ttl_is_kept() {
+    let cache = MokaResponseCache::new(100);
+    cache.insert("/api/users".to_string(), response("forever"), None).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(cache.get("/api/users").await.unwrap().is_some());
+}
+
+/// Needs a Redis at `REDIS_URL` (default `redis://127.0.0.1:6379`):
+/// `cargo test --features redis --test response_cache_test`
+#[cfg(feature = "redis")]
+mod redis_backend {
+    use super::*;
+    use rustway::features::cache::redis_store::RedisResponseCache;
+
+    async fn cache() -> RedisResponseCache {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1:6379".to_string());
+        let prefix = format!("rustygw:test:{}:", std::process::id());
+        RedisResponseCache::connect(&url, &prefix).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_redis_get_insert_invalidate() {
+        exercise_get_insert(&cache().await, "/api/users/get").await;
+    }
+
+    #[tokio::test]
+    async fn test_redis_entries_expire() {
+        exercise_expiry(&cache().await, "/api/users/expire").await;
+    }
+
+    #[tokio::test]
+    async fn test_redis_insert_overwrites() {
+        exercise_overwrite(&cache().await, "/api/users/overwrite").await;
+    }
+}
+
+#[test]
+fn test_ttl_jitter_spreads_expiry_of_a_batch() {
+    let ttl = Duration::from_secs(60);
+    let ttls: Vec<Duration> = (0..100).map(|_| jittered_ttl(ttl, 20)).collect();
+
+    // Every entry stays within +/-20% of the configured TTL
+    assert!(
+        ttls.iter()
+            .all(|t| *t >= Duration::from_secs(48) && *t <= Duration::from_secs(72))
+    );
+
+    // ...but they don't expire together
+    let min = ttls.iter().min().unwrap();
+    let max = ttls.iter().max().unwrap();
+    assert!(
+        (*max).checked_sub(*min).unwrap() > Duration::from_secs(10),
+        "spread {:?}..{:?}",
+        min,
+        max
+    );
+    assert!(ttls.iter().filter(|t| **t < ttl).count() > 10);
+    assert!(ttls.iter().filter(|t| **t > ttl).count() > 10);
+}
+
+#[test]
+fn test_no_jitter_keeps_ttl() {
+    let ttl = Duration::from_secs(60);
+    assert!((0..10).all(|_| jittered_ttl(ttl, 0) == ttl));
+}
+
+#[test]
+fn test_ttl_jitter_of_100_percent_is_rejected() {
+    let yaml = r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: products
+    path: /api/products
+    destination: http://localhost:9001
+    cache: {ttl: 60s, ttl_jitter: 100}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#;
+    let err = rustway::config::GatewayConfig::from_yaml(yaml).unwrap_err().to_string();
+    assert!(err.contains("ttl_jitter must be below 100"), "{}", err);
+}
@@ -0,0 +1,123 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use axum::{Router, body::Body, response::IntoResponse};
+use http::{HeaderMap, Request, StatusCode};
+use tokio::net::TcpListener;
+
+/// Answers with a mix of useful and internal headers.
+async fn leaky_backend() -> String {
+    let app = Router::new().fallback(|| async {
+        (
+            [
+                ("content-type", "application/json"),
+                ("cache-control", "max-age=60"),
+                ("server", "internal-app/1.2.3"),
+                ("x-powered-by", "Express"),
+                ("x-backend-node", "pod-7f9c"),
+            ],
+            "{}",
+        )
+            .into_response()
+    });
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
+
+async fn headers(app: &Router, uri: &str) -> HeaderMap {
+    let response = common::send(app, Request::builder().uri(uri).body(Body::empty()).unwrap()).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    response.headers().clone()
+}
+
+#[tokio::test]
+async fn test_only_allowlisted_response_headers_reach_the_client() {
+    let backend = leaky_backend().await;
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+defaults:
+  response_header_allowlist: [content-type]
+routes:
+  - name: strict
+    path: /strict
+    destination: "{backend}"
+    response_header_allowlist: [Content-Type, Cache-Control]
+    transform:
+      response_headers:
+        x-gateway: rustygw
+  - name: inherited
+    path: /inherited
+    destination: "{backend}"
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend
+    ))
+    .await;
+
+    let strict = headers(&app, "/strict").await;
+    assert_eq!(strict["content-type"], "application/json");
+    assert_eq!(strict["cache-control"], "max-age=60");
+    assert_eq!(strict["x-gateway"], "rustygw");
+    assert!(strict.contains_key("x-request-id"));
+    for leaked in ["server", "x-powered-by", "x-backend-node"] {
+        assert!(!strict.contains_key(leaked), "{} leaked: {:?}", leaked, strict);
+    }
+
+    let inherited = headers(&app, "/inherited").await;
+    assert_eq!(inherited["content-type"], "application/json");
+    assert!(!inherited.contains_key("cache-control"));
+    assert!(!inherited.contains_key("x-powered-by"));
+}
+
+#[tokio::test]
+async fn test_all_response_headers_pass_by_default() {
+    let backend = leaky_backend().await;
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: open
+    path: /open
+    destination: "{backend}"
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend
+    ))
+    .await;
+
+    let open = headers(&app, "/open").await;
+    assert_eq!(open["x-powered-by"], "Express");
+    assert_eq!(open["server"], "internal-app/1.2.3");
+}
+
+#[test]
+fn test_invalid_allowlist_entry_fails_validation() {
+    let err = rustway::config::GatewayConfig::from_yaml(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: api
+    path: /api
+    destination: http://localhost:9001
+    response_header_allowlist: ["bad header"]
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(
+        err.contains("invalid response_header_allowlist entry 'bad header'"),
+        "{}",
+        err
+    );
+}
@@ -0,0 +1,69 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use axum::{Router, body::Body, extract::Path, response::IntoResponse, routing::get};
+use http::{HeaderMap, HeaderName, HeaderValue, Request, StatusCode};
+use tokio::net::TcpListener;
+
+/// Answers `/headers/{count}/{size}` with `count` extra headers of `size` bytes each.
+async fn header_backend() -> String {
+    let app = Router::new().route(
+        "/headers/{count}/{size}",
+        get(|Path((count, size)): Path<(usize, usize)>| async move {
+            let mut headers = HeaderMap::new();
+            for i in 0..count {
+                headers.insert(
+                    HeaderName::try_from(format!("x-filler-{}", i)).unwrap(),
+                    HeaderValue::from_str(&"a".repeat(size)).unwrap(),
+                );
+            }
+            (headers, "ok").into_response()
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
+
+async fn status_for(count: usize, size: usize) -> StatusCode {
+    let backend = header_backend().await;
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+tuning:
+  max_response_header_bytes: 8192
+routes:
+  - name: headers
+    path: /api/headers
+    destination: "{backend}/headers/{count}/{size}"
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend,
+        count = count,
+        size = size
+    ))
+    .await;
+    let request = Request::builder().uri("/api/headers").body(Body::empty()).unwrap();
+    common::send(&app, request).await.status()
+}
+
+#[tokio::test]
+async fn test_headers_within_limit_are_relayed() {
+    assert_eq!(status_for(10, 100).await, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_headers_over_limit_return_502() {
+    // 40 x ~1KB, well past the 8KB limit but within what the HTTP client parses
+    assert_eq!(status_for(40, 1000).await, StatusCode::BAD_GATEWAY);
+}
+
+#[tokio::test]
+async fn test_huge_header_section_returns_502() {
+    // More headers than the HTTP client accepts at all
+    assert_eq!(status_for(500, 200).await, StatusCode::BAD_GATEWAY);
+}
@@ -0,0 +1,85 @@
+//! `Retry-After` on responses rejected by the rate limiter.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+use axum::{Router, body::Body, extract::ConnectInfo, middleware::from_fn_with_state, routing::any};
+use http::{Request, StatusCode, header::RETRY_AFTER};
+use rustway::{
+    features::rate_limiter::state::{InMemoryRateLimitState, RateLimitDecision, RateLimitState},
+    middleware::{rate_limiter::rate_limit::layer as ratelimiter_layer, route_match::layer as route_match_layer},
+    state::AppState,
+};
+use tower::ServiceExt;
+
+const CONFIG: &str = r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: reports
+    path: /api/reports
+    destination: http://localhost:9001
+    rate_limit: {requests: 2, period: 1m}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#;
+
+fn app(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/{*path}", any(|| async { StatusCode::OK }))
+        .route_layer(from_fn_with_state(state.clone(), ratelimiter_layer))
+        .route_layer(from_fn_with_state(state.clone(), route_match_layer))
+        .with_state(state)
+}
+
+async fn send(app: &Router) -> http::Response<Body> {
+    let mut req = Request::builder().uri("/api/reports").body(Body::empty()).unwrap();
+    req.extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 4000))));
+    app.clone().oneshot(req).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_limited_response_says_when_to_retry() {
+    let app = app(common::test_state(CONFIG).await);
+    for _ in 0..2 {
+        let resp = send(&app).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert!(resp.headers().get(RETRY_AFTER).is_none());
+    }
+
+    let resp = send(&app).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    // 2 per minute refills a token every 30s
+    let retry_after: u64 = resp.headers()[RETRY_AFTER].to_str().unwrap().parse().unwrap();
+    assert!((29..=30).contains(&retry_after), "Retry-After: {}", retry_after);
+}
+
+#[tokio::test]
+async fn test_store_reports_time_until_next_token() {
+    let store = InMemoryRateLimitState::new();
+    // One token, refilling at half a token per second
+    assert_eq!(
+        store.check_and_update("client", 1, 0.5).await.unwrap(),
+        RateLimitDecision::Allowed
+    );
+    match store.check_and_update("client", 1, 0.5).await.unwrap() {
+        RateLimitDecision::Limited { retry_after } => {
+            assert!(retry_after <= Duration::from_secs(2), "{:?}", retry_after);
+            assert!(retry_after > Duration::from_millis(1900), "{:?}", retry_after);
+        }
+        RateLimitDecision::Allowed => panic!("second request was allowed"),
+    }
+}
+
+#[tokio::test]
+async fn test_rounds_up_to_at_least_one_second() {
+    let app = app(common::test_state(&CONFIG.replace("requests: 2, period: 1m", "requests: 1, period: 100ms")).await);
+    assert_eq!(send(&app).await.status(), StatusCode::OK);
+
+    let resp = send(&app).await;
+    assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(resp.headers()[RETRY_AFTER], "1");
+}
@@ -0,0 +1,104 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::sync::{
+    Arc,
+    atomic::{AtomicUsize, Ordering},
+};
+
+use axum::{Router, body::Body, routing::get};
+use http::{Request, StatusCode};
+use tokio::net::TcpListener;
+
+/// Always answers 503 and counts how often it was called.
+async fn failing_backend() -> (String, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let app = Router::new().route(
+        "/{*path}",
+        get(move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, hits)
+}
+
+async fn app(backend: &str, retry_budget: &str) -> Router {
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+{retry_budget}
+routes:
+  - name: orders
+    path: /orders
+    destination: "{backend}"
+    retry: {{count: 3, backoff: 1ms, retry_on: [503]}}
+  - name: invoices
+    path: /invoices
+    destination: "{backend}"
+    retry: {{count: 3, backoff: 1ms, retry_on: [503]}}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend,
+        retry_budget = retry_budget
+    ))
+    .await;
+    app
+}
+
+async fn burst(app: &Router, requests: usize) {
+    for i in 0..requests {
+        let path = if i % 2 == 0 { "/orders/1" } else { "/invoices/1" };
+        let response = common::send(app, Request::builder().uri(path).body(Body::empty()).unwrap()).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+}
+
+#[tokio::test]
+async fn test_retries_across_routes_are_bounded_by_budget() {
+    let (backend, hits) = failing_backend().await;
+    let app = app(&backend, "  retry_budget:\n    retries: 5\n    period: 1h").await;
+
+    burst(&app, 20).await;
+
+    // One first attempt per request, plus at most the budget in retries
+    assert_eq!(hits.load(Ordering::SeqCst), 20 + 5);
+}
+
+#[tokio::test]
+async fn test_retries_are_unbounded_without_budget() {
+    let (backend, hits) = failing_backend().await;
+    let app = app(&backend, "").await;
+
+    burst(&app, 20).await;
+
+    assert_eq!(hits.load(Ordering::SeqCst), 20 * 4);
+}
+
+#[test]
+fn test_invalid_retry_budget_period_fails_validation() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+  retry_budget:
+    retries: 10
+    period: often
+routes: []
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    );
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(err.contains("Retry budget is invalid: period 'often'"), "{}", err);
+}
@@ -0,0 +1,134 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use axum::{Router, body::Body, routing::any};
+use http::{Request, StatusCode};
+use rustway::config::RetryConfig;
+use tokio::net::TcpListener;
+
+/// A backend that always answers 503 and counts the requests it gets.
+async fn unavailable_backend() -> (String, Arc<AtomicUsize>) {
+    let hits = Arc::new(AtomicUsize::new(0));
+    let counter = hits.clone();
+    let app = Router::new().route(
+        "/{*path}",
+        any(move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (url, hits)
+}
+
+async fn app(destination: &str, retry: &str) -> Router {
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: orders
+    path: /orders
+    destination: "{destination}"
+    retry: {retry}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#
+    ))
+    .await;
+    app
+}
+
+fn request(method: &str) -> Request<Body> {
+    Request::builder()
+        .method(method)
+        .uri("/orders/1")
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_exhausted_retries_return_last_status() {
+    let (backend, hits) = unavailable_backend().await;
+    let app = app(&backend, "{max_attempts: 3, backoff: 1ms}").await;
+
+    let response = common::send(&app, request("GET")).await;
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn test_exhausted_retries_on_connection_failure_return_502() {
+    // Nothing listens here once the listener is dropped
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let closed = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+    let app = app(&closed, "{max_attempts: 3, backoff: 1ms}").await;
+
+    let response = common::send(&app, request("GET")).await;
+
+    assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+}
+
+#[tokio::test]
+async fn test_backoff_doubles_between_attempts() {
+    let (backend, hits) = unavailable_backend().await;
+    let app = app(&backend, "{max_attempts: 4, backoff: 50ms}").await;
+
+    let started = Instant::now();
+    common::send(&app, request("GET")).await;
+
+    // 50ms + 100ms + 200ms
+    assert_eq!(hits.load(Ordering::SeqCst), 4);
+    assert!(
+        started.elapsed() >= Duration::from_millis(350),
+        "{:?}",
+        started.elapsed()
+    );
+}
+
+#[tokio::test]
+async fn test_non_idempotent_methods_are_not_retried_by_default() {
+    let (backend, hits) = unavailable_backend().await;
+    let app = app(&backend, "{max_attempts: 3, backoff: 1ms}").await;
+
+    let response = common::send(&app, request("POST")).await;
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(hits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_non_idempotent_methods_retry_when_opted_in() {
+    let (backend, hits) = unavailable_backend().await;
+    let app = app(&backend, "{max_attempts: 3, backoff: 1ms, retry_non_idempotent: true}").await;
+
+    common::send(&app, request("POST")).await;
+
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn test_max_attempts_overrides_count() {
+    let retry: RetryConfig = serde_yaml::from_str("{count: 5, max_attempts: 2}").unwrap();
+    assert_eq!(retry.attempts(), 2);
+
+    let retry: RetryConfig = serde_yaml::from_str("{count: 5}").unwrap();
+    assert_eq!(retry.attempts(), 6);
+    assert!(!retry.retry_non_idempotent);
+}
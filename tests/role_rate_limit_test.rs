@@ -0,0 +1,134 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{Router, body::Body, extract::ConnectInfo, middleware::from_fn_with_state, routing::any};
+use http::{Request, StatusCode};
+use jsonwebtoken::{EncodingKey, Header, encode};
+use rustway::{
+    features::auth::auth::Claims,
+    middleware::{
+        auth::auth::layer as auth_layer, rate_limiter::rate_limit::layer as ratelimiter_layer,
+        route_match::layer as route_match_layer,
+    },
+    state::AppState,
+};
+use tower::ServiceExt;
+
+const CONFIG: &str = r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: reports
+    path: /api/reports
+    destination: http://localhost:9001
+    auth:
+      type: Jwt
+    rate_limit:
+      requests: 1
+      period: 1m
+      roles:
+        - role: admin
+          requests: 5
+        - role: user
+          requests: 2
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#;
+
+fn app(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/{*path}", any(|| async { StatusCode::OK }))
+        .route_layer(from_fn_with_state(state.clone(), ratelimiter_layer))
+        .route_layer(from_fn_with_state(state.clone(), auth_layer))
+        .route_layer(from_fn_with_state(state.clone(), route_match_layer))
+        .with_state(state)
+}
+
+fn token(roles: &[&str]) -> String {
+    let exp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 3600;
+    let claims = Claims {
+        sub: "tester".to_string(),
+        roles: roles.iter().map(ToString::to_string).collect(),
+        exp: usize::try_from(exp).unwrap(),
+        iss: None,
+        aud: None,
+        extra: HashMap::new(),
+    };
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(common::TEST_JWT_SECRET.as_bytes()),
+    )
+    .unwrap()
+}
+
+/// Number of requests accepted before the first 429, out of `attempts`.
+async fn allowed_requests(app: &Router, token: &str, attempts: usize) -> usize {
+    let mut allowed = 0;
+    for _ in 0..attempts {
+        let mut req = Request::builder()
+            .uri("/api/reports")
+            .header("Authorization", format!("Bearer {}", token))
+            .body(Body::empty())
+            .unwrap();
+        req.extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 4000))));
+        match app.clone().oneshot(req).await.unwrap().status() {
+            StatusCode::OK => allowed += 1,
+            StatusCode::TOO_MANY_REQUESTS => break,
+            other => panic!("unexpected status {}", other),
+        }
+    }
+    allowed
+}
+
+#[tokio::test]
+async fn test_admin_gets_higher_limit_than_user() {
+    let app = app(common::test_state(CONFIG).await);
+
+    assert_eq!(allowed_requests(&app, &token(&["admin"]), 10).await, 5);
+    assert_eq!(allowed_requests(&app, &token(&["user"]), 10).await, 2);
+}
+
+#[tokio::test]
+async fn test_first_listed_role_wins() {
+    let app = app(common::test_state(CONFIG).await);
+
+    assert_eq!(allowed_requests(&app, &token(&["user", "admin"]), 10).await, 5);
+}
+
+#[tokio::test]
+async fn test_unmatched_role_uses_route_default() {
+    let app = app(common::test_state(CONFIG).await);
+
+    assert_eq!(allowed_requests(&app, &token(&["guest"]), 10).await, 1);
+}
+
+#[test]
+fn test_role_limit_inherits_period() {
+    let config = common::parse_config(CONFIG);
+    let rate_limit = config.routes[0].rate_limit.as_ref().unwrap();
+
+    assert_eq!(
+        rate_limit.limit_for_roles(&["admin".to_string()]),
+        (Some("admin"), 5, "1m")
+    );
+    assert_eq!(rate_limit.limit_for_roles(&[]), (None, 1, "1m"));
+}
+
+#[test]
+fn test_zero_role_limit_fails_validation() {
+    let mut config = common::parse_config(&CONFIG.replace("requests: 2", "requests: 0"));
+    config.resolve_services_pub();
+    config.apply_defaults_pub();
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(err.contains("role 'user'"));
+}
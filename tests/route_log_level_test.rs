@@ -0,0 +1,137 @@
+//! Logs are captured on the test's own thread, so requests go through the
+//! in-memory router.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use axum::body::Body;
+use http::{Request, StatusCode};
+use rustway::utils::logging::route_level_filter;
+use tokio::net::TcpListener;
+use tracing::Level;
+use tracing_subscriber::{Layer, fmt::MakeWriter, layer::SubscriberExt};
+
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedLogs {
+    fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = CapturedLogs;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+async fn start_backend() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+    url
+}
+
+async fn logs_for(path: &str) -> String {
+    let backend = start_backend().await;
+    let (app, _state) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: flaky
+    path: /api/flaky
+    destination: "{backend}/echo"
+    log_level: debug
+  - name: quiet
+    path: /api/quiet
+    destination: "{backend}/echo"
+  - name: muted
+    path: /api/muted
+    destination: "{backend}/echo"
+    log_level: warn
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend
+    ))
+    .await;
+
+    let logs = CapturedLogs::default();
+    let subscriber = tracing_subscriber::registry().with(
+        tracing_subscriber::fmt::layer()
+            .with_writer(logs.clone())
+            .with_ansi(false)
+            .with_filter(route_level_filter(Level::INFO)),
+    );
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let request = Request::builder().uri(path).body(Body::empty()).unwrap();
+    let response = common::send(&app, request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    logs.contents()
+}
+
+#[tokio::test]
+async fn test_route_with_debug_level_logs_debug() {
+    let logs = logs_for("/api/flaky").await;
+    let line = logs
+        .lines()
+        .find(|l| l.contains("Backend responded"))
+        .unwrap_or_else(|| panic!("no debug log in:\n{}", logs));
+    assert!(line.contains("DEBUG"), "{}", line);
+    assert!(line.contains("route=flaky"), "{}", line);
+    assert!(logs.contains("access"), "{}", logs);
+}
+
+#[tokio::test]
+async fn test_route_at_default_level_does_not_log_debug() {
+    let logs = logs_for("/api/quiet").await;
+    assert!(!logs.contains("DEBUG"), "{}", logs);
+    // Still logged at the global info level
+    assert!(logs.contains("access"), "{}", logs);
+}
+
+#[tokio::test]
+async fn test_route_level_can_be_quieter_than_global() {
+    let logs = logs_for("/api/muted").await;
+    assert!(!logs.contains("access"), "{}", logs);
+}
+
+#[test]
+fn test_invalid_log_level_fails_validation() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: flaky
+    path: /api/flaky
+    destination: http://localhost:9001
+    log_level: verbose
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    );
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(err.contains("invalid log_level 'verbose'"), "{}", err);
+}
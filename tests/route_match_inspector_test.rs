@@ -0,0 +1,158 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{collections::HashMap, time::Duration};
+
+use axum::{Json, Router, body::Body, routing::post};
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use rustway::config::{ApiKeyDetails, ApiKeyStore, RouteMatchRule};
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+
+const CONFIG: &str = r#"
+server:
+  addr: "127.0.0.1:8094"
+  admin:
+    auth: {type: ApiKey, roles: [admin]}
+routes:
+  - name: api
+    path: /api
+    destination: "http://api:8000"
+  - name: users
+    path: /api/users
+    destination: "http://users:8000"
+  - name: user_orders
+    path: /api/users/{id}/orders
+    destination: "http://orders:8000/{id}"
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#;
+
+async fn test_app() -> Router {
+    let key_store = ApiKeyStore {
+        keys: HashMap::from([(
+            "ops-key".to_string(),
+            ApiKeyDetails {
+                user_id: "ops".to_string(),
+                roles: vec!["admin".to_string()],
+                status: "active".to_string(),
+            },
+        )]),
+        ..Default::default()
+    };
+    common::test_app_with_keys(CONFIG, key_store).await.0
+}
+
+async fn inspect(app: &Router, query: &str) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .uri(format!("/admin/match?{}", query))
+        .header("Authorization", "Bearer ops-key")
+        .body(Body::empty())
+        .unwrap();
+    let response = common::send(app, request).await;
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn test_reports_longest_prefix_match() {
+    let app = test_app().await;
+    let (status, body) = inspect(&app, "path=/api/users/42&host=example.com&method=GET").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["matched"], true);
+    assert_eq!(body["route"], "users");
+    assert_eq!(body["route_path"], "/api/users");
+    assert_eq!(body["rule"], "prefix");
+    assert_eq!(body["request"]["host"], "example.com");
+    assert_eq!(body["request"]["method"], "GET");
+}
+
+#[tokio::test]
+async fn test_reports_pattern_match() {
+    let app = test_app().await;
+    let (_, body) = inspect(&app, "path=/api/users/42/orders").await;
+
+    assert_eq!(body["route"], "user_orders");
+    assert_eq!(body["rule"], "pattern");
+}
+
+#[tokio::test]
+async fn test_reports_no_match() {
+    let app = test_app().await;
+    let (status, body) = inspect(&app, "path=/nowhere").await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["matched"], false);
+    assert!(body["route"].is_null());
+    assert!(body["reason"].is_string());
+}
+
+#[tokio::test]
+async fn test_requires_admin_auth() {
+    let app = test_app().await;
+    let request = Request::builder()
+        .uri("/admin/match?path=/api")
+        .body(Body::empty())
+        .unwrap();
+
+    assert_eq!(common::send(&app, request).await.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[test]
+fn test_find_route_with_rule() {
+    let mut config = common::parse_config(CONFIG);
+    config.build_route_tree_pub();
+
+    let (route, rule) = config.find_route_with_rule("/api").unwrap();
+    assert_eq!((route.name.as_str(), rule), ("api", RouteMatchRule::Pattern));
+    let (route, rule) = config.find_route_with_rule("/api/other").unwrap();
+    assert_eq!((route.name.as_str(), rule), ("api", RouteMatchRule::Prefix));
+    assert!(config.find_route_with_rule("/").is_none());
+}
+
+#[tokio::test]
+async fn test_slow_admin_auth_does_not_block_config_reloads() {
+    // An introspection endpoint that takes a second to answer
+    let introspection = Router::new().route(
+        "/introspect",
+        post(|| async {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            Json(json!({"active": true, "sub": "ops", "roles": []}))
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/introspect", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, introspection).await.unwrap() });
+
+    let (app, state) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+  admin:
+    auth: {{type: Introspection, introspection_url: "{url}", timeout: 5s}}
+routes: []
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#
+    ))
+    .await;
+    let pending = tokio::spawn(async move {
+        let request = Request::builder()
+            .uri("/admin/match?path=/api")
+            .header("Authorization", "Bearer some-token")
+            .body(Body::empty())
+            .unwrap();
+        common::send(&app, request).await.status()
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    // A reload takes the write lock while the admin request is still authenticating
+    let write = tokio::time::timeout(Duration::from_millis(200), state.config.write()).await;
+    assert!(write.is_ok(), "config write waited on admin auth");
+    drop(write);
+    assert_eq!(pending.await.unwrap(), StatusCode::OK);
+}
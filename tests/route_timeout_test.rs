@@ -0,0 +1,56 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use axum::{Router, body::Body};
+use http::{Request, StatusCode};
+use tokio::net::TcpListener;
+
+async fn app() -> Router {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: api
+    path: /api
+    destination: "{backend}"
+    timeout: 200ms
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend
+    ))
+    .await;
+    app
+}
+
+fn get(uri: &str) -> Request<Body> {
+    Request::builder().uri(uri).body(Body::empty()).unwrap()
+}
+
+#[tokio::test]
+async fn test_backend_slower_than_route_timeout_returns_504() {
+    let app = app().await;
+
+    let started = Instant::now();
+    let response = common::send(&app, get("/api/slow?ms=2000")).await;
+
+    assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+    assert!(started.elapsed() < Duration::from_secs(2), "{:?}", started.elapsed());
+}
+
+#[tokio::test]
+async fn test_backend_within_route_timeout_succeeds() {
+    let app = app().await;
+
+    let response = common::send(&app, get("/api/slow?ms=10")).await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+}
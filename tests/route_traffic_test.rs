@@ -0,0 +1,118 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{Router, body::Body};
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use rustway::{
+    config::{ApiKeyDetails, ApiKeyStore},
+    features::traffic::RouteTraffic,
+};
+use serde_json::Value;
+use tokio::net::TcpListener;
+
+async fn start_backend() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+    url
+}
+
+async fn test_app() -> Router {
+    let backend = start_backend().await;
+    let key_store = ApiKeyStore {
+        keys: HashMap::from([(
+            "ops-key".to_string(),
+            ApiKeyDetails {
+                user_id: "ops".to_string(),
+                roles: vec!["admin".to_string()],
+                status: "active".to_string(),
+            },
+        )]),
+        ..Default::default()
+    };
+    let (app, _state) = common::test_app_with_keys(
+        &format!(
+            r#"
+server:
+  addr: "127.0.0.1:8094"
+  admin:
+    auth: {{type: ApiKey, roles: [admin]}}
+routes:
+  - name: busy
+    path: /api/busy
+    destination: "{backend}/echo"
+  - name: dead
+    path: /api/dead
+    destination: "{backend}/echo"
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+            backend = backend
+        ),
+        key_store,
+    )
+    .await;
+    app
+}
+
+async fn admin_routes(app: &Router, key: &str) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .uri("/admin/routes")
+        .header("Authorization", format!("Bearer {}", key))
+        .body(Body::empty())
+        .unwrap();
+    let response = common::send(app, request).await;
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+fn now_ms() -> u64 {
+    u64::try_from(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis()).unwrap()
+}
+
+#[tokio::test]
+async fn test_hit_route_is_counted_and_untouched_route_is_not() {
+    let app = test_app().await;
+    let before = now_ms();
+    for _ in 0..3 {
+        let response = common::send(&app, Request::builder().uri("/api/busy").body(Body::empty()).unwrap()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    let (status, body) = admin_routes(&app, "ops-key").await;
+    assert_eq!(status, StatusCode::OK);
+    let routes = body["routes"].as_array().unwrap();
+    let route = |name: &str| routes.iter().find(|r| r["name"] == name).unwrap().clone();
+
+    let busy = route("busy");
+    assert_eq!(busy["requests"], 3);
+    let last_seen = busy["last_request_ms"].as_u64().unwrap();
+    assert!(last_seen >= before && last_seen <= now_ms(), "{}", busy);
+
+    let dead = route("dead");
+    assert_eq!(dead["requests"], 0);
+    assert!(dead["last_request_ms"].is_null());
+}
+
+#[tokio::test]
+async fn test_admin_routes_requires_auth() {
+    let app = test_app().await;
+    assert_eq!(admin_routes(&app, "bogus").await.0, StatusCode::UNAUTHORIZED);
+}
+
+#[test]
+fn test_snapshot_of_unknown_route_is_empty() {
+    let traffic = RouteTraffic::new();
+    traffic.record("busy");
+    assert_eq!(traffic.snapshot("busy").requests, 1);
+    assert_eq!(traffic.snapshot("dead").requests, 0);
+    assert_eq!(traffic.snapshot("dead").last_request_ms, None);
+}
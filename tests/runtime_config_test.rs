@@ -0,0 +1,64 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use rustway::config::{GatewayConfig, RuntimeConfig};
+
+#[test]
+fn test_runtime_config_defaults() {
+    let cfg: GatewayConfig = serde_yaml::from_str(
+        r#"
+server:
+  addr: "0.0.0.0:8094"
+routes: []
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap();
+    assert!(cfg.server.runtime.worker_threads.is_none());
+    assert!(cfg.server.runtime.max_blocking_threads.is_none());
+}
+
+#[test]
+fn test_runtime_config_parsing() {
+    let cfg: GatewayConfig = serde_yaml::from_str(
+        r#"
+server:
+  addr: "0.0.0.0:8094"
+  runtime:
+    worker_threads: 2
+    max_blocking_threads: 16
+routes: []
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap();
+    assert_eq!(cfg.server.runtime.worker_threads, Some(2));
+    assert_eq!(cfg.server.runtime.max_blocking_threads, Some(16));
+}
+
+#[test]
+fn test_runtime_starts_with_configured_workers() {
+    let runtime_cfg = RuntimeConfig {
+        worker_threads: Some(2),
+        max_blocking_threads: Some(4),
+    };
+    let runtime = runtime_cfg.build().unwrap();
+    assert_eq!(runtime.metrics().num_workers(), 2);
+
+    let listener_addr = runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap()
+    });
+    assert!(listener_addr.port() > 0);
+}
+
+#[test]
+fn test_runtime_zero_workers_falls_back_to_default() {
+    let runtime_cfg = RuntimeConfig {
+        worker_threads: Some(0),
+        max_blocking_threads: None,
+    };
+    let runtime = runtime_cfg.build().unwrap();
+    assert!(runtime.metrics().num_workers() > 0);
+}
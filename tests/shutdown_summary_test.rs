@@ -0,0 +1,138 @@
+//! Logs are captured on the test's own thread, which also runs the gateway's
+//! tasks on the default current-thread runtime.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use rustway::{
+    config::{ApiKeyStore, GatewayConfig, SecretsConfig},
+    run_with_shutdown,
+};
+use tokio::{net::TcpListener, sync::oneshot};
+use tracing_subscriber::{fmt::MakeWriter, layer::SubscriberExt};
+
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedLogs {
+    fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = CapturedLogs;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+#[tokio::test]
+async fn test_shutdown_logs_summary_of_requests_served() {
+    let logs = CapturedLogs::default();
+    let _guard = tracing::subscriber::set_default(
+        tracing_subscriber::registry().with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(logs.clone())
+                .with_ansi(false),
+        ),
+    );
+
+    let backend_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend = format!("http://{}", backend_listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(backend_listener, common::harness::example_backend())
+            .await
+            .unwrap();
+    });
+
+    let config = GatewayConfig::from_yaml(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:0"
+routes:
+  - name: products
+    path: /api/products
+    destination: "{backend}"
+    cache: {{ttl: 60s}}
+  - name: failing
+    path: /api/failing
+    destination: "{backend}/status/500"
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend
+    ))
+    .unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let (stop, stopped) = oneshot::channel::<()>();
+    let gateway = tokio::spawn(run_with_shutdown(
+        config,
+        SecretsConfig {
+            jwt_secret: common::TEST_JWT_SECRET.to_string(),
+            ..Default::default()
+        },
+        ApiKeyStore::default(),
+        listener,
+        async move {
+            let _ = stopped.await;
+        },
+    ));
+
+    let client = reqwest::Client::new();
+    let status = |path: &'static str| {
+        let (client, base_url) = (client.clone(), base_url.clone());
+        async move {
+            client
+                .get(format!("{}{}", base_url, path))
+                .send()
+                .await
+                .unwrap()
+                .status()
+        }
+    };
+    assert_eq!(status("/api/products/list").await, 200); // cache miss
+    assert_eq!(status("/api/products/list").await, 200); // cache hit
+    assert_eq!(status("/api/failing").await, 500);
+    assert_eq!(status("/api/missing").await, 404);
+    drop(client);
+
+    stop.send(()).unwrap();
+    gateway.await.unwrap().unwrap();
+
+    let logs = logs.contents();
+    let summary = logs
+        .lines()
+        .find(|line| line.contains("Shutdown summary"))
+        .unwrap_or_else(|| panic!("no shutdown summary in:\n{}", logs));
+    for field in [
+        "requests=4",
+        "client_errors=1",
+        "server_errors=1",
+        "cache_hits=1",
+        "cache_misses=1",
+        "cache_hit_rate=0.5",
+        "peak_concurrency=1",
+    ] {
+        assert!(summary.contains(field), "missing {} in: {}", field, summary);
+    }
+    assert!(logs.contains("Gateway stopped"), "{}", logs);
+}
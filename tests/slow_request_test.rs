@@ -0,0 +1,129 @@
+//! The slow-request warning is checked by capturing log output on the test's
+//! own thread, so requests go through the in-memory router.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use axum::body::Body;
+use http::{Request, StatusCode};
+use tokio::net::TcpListener;
+use tracing_subscriber::fmt::MakeWriter;
+
+#[derive(Clone, Default)]
+struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedLogs {
+    fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = CapturedLogs;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+async fn start_backend() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+    url
+}
+
+async fn slow_request_logs(ms: u64) -> String {
+    let backend = start_backend().await;
+    let (app, _state) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: slow
+    path: /api/slow
+    destination: "{backend}/slow"
+observability:
+  slow_request_threshold: 100ms
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend
+    ))
+    .await;
+
+    let logs = CapturedLogs::default();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(logs.clone())
+        .with_max_level(tracing::Level::WARN)
+        .with_ansi(false)
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let request = Request::builder()
+        .uri(format!("/api/slow?ms={}", ms))
+        .header("x-request-id", "slow-req-1")
+        .body(Body::empty())
+        .unwrap();
+    let response = common::send(&app, request).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    logs.contents()
+}
+
+#[tokio::test]
+async fn test_request_above_threshold_logs_slow_request_warning() {
+    let logs = slow_request_logs(250).await;
+    let line = logs
+        .lines()
+        .find(|l| l.contains("slow request"))
+        .unwrap_or_else(|| panic!("no slow request warning in:\n{}", logs));
+    assert!(line.contains("WARN"), "{}", line);
+    assert!(line.contains("method=GET"), "{}", line);
+    assert!(line.contains("path=/api/slow"), "{}", line);
+    assert!(line.contains("status=200"), "{}", line);
+    assert!(line.contains("latency_ms="), "{}", line);
+    assert!(line.contains("request_id=slow-req-1"), "{}", line);
+}
+
+#[tokio::test]
+async fn test_request_below_threshold_is_not_flagged() {
+    let logs = slow_request_logs(0).await;
+    assert!(!logs.contains("slow request"), "{}", logs);
+}
+
+#[test]
+fn test_invalid_threshold_fails_to_load() {
+    let err = serde_yaml::from_str::<rustway::config::GatewayConfig>(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: slow
+    path: /api/slow
+    destination: http://localhost:9001
+observability:
+  slow_request_threshold: soon
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("invalid duration 'soon'"), "{}", err);
+}
@@ -0,0 +1,86 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::{path::PathBuf, sync::Arc};
+
+use http::{StatusCode, header::CONTENT_TYPE};
+use http_body_util::BodyExt;
+use moka::future::Cache;
+use rustway::{
+    errors::AppError,
+    state::CachedResponse,
+    static_file::{content_type_for, resolve_static_path, serve_static_file},
+};
+
+fn fixture_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rustygw-static-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(dir.join("docs")).unwrap();
+    std::fs::write(dir.join("docs/openapi.json"), r#"{"openapi":"3.0.0"}"#).unwrap();
+    std::fs::write(dir.join("secret.txt"), "do not serve").unwrap();
+    dir
+}
+
+fn cache() -> Cache<PathBuf, Arc<CachedResponse>> {
+    Cache::builder().max_capacity(10).build()
+}
+
+#[tokio::test]
+async fn test_serves_file_with_content_type() {
+    let dir = fixture_dir("serve");
+    let file = dir.join("docs/openapi.json");
+
+    let response = serve_static_file(&cache(), file.to_str().unwrap(), "").await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers().get(CONTENT_TYPE).unwrap(), "application/json");
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(&body[..], br#"{"openapi":"3.0.0"}"#);
+}
+
+#[tokio::test]
+async fn test_serves_file_from_directory() {
+    let dir = fixture_dir("dir");
+    let root = dir.join("docs");
+
+    let response = serve_static_file(&cache(), root.to_str().unwrap(), "/openapi.json")
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_missing_file_is_not_found() {
+    let dir = fixture_dir("missing");
+    let root = dir.join("docs");
+
+    let result = serve_static_file(&cache(), root.to_str().unwrap(), "/nope.json").await;
+    assert!(matches!(result, Err(AppError::StaticFileNotFound)));
+}
+
+#[test]
+fn test_path_traversal_rejected() {
+    let dir = fixture_dir("traversal");
+    let root = dir.join("docs");
+
+    assert!(resolve_static_path(&root, "/../secret.txt").is_none());
+    assert!(resolve_static_path(&root, "/docs/../../secret.txt").is_none());
+    assert!(resolve_static_path(&root, "//etc/passwd").is_none());
+    assert!(resolve_static_path(&root, "/openapi.json").is_some());
+}
+
+#[test]
+fn test_single_file_root_rejects_subpaths() {
+    let dir = fixture_dir("single");
+    let file = dir.join("docs/openapi.json");
+
+    assert!(resolve_static_path(&file, "").is_some());
+    assert!(resolve_static_path(&file, "/other").is_none());
+}
+
+#[test]
+fn test_content_type_mapping() {
+    assert_eq!(content_type_for(&PathBuf::from("a.JSON")), "application/json");
+    assert_eq!(
+        content_type_for(&PathBuf::from("index.html")),
+        "text/html; charset=utf-8"
+    );
+    assert_eq!(content_type_for(&PathBuf::from("blob")), "application/octet-stream");
+}
@@ -0,0 +1,86 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use common::harness::TestGateway;
+
+#[tokio::test]
+async fn test_upstream_418_is_presented_as_503_without_tripping_breaker() {
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: teapot
+    path: /api/teapot
+    destination: "{backend}/status/418"
+    status_map: {418: 503}
+    circuit_breaker: {failure_threshold: 1, success_threshold: 1, open_duration: 60s}
+"#,
+    )
+    .await;
+    let url = format!("{}/api/teapot", gateway.base_url);
+
+    // The breaker only counts upstream 5xx, so every request still reaches the
+    // backend; an open breaker would answer with its own "Service Unavailable" body
+    for _ in 0..3 {
+        let resp = reqwest::get(&url).await.unwrap();
+        assert_eq!(resp.status(), 503);
+        assert_eq!(resp.text().await.unwrap(), "");
+    }
+}
+
+#[tokio::test]
+async fn test_breaker_counts_upstream_500_mapped_to_200() {
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: masked
+    path: /api/masked
+    destination: "{backend}/status/500"
+    status_map: {500: 200}
+    circuit_breaker: {failure_threshold: 2, success_threshold: 1, open_duration: 60s}
+"#,
+    )
+    .await;
+    let url = format!("{}/api/masked", gateway.base_url);
+
+    // Clients see 200, but the breaker sees the upstream 500s and opens
+    assert_eq!(reqwest::get(&url).await.unwrap().status(), 200);
+    assert_eq!(reqwest::get(&url).await.unwrap().status(), 200);
+    let rejected = reqwest::get(&url).await.unwrap();
+    assert_eq!(rejected.status(), 503);
+    assert_eq!(rejected.text().await.unwrap(), "Service Unavailable");
+}
+
+#[tokio::test]
+async fn test_unmapped_statuses_pass_through() {
+    let gateway = TestGateway::start(
+        r#"
+routes:
+  - name: teapot
+    path: /api/teapot
+    destination: "{backend}/status/404"
+    status_map: {418: 503}
+"#,
+    )
+    .await;
+
+    let resp = reqwest::get(format!("{}/api/teapot", gateway.base_url)).await.unwrap();
+    assert_eq!(resp.status(), 404);
+}
+
+#[test]
+fn test_invalid_status_code_fails_validation() {
+    let yaml = r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: teapot
+    path: /api/teapot
+    destination: http://localhost:9001
+    status_map: {418: 1000}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#;
+    let err = rustway::config::GatewayConfig::from_yaml(yaml).unwrap_err().to_string();
+    assert!(err.contains("1000 is not a valid status code"), "{}", err);
+}
@@ -0,0 +1,137 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use rustway::{
+    config::CircuitBreakerConfig,
+    features::{
+        circuit_breaker::circuit_breaker::CircuitBreakerStore,
+        rate_limiter::state::{InMemoryRateLimitState, RateLimitState},
+        store_cleanup::SweepStats,
+    },
+};
+
+#[tokio::test]
+async fn test_incremental_sweep_checks_at_most_one_batch() {
+    let store = InMemoryRateLimitState::new();
+    for i in 0..1000 {
+        // Refills its token almost at once, so every bucket goes idle
+        assert!(
+            store
+                .check_and_update(&format!("client-{}", i), 1, 1_000_000.0)
+                .await
+                .unwrap()
+                .is_allowed()
+        );
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+
+    let stats = store.sweep_idle(Some(100));
+    assert_eq!(
+        stats,
+        SweepStats {
+            examined: 100,
+            removed: 100
+        }
+    );
+    assert_eq!(store.bucket_count(), 900);
+
+    let stats = store.sweep_idle(None);
+    assert_eq!(
+        stats,
+        SweepStats {
+            examined: 900,
+            removed: 900
+        }
+    );
+    assert_eq!(store.bucket_count(), 0);
+}
+
+#[tokio::test]
+async fn test_buckets_still_refilling_are_kept() {
+    let store = InMemoryRateLimitState::new();
+    assert!(store.check_and_update("busy", 10, 0.001).await.unwrap().is_allowed());
+    assert!(
+        store
+            .check_and_update("idle", 1, 1_000_000.0)
+            .await
+            .unwrap()
+            .is_allowed()
+    );
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+
+    assert_eq!(
+        store.sweep_idle(None),
+        SweepStats {
+            examined: 2,
+            removed: 1
+        }
+    );
+    assert_eq!(store.bucket_count(), 1);
+    // The kept key is offered again on the next sweep
+    assert_eq!(
+        store.sweep_idle(Some(10)),
+        SweepStats {
+            examined: 1,
+            removed: 0
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_only_healthy_unused_circuits_are_reaped() {
+    let config = CircuitBreakerConfig {
+        failure_threshold: 5,
+        success_threshold: 1,
+        open_duration: "30s".to_string(),
+        max_open_duration: None,
+        backoff_multiplier: 2.0,
+        backoff_reset_after: None,
+    };
+    let store = CircuitBreakerStore::new();
+    store.get_or_insert("healthy");
+    store.get_or_insert("failing").record("failing", true, &config).await;
+    let held = store.for_destination("api", "http://backend");
+
+    assert_eq!(
+        store.sweep_idle(None),
+        SweepStats {
+            examined: 3,
+            removed: 1
+        }
+    );
+    assert_eq!(store.circuit_count(), 2);
+
+    drop(held);
+    assert_eq!(
+        store.sweep_idle(None),
+        SweepStats {
+            examined: 2,
+            removed: 1
+        }
+    );
+    assert_eq!(store.circuit_count(), 1);
+}
+
+#[test]
+fn test_invalid_cleanup_tuning_fails_validation() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes: []
+identity:
+  api_key_store_path: ./api_keys.yaml
+tuning:
+  store_cleanup_interval: 0s
+  store_cleanup_batch: 0
+"#,
+    );
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(
+        err.contains("store_cleanup_interval must be greater than zero"),
+        "{}",
+        err
+    );
+    assert!(err.contains("store_cleanup_batch must be at least 1"), "{}", err);
+}
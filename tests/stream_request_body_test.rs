@@ -0,0 +1,139 @@
+//! Uploads through routes with `stream_request_body`. The client body only
+//! finishes after the backend has seen its first chunk, which a buffering
+//! proxy never allows, so a passing upload shows the body was streamed.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use axum::{Router, body::Body, routing::post};
+use bytes::Bytes;
+use futures::StreamExt;
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use tokio::{net::TcpListener, sync::Notify};
+
+const CHUNK: usize = 64 * 1024;
+
+/// Counts the bytes it receives and signals `first_chunk` as soon as the first arrives.
+async fn counting_backend(first_chunk: Arc<Notify>) -> String {
+    let app = Router::new().route(
+        "/upload",
+        post(move |body: Body| {
+            let first_chunk = first_chunk.clone();
+            async move {
+                let mut stream = body.into_data_stream();
+                let mut total = 0;
+                while let Some(chunk) = stream.next().await {
+                    total += chunk.unwrap().len();
+                    first_chunk.notify_one();
+                }
+                total.to_string()
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
+
+async fn app(backend: &str, stream: bool) -> Router {
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+  pool:
+    body_limit: 8MB
+routes:
+  - name: upload
+    path: /api/upload
+    destination: "{backend}/upload"
+    stream_request_body: {stream}
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend,
+        stream = stream
+    ))
+    .await;
+    app
+}
+
+/// One chunk, then the remaining `chunks - 1` once `first_chunk` fires.
+fn gated_body(chunks: usize, first_chunk: Arc<Notify>) -> Body {
+    let first = futures::stream::once(async { Ok::<_, std::io::Error>(Bytes::from(vec![b'x'; CHUNK])) });
+    let rest = futures::stream::once(async move {
+        first_chunk.notified().await;
+        futures::stream::iter((1..chunks).map(|_| Ok(Bytes::from(vec![b'x'; CHUNK]))))
+    })
+    .flatten();
+    Body::from_stream(first.chain(rest))
+}
+
+fn upload(body: Body) -> Request<Body> {
+    Request::builder().method("POST").uri("/api/upload").body(body).unwrap()
+}
+
+#[tokio::test]
+async fn test_streaming_route_forwards_full_body_as_it_arrives() {
+    let first_chunk = Arc::new(Notify::new());
+    let backend = counting_backend(first_chunk.clone()).await;
+    let app = app(&backend, true).await;
+
+    // 6MB, sent only after the backend has started receiving
+    let chunks = 96;
+    let response = tokio::time::timeout(
+        Duration::from_secs(10),
+        common::send(&app, upload(gated_body(chunks, first_chunk))),
+    )
+    .await
+    .expect("upload stalled: body was not streamed");
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, (chunks * CHUNK).to_string());
+}
+
+#[tokio::test]
+async fn test_buffering_route_waits_for_whole_body() {
+    let first_chunk = Arc::new(Notify::new());
+    let backend = counting_backend(first_chunk.clone()).await;
+    let app = app(&backend, false).await;
+
+    let result = tokio::time::timeout(
+        Duration::from_millis(500),
+        common::send(&app, upload(gated_body(4, first_chunk))),
+    )
+    .await;
+    assert!(
+        result.is_err(),
+        "buffered route should not reach the backend mid-upload"
+    );
+}
+
+#[tokio::test]
+async fn test_streamed_body_over_limit_returns_413() {
+    let backend = counting_backend(Arc::new(Notify::new())).await;
+    let app = app(&backend, true).await;
+
+    // 10MB without a content-length, so the limit is hit mid-stream
+    let chunks = futures::stream::iter((0..160).map(|_| Ok::<_, std::io::Error>(Bytes::from(vec![b'x'; CHUNK]))));
+    let response = common::send(&app, upload(Body::from_stream(chunks))).await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn test_declared_length_over_limit_is_rejected_up_front() {
+    let backend = counting_backend(Arc::new(Notify::new())).await;
+    let app = app(&backend, true).await;
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/upload")
+        .header("content-length", 9 * 1024 * 1024)
+        .body(Body::empty())
+        .unwrap();
+    let response = common::send(&app, request).await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
@@ -0,0 +1,86 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use axum::{Router, body::Body, routing::get};
+use bytes::Bytes;
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use tokio::net::TcpListener;
+
+const CHUNK: usize = 64 * 1024;
+const CHUNKS: usize = 4096; // 256 MiB in total
+
+/// A backend that generates a large body on the fly, so it never holds it
+/// in memory either.
+async fn start_backend() -> String {
+    let app = Router::new().route(
+        "/download",
+        get(|| async {
+            let chunks =
+                futures::stream::iter((0..CHUNKS).map(|_| Ok::<_, std::io::Error>(Bytes::from(vec![b'x'; CHUNK]))));
+            Body::from_stream(chunks)
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    url
+}
+
+/// Resident set size in bytes, where `/proc` is available.
+fn rss_bytes() -> Option<usize> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|l| l.starts_with("VmRSS:"))?;
+    let kb: usize = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[tokio::test]
+async fn test_large_response_is_streamed_not_buffered() {
+    let backend = start_backend().await;
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: files
+    path: /files
+    destination: "{backend}"
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend
+    ))
+    .await;
+
+    let response = common::send(
+        &app,
+        Request::builder().uri("/files/download").body(Body::empty()).unwrap(),
+    )
+    .await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.headers().contains_key("x-request-id"));
+
+    let rss_before = rss_bytes();
+    let mut peak_rss = rss_before;
+    let mut received = 0;
+    let mut body = response.into_body();
+    while let Some(frame) = body.frame().await {
+        if let Ok(data) = frame.unwrap().into_data() {
+            received += data.len();
+        }
+        peak_rss = peak_rss.max(rss_bytes());
+    }
+
+    assert_eq!(received, CHUNK * CHUNKS);
+    if let (Some(before), Some(peak)) = (rss_before, peak_rss) {
+        let growth = peak.saturating_sub(before);
+        assert!(
+            growth < received / 4,
+            "RSS grew by {} bytes for a {} byte body",
+            growth,
+            received
+        );
+    }
+}
@@ -0,0 +1,159 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{net::SocketAddr, path::PathBuf, sync::Arc};
+
+use axum::{Router, routing::get};
+use common::tls::AcceptAnyCert;
+use rustls::{
+    ClientConfig, ProtocolVersion, SupportedProtocolVersion,
+    crypto::ring,
+    pki_types::ServerName,
+    version::{TLS12, TLS13},
+};
+use rustway::features::tls::{ReloadableCertResolver, TlsConfig, serve_tls, server_config};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use tokio_rustls::TlsConnector;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/tls")
+        .join(name)
+}
+
+/// A TLS gateway answering `/health` with `extra` added to its `tls` block.
+async fn start(extra: &str) -> SocketAddr {
+    let tls: TlsConfig = serde_yaml::from_str(&format!(
+        "cert_path: {}\nkey_path: {}\n{}",
+        fixture("cert_a.pem").display(),
+        fixture("key_a.pem").display(),
+        extra
+    ))
+    .unwrap();
+    let resolver = Arc::new(ReloadableCertResolver::from_files(&tls.cert_path, &tls.key_path).unwrap());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().route("/health", get(|| async { "OK" }));
+    tokio::spawn(serve_tls(
+        listener,
+        app,
+        server_config(resolver, &tls).unwrap(),
+        std::future::pending(),
+    ));
+    addr
+}
+
+/// Handshakes offering only `versions`; the negotiated version on success.
+async fn handshake(
+    addr: SocketAddr,
+    versions: &[&'static SupportedProtocolVersion],
+) -> Result<(ProtocolVersion, String), std::io::Error> {
+    let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_protocol_versions(versions)
+        .unwrap()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCert))
+        .with_no_client_auth();
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let tls = TlsConnector::from(Arc::new(config))
+        .connect(ServerName::try_from("localhost").unwrap(), stream)
+        .await?;
+    let connection = tls.get_ref().1;
+    Ok((
+        connection.protocol_version().unwrap(),
+        format!("{:?}", connection.negotiated_cipher_suite().unwrap().suite()),
+    ))
+}
+
+/// A hand-built TLS 1.1 ClientHello, as an old client would send; rustls has
+/// no way to speak 1.1 itself.
+fn tls11_client_hello() -> Vec<u8> {
+    let mut hello = vec![0x03, 0x02]; // client_version TLS 1.1
+    hello.extend([0x42; 32]); // random
+    hello.push(0); // no session id
+    hello.extend([0x00, 0x04, 0xc0, 0x13, 0x00, 0x2f]); // ECDHE-RSA-AES128-SHA, AES128-SHA
+    hello.extend([0x01, 0x00]); // null compression
+    hello.extend([0x00, 0x00]); // no extensions
+
+    let [hi, lo] = u16::try_from(hello.len()).unwrap().to_be_bytes();
+    let mut handshake = vec![0x01, 0x00, hi, lo];
+    handshake.extend(hello);
+    let [hi, lo] = u16::try_from(handshake.len()).unwrap().to_be_bytes();
+    let mut record = vec![0x16, 0x03, 0x02, hi, lo];
+    record.extend(handshake);
+    record
+}
+
+#[tokio::test]
+async fn test_tls11_client_rejected_and_tls13_accepted_with_min_12() {
+    let addr = start("min_version: 1.2").await;
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(&tls11_client_hello()).await.unwrap();
+    let mut reply = Vec::new();
+    let _ = stream.read_to_end(&mut reply).await;
+    // Either a bare close or an alert record, never a ServerHello (0x16)
+    assert!(reply.is_empty() || reply[0] == 0x15, "unexpected reply {:02x?}", reply);
+
+    let (version, _) = handshake(addr, &[&TLS13]).await.unwrap();
+    assert_eq!(version, ProtocolVersion::TLSv1_3);
+    let (version, _) = handshake(addr, &[&TLS12]).await.unwrap();
+    assert_eq!(version, ProtocolVersion::TLSv1_2);
+}
+
+#[tokio::test]
+async fn test_min_13_rejects_tls12_clients() {
+    let addr = start("min_version: \"1.3\"").await;
+
+    assert!(handshake(addr, &[&TLS12]).await.is_err());
+    assert_eq!(handshake(addr, &[&TLS13]).await.unwrap().0, ProtocolVersion::TLSv1_3);
+}
+
+#[tokio::test]
+async fn test_cipher_suites_limit_what_is_negotiated() {
+    let addr = start("cipher_suites: [TLS13_CHACHA20_POLY1305_SHA256]").await;
+
+    let (_, suite) = handshake(addr, &[&TLS13]).await.unwrap();
+    assert_eq!(suite, "TLS13_CHACHA20_POLY1305_SHA256");
+    // No TLS 1.2 suite is left to agree on
+    assert!(handshake(addr, &[&TLS12]).await.is_err());
+}
+
+#[test]
+fn test_invalid_tls_settings_fail_validation() {
+    let config = |tls: &str| {
+        format!(
+            r#"
+server:
+  addr: "127.0.0.1:8094"
+  tls: {{cert_path: /tmp/cert.pem, key_path: /tmp/key.pem, {}}}
+routes: []
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+            tls
+        )
+    };
+    let error = |tls: &str| {
+        rustway::config::GatewayConfig::from_yaml(&config(tls))
+            .unwrap_err()
+            .to_string()
+    };
+
+    let err = error("cipher_suites: [TLS_RSA_WITH_RC4_128_MD5]");
+    assert!(
+        err.contains("Unknown cipher suite 'TLS_RSA_WITH_RC4_128_MD5'"),
+        "{}",
+        err
+    );
+
+    let err = error("min_version: 1.3, cipher_suites: [TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256]");
+    assert!(err.contains("no usable cipher suites"), "{}", err);
+
+    let err = error("min_version: 1.1");
+    assert!(err.contains("unsupported TLS version '1.1'"), "{}", err);
+}
@@ -0,0 +1,151 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use axum::{Router, routing::get};
+use common::tls::AcceptAnyCert;
+use rustls::{
+    ClientConfig,
+    crypto::ring,
+    pki_types::{CertificateDer, ServerName, pem::PemObject},
+};
+use rustway::{
+    features::tls::{ReloadableCertResolver, TlsConfig, serve_tls, server_config},
+    utils::hot_reload::watch_tls_files,
+};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::TlsConnector;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/tls")
+        .join(name)
+}
+
+fn tls_config(dir: &std::path::Path) -> TlsConfig {
+    serde_yaml::from_str(&format!(
+        "cert_path: {}\nkey_path: {}",
+        dir.join("cert.pem").display(),
+        dir.join("key.pem").display()
+    ))
+    .unwrap()
+}
+
+fn install(dir: &std::path::Path, variant: &str) {
+    std::fs::copy(fixture(&format!("cert_{}.pem", variant)), dir.join("cert.pem")).unwrap();
+    std::fs::copy(fixture(&format!("key_{}.pem", variant)), dir.join("key.pem")).unwrap();
+}
+
+fn fixture_der(variant: &str) -> CertificateDer<'static> {
+    CertificateDer::from_pem_file(fixture(&format!("cert_{}.pem", variant))).unwrap()
+}
+
+async fn presented_cert(addr: std::net::SocketAddr) -> CertificateDer<'static> {
+    let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCert))
+        .with_no_client_auth();
+    let connector = TlsConnector::from(Arc::new(config));
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let tls = connector
+        .connect(ServerName::try_from("localhost").unwrap(), stream)
+        .await
+        .unwrap();
+    tls.get_ref().1.peer_certificates().unwrap()[0].clone().into_owned()
+}
+
+#[test]
+fn test_reload_swaps_certificate() {
+    let dir = std::env::temp_dir().join(format!("rustygw-tls-swap-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    install(&dir, "a");
+
+    let resolver = ReloadableCertResolver::from_files(dir.join("cert.pem"), dir.join("key.pem")).unwrap();
+    assert_eq!(resolver.current().cert[0], fixture_der("a"));
+
+    install(&dir, "b");
+    resolver.reload().unwrap();
+    assert_eq!(resolver.current().cert[0], fixture_der("b"));
+}
+
+#[test]
+fn test_failed_reload_keeps_old_certificate() {
+    let dir = std::env::temp_dir().join(format!("rustygw-tls-bad-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    install(&dir, "a");
+
+    let resolver = ReloadableCertResolver::from_files(dir.join("cert.pem"), dir.join("key.pem")).unwrap();
+    std::fs::write(dir.join("cert.pem"), "not a certificate").unwrap();
+    assert!(resolver.reload().is_err());
+    assert_eq!(resolver.current().cert[0], fixture_der("a"));
+}
+
+#[tokio::test]
+async fn test_replacing_cert_files_serves_new_certificate() {
+    let dir = std::env::temp_dir().join(format!("rustygw-tls-watch-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    install(&dir, "a");
+
+    let resolver = Arc::new(ReloadableCertResolver::from_files(dir.join("cert.pem"), dir.join("key.pem")).unwrap());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = Router::new().route("/health", get(|| async { "OK" }));
+    tokio::spawn(serve_tls(
+        listener,
+        app,
+        server_config(resolver.clone(), &tls_config(&dir)).unwrap(),
+        std::future::pending(),
+    ));
+    tokio::spawn(watch_tls_files(resolver.clone(), Duration::from_millis(200)));
+
+    assert_eq!(presented_cert(addr).await, fixture_der("a"));
+
+    // Give the watcher time to register before touching the files
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    install(&dir, "b");
+
+    let expected = fixture_der("b");
+    let mut presented = presented_cert(addr).await;
+    for _ in 0..50 {
+        if presented == expected {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        presented = presented_cert(addr).await;
+    }
+    assert_eq!(presented, expected);
+}
+
+#[tokio::test]
+async fn test_shutdown_does_not_wait_on_a_stalled_handshake() {
+    let tls: TlsConfig = serde_yaml::from_str(&format!(
+        "cert_path: {}\nkey_path: {}",
+        fixture("cert_a.pem").display(),
+        fixture("key_a.pem").display()
+    ))
+    .unwrap();
+    let resolver = Arc::new(ReloadableCertResolver::from_files(&tls.cert_path, &tls.key_path).unwrap());
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    let server = tokio::spawn(serve_tls(
+        listener,
+        Router::new().route("/health", get(|| async { "OK" })),
+        server_config(resolver, &tls).unwrap(),
+        async {
+            let _ = shutdown_rx.await;
+        },
+    ));
+
+    // Connects but never sends a ClientHello
+    let _stalled = TcpStream::connect(addr).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    shutdown_tx.send(()).unwrap();
+    let result = tokio::time::timeout(Duration::from_secs(2), server).await;
+    assert!(result.is_ok(), "shutdown waited on the handshake");
+}
@@ -0,0 +1,154 @@
+//! `TrustedHeader` auth. In-memory requests arrive from 127.0.0.1, so a route
+//! trusting 127.0.0.1 sees a trusted peer and one trusting 10.0.0.0/8 doesn't.
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use axum::{Router, body::Body, response::Response};
+use http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use jsonwebtoken::{EncodingKey, Header, encode};
+use rustway::features::auth::auth::Claims;
+use serde_json::Value;
+use tokio::net::TcpListener;
+
+async fn app() -> Router {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let backend = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, common::harness::example_backend()).await.unwrap() });
+
+    let (app, _) = common::test_app(&format!(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: mesh
+    path: /mesh
+    destination: "{backend}/echo"
+    auth:
+      type: TrustedHeader
+      trusted_ips: [127.0.0.1/32]
+      roles: [orders]
+  - name: edge
+    path: /edge
+    destination: "{backend}/echo"
+    auth:
+      type: TrustedHeader
+      trusted_ips: [10.0.0.0/8]
+  - name: mixed
+    path: /mixed
+    destination: "{backend}/echo"
+    auth:
+      - type: TrustedHeader
+        trusted_ips: [10.0.0.0/8]
+      - type: Jwt
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+        backend = backend
+    ))
+    .await;
+    app
+}
+
+async fn get(app: &Router, uri: &str, headers: &[(&str, &str)]) -> Response {
+    let mut request = Request::builder().uri(uri);
+    for (name, value) in headers {
+        request = request.header(*name, *value);
+    }
+    common::send(app, request.body(Body::empty()).unwrap()).await
+}
+
+fn jwt() -> String {
+    let exp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 3600;
+    let claims = Claims {
+        sub: "tester".to_string(),
+        roles: vec![],
+        exp: usize::try_from(exp).unwrap(),
+        iss: None,
+        aud: None,
+        extra: HashMap::new(),
+    };
+    encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(common::TEST_JWT_SECRET.as_bytes()),
+    )
+    .unwrap()
+}
+
+const IDENTITY: [(&str, &str); 2] = [("x-authenticated-user", "alice"), ("x-user-roles", "orders, admin")];
+
+#[tokio::test]
+async fn test_headers_from_trusted_source_authenticate() {
+    let app = app().await;
+
+    let response = get(&app, "/mesh", &IDENTITY).await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    // Roles come from the header too
+    let response = get(&app, "/mesh", &[("x-authenticated-user", "alice")]).await;
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let response = get(&app, "/mesh", &[]).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_headers_from_untrusted_source_are_rejected() {
+    let app = app().await;
+
+    let response = get(&app, "/edge", &IDENTITY).await;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert!(
+        String::from_utf8_lossy(&body).contains("only accepted from trusted sources"),
+        "{:?}",
+        body
+    );
+}
+
+#[tokio::test]
+async fn test_untrusted_identity_headers_are_stripped_when_another_method_succeeds() {
+    let app = app().await;
+    let token = format!("Bearer {}", jwt());
+
+    let response = get(&app, "/mixed", &[IDENTITY[0], IDENTITY[1], ("authorization", &token)]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    let echoed: Value = serde_json::from_slice(&body).unwrap();
+    assert!(echoed["headers"].get("x-authenticated-user").is_none(), "{}", echoed);
+    assert!(echoed["headers"].get("x-user-roles").is_none(), "{}", echoed);
+}
+
+#[test]
+fn test_trusted_header_requires_valid_trusted_ips() {
+    let config = common::parse_config(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: open
+    path: /open
+    destination: http://localhost:9001
+    auth:
+      type: TrustedHeader
+  - name: typo
+    path: /typo
+    destination: http://localhost:9001
+    auth:
+      type: TrustedHeader
+      trusted_ips: [10.0.0.0/33]
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    );
+    let err = config.validate_pub().unwrap_err().to_string();
+    assert!(err.contains("TrustedHeader requires trusted_ips"), "{}", err);
+    assert!(err.contains("trusted_ips entry '10.0.0.0/33'"), "{}", err);
+}
@@ -0,0 +1,63 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::time::Duration;
+
+use rustway::{config::TuningConfig, constants};
+
+const BASE: &str = r#"
+server:
+  addr: "127.0.0.1:8094"
+routes: []
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#;
+
+#[test]
+fn test_tuning_defaults_come_from_constants() {
+    let tuning = common::parse_config(BASE).tuning;
+    assert_eq!(tuning.static_cache_capacity, constants::DEFAULT_STATIC_CACHE_CAPACITY);
+    assert_eq!(tuning.static_cache_ttl, constants::DEFAULT_STATIC_CACHE_TTL);
+    assert_eq!(tuning.health_check_timeout, constants::DEFAULT_HEALTH_CHECK_TIMEOUT);
+    assert_eq!(tuning.reload_channel_buffer, constants::DEFAULT_RELOAD_CHANNEL_BUFFER);
+    assert_eq!(tuning.tls_reload_debounce, constants::DEFAULT_TLS_RELOAD_DEBOUNCE);
+}
+
+#[test]
+fn test_partial_tuning_keeps_other_defaults() {
+    let tuning: TuningConfig = serde_yaml::from_str("static_cache_capacity: 42").unwrap();
+    assert_eq!(tuning.static_cache_capacity, 42);
+    assert_eq!(tuning.static_cache_ttl, constants::DEFAULT_STATIC_CACHE_TTL);
+}
+
+#[tokio::test]
+async fn test_overridden_tuning_is_applied_to_state() {
+    let state = common::test_state(&format!(
+        "{}{}",
+        BASE,
+        r"
+tuning:
+  static_cache_capacity: 5
+  static_cache_ttl: 2m
+  health_check_timeout: 750ms
+  reload_channel_buffer: 8
+"
+    ))
+    .await;
+
+    let policy = state.static_cache.policy();
+    assert_eq!(policy.max_capacity(), Some(5));
+    assert_eq!(policy.time_to_live(), Some(Duration::from_secs(120)));
+    assert_eq!(state.health_checker.probe_timeout(), Duration::from_millis(750));
+}
+
+#[tokio::test]
+async fn test_default_tuning_is_applied_to_state() {
+    let state = common::test_state(BASE).await;
+
+    let policy = state.static_cache.policy();
+    assert_eq!(policy.max_capacity(), Some(constants::DEFAULT_STATIC_CACHE_CAPACITY));
+    assert_eq!(policy.time_to_live(), Some(Duration::from_secs(60)));
+    assert_eq!(state.health_checker.probe_timeout(), Duration::from_secs(3));
+}
@@ -0,0 +1,85 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+use std::{
+    collections::HashSet,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{Router, extract::ConnectInfo, routing::any};
+use reqwest::Client;
+use rustway::features::warmup::{WarmupStats, warm_up};
+use tokio::net::TcpListener;
+
+/// A backend that records the client port of every request, one per connection.
+async fn port_recording_backend() -> (String, Arc<Mutex<Vec<u16>>>) {
+    let ports: Arc<Mutex<Vec<u16>>> = Arc::default();
+    let recorded = ports.clone();
+    let app = Router::new().route(
+        "/{*path}",
+        any(move |ConnectInfo(addr): ConnectInfo<SocketAddr>| {
+            let recorded = recorded.clone();
+            async move {
+                recorded.lock().unwrap().push(addr.port());
+                // Slow enough that concurrent warm-up requests can't share a connection
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                "ok"
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+            .await
+            .unwrap();
+    });
+    (url, ports)
+}
+
+#[tokio::test]
+async fn test_warm_up_opens_connections_reused_by_later_requests() {
+    let (backend, ports) = port_recording_backend().await;
+    let client = Client::builder().pool_max_idle_per_host(8).build().unwrap();
+    let destination = format!("{}/api", backend);
+
+    let stats = warm_up(&[(client.clone(), destination.clone())], 3, Duration::from_secs(2)).await;
+    assert_eq!(
+        stats,
+        WarmupStats {
+            connections: 3,
+            failures: 0
+        }
+    );
+    let warmed: HashSet<u16> = ports.lock().unwrap().iter().copied().collect();
+    assert_eq!(warmed.len(), 3);
+
+    // The first real request goes out on an already open connection
+    client.get(&destination).send().await.unwrap();
+    let first_request_port = *ports.lock().unwrap().last().unwrap();
+    assert!(warmed.contains(&first_request_port));
+}
+
+#[tokio::test]
+async fn test_unreachable_destination_is_skipped() {
+    let (backend, _) = port_recording_backend().await;
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let closed = format!("http://{}", listener.local_addr().unwrap());
+    drop(listener);
+    let client = Client::new();
+
+    let stats = warm_up(
+        &[(client.clone(), closed), (client, backend)],
+        2,
+        Duration::from_secs(2),
+    )
+    .await;
+    assert_eq!(
+        stats,
+        WarmupStats {
+            connections: 2,
+            failures: 2
+        }
+    );
+}
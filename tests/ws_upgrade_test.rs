@@ -0,0 +1,147 @@
+#![allow(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+
+mod common;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::body::Body;
+use common::harness::{HARNESS_JWT_SECRET, TestGateway};
+use futures::{SinkExt, StreamExt};
+use http::{Request, StatusCode};
+use jsonwebtoken::{EncodingKey, Header, encode};
+use serde_json::json;
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{Error as WsError, Message, client::IntoClientRequest},
+};
+
+const ROUTES: &str = r#"
+routes:
+  - name: chat
+    path: /api/chat
+    destination: "{backend}/ws-echo"
+    auth:
+      type: Jwt
+    rate_limit: {requests: 2, period: 1m}
+    circuit_breaker: {failure_threshold: 1, success_threshold: 1, open_duration: 60s}
+    cache: {ttl: 60s}
+  - name: down
+    path: /api/down
+    destination: "http://127.0.0.1:9"
+"#;
+
+fn token() -> String {
+    let exp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() + 3600;
+    encode(
+        &Header::default(),
+        &json!({ "sub": "tester", "roles": [], "exp": exp }),
+        &EncodingKey::from_secret(HARNESS_JWT_SECRET.as_bytes()),
+    )
+    .unwrap()
+}
+
+fn ws_request(gateway: &TestGateway, path: &str, token: Option<&str>) -> http::Request<()> {
+    let url = format!("{}{}", gateway.base_url.replacen("http://", "ws://", 1), path);
+    let mut request = url.into_client_request().unwrap();
+    if let Some(token) = token {
+        request
+            .headers_mut()
+            .insert("authorization", format!("Bearer {}", token).parse().unwrap());
+    }
+    request
+}
+
+fn rejected_status(error: WsError) -> u16 {
+    match error {
+        WsError::Http(response) => response.status().as_u16(),
+        other => panic!("expected an HTTP rejection, got {}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_websocket_is_proxied_and_echoes() {
+    let gateway = TestGateway::start(ROUTES).await;
+
+    let (mut socket, response) = connect_async(ws_request(&gateway, "/api/chat", Some(&token())))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::SWITCHING_PROTOCOLS);
+
+    socket.send(Message::text("hello")).await.unwrap();
+    let reply = socket.next().await.unwrap().unwrap();
+    assert_eq!(reply.into_text().unwrap().as_str(), "hello");
+    socket.send(Message::binary(vec![1, 2, 3])).await.unwrap();
+    let reply = socket.next().await.unwrap().unwrap();
+    assert_eq!(reply.into_data().as_ref(), &[1, 2, 3]);
+}
+
+#[tokio::test]
+async fn test_auth_and_rate_limit_run_before_the_upgrade() {
+    let gateway = TestGateway::start(ROUTES).await;
+
+    let unauthenticated = connect_async(ws_request(&gateway, "/api/chat", None))
+        .await
+        .unwrap_err();
+    assert_eq!(rejected_status(unauthenticated), 401);
+
+    let token = token();
+    for _ in 0..2 {
+        connect_async(ws_request(&gateway, "/api/chat", Some(&token)))
+            .await
+            .unwrap();
+    }
+    let limited = connect_async(ws_request(&gateway, "/api/chat", Some(&token)))
+        .await
+        .unwrap_err();
+    assert_eq!(rejected_status(limited), 429);
+}
+
+#[tokio::test]
+async fn test_unreachable_backend_is_502() {
+    let gateway = TestGateway::start(ROUTES).await;
+
+    let error = connect_async(ws_request(&gateway, "/api/down", None))
+        .await
+        .unwrap_err();
+    assert_eq!(rejected_status(error), 502);
+}
+
+#[tokio::test]
+async fn test_upgrade_without_connection_state_is_not_proxied_as_http() {
+    // In-process requests have no connection to upgrade; they must not reach
+    // the backend as plain HTTP (which would be a 502 here)
+    let (app, _) = common::test_app(
+        r#"
+server:
+  addr: "127.0.0.1:8094"
+routes:
+  - name: chat
+    path: /api/chat
+    destination: http://127.0.0.1:9
+identity:
+  api_key_store_path: ./api_keys.yaml
+"#,
+    )
+    .await;
+    let request = Request::builder()
+        .uri("/api/chat/room-1")
+        .header("connection", "Upgrade")
+        .header("upgrade", "WebSocket")
+        .header("sec-websocket-version", "13")
+        .header("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ==")
+        .body(Body::empty())
+        .unwrap();
+
+    assert_eq!(common::send(&app, request).await.status(), StatusCode::UPGRADE_REQUIRED);
+}
+
+#[tokio::test]
+async fn test_plain_request_is_still_proxied() {
+    let gateway = TestGateway::start(ROUTES).await;
+
+    let status = reqwest::get(format!("{}/api/down", gateway.base_url))
+        .await
+        .unwrap()
+        .status();
+    assert_eq!(status, 502);
+}